@@ -0,0 +1,216 @@
+//! The terminal's alternate screen, used by `get --reveal`. It has no
+//! scrollback, so a secret shown there is gone once secrt switches back,
+//! including when secrt is interrupted while the secret is on screen.
+
+use std::io::{self, Write};
+
+/// Switch to and from the alternate screen, and clear it.
+pub const ALT_SCREEN_ON: &str = "\x1b[?1049h";
+pub const ALT_SCREEN_OFF: &str = "\x1b[?1049l";
+pub const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+
+/// Written to stdout by the signal handler: clear the screen, then leave
+/// the alternate screen.
+#[cfg(unix)]
+const RESTORE: &[u8] = b"\x1b[H\x1b[2J\x1b[?1049l";
+
+/// Signals that end the process while a secret is shown.
+#[cfg(unix)]
+const SIGNALS: [libc::c_int; 4] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+/// While alive, SIGINT, SIGTERM, SIGHUP and SIGQUIT first clear the
+/// screen, leave the alternate screen and restore the terminal modes seen
+/// when the guard was created (a prompt may have turned echo off), then
+/// take their usual effect. Dropping the guard puts the previous handlers
+/// back. A no-op on non-Unix platforms.
+pub struct SignalGuard {
+    #[cfg(unix)]
+    previous: [libc::sigaction; 4],
+}
+
+#[cfg(unix)]
+mod saved {
+    use std::cell::UnsafeCell;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Terminal modes to restore from the signal handler. Written before
+    /// the handlers are installed and only read by them afterwards.
+    pub struct Termios {
+        modes: UnsafeCell<Option<libc::termios>>,
+        set: AtomicBool,
+    }
+
+    // SAFETY: `store` runs before any handler that calls `get` is
+    // installed, and the flag orders the write before the reads.
+    unsafe impl Sync for Termios {}
+
+    pub static TERMIOS: Termios = Termios {
+        modes: UnsafeCell::new(None),
+        set: AtomicBool::new(false),
+    };
+
+    impl Termios {
+        pub fn store(&self, modes: Option<libc::termios>) {
+            self.set.store(false, Ordering::SeqCst);
+            // SAFETY: no handler reads while `set` is false.
+            unsafe { *self.modes.get() = modes };
+            self.set.store(true, Ordering::SeqCst);
+        }
+
+        pub fn get(&self) -> Option<libc::termios> {
+            if !self.set.load(Ordering::SeqCst) {
+                return None;
+            }
+            // SAFETY: written before `set` became true and not since.
+            unsafe { *self.modes.get() }
+        }
+    }
+}
+
+impl SignalGuard {
+    pub fn install() -> SignalGuard {
+        #[cfg(unix)]
+        {
+            // SAFETY: plain libc calls on stdout and on handler tables owned
+            // by this guard; `on_signal` only uses async-signal-safe calls.
+            unsafe {
+                let mut modes: libc::termios = std::mem::zeroed();
+                let modes =
+                    (libc::tcgetattr(libc::STDOUT_FILENO, &mut modes) == 0).then_some(modes);
+                saved::TERMIOS.store(modes);
+
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as usize;
+                // Back to the default action once the handler runs, so the
+                // re-raised signal ends the process as it otherwise would.
+                action.sa_flags = libc::SA_RESETHAND;
+                libc::sigemptyset(&mut action.sa_mask);
+                let mut previous: [libc::sigaction; 4] = std::mem::zeroed();
+                for (sig, prev) in SIGNALS.iter().zip(previous.iter_mut()) {
+                    libc::sigaction(*sig, &action, prev);
+                }
+                SignalGuard { previous }
+            }
+        }
+        #[cfg(not(unix))]
+        SignalGuard {}
+    }
+}
+
+impl Drop for SignalGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        // SAFETY: restores the actions saved by `install`.
+        unsafe {
+            for (sig, prev) in SIGNALS.iter().zip(self.previous.iter()) {
+                libc::sigaction(*sig, prev, std::ptr::null_mut());
+            }
+        }
+    }
+}
+
+/// Write `prompt` and wait for a single key press on the terminal. The key
+/// is read unbuffered and without echo, and anything else it sent (the rest
+/// of an arrow key's sequence, say) is discarded. Ctrl+C still interrupts.
+/// Without Unix terminal modes this waits for Enter instead.
+pub fn read_key(prompt: &str, w: &mut dyn Write) -> io::Result<()> {
+    w.write_all(prompt.as_bytes())?;
+    w.flush()?;
+    #[cfg(unix)]
+    {
+        use std::io::Read;
+        use std::os::unix::io::AsRawFd;
+
+        let mut tty = std::fs::File::options()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")?;
+        let fd = tty.as_raw_fd();
+        // SAFETY: termios calls on a descriptor we own; the original modes
+        // are put back before returning.
+        let saved = unsafe {
+            let mut saved: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(fd, &mut saved) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = saved;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            saved
+        };
+        let read = tty.read(&mut [0u8; 1]);
+        // SAFETY: as above.
+        unsafe {
+            libc::tcflush(fd, libc::TCIFLUSH);
+            libc::tcsetattr(fd, libc::TCSANOW, &saved);
+        }
+        read.map(|_| ())
+    }
+    #[cfg(not(unix))]
+    {
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn on_signal(sig: libc::c_int) {
+    // SAFETY: write, tcsetattr and raise are async-signal-safe.
+    unsafe {
+        libc::write(
+            libc::STDOUT_FILENO,
+            RESTORE.as_ptr() as *const libc::c_void,
+            RESTORE.len(),
+        );
+        if let Some(modes) = saved::TERMIOS.get() {
+            libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, &modes);
+        }
+        // Delivered with the default action once this handler returns
+        libc::raise(sig);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A child process that gets SIGTERM while guarded leaves the alternate
+    /// screen on its stdout and still dies from the signal.
+    #[test]
+    fn signal_leaves_alternate_screen() {
+        unsafe {
+            let mut fds = [0; 2];
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            let pid = libc::fork();
+            assert!(pid >= 0);
+            if pid == 0 {
+                // Child: only async-signal-safe calls from here on
+                libc::dup2(fds[1], libc::STDOUT_FILENO);
+                let _guard = SignalGuard::install();
+                libc::raise(libc::SIGTERM);
+                libc::_exit(0);
+            }
+            libc::close(fds[1]);
+            let mut buf = [0u8; 64];
+            let n = libc::read(fds[0], buf.as_mut_ptr() as *mut libc::c_void, buf.len());
+            libc::close(fds[0]);
+            let mut status = 0;
+            assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+            assert!(libc::WIFSIGNALED(status), "child exited normally");
+            assert_eq!(libc::WTERMSIG(status), libc::SIGTERM);
+            assert_eq!(&buf[..n.max(0) as usize], RESTORE);
+        }
+    }
+
+    #[test]
+    fn restore_matches_the_sequences_used_by_get() {
+        assert_eq!(
+            RESTORE,
+            format!("{}{}", CLEAR_SCREEN, ALT_SCREEN_OFF).as_bytes()
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! `login` and `logout`: keep the API key in the OS keychain instead of
+//! the config file.
+
+use std::io::{Read, Write};
+
+use crate::cli::{
+    load_profiled_config, parse_flags, print_login_help, print_logout_help, resolve_globals,
+    CliError, Deps, ParsedArgs,
+};
+use crate::color::{CHECK, DIM, SUCCESS, WARN};
+use crate::config::enable_keychain;
+use crate::passphrase::write_error;
+
+/// Parse flags and resolve globals for login/logout, which take no
+/// positional arguments.
+fn parse_auth_args(
+    command: &str,
+    args: &[String],
+    deps: &mut Deps,
+    print_help: fn(&mut Deps),
+) -> Result<ParsedArgs, i32> {
+    let mut pa = match parse_flags(command, args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_help(deps);
+            return Err(0);
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return Err(2);
+        }
+    };
+    if let Some(extra) = pa.args.first() {
+        let msg = format!("unexpected argument {:?}", extra);
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            &msg,
+        );
+        return Err(2);
+    }
+    let flag_key = std::mem::take(&mut pa.api_key);
+    if let Err(e) = resolve_globals(&mut pa, deps) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return Err(2);
+    }
+    pa.api_key = flag_key;
+    Ok(pa)
+}
+
+pub fn run_login(args: &[String], deps: &mut Deps) -> i32 {
+    let pa = match parse_auth_args("login", args, deps, print_login_help) {
+        Ok(pa) => pa,
+        Err(code) => return code,
+    };
+    let is_tty = (deps.is_tty)();
+    match login(&pa, deps) {
+        Ok(()) => 0,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            1
+        }
+    }
+}
+
+fn login(pa: &ParsedArgs, deps: &mut Deps) -> Result<(), String> {
+    let c = deps.style.color_func((deps.is_tty)());
+
+    // --api-key on the command line, else a hidden prompt, else stdin
+    let key = if !pa.api_key.is_empty() {
+        pa.api_key.clone()
+    } else if (deps.is_tty)() {
+        (deps.read_pass)("API key: ", &mut deps.stderr)
+            .map_err(|e| format!("failed to read API key: {}", e))?
+    } else {
+        let mut buf = String::new();
+        deps.stdin
+            .read_to_string(&mut buf)
+            .map_err(|e| format!("failed to read API key from stdin: {}", e))?;
+        buf
+    };
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("API key must not be empty".into());
+    }
+
+    let info = (deps.make_api)(&pa.base_url, &key)
+        .info()
+        .map_err(|e| format!("could not verify API key with {}: {}", pa.base_url, e))?;
+    if !info.authenticated {
+        return Err(format!("{} did not accept this API key", pa.base_url));
+    }
+
+    let (config, active) = load_profiled_config(&pa.profile, None, deps)?;
+    (deps.set_keychain_secret)(&config.keychain_key("api_key"), &key)?;
+
+    let profile_note = match active {
+        Some((ref name, _)) => format!(" (profile {})", name),
+        None => String::new(),
+    };
+    let _ = writeln!(
+        deps.stderr,
+        "{} Logged in to {}{}; API key saved to OS keychain",
+        c(SUCCESS, deps.style.glyph(CHECK)),
+        pa.base_url,
+        profile_note,
+    );
+
+    // Keychain reads are opt-in; turn them on so the stored key is used.
+    if let Some(path) = enable_keychain(&config, &*deps.getenv)? {
+        let _ = writeln!(
+            deps.stderr,
+            "  {}",
+            c(DIM, &format!("Enabled use_keychain in {}", path.display())),
+        );
+    }
+
+    if (deps.getenv)("SECRET_API_KEY").is_some() {
+        let _ = writeln!(
+            deps.stderr,
+            "{} SECRET_API_KEY is set and takes precedence over the keychain",
+            c(WARN, "warning:"),
+        );
+    } else if config.api_key.is_some() {
+        let _ = writeln!(
+            deps.stderr,
+            "  {}",
+            c(
+                DIM,
+                "The config file also has an api_key; the keychain entry now takes precedence."
+            ),
+        );
+    }
+    Ok(())
+}
+
+pub fn run_logout(args: &[String], deps: &mut Deps) -> i32 {
+    let pa = match parse_auth_args("logout", args, deps, print_logout_help) {
+        Ok(pa) => pa,
+        Err(code) => return code,
+    };
+    let is_tty = (deps.is_tty)();
+    let c = deps.style.color_func(is_tty);
+    let result = load_profiled_config(&pa.profile, None, deps)
+        .and_then(|(config, _)| (deps.delete_keychain_secret)(&config.keychain_key("api_key")));
+    match result {
+        Ok(()) => {
+            let _ = writeln!(
+                deps.stderr,
+                "{} Removed API key for {} from OS keychain",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                pa.base_url,
+            );
+            0
+        }
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            1
+        }
+    }
+}
@@ -0,0 +1,993 @@
+//! Batch send and get.
+//!
+//! `send --batch` creates one distinct secret per recipient from a CSV or
+//! NDJSON file and writes the links to a results file. `get --batch` claims
+//! a list of share links and reports each one, continuing past failures.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::burn::resolve_target;
+use crate::cli::{Deps, ParsedArgs};
+use crate::client::{CreateRequest, SecretApi};
+use crate::color::{ARROW, CHECK, CROSS, DIM, ERROR, LABEL, LOCK, SUCCESS, WARN};
+use crate::envelope::{self, format_share_link, EnvelopeError, OpenParams, SealParams};
+use crate::fileutil::{extract_file_hint, resolve_output_path};
+use crate::gen::generate_password_from_args;
+use crate::history::{now_rfc3339, HistoryEntry};
+use crate::info::{check_envelope_size, check_ttl, fetch_info_cached, min_envelope_size};
+use crate::passphrase::{
+    has_explicit_passphrase, resolve_passphrase, resolve_passphrase_for_send, write_error,
+};
+use crate::sanitize::for_terminal;
+use crate::send::record_history;
+
+/// Value that asks for a generated password instead of a literal secret.
+pub const GEN_VALUE: &str = "gen";
+
+/// Retries for a row rejected by the server's rate limiter.
+const RATE_LIMIT_RETRIES: u32 = 3;
+
+/// One input row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRow {
+    /// 1-based line (CSV record start or NDJSON line) for error messages.
+    pub line: usize,
+    pub recipient: String,
+    pub value: String,
+    pub ttl: String,
+    pub label: String,
+}
+
+/// Outcome for one row, as written to the results file.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct BatchResult {
+    pub recipient: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub share_link: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub expires_at: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub password: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub error: String,
+}
+
+const RESULT_COLUMNS: &[&str] = &[
+    "recipient",
+    "label",
+    "id",
+    "share_link",
+    "expires_at",
+    "password",
+    "error",
+];
+
+fn is_ndjson_path(path: &str) -> bool {
+    path.ends_with(".ndjson") || path.ends_with(".jsonl")
+}
+
+/// Parse batch input. NDJSON is used for `.ndjson`/`.jsonl` files or when
+/// the first non-blank line starts with `{`; anything else is CSV with a
+/// header row.
+pub fn parse_batch(data: &str, path: &str) -> Result<Vec<BatchRow>, String> {
+    let first = data.lines().map(str::trim).find(|l| !l.is_empty());
+    let rows = if is_ndjson_path(path) || first.is_some_and(|l| l.starts_with('{')) {
+        parse_ndjson(data)?
+    } else {
+        parse_csv_rows(data)?
+    };
+    if rows.is_empty() {
+        return Err("batch input has no rows".into());
+    }
+    for row in &rows {
+        if row.recipient.is_empty() {
+            return Err(format!("line {}: recipient is required", row.line));
+        }
+        if row.value.is_empty() {
+            return Err(format!("line {}: value is required", row.line));
+        }
+        if !row.ttl.is_empty() {
+            envelope::parse_ttl(&row.ttl).map_err(|e| format!("line {}: {}", row.line, e))?;
+        }
+    }
+    Ok(rows)
+}
+
+fn parse_ndjson(data: &str) -> Result<Vec<BatchRow>, String> {
+    #[derive(serde::Deserialize)]
+    struct Line {
+        #[serde(default)]
+        recipient: String,
+        #[serde(default)]
+        value: String,
+        #[serde(default)]
+        ttl: String,
+        #[serde(default)]
+        label: String,
+    }
+
+    let mut rows = Vec::new();
+    for (n, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let l: Line = serde_json::from_str(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        rows.push(BatchRow {
+            line: n + 1,
+            recipient: l.recipient,
+            value: l.value,
+            ttl: l.ttl,
+            label: l.label,
+        });
+    }
+    Ok(rows)
+}
+
+fn parse_csv_rows(data: &str) -> Result<Vec<BatchRow>, String> {
+    let mut records = parse_csv(data)?.into_iter();
+    let (_, header) = records.next().ok_or("batch input is empty")?;
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let col = |name: &str| header.iter().position(|h| h == name);
+    let recipient = col("recipient").ok_or("CSV header must include a recipient column")?;
+    let value = col("value").ok_or("CSV header must include a value column")?;
+    let (ttl, label) = (col("ttl"), col("label"));
+    let field = |rec: &[String], i: Option<usize>| {
+        i.and_then(|i| rec.get(i))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    Ok(records
+        .filter(|(_, rec)| !rec.iter().all(|f| f.trim().is_empty()))
+        .map(|(line, rec)| BatchRow {
+            line,
+            recipient: field(&rec, Some(recipient)),
+            // Secret values are taken verbatim.
+            value: rec.get(value).cloned().unwrap_or_default(),
+            ttl: field(&rec, ttl),
+            label: field(&rec, label),
+        })
+        .collect())
+}
+
+/// Minimal RFC 4180 reader: quoted fields may contain commas, newlines and
+/// doubled quotes. Returns each record with the line it starts on.
+fn parse_csv(data: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = data.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(ch);
+                }
+                _ => field.push(ch),
+            }
+            continue;
+        }
+        match ch {
+            '"' if field.is_empty() => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            _ => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err(format!("line {}: unterminated quoted field", start));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    Ok(records)
+}
+
+fn csv_escape(v: &str) -> String {
+    if v.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", v.replace('"', "\"\""))
+    } else {
+        v.to_string()
+    }
+}
+
+/// Writes results as they are produced, so a partial run still leaves a
+/// usable record of the links already created. Without `--results` the
+/// results go to stdout.
+struct ResultWriter {
+    file: Option<fs::File>,
+    ndjson: bool,
+}
+
+impl ResultWriter {
+    fn open(pa: &ParsedArgs) -> Result<Self, String> {
+        let file = if pa.results.is_empty() || pa.results == "-" {
+            None
+        } else {
+            Some(create_private(Path::new(&pa.results))?)
+        };
+        Ok(ResultWriter {
+            file,
+            ndjson: pa.json || is_ndjson_path(&pa.results),
+        })
+    }
+
+    fn write_line(&mut self, stdout: &mut dyn Write, line: &str) -> Result<(), String> {
+        let out: &mut dyn Write = match self.file {
+            Some(ref mut f) => f,
+            None => stdout,
+        };
+        writeln!(out, "{}", line)
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("write results: {}", e))
+    }
+
+    fn header(&mut self, stdout: &mut dyn Write) -> Result<(), String> {
+        if self.ndjson {
+            return Ok(());
+        }
+        self.write_line(stdout, &RESULT_COLUMNS.join(","))
+    }
+
+    fn write(&mut self, stdout: &mut dyn Write, r: &BatchResult) -> Result<(), String> {
+        let line = if self.ndjson {
+            serde_json::to_string(r).unwrap()
+        } else {
+            [
+                &r.recipient,
+                &r.label,
+                &r.id,
+                &r.share_link,
+                &r.expires_at,
+                &r.password,
+                &r.error,
+            ]
+            .iter()
+            .map(|v| csv_escape(v))
+            .collect::<Vec<_>>()
+            .join(",")
+        };
+        self.write_line(stdout, &line)
+    }
+}
+
+/// Results contain live links, so the file is created owner-only.
+fn create_private(path: &Path) -> Result<fs::File, String> {
+    open_private(path, false)
+}
+
+/// Open `path` for writing with mode 0600. With `new_only`, an existing
+/// file is an error rather than being truncated.
+fn open_private(path: &Path, new_only: bool) -> Result<fs::File, String> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true);
+    if new_only {
+        opts.create_new(true);
+    } else {
+        opts.create(true).truncate(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    opts.open(path)
+        .map_err(|e| format!("create {}: {}", path.display(), e))
+}
+
+fn read_batch_input(pa: &ParsedArgs, deps: &mut Deps) -> Result<String, String> {
+    if pa.batch == "-" {
+        let mut data = String::new();
+        deps.stdin
+            .read_to_string(&mut data)
+            .map_err(|e| format!("read stdin: {}", e))?;
+        Ok(data)
+    } else {
+        fs::read_to_string(&pa.batch).map_err(|e| format!("read {}: {}", pa.batch, e))
+    }
+}
+
+/// Entry point for `send --batch`. Returns the exit code.
+pub fn run_send_batch(pa: &ParsedArgs, deps: &mut Deps) -> i32 {
+    let is_tty = (deps.is_tty)();
+
+    let conflict = if !pa.text.is_empty() || !pa.file.is_empty() || !pa.args.is_empty() {
+        Some("--batch cannot be combined with --text, --file, or gen")
+    } else if pa.wait {
+        Some("--wait cannot be used with --batch")
+    } else if !pa.label.is_empty() {
+        Some("--label cannot be used with --batch; use a label column instead")
+    } else if pa.batch == "-" && pa.passphrase_prompt {
+        Some("--passphrase-prompt cannot be used when the batch is read from stdin")
+    } else {
+        None
+    };
+    if let Some(msg) = conflict {
+        write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, msg);
+        return 2;
+    }
+
+    let rows = match read_batch_input(pa, deps).and_then(|d| parse_batch(&d, &pa.batch)) {
+        Ok(r) => r,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 2;
+        }
+    };
+    if !pa.ttl.is_empty() {
+        if let Err(e) = envelope::parse_ttl(&pa.ttl) {
+            write_error(
+                &mut deps.stderr,
+                &deps.style,
+                pa.json,
+                is_tty,
+                &e.to_string(),
+            );
+            return 2;
+        }
+    }
+
+    // One passphrase for the whole batch.
+    let passphrase = match resolve_passphrase_for_send(pa, deps) {
+        Ok(p) => p,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 2;
+        }
+    };
+
+    let mut writer = match ResultWriter::open(pa).and_then(|mut w| {
+        w.header(&mut *deps.stdout)?;
+        Ok(w)
+    }) {
+        Ok(w) => w,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 1;
+        }
+    };
+
+    // A single client is reused for every row so the connection is shared.
+    let client = (deps.make_api)(&pa.base_url, &pa.api_key);
+    let server_info = fetch_info_cached(&*client, &*deps.getenv, &pa.base_url, &pa.api_key);
+    let authed = !pa.api_key.is_empty();
+    let c = deps.style.color_func(is_tty);
+    let show_progress = !pa.json && !pa.silent;
+
+    let mut failed = 0;
+    for row in &rows {
+        let mut result = BatchResult {
+            recipient: row.recipient.clone(),
+            label: row.label.clone(),
+            ..Default::default()
+        };
+        match send_row(
+            &*client,
+            row,
+            &passphrase,
+            server_info.as_ref(),
+            authed,
+            pa,
+            deps,
+        ) {
+            Ok(sent) => {
+                if show_progress {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}",
+                        c(SUCCESS, deps.style.glyph(CHECK)),
+                        row.recipient
+                    );
+                }
+                result.id = sent.id;
+                result.share_link = sent.share_link;
+                result.expires_at = sent.expires_at;
+                result.password = sent.password;
+            }
+            Err(e) => {
+                failed += 1;
+                if !pa.json {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}: {}",
+                        c(ERROR, deps.style.glyph(CROSS)),
+                        row.recipient,
+                        e
+                    );
+                }
+                result.error = e;
+            }
+        }
+        if let Err(e) = writer.write(&mut *deps.stdout, &result) {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 1;
+        }
+    }
+
+    if !pa.json && (!pa.silent || failed > 0) {
+        let noun = if rows.len() == 1 { "secret" } else { "secrets" };
+        let _ = write!(
+            deps.stderr,
+            "Sent {} of {} {}",
+            rows.len() - failed,
+            rows.len(),
+            noun
+        );
+        if failed > 0 {
+            let _ = write!(deps.stderr, "; {} failed", failed);
+        }
+        if pa.results.is_empty() || pa.results == "-" {
+            let _ = writeln!(deps.stderr, ".");
+        } else {
+            let _ = writeln!(deps.stderr, ". Results written to {}.", pa.results);
+        }
+    }
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+struct SentRow {
+    id: String,
+    share_link: String,
+    expires_at: String,
+    password: String,
+}
+
+fn send_row(
+    client: &dyn SecretApi,
+    row: &BatchRow,
+    passphrase: &str,
+    server_info: Option<&crate::client::InfoResponse>,
+    authed: bool,
+    pa: &ParsedArgs,
+    deps: &mut Deps,
+) -> Result<SentRow, String> {
+    let password = if row.value == GEN_VALUE {
+        generate_password_from_args(pa, &*deps.rand_bytes)?
+    } else {
+        String::new()
+    };
+    let plaintext = if password.is_empty() {
+        row.value.as_bytes().to_vec()
+    } else {
+        password.as_bytes().to_vec()
+    };
+
+    let ttl = if row.ttl.is_empty() {
+        &pa.ttl
+    } else {
+        &row.ttl
+    };
+    let ttl_seconds = if ttl.is_empty() {
+        None
+    } else {
+        Some(envelope::parse_ttl(ttl).map_err(|e| e.to_string())?)
+    };
+
+    if let Some(info) = server_info {
+        ttl_seconds.map_or(Ok(()), |t| check_ttl(t, info))?;
+        check_envelope_size(min_envelope_size(plaintext.len()), false, info, authed)?;
+    }
+
+    let plaintext_len = plaintext.len();
+    let sealed = envelope::seal(SealParams {
+        plaintext,
+        passphrase: passphrase.to_string(),
+        rand_bytes: &*deps.rand_bytes,
+        hint: None,
+        iterations: 0,
+    })
+    .map_err(|e| format!("encryption failed: {}", e))?;
+
+    if let Some(info) = server_info {
+        let size = serde_json::to_vec(&sealed.envelope)
+            .map(|v| v.len())
+            .unwrap_or(0);
+        check_envelope_size(size, true, info, authed)?;
+    }
+
+    let mut retries = 0;
+    let resp = loop {
+        let req = CreateRequest {
+            envelope: sealed.envelope.clone(),
+            claim_hash: sealed.claim_hash.clone(),
+            ttl_seconds,
+        };
+        match client.create(req) {
+            Err(e) if e.contains("(429)") && retries < RATE_LIMIT_RETRIES => {
+                retries += 1;
+                (deps.sleep)(Duration::from_secs(1 << retries));
+            }
+            other => break other?,
+        }
+    };
+
+    if pa.history {
+        record_history(
+            deps,
+            HistoryEntry {
+                id: resp.id.clone(),
+                base_url: pa.base_url.clone(),
+                label: row.label.clone(),
+                size: plaintext_len as u64,
+                created_at: now_rfc3339(),
+                expires_at: resp.expires_at.clone(),
+                passphrase: !passphrase.is_empty(),
+                burned_at: String::new(),
+            },
+        );
+    }
+
+    Ok(SentRow {
+        share_link: format_share_link(&resp.share_url, &sealed.url_key),
+        id: resp.id,
+        expires_at: resp.expires_at,
+        password,
+    })
+}
+
+/// Report for one link in `get --batch`.
+#[derive(Debug, Default, serde::Serialize)]
+struct GetResult {
+    line: usize,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    id: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plaintext: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plaintext_base64: Option<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    filename: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    mime: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    expires_at: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    error: String,
+}
+
+/// Share links to claim, one per line. Blank lines and `#` comments are
+/// skipped; returns (line number, link).
+pub fn parse_link_list(data: &str) -> Vec<(usize, String)> {
+    data.lines()
+        .enumerate()
+        .map(|(n, l)| (n + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+        .map(|(n, l)| (n, l.to_string()))
+        .collect()
+}
+
+/// How each envelope in a batch is decrypted.
+struct Decryptor {
+    /// Set by -p, --passphrase-env, --passphrase-file or --passphrase-cmd:
+    /// the only one tried.
+    explicit: Option<String>,
+    /// Configured passphrases, plus any entered at the prompt.
+    candidates: Vec<String>,
+    /// Prompt on a TTY when no candidate matches.
+    can_prompt: bool,
+}
+
+impl Decryptor {
+    fn open(
+        &mut self,
+        env: &serde_json::Value,
+        url_key: &[u8],
+        id: &str,
+        deps: &mut Deps,
+    ) -> Result<Vec<u8>, String> {
+        let try_open = |passphrase: &str| {
+            envelope::open(OpenParams {
+                envelope: env.clone(),
+                url_key: url_key.to_vec(),
+                passphrase: passphrase.to_string(),
+            })
+        };
+
+        if let Some(ref p) = self.explicit {
+            return try_open(p).map_err(|e| match e {
+                EnvelopeError::DecryptionFailed => "wrong passphrase".to_string(),
+                e => e.to_string(),
+            });
+        }
+
+        let needs_pass = envelope::requires_passphrase(env);
+        if !needs_pass {
+            return try_open("").map_err(|e| match e {
+                EnvelopeError::DecryptionFailed => "decryption failed".to_string(),
+                e => e.to_string(),
+            });
+        }
+        for candidate in &self.candidates {
+            match try_open(candidate) {
+                Ok(p) => return Ok(p),
+                Err(EnvelopeError::DecryptionFailed) => continue,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        if !self.can_prompt {
+            return Err(if self.candidates.is_empty() {
+                "passphrase-protected; use -p, --passphrase-env, --passphrase-file, or --passphrase-cmd".into()
+            } else {
+                format!(
+                    "passphrase-protected; none of {} configured passphrase(s) matched",
+                    self.candidates.len()
+                )
+            });
+        }
+
+        let c = deps.style.color_func(true);
+        let _ = writeln!(
+            deps.stderr,
+            "{} {}",
+            c(WARN, deps.style.glyph(LOCK)),
+            c(DIM, &format!("{} is passphrase-protected", id))
+        );
+        loop {
+            let prompt = format!("{} ", c(LABEL, "Passphrase:"));
+            let passphrase = match (deps.read_pass)(&prompt, &mut deps.stderr) {
+                Ok(p) if !p.is_empty() => p,
+                Ok(_) => return Err("no passphrase entered".into()),
+                Err(e) => return Err(format!("read passphrase: {}", e)),
+            };
+            match try_open(&passphrase) {
+                Ok(p) => {
+                    // Links in one batch often share a passphrase.
+                    self.candidates.push(passphrase);
+                    return Ok(p);
+                }
+                Err(EnvelopeError::DecryptionFailed) => {
+                    let _ = writeln!(deps.stderr, "{}", c(WARN, "Wrong passphrase, try again."));
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+/// Entry point for `get --batch`. Returns the exit code.
+pub fn run_get_batch(pa: &ParsedArgs, deps: &mut Deps) -> i32 {
+    let is_tty = (deps.is_tty)();
+
+    let conflict = if !pa.args.is_empty() {
+        Some("a share URL cannot be combined with --batch")
+    } else if !pa.output.is_empty() {
+        Some("--output cannot be used with --batch; use --output-dir")
+    } else {
+        None
+    };
+    if let Some(msg) = conflict {
+        write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, msg);
+        return 2;
+    }
+
+    let links = match read_batch_input(pa, deps) {
+        Ok(d) => parse_link_list(&d),
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 2;
+        }
+    };
+    if links.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            is_tty,
+            "batch input has no links",
+        );
+        return 2;
+    }
+
+    let explicit_flag = has_explicit_passphrase(pa);
+    let explicit = if explicit_flag {
+        match resolve_passphrase(pa, deps) {
+            Ok(p) => Some(p),
+            Err(e) => {
+                write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+                return 2;
+            }
+        }
+    } else {
+        None
+    };
+    let mut candidates: Vec<String> = Vec::new();
+    if !pa.no_passphrase {
+        let configured = std::iter::once(&pa.passphrase_default).chain(&pa.decryption_passphrases);
+        for p in configured {
+            if !p.is_empty() && !candidates.contains(p) {
+                candidates.push(p.clone());
+            }
+        }
+    }
+    let mut decryptor = Decryptor {
+        explicit,
+        candidates,
+        can_prompt: is_tty && pa.batch != "-",
+    };
+
+    let out_dir = if pa.output_dir.is_empty() {
+        None
+    } else {
+        let dir = PathBuf::from(&pa.output_dir);
+        if let Err(e) = create_private_dir(&dir) {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 1;
+        }
+        Some(dir)
+    };
+
+    let c = deps.style.color_func(is_tty);
+    let show_progress = !pa.json && !pa.silent;
+    // Clients are reused per server so each host keeps one connection.
+    let mut clients: HashMap<String, Box<dyn SecretApi>> = HashMap::new();
+    let mut failed = 0;
+
+    for (line, link) in &links {
+        let mut result = GetResult {
+            line: *line,
+            ..Default::default()
+        };
+        let outcome = get_one(link, pa, &mut clients, &mut decryptor, &mut result, deps)
+            .and_then(|plaintext| deliver(&plaintext, out_dir.as_deref(), &mut result));
+        match outcome {
+            Ok(()) => {
+                if show_progress && out_dir.is_some() {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {} {} {}",
+                        c(SUCCESS, deps.style.glyph(CHECK)),
+                        result.id,
+                        deps.style.glyph(ARROW),
+                        result.path
+                    );
+                } else if show_progress {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}",
+                        c(SUCCESS, deps.style.glyph(CHECK)),
+                        result.id
+                    );
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                if !pa.json {
+                    let who = if result.id.is_empty() {
+                        format!("line {}", line)
+                    } else {
+                        result.id.clone()
+                    };
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}: {}",
+                        c(ERROR, deps.style.glyph(CROSS)),
+                        who,
+                        for_terminal(&e)
+                    );
+                }
+                result.error = e;
+            }
+        }
+        // Secrets go to stdout only when there is no output directory; the
+        // per-link report always does with --json.
+        if out_dir.is_none() || pa.json {
+            let line = serde_json::to_string(&result).unwrap();
+            let line = if pa.raw || !(deps.is_stdout_tty)() {
+                line.into()
+            } else {
+                for_terminal(&line)
+            };
+            let _ = writeln!(deps.stdout, "{}", line);
+            let _ = deps.stdout.flush();
+        }
+    }
+
+    if !pa.json && (!pa.silent || failed > 0) {
+        let noun = if links.len() == 1 {
+            "secret"
+        } else {
+            "secrets"
+        };
+        let _ = write!(
+            deps.stderr,
+            "Retrieved {} of {} {}",
+            links.len() - failed,
+            links.len(),
+            noun
+        );
+        if failed > 0 {
+            let _ = writeln!(deps.stderr, "; {} failed.", failed);
+        } else {
+            let _ = writeln!(deps.stderr, ".");
+        }
+    }
+
+    if failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Claim and decrypt one link, filling in the report's metadata.
+fn get_one(
+    link: &str,
+    pa: &ParsedArgs,
+    clients: &mut HashMap<String, Box<dyn SecretApi>>,
+    decryptor: &mut Decryptor,
+    result: &mut GetResult,
+    deps: &mut Deps,
+) -> Result<Vec<u8>, String> {
+    let (id, url_key) =
+        envelope::parse_share_url(link).map_err(|e| format!("invalid share URL: {}", e))?;
+    result.id = id.clone();
+    let (_, base_url) = resolve_target(link, pa, deps)?;
+    let claim_token = envelope::derive_claim_token(&url_key)
+        .map_err(|e| format!("key derivation failed: {}", e))?;
+
+    let client = clients
+        .entry(base_url.clone())
+        .or_insert_with(|| (deps.make_api)(&base_url, &pa.api_key));
+    let resp = client
+        .claim(&id, &claim_token)
+        .map_err(|e| format!("get failed: {}", e))?;
+    result.expires_at = resp.expires_at.clone();
+    if let Some(fh) = extract_file_hint(&resp.envelope) {
+        result.filename = fh.filename;
+        result.mime = fh.mime;
+    }
+    decryptor.open(&resp.envelope, &url_key, &id, deps)
+}
+
+/// Write a decrypted secret into the output directory, or into the report
+/// for NDJSON output.
+fn deliver(plaintext: &[u8], out_dir: Option<&Path>, result: &mut GetResult) -> Result<(), String> {
+    let Some(dir) = out_dir else {
+        match std::str::from_utf8(plaintext) {
+            Ok(text) => result.plaintext = Some(text.to_string()),
+            Err(_) => {
+                use base64::engine::general_purpose::STANDARD;
+                use base64::Engine;
+                result.plaintext_base64 = Some(STANDARD.encode(plaintext));
+            }
+        }
+        return Ok(());
+    };
+
+    // The file hint name is already sanitized to a bare filename.
+    let name = if result.filename.is_empty() {
+        result.id.clone()
+    } else {
+        result.filename.clone()
+    };
+    let path = resolve_output_path(&dir.join(name).to_string_lossy())?;
+    open_private(&path, true).and_then(|mut f| {
+        f.write_all(plaintext)
+            .map_err(|e| format!("write {}: {}", path.display(), e))
+    })?;
+    result.path = path.to_string_lossy().into_owned();
+    Ok(())
+}
+
+fn create_private_dir(dir: &Path) -> Result<(), String> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(dir)
+        .map_err(|e| format!("create {}: {}", dir.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_with_quotes_and_optional_columns() {
+        let data = "Recipient,value,label\n\
+                    alice@example.com,\"a,b \"\"c\"\"\",prod\n\
+                    \n\
+                    bob@example.com,gen,\r\n";
+        let rows = parse_batch(data, "in.csv").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].value, "a,b \"c\"");
+        assert_eq!(rows[0].label, "prod");
+        assert_eq!(rows[0].ttl, "");
+        assert_eq!(rows[1].recipient, "bob@example.com");
+        assert_eq!(rows[1].value, "gen");
+        assert_eq!(rows[1].line, 4);
+    }
+
+    #[test]
+    fn csv_multiline_field_tracks_lines() {
+        let data = "recipient,value\na,\"line1\nline2\"\nb,x\n";
+        let rows = parse_batch(data, "in.csv").unwrap();
+        assert_eq!(rows[0].value, "line1\nline2");
+        assert_eq!(rows[1].line, 4);
+    }
+
+    #[test]
+    fn csv_requires_columns() {
+        let err = parse_batch("name,value\na,b\n", "in.csv").unwrap_err();
+        assert!(err.contains("recipient"), "{}", err);
+        let err = parse_batch("recipient,value\na,\n", "in.csv").unwrap_err();
+        assert!(err.contains("line 2: value is required"), "{}", err);
+    }
+
+    #[test]
+    fn csv_unterminated_quote() {
+        let err = parse_batch("recipient,value\na,\"oops\n", "in.csv").unwrap_err();
+        assert!(err.contains("unterminated"), "{}", err);
+    }
+
+    #[test]
+    fn ndjson_detected_by_content() {
+        let data = "{\"recipient\":\"a\",\"value\":\"s\",\"ttl\":\"1h\"}\n\n{\"recipient\":\"b\",\"value\":\"gen\"}\n";
+        let rows = parse_batch(data, "-").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].ttl, "1h");
+        assert_eq!(rows[1].line, 3);
+    }
+
+    #[test]
+    fn invalid_row_ttl_rejected() {
+        let err = parse_batch("recipient,value,ttl\na,b,forever\n", "in.csv").unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+    }
+
+    #[test]
+    fn link_list_skips_blanks_and_comments() {
+        let links = parse_link_list("# vendor creds\n\n  https://a/s/x#k  \nhttps://b/s/y#k\n");
+        assert_eq!(
+            links,
+            vec![
+                (3, "https://a/s/x#k".to_string()),
+                (4, "https://b/s/y#k".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_escape_quotes_when_needed() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("a,b"), "\"a,b\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -1,27 +1,69 @@
-use std::io::Write;
+use std::io::{BufRead, Read, Write};
 
-use crate::cli::{parse_flags, print_burn_help, resolve_globals, CliError, Deps};
-use crate::color::{color_func, SUCCESS};
+use crate::cli::{
+    parse_flags, print_burn_help, resolve_api_key, resolve_globals, CliError, Deps, ParsedArgs,
+};
+use crate::color::{CHECK, CROSS, DIM, ERROR, SUCCESS};
 use crate::envelope;
+use crate::history::{
+    history_path_with, load_history, mark_burned, parse_sent_since, HistoryFilter,
+};
 use crate::passphrase::write_error;
+use crate::sanitize::for_terminal;
+
+/// A secret selected for burning.
+struct BurnTarget {
+    id: String,
+    base_url: String,
+    label: String,
+}
 
 pub fn run_burn(args: &[String], deps: &mut Deps) -> i32 {
-    let mut pa = match parse_flags(args) {
+    let mut pa = match parse_flags("burn", args) {
         Ok(pa) => pa,
         Err(CliError::ShowHelp) => {
             print_burn_help(deps);
             return 0;
         }
         Err(CliError::Error(e)) => {
-            write_error(&mut deps.stderr, false, (deps.is_tty)(), &e);
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
             return 2;
         }
     };
-    resolve_globals(&mut pa, deps);
+    if let Err(e) = resolve_globals(&mut pa, deps).and_then(|_| resolve_api_key(&mut pa)) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    let from_history =
+        pa.last || pa.all_unexpired || !pa.label.is_empty() || !pa.sent_since.is_empty();
+    let bulk = from_history || !pa.from_file.is_empty();
+
+    if bulk && !pa.args.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "an explicit ID cannot be combined with --from-file or history selectors",
+        );
+        return 2;
+    }
+    if from_history && !pa.from_file.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "--from-file cannot be combined with history selectors",
+        );
+        return 2;
+    }
 
-    if pa.args.is_empty() {
+    if !bulk && pa.args.is_empty() {
         write_error(
             &mut deps.stderr,
+            &deps.style,
             pa.json,
             (deps.is_tty)(),
             "secret ID or share URL is required",
@@ -32,6 +74,7 @@ pub fn run_burn(args: &[String], deps: &mut Deps) -> i32 {
     if pa.api_key.is_empty() {
         write_error(
             &mut deps.stderr,
+            &deps.style,
             pa.json,
             (deps.is_tty)(),
             "--api-key is required for burn",
@@ -39,51 +82,31 @@ pub fn run_burn(args: &[String], deps: &mut Deps) -> i32 {
         return 2;
     }
 
-    // Extract ID: might be a share URL or bare ID
-    let id_or_url = &pa.args[0];
-    let mut secret_id = id_or_url.clone();
-    let mut base_url = pa.base_url.clone();
+    if bulk {
+        return run_burn_bulk(&pa, from_history, deps);
+    }
 
-    if id_or_url.contains('/') || id_or_url.contains('#') {
-        match envelope::parse_share_url(id_or_url) {
-            Ok((id, _)) => {
-                secret_id = id;
-                // Derive base URL from share URL if not explicitly set via flag/env
-                if !pa.base_url_from_flag
-                    && (deps.getenv)("SECRET_BASE_URL").is_none()
-                    && id_or_url.contains("://")
-                {
-                    if let Some(scheme_end) = id_or_url.find("://") {
-                        let after_scheme = &id_or_url[scheme_end + 3..];
-                        if let Some(path_start) = after_scheme.find('/') {
-                            base_url = id_or_url[..scheme_end + 3 + path_start].to_string();
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                write_error(
-                    &mut deps.stderr,
-                    pa.json,
-                    (deps.is_tty)(),
-                    &format!("invalid URL: {}", e),
-                );
-                return 2;
-            }
+    let (secret_id, base_url) = match resolve_target(&pa.args[0], &pa, deps) {
+        Ok(t) => t,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
         }
-    }
+    };
 
     let client = (deps.make_api)(&base_url, &pa.api_key);
 
     if let Err(e) = client.burn(&secret_id) {
         write_error(
             &mut deps.stderr,
+            &deps.style,
             pa.json,
             (deps.is_tty)(),
             &format!("burn failed: {}", e),
         );
         return 1;
     }
+    record_burned(deps, &[secret_id]);
 
     if pa.json {
         let _ = writeln!(
@@ -92,11 +115,269 @@ pub fn run_burn(args: &[String], deps: &mut Deps) -> i32 {
             serde_json::to_string(&serde_json::json!({"ok": true})).unwrap()
         );
     } else if (deps.is_tty)() && !pa.silent {
-        let c = color_func(true);
-        let _ = writeln!(deps.stderr, "{} Secret burned.", c(SUCCESS, "\u{2713}"));
+        let c = deps.style.color_func(true);
+        let _ = writeln!(
+            deps.stderr,
+            "{} Secret burned.",
+            c(SUCCESS, deps.style.glyph(CHECK))
+        );
     } else if !pa.silent {
         let _ = writeln!(deps.stderr, "Secret burned.");
     }
 
     0
 }
+
+/// Turn an ID or share URL into (id, base_url). A full share URL supplies
+/// its own base URL unless one was set explicitly via flag or env.
+pub(crate) fn resolve_target(
+    id_or_url: &str,
+    pa: &ParsedArgs,
+    deps: &Deps,
+) -> Result<(String, String), String> {
+    let mut secret_id = id_or_url.to_string();
+    let mut base_url = pa.base_url.clone();
+
+    if id_or_url.contains('/') || id_or_url.contains('#') {
+        let (id, _) =
+            envelope::parse_share_url(id_or_url).map_err(|e| format!("invalid URL: {}", e))?;
+        secret_id = id;
+        // Derive base URL from share URL if not explicitly set via flag/env
+        if !pa.base_url_from_flag
+            && (deps.getenv)("SECRET_BASE_URL").is_none()
+            && id_or_url.contains("://")
+        {
+            if let Some(scheme_end) = id_or_url.find("://") {
+                let after_scheme = &id_or_url[scheme_end + 3..];
+                if let Some(path_start) = after_scheme.find('/') {
+                    base_url = id_or_url[..scheme_end + 3 + path_start].to_string();
+                }
+            }
+        }
+    }
+    Ok((secret_id, base_url))
+}
+
+/// Best-effort: flag burned IDs in the local history so `list` and later
+/// bulk burns skip them.
+fn record_burned(deps: &mut Deps, ids: &[String]) {
+    if ids.is_empty() {
+        return;
+    }
+    if let Some(path) = history_path_with(&*deps.getenv) {
+        if path.exists() {
+            if let Err(e) = mark_burned(&path, ids) {
+                let _ = writeln!(deps.stderr, "warning: could not update send history: {}", e);
+            }
+        }
+    }
+}
+
+/// Select live secrets from the local history. Only secrets sent to the
+/// active server are selected, since the API key belongs to that server;
+/// the others are counted in the returned number so they can be mentioned.
+fn targets_from_history(pa: &ParsedArgs, deps: &Deps) -> Result<(Vec<BurnTarget>, usize), String> {
+    let filter = HistoryFilter {
+        label: pa.label.clone(),
+        sent_since_secs: parse_sent_since(&pa.sent_since)?,
+        unexpired: true,
+        base_url: None,
+    };
+    let path = history_path_with(&*deps.getenv).ok_or("could not determine config directory")?;
+    let now = chrono::Utc::now();
+    let (mut selected, elsewhere): (Vec<BurnTarget>, Vec<BurnTarget>) = load_history(&path)
+        .into_iter()
+        .filter(|e| filter.matches(e, now))
+        .map(|e| BurnTarget {
+            id: e.id,
+            base_url: e.base_url,
+            label: e.label,
+        })
+        .partition(|t| same_server(&t.base_url, &pa.base_url));
+    if pa.last {
+        selected = selected.pop().into_iter().collect();
+    }
+    Ok((selected, elsewhere.len()))
+}
+
+/// Whether two base URLs name the same server.
+fn same_server(a: &str, b: &str) -> bool {
+    a.trim_end_matches('/') == b.trim_end_matches('/')
+}
+
+/// Read IDs or share URLs, one per line, from a file or stdin (`-`).
+/// Blank lines and `#` comments are skipped.
+fn targets_from_file(pa: &ParsedArgs, deps: &mut Deps) -> Result<Vec<BurnTarget>, String> {
+    let mut data = String::new();
+    if pa.from_file == "-" {
+        deps.stdin
+            .read_to_string(&mut data)
+            .map_err(|e| format!("read stdin: {}", e))?;
+    } else {
+        data = std::fs::read_to_string(&pa.from_file)
+            .map_err(|e| format!("read {}: {}", pa.from_file, e))?;
+    }
+
+    let mut targets = Vec::new();
+    for (n, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (id, base_url) =
+            resolve_target(line, pa, deps).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        // The API key is for the active server; don't send it anywhere else
+        if !same_server(&base_url, &pa.base_url) {
+            return Err(format!(
+                "line {}: {} is not on the active server {} (use --base-url or --profile to burn it)",
+                n + 1,
+                base_url,
+                pa.base_url
+            ));
+        }
+        targets.push(BurnTarget {
+            id,
+            base_url,
+            label: String::new(),
+        });
+    }
+    Ok(targets)
+}
+
+fn run_burn_bulk(pa: &ParsedArgs, from_history: bool, deps: &mut Deps) -> i32 {
+    let is_tty = (deps.is_tty)();
+    let selected = if from_history {
+        targets_from_history(pa, deps)
+    } else {
+        targets_from_file(pa, deps).map(|t| (t, 0))
+    };
+    let targets = match selected {
+        Ok((t, elsewhere)) => {
+            if elsewhere > 0 && !pa.json && !pa.silent {
+                let _ = writeln!(
+                    deps.stderr,
+                    "note: skipping {} matching secret(s) sent to other servers; use --base-url or --profile to burn them",
+                    elsewhere
+                );
+            }
+            t
+        }
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 2;
+        }
+    };
+
+    if targets.is_empty() {
+        if pa.json {
+            let out = serde_json::json!({"burned": [], "failed": []});
+            let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+        } else if !pa.silent {
+            let _ = writeln!(deps.stderr, "Nothing to burn: no matching live secrets.");
+        }
+        return 0;
+    }
+
+    let c = deps.style.color_func(is_tty);
+    let noun = if targets.len() == 1 {
+        "secret"
+    } else {
+        "secrets"
+    };
+
+    if !pa.yes {
+        // The prompt reads from stdin, so it needs a terminal that isn't
+        // already supplying the IDs.
+        if !is_tty || pa.from_file == "-" {
+            write_error(
+                &mut deps.stderr,
+                &deps.style,
+                pa.json,
+                is_tty,
+                &format!(
+                    "refusing to burn {} {} without confirmation (use --yes)",
+                    targets.len(),
+                    noun
+                ),
+            );
+            return 2;
+        }
+        for t in &targets {
+            let _ = writeln!(deps.stderr, "  {}  {}", t.id, c(DIM, &t.label));
+        }
+        let _ = write!(deps.stderr, "Burn {} {}? [y/N] ", targets.len(), noun);
+        let _ = deps.stderr.flush();
+        let mut answer = String::new();
+        let _ = std::io::BufReader::new(&mut *deps.stdin).read_line(&mut answer);
+        if !matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+            let _ = writeln!(deps.stderr, "Aborted.");
+            return 1;
+        }
+    }
+
+    let mut burned: Vec<String> = Vec::new();
+    let mut failed: Vec<(String, String)> = Vec::new();
+    for t in &targets {
+        let client = (deps.make_api)(&t.base_url, &pa.api_key);
+        match client.burn(&t.id) {
+            Ok(()) => {
+                if !pa.json && !pa.silent {
+                    let label = if t.label.is_empty() {
+                        String::new()
+                    } else {
+                        format!("  {}", c(DIM, &t.label))
+                    };
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}{}",
+                        c(SUCCESS, deps.style.glyph(CHECK)),
+                        t.id,
+                        label
+                    );
+                }
+                burned.push(t.id.clone());
+            }
+            Err(e) => {
+                if !pa.json {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}: {}",
+                        c(ERROR, deps.style.glyph(CROSS)),
+                        for_terminal(&t.id),
+                        for_terminal(&e)
+                    );
+                }
+                failed.push((t.id.clone(), e));
+            }
+        }
+    }
+    record_burned(deps, &burned);
+
+    if pa.json {
+        let failed_json: Vec<serde_json::Value> = failed
+            .iter()
+            .map(|(id, e)| serde_json::json!({"id": id, "error": e}))
+            .collect();
+        let out = serde_json::json!({"burned": burned, "failed": failed_json});
+        let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+    } else if !pa.silent || !failed.is_empty() {
+        let _ = write!(
+            deps.stderr,
+            "Burned {} of {} {}",
+            burned.len(),
+            targets.len(),
+            noun
+        );
+        if failed.is_empty() {
+            let _ = writeln!(deps.stderr, ".");
+        } else {
+            let _ = writeln!(deps.stderr, "; {} failed.", failed.len());
+        }
+    }
+
+    if failed.is_empty() {
+        0
+    } else {
+        1
+    }
+}
@@ -1,22 +1,76 @@
 use std::io::{self, Read, Write};
 
+use crate::auth::{run_login, run_logout};
 use crate::burn::run_burn;
 use crate::client::SecretApi;
-use crate::color::{color_func, ARG, CMD, DIM, HEADING, OPT, SUCCESS};
-use crate::completion::{BASH_COMPLETION, FISH_COMPLETION, ZSH_COMPLETION};
+use crate::color::{
+    self, ColorMode, Style, ARG, CHECK, CMD, DASH, DIM, HEADING, LABEL, OPT, SUCCESS, WARN,
+};
+use crate::credential::{run_credential_command, run_credential_list_command, COMMAND_TIMEOUT};
+use crate::doctor::run_doctor;
+use crate::flags::{self, Arg};
 use crate::gen::run_gen;
 use crate::get::run_get;
+use crate::help;
+use crate::history::run_list;
+use crate::info::run_info;
+use crate::man::run_man;
 use crate::send::run_send;
+use crate::watch::run_watch;
 
 const DEFAULT_BASE_URL: &str = "https://secrt.ca";
+
+/// Commands listed in the main help, in display order. Completion scripts
+/// are generated from the same list.
+pub const TOP_LEVEL_COMMANDS: &[(&str, &str)] = &[
+    ("send", "Encrypt and upload a secret"),
+    ("get", "Retrieve and decrypt a secret"),
+    ("burn", "Destroy a secret (requires API key)"),
+    ("info", "Show server limits and account quota"),
+    ("login", "Store an API key in the OS keychain"),
+    ("logout", "Remove the stored API key"),
+    ("list", "List secrets you have sent (local history)"),
+    ("watch", "Wait until a secret is claimed"),
+    ("dev-server", "Run a local in-memory server for testing"),
+    ("gen", "Generate a random password"),
+    ("config", "Show or initialize configuration"),
+    ("doctor", "Check config, keychain and server connectivity"),
+    ("version", "Show version"),
+    ("help", "Show this help"),
+    ("completion", "Output shell completion script"),
+    ("man", "Output a man page"),
+];
+
+/// Subcommands of `secrt config`.
+pub const CONFIG_SUBCOMMANDS: &[&str] = &[
+    "init",
+    "path",
+    "set",
+    "get",
+    "unset",
+    "lock",
+    "unlock",
+    "set-passphrase",
+    "delete-passphrase",
+    "passphrases",
+];
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub type GetenvFn = Box<dyn Fn(&str) -> Option<String>>;
 pub type RandBytesFn = Box<dyn Fn(&mut [u8]) -> Result<(), crate::envelope::EnvelopeError>>;
 pub type ReadPassFn = Box<dyn Fn(&str, &mut dyn Write) -> io::Result<String>>;
+pub type ReadKeyFn = Box<dyn Fn(&str, &mut dyn Write) -> io::Result<()>>;
 pub type MakeApiFn = Box<dyn Fn(&str, &str) -> Box<dyn SecretApi>>;
 pub type KeychainGetFn = Box<dyn Fn(&str) -> Option<String>>;
 pub type KeychainListFn = Box<dyn Fn(&str) -> Vec<String>>;
+pub type KeychainSetFn = Box<dyn Fn(&str, &str) -> Result<(), String>>;
+pub type KeychainDeleteFn = Box<dyn Fn(&str) -> Result<(), String>>;
+pub type SleepFn = Box<dyn Fn(std::time::Duration)>;
+pub type ClipboardCopyFn = Box<dyn Fn(&str, &[u8]) -> Result<(), String>>;
+pub type ClipboardReadFn = Box<dyn Fn(&str) -> Result<Vec<u8>, String>>;
+pub type ClipboardProbeFn = Box<dyn Fn(&str) -> Result<(), String>>;
+pub type ClipboardClearFn = Box<dyn Fn(&str, &[u8], std::time::Duration) -> Result<(), String>>;
+pub type RunCommandFn = Box<dyn Fn(&[String], &[(String, String)]) -> Result<i32, String>>;
 
 /// Injectable dependencies for testing.
 pub struct Deps {
@@ -28,9 +82,25 @@ pub struct Deps {
     pub getenv: GetenvFn,
     pub rand_bytes: RandBytesFn,
     pub read_pass: ReadPassFn,
+    /// Show a prompt and wait for any key.
+    pub read_key: ReadKeyFn,
     pub make_api: MakeApiFn,
     pub get_keychain_secret: KeychainGetFn,
     pub get_keychain_secret_list: KeychainListFn,
+    pub set_keychain_secret: KeychainSetFn,
+    pub delete_keychain_secret: KeychainDeleteFn,
+    pub sleep: SleepFn,
+    /// Clipboard access; each takes the `clipboard_tool` setting.
+    pub copy_to_clipboard: ClipboardCopyFn,
+    pub read_clipboard: ClipboardReadFn,
+    /// Whether the clipboard can be used, without touching its contents.
+    pub clipboard_available: ClipboardProbeFn,
+    pub schedule_clipboard_clear: ClipboardClearFn,
+    /// Run a command with extra environment variables; returns its exit code.
+    pub run_command: RunCommandFn,
+    /// Colors and glyphs for output. Set by `run` from `--color`, `--plain`
+    /// and the environment, then from the config file once it is loaded.
+    pub style: Style,
 }
 
 /// Parsed global and command-specific flags.
@@ -42,6 +112,7 @@ pub struct ParsedArgs {
     pub base_url: String,
     pub base_url_from_flag: bool,
     pub api_key: String,
+    pub profile: String,
     pub json: bool,
 
     // Send
@@ -50,6 +121,21 @@ pub struct ParsedArgs {
     pub file: String,
     pub multi_line: bool,
     pub trim: bool,
+    pub label: String,
+    pub wait: bool,
+    pub batch: String,
+    pub results: String,
+    pub from_clipboard: bool,
+
+    // Send and get
+    pub copy: bool,
+    pub format: String,
+    pub template: String,
+    pub name: String,
+
+    // Watch (also send --wait)
+    pub interval: String,
+    pub timeout: String,
 
     // Input visibility
     pub show: bool,
@@ -62,10 +148,34 @@ pub struct ParsedArgs {
     pub passphrase_prompt: bool,
     pub passphrase_env: String,
     pub passphrase_file: String,
+    pub passphrase_cmd: String,
     pub no_passphrase: bool,
 
     // Get
     pub output: String,
+    pub output_dir: String,
+    pub exec: bool,
+    pub env: String,
+    pub dotenv: bool,
+    pub reveal: bool,
+    pub reveal_for: String,
+    pub raw: bool,
+
+    // List / burn
+    pub sent_since: String,
+    pub unexpired: bool,
+    pub prune: bool,
+    pub last: bool,
+    pub all_unexpired: bool,
+    pub from_file: String,
+    pub yes: bool,
+
+    // Dev server
+    pub listen: String,
+    pub limits: String,
+    pub default_ttl: String,
+    pub max_ttl: String,
+    pub no_rate_limit: bool,
 
     // Gen
     pub gen_length: u32,
@@ -75,9 +185,20 @@ pub struct ParsedArgs {
     pub gen_grouped: bool,
     pub gen_count: u32,
 
+    // Doctor
+    pub fix: bool,
+
     // Populated from config file (not from CLI flags)
     pub passphrase_default: String,
     pub show_default: bool,
+    pub history: bool,
+    pub clipboard_tool: String,
+    pub clipboard_clear: String,
+    /// Credential helpers, run on first use by `resolve_api_key`,
+    /// `resolve_passphrase_default` and `resolve_decryption_passphrases`.
+    pub api_key_command: String,
+    pub passphrase_command: String,
+    pub decryption_passphrases_command: String,
 
     // Decryption passphrase list (from config/keychain, not CLI flags)
     pub decryption_passphrases: Vec<String>,
@@ -91,6 +212,14 @@ pub enum CliError {
 
 /// Main entry point. Returns exit code.
 pub fn run(args: &[String], deps: &mut Deps) -> i32 {
+    let args = match apply_style(args, deps) {
+        Ok(args) => args,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    let args = &args[..];
     if args.len() < 2 {
         print_usage(deps);
         return 2;
@@ -118,10 +247,31 @@ pub fn run(args: &[String], deps: &mut Deps) -> i32 {
         }
         "help" => run_help(remaining, deps),
         "completion" => run_completion(remaining, deps),
+        "man" => run_man(remaining, deps),
+        // Hidden: candidate values for the completion scripts
+        "__complete" => crate::completion::run_complete(remaining, deps),
+        // Hidden: clears the clipboard for `get --copy` once its time is up
+        "__clipboard-clear" => crate::clipboard::run_clear(remaining, deps),
         "config" => run_config(remaining, deps),
         "send" => run_send(remaining, deps),
         "get" => run_get(remaining, deps),
         "burn" => run_burn(remaining, deps),
+        "info" => run_info(remaining, deps),
+        "login" => run_login(remaining, deps),
+        "doctor" => run_doctor(remaining, deps),
+        "logout" => run_logout(remaining, deps),
+        "list" => run_list(remaining, deps),
+        "watch" => run_watch(remaining, deps),
+        #[cfg(feature = "dev-server")]
+        "dev-server" => crate::devserver::run_dev_server(remaining, deps),
+        #[cfg(not(feature = "dev-server"))]
+        "dev-server" => {
+            let _ = writeln!(
+                deps.stderr,
+                "error: this build does not include dev-server; rebuild with --features dev-server"
+            );
+            2
+        }
         "gen" | "generate" => run_gen(remaining, deps),
         _ if looks_like_share_url(command) => {
             // Implicit get: treat share URLs/bare IDs as `secrt get <url>`
@@ -135,6 +285,92 @@ pub fn run(args: &[String], deps: &mut Deps) -> i32 {
     }
 }
 
+/// Take `--color` and `--plain` out of `args` and set the output style from
+/// them and the environment. They may come before or after
+/// the command; values of the command's own flags are skipped over, so
+/// `send --text --plain` still sends the text "--plain".
+fn apply_style(args: &[String], deps: &mut Deps) -> Result<Vec<String>, String> {
+    let mut rest: Vec<String> = args.iter().take(1).cloned().collect();
+    let mut flag_mode = None;
+    let mut plain = false;
+    let mut iter = args.iter().skip(1);
+    while let Some(a) = iter.next() {
+        // Leave alone everything after `--` and the hidden commands' arguments
+        if a == "--" || rest.get(1).is_some_and(|c| c.starts_with("__")) {
+            rest.push(a.clone());
+            rest.extend(iter.cloned());
+            break;
+        }
+        let (flag, inline) = match a.split_once('=') {
+            Some((flag, v)) if a.starts_with("--") => (flag, Some(v)),
+            _ => (a.as_str(), None),
+        };
+        if a == "--plain" {
+            plain = true;
+            continue;
+        }
+        if flag == "--color" {
+            let value = match inline {
+                Some(v) => v,
+                None => iter.next().ok_or("--color requires a value")?,
+            };
+            flag_mode = Some(ColorMode::parse(value).ok_or_else(|| {
+                format!(
+                    "unknown --color {:?} (valid: {})",
+                    value,
+                    color::MODES.join(", ")
+                )
+            })?);
+            continue;
+        }
+        rest.push(a.clone());
+        let command = match rest.get(1).map(String::as_str) {
+            Some("generate") => "gen",
+            Some(c) if looks_like_share_url(c) => "get",
+            Some(c) => c,
+            None => "",
+        };
+        if rest.len() > 2 && inline.is_none() && flags::takes_value(command, a) {
+            if let Some(v) = iter.next() {
+                rest.push(v.clone());
+            }
+        }
+    }
+
+    deps.style = Style::from_flags(flag_mode, plain, &*deps.getenv);
+    Ok(rest)
+}
+
+/// Layer the config file's `color`, `plain` and `[theme]` settings under
+/// the command-line flags. Called by whatever loads the config for the run.
+pub(crate) fn apply_config_style(config: &crate::config::Config, deps: &mut Deps) {
+    let mode = config.color.as_deref().and_then(|v| {
+        let mode = ColorMode::parse(v);
+        if mode.is_none() {
+            let _ = writeln!(
+                deps.stderr,
+                "warning: ignoring color {:?} in config (valid: {})",
+                v,
+                color::MODES.join(", ")
+            );
+        }
+        mode
+    });
+    deps.style = deps.style.with_config(
+        mode,
+        config.plain == Some(true),
+        config.theme.clone(),
+        &*deps.getenv,
+    );
+}
+
+/// Help and usage screens don't otherwise read the config file, so they
+/// load it only for its style settings.
+pub(crate) fn apply_help_style(deps: &mut Deps) {
+    let config = crate::config::load_config_with(&*deps.getenv, &mut io::sink());
+    apply_config_style(&config, deps);
+}
+
 /// Detect whether a string looks like a share URL (contains `#` followed by
 /// a base64url string of >= 22 chars). The threshold prevents false positives
 /// on short fragments while being well below the actual 43-char key length.
@@ -154,13 +390,9 @@ fn run_help(args: &[String], deps: &mut Deps) -> i32 {
         print_help(deps);
         return 0;
     }
-    match args[0].as_str() {
-        "send" => print_send_help(deps),
-        "get" => print_get_help(deps),
-        "burn" => print_burn_help(deps),
-        "gen" | "generate" => print_gen_help(deps),
-        "config" => print_config_help(deps),
-        _ => {
+    match help::page(&args[0]) {
+        Some(page) => help::print(deps, page),
+        None => {
             let _ = writeln!(deps.stderr, "error: unknown command {:?}", args[0]);
             return 2;
         }
@@ -169,39 +401,39 @@ fn run_help(args: &[String], deps: &mut Deps) -> i32 {
 }
 
 fn run_completion(args: &[String], deps: &mut Deps) -> i32 {
+    let supported = crate::completion::SHELLS.join(", ");
     if args.len() != 1 {
         let _ = writeln!(
             deps.stderr,
-            "error: specify a shell (supported: bash, zsh, fish)"
+            "error: specify a shell (supported: {})",
+            supported
         );
         return 2;
     }
-    match args[0].as_str() {
-        "bash" => {
-            let _ = write!(deps.stdout, "{}", BASH_COMPLETION);
-        }
-        "zsh" => {
-            let _ = write!(deps.stdout, "{}", ZSH_COMPLETION);
-        }
-        "fish" => {
-            let _ = write!(deps.stdout, "{}", FISH_COMPLETION);
+    match crate::completion::script(&args[0]) {
+        Some(script) => {
+            let _ = write!(deps.stdout, "{}", script);
+            0
         }
-        _ => {
+        None => {
             let _ = writeln!(
                 deps.stderr,
-                "error: unsupported shell {:?} (supported: bash, zsh, fish)",
-                args[0]
+                "error: unsupported shell {:?} (supported: {})",
+                args[0], supported
             );
-            return 2;
+            2
         }
     }
-    0
 }
 
-/// Parse command-specific flags from args.
-pub fn parse_flags(args: &[String]) -> Result<ParsedArgs, CliError> {
+/// Parse the flags of `command` from args. Only flags in the command's table
+/// (see `flags`) are accepted; anything else is an error that points at the
+/// right command or a similarly spelled flag.
+pub fn parse_flags(command: &str, args: &[String]) -> Result<ParsedArgs, CliError> {
+    let table = flags::command(command);
     let mut pa = ParsedArgs::default();
     let mut positional = Vec::new();
+    let mut seen: Vec<&str> = Vec::new();
 
     let mut i = 0;
     while i < args.len() {
@@ -236,87 +468,156 @@ pub fn parse_flags(args: &[String]) -> Result<ParsedArgs, CliError> {
             (arg.as_str(), None)
         };
 
-        /// Read the value for a flag that requires one, preferring an inline
-        /// `--flag=value` if present, otherwise consuming the next argument.
-        macro_rules! next_val {
-            ($flag_name:expr) => {{
-                if let Some(ref v) = inline_val {
-                    v.clone()
-                } else {
+        let Some(def) = table.find(flag) else {
+            return Err(CliError::Error(flags::unknown_flag_error(command, flag)));
+        };
+        if def.long == "--help" {
+            return Err(CliError::ShowHelp);
+        }
+
+        // Read the value for a flag that requires one, preferring an inline
+        // `--flag=value` if present, otherwise consuming the next argument.
+        let value = match def.arg {
+            Arg::None => {
+                if inline_val.is_some() {
+                    return Err(CliError::Error(format!(
+                        "{} does not take a value",
+                        def.long
+                    )));
+                }
+                String::new()
+            }
+            Arg::Text(_) | Arg::Count(_) => match inline_val {
+                Some(v) => v,
+                None => {
                     i += 1;
                     if i >= args.len() {
-                        return Err(CliError::Error(format!("{} requires a value", $flag_name)));
+                        return Err(CliError::Error(format!("{} requires a value", def.long)));
                     }
                     args[i].clone()
                 }
-            }};
+            },
+        };
+        if matches!(def.arg, Arg::Count(_))
+            && value.parse::<u32>().ok().filter(|&n| n >= 1).is_none()
+        {
+            return Err(CliError::Error(format!(
+                "{} requires a positive integer, got {:?}",
+                def.long, value
+            )));
         }
+        apply_flag(&mut pa, def.long, value);
+        seen.push(def.long);
+        i += 1;
+    }
 
-        match flag {
-            "--help" | "-h" => return Err(CliError::ShowHelp),
-            "--json" => pa.json = true,
-            "--base-url" => {
-                pa.base_url = next_val!("--base-url");
-                pa.base_url_from_flag = true;
-            }
-            "--api-key" => pa.api_key = next_val!("--api-key"),
-            "--ttl" => pa.ttl = next_val!("--ttl"),
-            "--text" => pa.text = next_val!("--text"),
-            "--file" | "-f" => pa.file = next_val!("-f/--file"),
-            "--multi-line" | "-m" => pa.multi_line = true,
-            "--trim" => pa.trim = true,
-            "--show" | "-s" => pa.show = true,
-            "--hidden" => pa.hidden = true,
-            "--silent" => pa.silent = true,
-            "--output" | "-o" => pa.output = next_val!("--output"),
-            "--passphrase-prompt" | "-p" => pa.passphrase_prompt = true,
-            "--no-passphrase" | "-n" => pa.no_passphrase = true,
-            "--passphrase-env" => pa.passphrase_env = next_val!("--passphrase-env"),
-            "--passphrase-file" => pa.passphrase_file = next_val!("--passphrase-file"),
-            // Gen flags
-            "--length" | "-L" => {
-                let val = next_val!("--length");
-                pa.gen_length = val.parse::<u32>().ok().filter(|&n| n >= 1).ok_or_else(|| {
-                    CliError::Error(format!(
-                        "--length requires a positive integer, got {:?}",
-                        val
-                    ))
-                })?;
-            }
-            "--no-symbols" | "-S" => pa.gen_no_symbols = true,
-            "--no-numbers" | "-N" => pa.gen_no_numbers = true,
-            "--no-caps" | "-C" => pa.gen_no_caps = true,
-            "--grouped" | "-G" => pa.gen_grouped = true,
-            "--count" => {
-                let val = next_val!("--count");
-                pa.gen_count = val.parse::<u32>().ok().filter(|&n| n >= 1).ok_or_else(|| {
-                    CliError::Error(format!(
-                        "--count requires a positive integer, got {:?}",
-                        val
-                    ))
-                })?;
-            }
-            _ => return Err(CliError::Error(format!("unknown flag: {}", arg))),
+    for group in table.exclusive {
+        let used: Vec<&str> = group.iter().copied().filter(|f| seen.contains(f)).collect();
+        if used.len() > 1 {
+            return Err(CliError::Error(if group.len() == 2 {
+                format!("{} cannot be combined with {}", used[0], used[1])
+            } else {
+                format!("specify at most one of {}", group.join(", "))
+            }));
         }
-        i += 1;
     }
 
     pa.args = positional;
     Ok(pa)
 }
 
+/// Store a validated flag value in its `ParsedArgs` field.
+fn apply_flag(pa: &mut ParsedArgs, long: &str, value: String) {
+    match long {
+        "--json" => pa.json = true,
+        "--base-url" => {
+            pa.base_url = value;
+            pa.base_url_from_flag = true;
+        }
+        "--api-key" => pa.api_key = value,
+        "--profile" => pa.profile = value,
+        "--ttl" => pa.ttl = value,
+        "--text" => pa.text = value,
+        "--file" => pa.file = value,
+        "--multi-line" => pa.multi_line = true,
+        "--trim" => pa.trim = true,
+        "--from-clipboard" => pa.from_clipboard = true,
+        "--copy" => pa.copy = true,
+        "--format" => {
+            pa.json = value == "json";
+            pa.format = value;
+        }
+        "--template" => pa.template = value,
+        "--name" => pa.name = value,
+        "--label" => pa.label = value,
+        "--wait" => pa.wait = true,
+        "--batch" => pa.batch = value,
+        "--results" => pa.results = value,
+        "--interval" => pa.interval = value,
+        "--timeout" => pa.timeout = value,
+        "--sent-since" => pa.sent_since = value,
+        "--unexpired" => pa.unexpired = true,
+        "--prune" => pa.prune = true,
+        "--last" => pa.last = true,
+        "--all-unexpired" => pa.all_unexpired = true,
+        "--from-file" => pa.from_file = value,
+        "--yes" => pa.yes = true,
+        "--fix" => pa.fix = true,
+        "--listen" => pa.listen = value,
+        "--limits" => pa.limits = value,
+        "--default-ttl" => pa.default_ttl = value,
+        "--max-ttl" => pa.max_ttl = value,
+        "--no-rate-limit" => pa.no_rate_limit = true,
+        "--show" => pa.show = true,
+        "--hidden" => pa.hidden = true,
+        "--silent" => pa.silent = true,
+        "--output" => pa.output = value,
+        "--exec" => pa.exec = true,
+        "--env" => pa.env = value,
+        "--dotenv" => pa.dotenv = true,
+        "--reveal" => pa.reveal = true,
+        "--raw" => pa.raw = true,
+        "--reveal-for" => {
+            pa.reveal = true;
+            pa.reveal_for = value;
+        }
+        "--output-dir" => pa.output_dir = value,
+        "--passphrase-prompt" => pa.passphrase_prompt = true,
+        "--no-passphrase" => pa.no_passphrase = true,
+        "--passphrase-env" => pa.passphrase_env = value,
+        "--passphrase-file" => pa.passphrase_file = value,
+        "--passphrase-cmd" => pa.passphrase_cmd = value,
+        // Gen flags; counts were checked by parse_flags
+        "--length" => pa.gen_length = value.parse().unwrap_or(1),
+        "--no-symbols" => pa.gen_no_symbols = true,
+        "--no-numbers" => pa.gen_no_numbers = true,
+        "--no-caps" => pa.gen_no_caps = true,
+        "--grouped" => pa.gen_grouped = true,
+        "--count" => pa.gen_count = value.parse().unwrap_or(1),
+        // Taken out by `run` before any command parses its flags
+        "--color" | "--plain" => {}
+        _ => unreachable!("flag table entry {} has no ParsedArgs field", long),
+    }
+}
+
 /// Fill in defaults: CLI flag > env var > config file > built-in default.
-pub fn resolve_globals(pa: &mut ParsedArgs, deps: &mut Deps) {
-    let config = crate::config::load_config_with(&*deps.getenv, &mut deps.stderr);
-    resolve_globals_with_config(pa, deps, &config);
+/// The active profile (if any) is layered over the top-level config first;
+/// a share URL in the first positional argument can select it by host.
+pub fn resolve_globals(pa: &mut ParsedArgs, deps: &mut Deps) -> Result<(), String> {
+    let share_url = pa.args.first().cloned();
+    let (config, _) = load_unsealed_config(&pa.profile, share_url.as_deref(), deps)?;
+    resolve_globals_with_config(pa, deps, &config)
 }
 
 /// Inner function that accepts an explicit Config (used by tests).
+/// Secrets resolve env var > credential helper command > keychain >
+/// config value. Helper commands are only recorded here; commands that
+/// need the value run them with the `resolve_*` functions below.
 pub fn resolve_globals_with_config(
     pa: &mut ParsedArgs,
     deps: &Deps,
     config: &crate::config::Config,
-) {
+) -> Result<(), String> {
     let use_kc = config.use_keychain.unwrap_or(false);
 
     if pa.base_url.is_empty() {
@@ -331,24 +632,28 @@ pub fn resolve_globals_with_config(
     if pa.api_key.is_empty() {
         if let Some(env) = (deps.getenv)("SECRET_API_KEY") {
             pa.api_key = env;
+        } else if let Some(ref cmd) = config.api_key_command {
+            pa.api_key_command = cmd.clone();
         } else if use_kc {
-            if let Some(val) = (deps.get_keychain_secret)("api_key") {
+            if let Some(val) = (deps.get_keychain_secret)(&config.keychain_key("api_key")) {
                 pa.api_key = val;
             }
         }
-        if pa.api_key.is_empty() {
+        if pa.api_key.is_empty() && pa.api_key_command.is_empty() {
             if let Some(ref key) = config.api_key {
                 pa.api_key = key.clone();
             }
         }
     }
     if pa.passphrase_default.is_empty() {
-        if use_kc {
-            if let Some(val) = (deps.get_keychain_secret)("passphrase") {
+        if let Some(ref cmd) = config.passphrase_command {
+            pa.passphrase_command = cmd.clone();
+        } else if use_kc {
+            if let Some(val) = (deps.get_keychain_secret)(&config.keychain_key("passphrase")) {
                 pa.passphrase_default = val;
             }
         }
-        if pa.passphrase_default.is_empty() {
+        if pa.passphrase_default.is_empty() && pa.passphrase_command.is_empty() {
             if let Some(ref pass) = config.passphrase {
                 pa.passphrase_default = pass.clone();
             }
@@ -357,6 +662,15 @@ pub fn resolve_globals_with_config(
     if let Some(show) = config.show_input {
         pa.show_default = show;
     }
+    pa.history = config.history.unwrap_or(false);
+    pa.clipboard_tool = config.clipboard_tool.clone().unwrap_or_default();
+    pa.clipboard_clear = config.clipboard_clear.clone().unwrap_or_default();
+    if config.reveal == Some(true) {
+        pa.reveal = true;
+    }
+    if pa.reveal_for.is_empty() {
+        pa.reveal_for = config.reveal_for.clone().unwrap_or_default();
+    }
 
     // default_ttl: only if no --ttl flag was provided
     if pa.ttl.is_empty() {
@@ -365,13 +679,24 @@ pub fn resolve_globals_with_config(
         }
     }
 
-    // decryption_passphrases: keychain (JSON array) then config, merged + deduped
+    // decryption_passphrases: command output (added by
+    // resolve_decryption_passphrases), keychain (JSON array), then config,
+    // merged + deduped
     {
-        let mut dp = if use_kc {
-            (deps.get_keychain_secret_list)("decryption_passphrases")
-        } else {
-            Vec::new()
-        };
+        pa.decryption_passphrases_command = config
+            .decryption_passphrases_command
+            .clone()
+            .unwrap_or_default();
+        let mut dp = Vec::new();
+        if use_kc {
+            let kc =
+                (deps.get_keychain_secret_list)(&config.keychain_key("decryption_passphrases"));
+            for p in kc {
+                if !dp.contains(&p) {
+                    dp.push(p);
+                }
+            }
+        }
         for p in &config.decryption_passphrases {
             if !dp.contains(p) {
                 dp.push(p.clone());
@@ -381,13 +706,81 @@ pub fn resolve_globals_with_config(
             pa.decryption_passphrases = dp;
         }
     }
+    Ok(())
+}
+
+/// Run `api_key_command` if it is where the API key comes from. Called
+/// only by commands whose requests use the key.
+pub fn resolve_api_key(pa: &mut ParsedArgs) -> Result<(), String> {
+    if pa.api_key.is_empty() && !pa.api_key_command.is_empty() {
+        let cmd = std::mem::take(&mut pa.api_key_command);
+        pa.api_key = run_credential_command(&cmd, COMMAND_TIMEOUT)
+            .map_err(|e| format!("api_key_command: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Run `passphrase_command` for the default passphrase, unless a
+/// passphrase flag or --no-passphrase makes it unused.
+pub fn resolve_passphrase_default(pa: &mut ParsedArgs) -> Result<(), String> {
+    if pa.passphrase_command.is_empty()
+        || pa.no_passphrase
+        || crate::passphrase::has_explicit_passphrase(pa)
+    {
+        return Ok(());
+    }
+    let cmd = std::mem::take(&mut pa.passphrase_command);
+    pa.passphrase_default = run_credential_command(&cmd, COMMAND_TIMEOUT)
+        .map_err(|e| format!("passphrase_command: {}", e))?;
+    Ok(())
+}
+
+/// Run `decryption_passphrases_command` and put its lines ahead of the
+/// keychain and config entries, unless a passphrase flag or
+/// --no-passphrase makes them unused.
+pub fn resolve_decryption_passphrases(pa: &mut ParsedArgs) -> Result<(), String> {
+    if pa.decryption_passphrases_command.is_empty()
+        || pa.no_passphrase
+        || crate::passphrase::has_explicit_passphrase(pa)
+    {
+        return Ok(());
+    }
+    let cmd = std::mem::take(&mut pa.decryption_passphrases_command);
+    let mut dp = run_credential_list_command(&cmd, COMMAND_TIMEOUT)
+        .map_err(|e| format!("decryption_passphrases_command: {}", e))?;
+    for p in std::mem::take(&mut pa.decryption_passphrases) {
+        if !dp.contains(&p) {
+            dp.push(p);
+        }
+    }
+    pa.decryption_passphrases = dp;
+    Ok(())
 }
 
 // --- Config subcommands ---
 
 fn run_config(args: &[String], deps: &mut Deps) -> i32 {
+    // --profile may appear anywhere; strip it before subcommand dispatch.
+    let mut profile = String::new();
+    let mut rest: Vec<String> = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(a) = iter.next() {
+        if a == "--profile" {
+            match iter.next() {
+                Some(v) => profile = v.clone(),
+                None => {
+                    let _ = writeln!(deps.stderr, "error: --profile requires a value");
+                    return 2;
+                }
+            }
+        } else {
+            rest.push(a.clone());
+        }
+    }
+    let args = &rest[..];
+
     if args.is_empty() {
-        return run_config_show(deps);
+        return run_config_show(&profile, deps);
     }
     match args[0].as_str() {
         "-h" | "--help" | "help" => {
@@ -402,20 +795,26 @@ fn run_config(args: &[String], deps: &mut Deps) -> i32 {
             let force = args.iter().any(|a| a == "--force");
             run_config_init(force, deps)
         }
-        "path" | "set-passphrase" | "delete-passphrase"
+        "path" | "set-passphrase" | "delete-passphrase" | "passphrases" | "set" | "get"
+        | "unset" | "lock" | "unlock"
             if args.iter().any(|a| a == "-h" || a == "--help") =>
         {
             print_config_help(deps);
             0
         }
         "path" => run_config_path(deps),
-        "set-passphrase" => run_config_set_passphrase(deps),
-        "delete-passphrase" => run_config_delete_passphrase(deps),
+        "set-passphrase" => run_config_set_passphrase(&profile, deps),
+        "delete-passphrase" => run_config_delete_passphrase(&profile, deps),
+        "passphrases" => run_config_passphrases(&args[1..], &profile, deps),
+        "set" => run_config_set(&args[1..], &profile, deps),
+        "get" | "unset" => run_config_get_unset(&args[0], &args[1..], &profile, deps),
+        "lock" | "unlock" => run_config_lock_unlock(&args[0], &args[1..], deps),
         _ => {
             let _ = writeln!(
                 deps.stderr,
-                "error: unknown config subcommand {:?} (try: init, path, set-passphrase, delete-passphrase, --help)",
-                args[0]
+                "error: unknown config subcommand {:?} (try: {}, --help)",
+                args[0],
+                CONFIG_SUBCOMMANDS.join(", ")
             );
             2
         }
@@ -423,14 +822,14 @@ fn run_config(args: &[String], deps: &mut Deps) -> i32 {
 }
 
 fn run_config_init(force: bool, deps: &mut Deps) -> i32 {
-    let c = color_func((deps.is_tty)());
+    let c = deps.style.color_func((deps.is_tty)());
     let path = crate::config::config_path_with(&*deps.getenv);
     match crate::config::init_config_at(path, force) {
         Ok(path) => {
             let _ = writeln!(
                 deps.stderr,
                 "{} Created config file at: {}",
-                c(SUCCESS, "\u{2713}"),
+                c(SUCCESS, deps.style.glyph(CHECK)),
                 path.display()
             );
             0
@@ -455,8 +854,458 @@ fn run_config_path(deps: &mut Deps) -> i32 {
     }
 }
 
-fn run_config_set_passphrase(deps: &mut Deps) -> i32 {
-    let c = color_func((deps.is_tty)());
+/// Where `config set/get/unset` should act: the key name plus the profile
+/// from a `profiles.<name>.<key>` key or `--profile`.
+fn config_key_target(raw: &str, profile: &str) -> Result<(String, Option<String>), String> {
+    let (name, key) = match raw
+        .strip_prefix("profiles.")
+        .and_then(|rest| rest.rsplit_once('.'))
+    {
+        Some((name, key)) => {
+            if !profile.is_empty() && profile != name {
+                return Err(format!(
+                    "{:?} names profile {:?} but --profile is {:?}",
+                    raw, name, profile
+                ));
+            }
+            (name, key)
+        }
+        None => (profile, raw),
+    };
+    crate::config::setting(key)?;
+    if name.is_empty() {
+        return Ok((key.to_string(), None));
+    }
+    if matches!(key, "default_profile" | "color" | "plain") {
+        return Err(format!("{} can only be set at the top level", key));
+    }
+    Ok((key.to_string(), Some(name.to_string())))
+}
+
+fn config_file_path(deps: &Deps) -> Result<std::path::PathBuf, String> {
+    crate::config::config_path_with(&*deps.getenv)
+        .ok_or_else(|| "could not determine config directory".to_string())
+}
+
+/// `config set <key> <value>...`: validate and write one setting.
+fn run_config_set(args: &[String], profile: &str, deps: &mut Deps) -> i32 {
+    use crate::config::SettingKind;
+
+    let c = deps.style.color_func((deps.is_tty)());
+    let Some(raw_key) = args.first() else {
+        let _ = writeln!(deps.stderr, "error: usage: secrt config set <key> <value>");
+        return 2;
+    };
+    let (key, target) = match config_key_target(raw_key, profile) {
+        Ok(t) => t,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    let table = target.as_deref().map(crate::config::profile_table);
+    let (kind, secret) = crate::config::setting(&key).unwrap_or((SettingKind::Text, false));
+    let config = crate::config::load_config_with(&*deps.getenv, &mut deps.stderr);
+    apply_config_style(&config, deps);
+
+    // Secrets belong in the keychain once it's in use.
+    if secret {
+        let profile_keychain = target
+            .as_ref()
+            .and_then(|name| config.profiles.get(name))
+            .and_then(|p| p.use_keychain);
+        if profile_keychain.or(config.use_keychain) == Some(true) {
+            let hint = match key.as_str() {
+                "api_key" => "secrt login",
+                "passphrase" => "secrt config set-passphrase",
+                _ => "secrt config passphrases add",
+            };
+            let _ = writeln!(
+                deps.stderr,
+                "error: refusing to write {} to the config file while use_keychain is on",
+                key
+            );
+            let _ = writeln!(
+                deps.stderr,
+                "hint: store it in the OS keychain with `{}`",
+                hint
+            );
+            return 1;
+        }
+    }
+
+    let mut values = args[1..].to_vec();
+    if values.is_empty() && secret && (deps.is_tty)() {
+        match (deps.read_pass)(&format!("{}: ", key), &mut deps.stderr) {
+            Ok(v) => values.push(v),
+            Err(e) => {
+                let _ = writeln!(deps.stderr, "error: failed to read {}: {}", key, e);
+                return 1;
+            }
+        }
+    }
+    if kind == SettingKind::Profile {
+        if let Some(name) = values.first() {
+            if !config.profiles.contains_key(name) {
+                let _ = writeln!(deps.stderr, "error: {}", config.unknown_profile(name));
+                return 2;
+            }
+        }
+    }
+    let literal = match crate::config::parse_setting(&key, kind, &values) {
+        Ok(l) => l,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+
+    let result = config_file_path(deps).and_then(|path| {
+        crate::config::set_config_value(&path, table.as_deref(), &key, &literal).map(|_| path)
+    });
+    match result {
+        Ok(path) => {
+            let shown = if !secret {
+                literal
+            } else if kind == SettingKind::List {
+                crate::config::mask_secret_list(&deps.style, &values)
+            } else {
+                crate::config::mask_secret(&deps.style, &values[0], key == "api_key")
+            };
+            let _ = writeln!(
+                deps.stderr,
+                "{} Set {} = {} in {}",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                raw_key,
+                shown,
+                path.display()
+            );
+            if secret && config.encrypted_secrets.is_some() {
+                let _ = writeln!(
+                    deps.stderr,
+                    "  {}",
+                    c(
+                        DIM,
+                        "Stored as plaintext; run secrt config lock to encrypt it."
+                    ),
+                );
+            }
+            0
+        }
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            1
+        }
+    }
+}
+
+/// `config get <key>` prints the value as written in the file (secrets
+/// masked); `config unset <key>` removes it.
+fn run_config_get_unset(action: &str, args: &[String], profile: &str, deps: &mut Deps) -> i32 {
+    let c = deps.style.color_func((deps.is_tty)());
+    let raw_key = match args {
+        [key] => key,
+        [] => {
+            let _ = writeln!(deps.stderr, "error: usage: secrt config {} <key>", action);
+            return 2;
+        }
+        [_, extra, ..] => {
+            let _ = writeln!(deps.stderr, "error: unexpected argument {:?}", extra);
+            return 2;
+        }
+    };
+    let (key, target) = match config_key_target(raw_key, profile) {
+        Ok(t) => t,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    let table = target.as_deref().map(crate::config::profile_table);
+    let path = match config_file_path(deps) {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 1;
+        }
+    };
+
+    if action == "unset" {
+        return match crate::config::unset_config_value(&path, table.as_deref(), &key) {
+            Ok(true) => {
+                let _ = writeln!(
+                    deps.stderr,
+                    "{} Removed {} from {}",
+                    c(SUCCESS, deps.style.glyph(CHECK)),
+                    raw_key,
+                    path.display()
+                );
+                0
+            }
+            Ok(false) => {
+                let _ = writeln!(
+                    deps.stderr,
+                    "error: {} is not set in {}",
+                    raw_key,
+                    path.display()
+                );
+                1
+            }
+            Err(e) => {
+                let _ = writeln!(deps.stderr, "error: {}", e);
+                1
+            }
+        };
+    }
+
+    match crate::config::get_config_value(&path, table.as_deref(), &key) {
+        Ok(Some(values)) => {
+            let secret = crate::config::setting(&key).is_ok_and(|(_, s)| s);
+            for v in values {
+                let shown = if secret {
+                    crate::config::mask_secret(&deps.style, &v, key == "api_key")
+                } else {
+                    v
+                };
+                let _ = writeln!(deps.stdout, "{}", shown);
+            }
+            0
+        }
+        Ok(None) => {
+            let _ = writeln!(
+                deps.stderr,
+                "error: {} is not set in {}",
+                raw_key,
+                path.display()
+            );
+            1
+        }
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            1
+        }
+    }
+}
+
+/// `config lock` seals the user config's plaintext secrets into
+/// `encrypted_secrets`; `config unlock` writes them back out.
+fn run_config_lock_unlock(action: &str, args: &[String], deps: &mut Deps) -> i32 {
+    let c = deps.style.color_func((deps.is_tty)());
+    if let Some(extra) = args.first() {
+        let _ = writeln!(deps.stderr, "error: unexpected argument {:?}", extra);
+        return 2;
+    }
+    let path = match config_file_path(deps) {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 1;
+        }
+    };
+    let config = match crate::config::parse_config_file(&path) {
+        Ok(c) => c,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 1;
+        }
+    };
+    // A new master passphrase is confirmed; an existing one is checked by
+    // decrypting with it.
+    let confirm = action == "lock" && config.encrypted_secrets.is_none();
+    let passphrase = match read_master_passphrase(confirm, deps) {
+        Ok(p) => p,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 1;
+        }
+    };
+
+    let result = if action == "lock" {
+        crate::config::lock_config_file(&path, &passphrase, &*deps.rand_bytes)
+    } else {
+        crate::config::unlock_config_file(&path, &passphrase)
+    };
+    match result {
+        Ok(keys) if action == "lock" => {
+            let _ = writeln!(
+                deps.stderr,
+                "{} Encrypted {} in {}",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                keys.join(", "),
+                path.display()
+            );
+            let _ = writeln!(
+                deps.stderr,
+                "  {}",
+                c(
+                    DIM,
+                    &format!(
+                        "secrt asks for the master passphrase when it needs them; scripts can set {}.",
+                        crate::config::MASTER_PASSPHRASE_ENV
+                    )
+                ),
+            );
+            0
+        }
+        Ok(keys) => {
+            let restored = if keys.is_empty() {
+                "no secrets".to_string()
+            } else {
+                keys.join(", ")
+            };
+            let _ = writeln!(
+                deps.stderr,
+                "{} Decrypted {} into {} as plaintext",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                restored,
+                path.display()
+            );
+            0
+        }
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            1
+        }
+    }
+}
+
+/// The master passphrase for `config lock/unlock`: `SECRT_MASTER_PASSPHRASE`,
+/// else a prompt (entered twice when `confirm` is set).
+fn read_master_passphrase(confirm: bool, deps: &mut Deps) -> Result<String, String> {
+    if let Some(pass) = (deps.getenv)(crate::config::MASTER_PASSPHRASE_ENV) {
+        if pass.is_empty() {
+            return Err(format!(
+                "{} is set but empty",
+                crate::config::MASTER_PASSPHRASE_ENV
+            ));
+        }
+        return Ok(pass);
+    }
+    if !(deps.is_tty)() {
+        return Err(format!(
+            "no terminal to prompt for the master passphrase; set {}",
+            crate::config::MASTER_PASSPHRASE_ENV
+        ));
+    }
+    let c = deps.style.color_func(true);
+    let prompt = format!("{} ", c(LABEL, "Master passphrase:"));
+    let pass = (deps.read_pass)(&prompt, &mut deps.stderr)
+        .map_err(|e| format!("read master passphrase: {}", e))?;
+    if pass.is_empty() {
+        return Err("master passphrase must not be empty".into());
+    }
+    if confirm {
+        let prompt = format!("{} ", c(LABEL, "          Confirm:"));
+        let again = (deps.read_pass)(&prompt, &mut deps.stderr)
+            .map_err(|e| format!("read master passphrase confirmation: {}", e))?;
+        if again != pass {
+            return Err("passphrases do not match".into());
+        }
+    }
+    Ok(pass)
+}
+
+pub(crate) type ProfiledConfig = (
+    crate::config::Config,
+    Option<(String, crate::config::ProfileSource)>,
+);
+
+/// Load the config and merge in the profile chosen by `--profile`,
+/// `SECRT_PROFILE`, the share URL's host or `default_profile`.
+/// `encrypted_secrets` are left sealed; see `load_unsealed_config`.
+pub(crate) fn load_profiled_config(
+    profile: &str,
+    share_url: Option<&str>,
+    deps: &mut Deps,
+) -> Result<ProfiledConfig, String> {
+    let config = crate::config::load_config_with(&*deps.getenv, &mut deps.stderr);
+    apply_config_style(&config, deps);
+    apply_profile(config, profile, share_url, deps)
+}
+
+/// Like `load_profiled_config`, but decrypts `encrypted_secrets` first,
+/// prompting for the master passphrase if needed.
+pub(crate) fn load_unsealed_config(
+    profile: &str,
+    share_url: Option<&str>,
+    deps: &mut Deps,
+) -> Result<ProfiledConfig, String> {
+    let mut config = crate::config::load_config_with(&*deps.getenv, &mut deps.stderr);
+    apply_config_style(&config, deps);
+    unseal_config(&mut config, deps)?;
+    apply_profile(config, profile, share_url, deps)
+}
+
+/// Select the active profile and layer it over `config`.
+pub(crate) fn apply_profile(
+    config: crate::config::Config,
+    profile: &str,
+    share_url: Option<&str>,
+    deps: &Deps,
+) -> Result<ProfiledConfig, String> {
+    let env = (deps.getenv)("SECRT_PROFILE");
+    let selected = config.select_profile(profile, env.as_deref(), share_url)?;
+    let config = match selected {
+        Some((ref name, _)) => config.with_profile(name),
+        None => config,
+    };
+    Ok((config, selected))
+}
+
+/// Decrypt `encrypted_secrets` into `config`. Without a terminal or
+/// `SECRT_MASTER_PASSPHRASE` the secrets are skipped with a warning, so
+/// scripts that don't need them keep working.
+pub(crate) fn unseal_config(
+    config: &mut crate::config::Config,
+    deps: &mut Deps,
+) -> Result<(), String> {
+    let Some(sealed) = config.encrypted_secrets.clone() else {
+        return Ok(());
+    };
+    if let Some(pass) = (deps.getenv)(crate::config::MASTER_PASSPHRASE_ENV) {
+        let secrets = crate::config::open_secrets(&sealed, &pass)
+            .map_err(|e| format!("{}: {}", crate::config::MASTER_PASSPHRASE_ENV, e))?;
+        config.fill_secrets(secrets);
+        return Ok(());
+    }
+    if !(deps.is_tty)() {
+        let _ = writeln!(
+            deps.stderr,
+            "warning: skipping encrypted_secrets; set {} to use them",
+            crate::config::MASTER_PASSPHRASE_ENV
+        );
+        config.encrypted_secrets = None;
+        return Ok(());
+    }
+    let c = deps.style.color_func(true);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let prompt = format!("{} ", c(LABEL, "Master passphrase:"));
+        let pass = (deps.read_pass)(&prompt, &mut deps.stderr)
+            .map_err(|e| format!("read master passphrase: {}", e))?;
+        match crate::config::open_secrets(&sealed, &pass) {
+            Ok(secrets) => {
+                config.fill_secrets(secrets);
+                return Ok(());
+            }
+            Err(e) if e == "wrong master passphrase" && attempts < 3 => {
+                let _ = writeln!(deps.stderr, "{}", c(WARN, "Wrong passphrase, try again."));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn run_config_set_passphrase(profile: &str, deps: &mut Deps) -> i32 {
+    let c = deps.style.color_func((deps.is_tty)());
+    let key = match load_profiled_config(profile, None, deps) {
+        Ok((config, _)) => config.keychain_key("passphrase"),
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
 
     let p1 = match (deps.read_pass)("Passphrase: ", &mut deps.stderr) {
         Ok(p) => p,
@@ -484,12 +1333,12 @@ fn run_config_set_passphrase(deps: &mut Deps) -> i32 {
         return 1;
     }
 
-    match crate::keychain::set_secret("passphrase", &p1) {
+    match (deps.set_keychain_secret)(&key, &p1) {
         Ok(()) => {
             let _ = writeln!(
                 deps.stderr,
                 "{} Passphrase saved to OS keychain",
-                c(SUCCESS, "\u{2713}")
+                c(SUCCESS, deps.style.glyph(CHECK))
             );
             0
         }
@@ -504,14 +1353,21 @@ fn run_config_set_passphrase(deps: &mut Deps) -> i32 {
     }
 }
 
-fn run_config_delete_passphrase(deps: &mut Deps) -> i32 {
-    let c = color_func((deps.is_tty)());
-    match crate::keychain::delete_secret("passphrase") {
+fn run_config_delete_passphrase(profile: &str, deps: &mut Deps) -> i32 {
+    let c = deps.style.color_func((deps.is_tty)());
+    let key = match load_profiled_config(profile, None, deps) {
+        Ok((config, _)) => config.keychain_key("passphrase"),
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    match (deps.delete_keychain_secret)(&key) {
         Ok(()) => {
             let _ = writeln!(
                 deps.stderr,
                 "{} Passphrase removed from OS keychain",
-                c(SUCCESS, "\u{2713}")
+                c(SUCCESS, deps.style.glyph(CHECK))
             );
             0
         }
@@ -522,8 +1378,216 @@ fn run_config_delete_passphrase(deps: &mut Deps) -> i32 {
     }
 }
 
+/// `config passphrases list|add|remove|promote`: edit the keychain's
+/// `decryption_passphrases` entry (a JSON array) without touching it by hand.
+fn run_config_passphrases(args: &[String], profile: &str, deps: &mut Deps) -> i32 {
+    let (config, _) = match load_profiled_config(profile, None, deps) {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    let action = args.first().map(String::as_str).unwrap_or("list");
+    let index_arg = args.get(1).map(String::as_str);
+    let expected_args = match action {
+        "list" | "add" => 1,
+        "remove" | "promote" => 2,
+        _ => {
+            let _ = writeln!(
+                deps.stderr,
+                "error: unknown passphrases action {:?} (try: list, add, remove, promote)",
+                action
+            );
+            return 2;
+        }
+    };
+    if args.len() > expected_args {
+        let _ = writeln!(
+            deps.stderr,
+            "error: unexpected argument {:?}",
+            args[expected_args]
+        );
+        return 2;
+    }
+
+    let list_key = config.keychain_key("decryption_passphrases");
+    let mut list = (deps.get_keychain_secret_list)(&list_key);
+
+    // remove/promote take a 1-based index into the keychain list
+    let index = if expected_args == 2 {
+        let Some(raw) = index_arg else {
+            let _ = writeln!(
+                deps.stderr,
+                "error: {} requires an index (see: secrt config passphrases list)",
+                action
+            );
+            return 2;
+        };
+        match raw.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= list.len() => n - 1,
+            _ if list.is_empty() => {
+                let _ = writeln!(
+                    deps.stderr,
+                    "error: no decryption passphrases are stored in the OS keychain"
+                );
+                return 1;
+            }
+            _ => {
+                let _ = writeln!(
+                    deps.stderr,
+                    "error: invalid index {:?} (expected 1-{})",
+                    raw,
+                    list.len()
+                );
+                return 2;
+            }
+        }
+    } else {
+        0
+    };
+
+    let c = deps.style.color_func((deps.is_tty)());
+    let result = match action {
+        "list" => {
+            write_passphrase_list(&config, &list, deps);
+            return 0;
+        }
+        "add" => read_new_passphrase(deps).and_then(|p| {
+            if list.contains(&p) {
+                return Err("that passphrase is already in the list".into());
+            }
+            list.push(p);
+            store_passphrase_list(&list_key, &list, deps)?;
+            Ok(format!(
+                "Added decryption passphrase #{} to OS keychain",
+                list.len()
+            ))
+        }),
+        "remove" => {
+            list.remove(index);
+            store_passphrase_list(&list_key, &list, deps)
+                .map(|()| format!("Removed decryption passphrase #{}", index + 1))
+        }
+        _ => {
+            // promote: the entry becomes the default; the old default takes
+            // its place in the list so older secrets still decrypt.
+            let default_key = config.keychain_key("passphrase");
+            let previous = (deps.get_keychain_secret)(&default_key).filter(|p| !p.is_empty());
+            let promoted = list.remove(index);
+            let mut note = String::new();
+            if let Some(prev) = previous {
+                if prev != promoted && !list.contains(&prev) {
+                    list.insert(index, prev);
+                    note = format!("; previous default kept as #{}", index + 1);
+                }
+            }
+            (deps.set_keychain_secret)(&default_key, &promoted)
+                .and_then(|()| store_passphrase_list(&list_key, &list, deps))
+                .map(|()| {
+                    format!(
+                        "Passphrase #{} is now the default passphrase{}",
+                        index + 1,
+                        note
+                    )
+                })
+        }
+    };
+
+    match result.and_then(|msg| {
+        crate::config::enable_keychain(&config, &*deps.getenv).map(|path| (msg, path))
+    }) {
+        Ok((msg, enabled)) => {
+            let _ = writeln!(
+                deps.stderr,
+                "{} {}",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                msg
+            );
+            if let Some(path) = enabled {
+                let _ = writeln!(
+                    deps.stderr,
+                    "  {}",
+                    c(DIM, &format!("Enabled use_keychain in {}", path.display())),
+                );
+            }
+            0
+        }
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            1
+        }
+    }
+}
+
+/// Prompt twice for a new passphrase.
+fn read_new_passphrase(deps: &mut Deps) -> Result<String, String> {
+    let p1 = (deps.read_pass)("Passphrase: ", &mut deps.stderr)
+        .map_err(|e| format!("failed to read passphrase: {}", e))?;
+    if p1.is_empty() {
+        return Err("passphrase must not be empty".into());
+    }
+    let p2 = (deps.read_pass)("   Confirm: ", &mut deps.stderr)
+        .map_err(|e| format!("failed to read confirmation: {}", e))?;
+    if p1 != p2 {
+        return Err("passphrases do not match".into());
+    }
+    Ok(p1)
+}
+
+/// Write the list back as a JSON array, or delete the entry once empty.
+fn store_passphrase_list(key: &str, list: &[String], deps: &mut Deps) -> Result<(), String> {
+    if list.is_empty() {
+        return (deps.delete_keychain_secret)(key);
+    }
+    let json = serde_json::to_string(list).map_err(|e| e.to_string())?;
+    (deps.set_keychain_secret)(key, &json)
+}
+
+fn write_passphrase_list(config: &crate::config::Config, list: &[String], deps: &mut Deps) {
+    let c = deps.style.color_func((deps.is_stdout_tty)());
+    let w = &mut deps.stdout;
+    let default = (deps.get_keychain_secret)(&config.keychain_key("passphrase"));
+    let _ = writeln!(w, "{}", c(HEADING, "DEFAULT PASSPHRASE"));
+    match default {
+        Some(ref p) if !p.is_empty() => {
+            let _ = writeln!(w, "  {}", crate::config::mask_secret(&deps.style, p, false));
+        }
+        _ => {
+            let _ = writeln!(w, "  {}", c(DIM, "(not set)"));
+        }
+    }
+    let _ = writeln!(w, "\n{}", c(HEADING, "DECRYPTION PASSPHRASES"));
+    if list.is_empty() {
+        let _ = writeln!(w, "  {}", c(DIM, "(none in OS keychain)"));
+    }
+    for (i, p) in list.iter().enumerate() {
+        let _ = writeln!(
+            w,
+            "  {} {}",
+            c(OPT, &format!("{:>2}.", i + 1)),
+            crate::config::mask_secret(&deps.style, p, false)
+        );
+    }
+    let in_file = config
+        .decryption_passphrases
+        .iter()
+        .filter(|p| !list.contains(p))
+        .count();
+    if in_file > 0 {
+        let _ = writeln!(
+            w,
+            "  {}",
+            c(
+                DIM,
+                &format!("+ {} more in the config file (tried after these)", in_file)
+            ),
+        );
+    }
+}
+
 /// Format seconds into a human-readable TTL string (e.g. "24h", "365d").
-fn format_ttl_seconds(secs: i64) -> String {
+pub(crate) fn format_ttl_seconds(secs: i64) -> String {
     if secs <= 0 {
         return "0s".into();
     }
@@ -558,7 +1622,7 @@ fn format_ttl_seconds(secs: i64) -> String {
 }
 
 /// Format bytes into a human-readable string (e.g. "256 KB", "1 MB").
-fn format_bytes(b: i64) -> String {
+pub(crate) fn format_bytes(b: i64) -> String {
     if b >= 1024 * 1024 && b % (1024 * 1024) == 0 {
         format!("{} MB", b / (1024 * 1024))
     } else if b >= 1024 && b % 1024 == 0 {
@@ -569,7 +1633,7 @@ fn format_bytes(b: i64) -> String {
 }
 
 /// Format a limit value, showing "unlimited" for zero.
-fn format_limit(n: i64) -> String {
+pub(crate) fn format_limit(n: i64) -> String {
     if n == 0 {
         "unlimited".into()
     } else {
@@ -577,29 +1641,57 @@ fn format_limit(n: i64) -> String {
     }
 }
 
-fn run_config_show(deps: &mut Deps) -> i32 {
-    let c = color_func((deps.is_stdout_tty)());
-    let config = crate::config::load_config_with(&*deps.getenv, &mut deps.stderr);
-
-    // Config file path
-    let resolved_path = crate::config::config_path_with(&*deps.getenv);
-    let config_path = resolved_path
+fn run_config_show(profile: &str, deps: &mut Deps) -> i32 {
+    let c = deps.style.color_func((deps.is_stdout_tty)());
+    let (config, active) = match load_unsealed_config(profile, None, deps) {
+        Ok(r) => r,
+        Err(e) => {
+            let _ = writeln!(deps.stderr, "error: {}", e);
+            return 2;
+        }
+    };
+    // Label values that come from the active profile's table, not the top level.
+    let active_table = active
+        .as_ref()
+        .and_then(|(name, _)| config.profiles.get(name));
+    let profile_label = active
         .as_ref()
-        .map(|p| p.display().to_string())
-        .unwrap_or_else(|| "(unknown)".into());
-    let config_exists = resolved_path.as_ref().map(|p| p.exists()).unwrap_or(false);
+        .map(|(name, _)| format!("profile {}", name))
+        .unwrap_or_default();
+    // e.g. "user config" or "project config, profile work"
+    let from_file = |field: &str, in_profile: bool| -> String {
+        let layer = config
+            .sources
+            .get(field)
+            .map_or("config file", |l| l.describe());
+        if in_profile {
+            format!("{}, {}", layer, profile_label)
+        } else {
+            layer.to_string()
+        }
+    };
+    let set_in = |f: fn(&crate::config::Profile) -> bool| active_table.is_some_and(f);
 
-    let _ = writeln!(
-        deps.stderr,
-        "{}\n  {} {}",
-        c(HEADING, "CONFIG FILE"),
-        c(DIM, &config_path),
-        if config_exists { "" } else { "(not found)" },
-    );
+    // Config files, lowest precedence first
+    let layers = crate::config::config_layers_with(&*deps.getenv);
+    let config_exists = layers
+        .iter()
+        .any(|(layer, p)| *layer == crate::config::ConfigLayer::User && p.exists());
+
+    let _ = writeln!(deps.stderr, "{}", c(HEADING, "CONFIG FILES"));
+    for (layer, path) in &layers {
+        let _ = writeln!(
+            deps.stderr,
+            "  {:<14} {} {}",
+            layer.describe(),
+            c(DIM, &path.display().to_string()),
+            if path.exists() { "" } else { "(not found)" },
+        );
+    }
     if !config_exists {
         let _ = writeln!(
             deps.stderr,
-            "  Run {} to create one.",
+            "  Run {} to create a user config.",
             c(CMD, "secrt config init"),
         );
     }
@@ -609,11 +1701,30 @@ fn run_config_show(deps: &mut Deps) -> i32 {
     let _ = writeln!(deps.stderr);
     let _ = writeln!(deps.stderr, "{}", c(HEADING, "EFFECTIVE SETTINGS"));
 
+    // profile: flag/env/default_profile/none
+    match active {
+        Some((ref name, src)) => {
+            let _ = writeln!(
+                deps.stderr,
+                "  {}: {} {}",
+                c(OPT, "profile"),
+                name,
+                c(DIM, &format!("({})", src.describe())),
+            );
+        }
+        None => {
+            let _ = writeln!(deps.stderr, "  {}: {}", c(OPT, "profile"), c(DIM, "(none)"),);
+        }
+    }
+
     // use_keychain: config/default
     let (use_kc_val, use_kc_src) = if let Some(v) = config.use_keychain {
-        (v.to_string(), "config file")
+        (
+            v.to_string(),
+            from_file("use_keychain", set_in(|p| p.use_keychain.is_some())),
+        )
     } else {
-        ("false".into(), "default")
+        ("false".into(), "default".into())
     };
     let _ = writeln!(
         deps.stderr,
@@ -625,11 +1736,14 @@ fn run_config_show(deps: &mut Deps) -> i32 {
 
     // base_url: flag/env/config/default
     let (base_url_val, base_url_src) = if let Some(env) = (deps.getenv)("SECRET_BASE_URL") {
-        (env, "env SECRET_BASE_URL")
+        (env, "env SECRET_BASE_URL".into())
     } else if let Some(ref url) = config.base_url {
-        (url.clone(), "config file")
+        (
+            url.clone(),
+            from_file("base_url", set_in(|p| p.base_url.is_some())),
+        )
     } else {
-        (DEFAULT_BASE_URL.into(), "default")
+        (DEFAULT_BASE_URL.into(), "default".into())
     };
     let _ = writeln!(
         deps.stderr,
@@ -641,19 +1755,36 @@ fn run_config_show(deps: &mut Deps) -> i32 {
 
     // api_key: env/keychain/config/none
     let (api_key_display, api_key_src) = if let Some(env) = (deps.getenv)("SECRET_API_KEY") {
-        (crate::config::mask_secret(&env, true), "env SECRET_API_KEY")
+        (
+            crate::config::mask_secret(&deps.style, &env, true),
+            "env SECRET_API_KEY".into(),
+        )
+    } else if let Some(ref cmd) = config.api_key_command {
+        (
+            format!("$({})", cmd),
+            from_file("api_key_command", set_in(|p| p.api_key_command.is_some())),
+        )
     } else if use_kc {
-        if let Some(val) = (deps.get_keychain_secret)("api_key") {
-            (crate::config::mask_secret(&val, true), "keychain")
+        if let Some(val) = (deps.get_keychain_secret)(&config.keychain_key("api_key")) {
+            (
+                crate::config::mask_secret(&deps.style, &val, true),
+                "keychain".into(),
+            )
         } else if let Some(ref key) = config.api_key {
-            (crate::config::mask_secret(key, true), "config file")
+            (
+                crate::config::mask_secret(&deps.style, key, true),
+                from_file("api_key", set_in(|p| p.api_key.is_some())),
+            )
         } else {
-            ("(not set)".into(), "")
+            ("(not set)".into(), String::new())
         }
     } else if let Some(ref key) = config.api_key {
-        (crate::config::mask_secret(key, true), "config file")
+        (
+            crate::config::mask_secret(&deps.style, key, true),
+            from_file("api_key", set_in(|p| p.api_key.is_some())),
+        )
     } else {
-        ("(not set)".into(), "")
+        ("(not set)".into(), String::new())
     };
     if api_key_src.is_empty() {
         let _ = writeln!(
@@ -672,19 +1803,36 @@ fn run_config_show(deps: &mut Deps) -> i32 {
         );
     }
 
-    // passphrase: keychain/config/none
-    let (pass_display, pass_src) = if use_kc {
-        if let Some(val) = (deps.get_keychain_secret)("passphrase") {
-            (crate::config::mask_secret(&val, false), "keychain")
+    // passphrase: command/keychain/config/none
+    let (pass_display, pass_src) = if let Some(ref cmd) = config.passphrase_command {
+        (
+            format!("$({})", cmd),
+            from_file(
+                "passphrase_command",
+                set_in(|p| p.passphrase_command.is_some()),
+            ),
+        )
+    } else if use_kc {
+        if let Some(val) = (deps.get_keychain_secret)(&config.keychain_key("passphrase")) {
+            (
+                crate::config::mask_secret(&deps.style, &val, false),
+                "keychain".into(),
+            )
         } else if let Some(ref pass) = config.passphrase {
-            (crate::config::mask_secret(pass, false), "config file")
+            (
+                crate::config::mask_secret(&deps.style, pass, false),
+                from_file("passphrase", set_in(|p| p.passphrase.is_some())),
+            )
         } else {
-            ("(not set)".into(), "")
+            ("(not set)".into(), String::new())
         }
     } else if let Some(ref pass) = config.passphrase {
-        (crate::config::mask_secret(pass, false), "config file")
+        (
+            crate::config::mask_secret(&deps.style, pass, false),
+            from_file("passphrase", set_in(|p| p.passphrase.is_some())),
+        )
     } else {
-        ("(not set)".into(), "")
+        ("(not set)".into(), String::new())
     };
     if pass_src.is_empty() {
         let _ = writeln!(
@@ -703,11 +1851,13 @@ fn run_config_show(deps: &mut Deps) -> i32 {
         );
     }
 
-    // Fetch server info (best-effort, non-fatal)
+    // Fetch server info (best-effort, non-fatal); never runs api_key_command
     let api_key_for_info = if let Some(env) = (deps.getenv)("SECRET_API_KEY") {
         env
+    } else if config.api_key_command.is_some() {
+        String::new()
     } else if use_kc {
-        if let Some(val) = (deps.get_keychain_secret)("api_key") {
+        if let Some(val) = (deps.get_keychain_secret)(&config.keychain_key("api_key")) {
             val
         } else {
             config.api_key.clone().unwrap_or_default()
@@ -727,7 +1877,13 @@ fn run_config_show(deps: &mut Deps) -> i32 {
             "  {}: {} {}",
             c(OPT, "default_ttl"),
             ttl,
-            c(DIM, "(config file)"),
+            c(
+                DIM,
+                &format!(
+                    "({})",
+                    from_file("default_ttl", set_in(|p| p.default_ttl.is_some()))
+                )
+            ),
         );
     } else if let Some(ref info) = server_info {
         let _ = writeln!(
@@ -748,9 +1904,12 @@ fn run_config_show(deps: &mut Deps) -> i32 {
 
     // show_input: config/default
     let (show_val, show_src) = if let Some(show) = config.show_input {
-        (show.to_string(), "config file")
+        (
+            show.to_string(),
+            from_file("show_input", set_in(|p| p.show_input.is_some())),
+        )
     } else {
-        ("false".into(), "default")
+        ("false".into(), "default".into())
     };
     let _ = writeln!(
         deps.stderr,
@@ -762,7 +1921,7 @@ fn run_config_show(deps: &mut Deps) -> i32 {
 
     // decryption_passphrases: keychain/config/both/none
     let kc_list = if use_kc {
-        (deps.get_keychain_secret_list)("decryption_passphrases")
+        (deps.get_keychain_secret_list)(&config.keychain_key("decryption_passphrases"))
     } else {
         Vec::new()
     };
@@ -784,11 +1943,20 @@ fn run_config_show(deps: &mut Deps) -> i32 {
                 merged.push(p.clone());
             }
         }
-        let masked = crate::config::mask_secret_list(&merged);
+        let masked = crate::config::mask_secret_list(&deps.style, &merged);
         let src = match (has_kc, has_cfg) {
-            (true, true) => "keychain + config file",
-            (true, false) => "keychain",
-            (false, true) => "config file",
+            (true, true) => format!(
+                "keychain + {}",
+                from_file(
+                    "decryption_passphrases",
+                    set_in(|p| p.decryption_passphrases.is_some())
+                )
+            ),
+            (true, false) => "keychain".into(),
+            (false, true) => from_file(
+                "decryption_passphrases",
+                set_in(|p| p.decryption_passphrases.is_some()),
+            ),
             (false, false) => unreachable!(),
         };
         let _ = writeln!(
@@ -799,6 +1967,40 @@ fn run_config_show(deps: &mut Deps) -> i32 {
             c(DIM, &format!("({} entries, {})", merged.len(), src)),
         );
     }
+    if let Some(ref cmd) = config.decryption_passphrases_command {
+        let src = from_file(
+            "decryption_passphrases_command",
+            set_in(|p| p.decryption_passphrases_command.is_some()),
+        );
+        let _ = writeln!(
+            deps.stderr,
+            "  {}: $({}) {}",
+            c(OPT, "decryption_passphrases_command"),
+            cmd,
+            c(DIM, &format!("({})", src)),
+        );
+    }
+
+    // PROFILES section
+    if !config.profiles.is_empty() {
+        let _ = writeln!(deps.stderr);
+        let _ = writeln!(deps.stderr, "{}", c(HEADING, "PROFILES"));
+        let active_name = active.as_ref().map(|(name, _)| name.as_str());
+        for (name, p) in &config.profiles {
+            let marker = if Some(name.as_str()) == active_name {
+                c(SUCCESS, "*")
+            } else {
+                " ".into()
+            };
+            let _ = writeln!(
+                deps.stderr,
+                "  {} {} {}",
+                marker,
+                c(OPT, name),
+                c(DIM, p.base_url.as_deref().unwrap_or("(inherits base_url)")),
+            );
+        }
+    }
 
     // SERVER LIMITS section
     let _ = writeln!(deps.stderr);
@@ -900,50 +2102,18 @@ fn run_config_show(deps: &mut Deps) -> i32 {
     0
 }
 
-// --- Help text formatting ---
-
-use crate::color::ColorFn;
-
-/// Write auto-aligned option rows:  flag [arg]   description
-fn write_option_rows(w: &mut dyn Write, c: &ColorFn, rows: &[(&str, &str, &str)]) {
-    let widths: Vec<usize> = rows
-        .iter()
-        .map(|(f, a, _)| {
-            if a.is_empty() {
-                f.len()
-            } else {
-                f.len() + 1 + a.len()
-            }
-        })
-        .collect();
-    let max = widths.iter().copied().max().unwrap_or(0);
-    for (i, (flags, arg, desc)) in rows.iter().enumerate() {
-        let pad = max - widths[i] + 2;
-        if arg.is_empty() {
-            let _ = writeln!(w, "  {}{:pad$}{}", c(OPT, flags), "", desc);
-        } else {
-            let _ = writeln!(w, "  {} {}{:pad$}{}", c(OPT, flags), c(ARG, arg), "", desc);
-        }
-    }
-}
-
-/// Write auto-aligned command rows:  command   description
-fn write_cmd_rows(w: &mut dyn Write, c: &ColorFn, rows: &[(&str, &str)]) {
-    let max = rows.iter().map(|(cmd, _)| cmd.len()).max().unwrap_or(0);
-    for (cmd, desc) in rows {
-        let pad = max - cmd.len() + 2;
-        let _ = writeln!(w, "  {}{:pad$}{}", c(CMD, cmd), "", desc);
-    }
-}
-
 // --- Help text ---
+//
+// The pages themselves live in `help`, shared with `secrt man`.
 
 fn print_usage(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
+    apply_help_style(deps);
+    let c = deps.style.color_func((deps.is_stdout_tty)());
     let _ = write!(
         deps.stderr,
-        "{} — one-time secret sharing\n\n  {}              share a secret (interactive)\n  {} {}       retrieve a secret\n\nRun '{}' for full usage.\n",
+        "{} {} one-time secret sharing\n\n  {}              share a secret (interactive)\n  {} {}       retrieve a secret\n\nRun '{}' for full usage.\n",
         c(CMD, "secrt"),
+        deps.style.glyph(DASH),
         c(CMD, "secrt send"),
         c(CMD, "secrt get"),
         c(ARG, "<url>"),
@@ -952,414 +2122,59 @@ fn print_usage(deps: &mut Deps) {
 }
 
 pub fn print_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(w, "{} — one-time secret sharing\n", c(CMD, "secrt"));
-    let _ = writeln!(
-        w,
-        "{}\n  {} {} {}\n",
-        c(HEADING, "USAGE"),
-        c(CMD, "secrt"),
-        c(CMD, "<command>"),
-        c(ARG, "[options]")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "COMMANDS"));
-    write_cmd_rows(
-        w,
-        &c,
-        &[
-            ("send", "Encrypt and upload a secret"),
-            ("get", "Retrieve and decrypt a secret"),
-            ("burn", "Destroy a secret (requires API key)"),
-            ("gen", "Generate a random password"),
-            ("config", "Show or initialize configuration"),
-            ("version", "Show version"),
-            ("help", "Show this help"),
-            ("completion", "Output shell completion script"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "GLOBAL OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            (
-                "--base-url",
-                "<url>",
-                "Server URL (default: https://secrt.ca)",
-            ),
-            ("--api-key", "<key>", "API key for authenticated access"),
-            ("--json", "", "Output as JSON"),
-            ("--silent", "", "Suppress status output"),
-            ("-h, --help", "", "Show help"),
-            ("-v, --version", "", "Show version"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "EXAMPLES"));
-    let _ = writeln!(w, "  {} Share a secret from stdin:", c(DIM, "#"));
-    let _ = writeln!(
-        w,
-        "  echo \"pw123\" | {} {}",
-        c(CMD, "secrt"),
-        c(CMD, "send")
-    );
-    let _ = writeln!(
-        w,
-        "\n  {} Generate and share a 32-char password (1h TTL):",
-        c(DIM, "#")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} 32 {} 1h",
-        c(CMD, "secrt"),
-        c(CMD, "send gen"),
-        c(OPT, "-L"),
-        c(OPT, "--ttl")
-    );
-    let _ = writeln!(w, "\n  {} Retrieve a secret:", c(DIM, "#"));
-    let _ = writeln!(w, "  {} https://secrt.ca/s/abc#key", c(CMD, "secrt get"));
+    help::print(deps, &help::MAIN);
 }
 
 pub fn print_send_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(
-        w,
-        "{} {} — Encrypt and upload a secret\n",
-        c(CMD, "secrt"),
-        c(CMD, "send")
-    );
-    let _ = writeln!(
-        w,
-        "{}\n  {} {} {}\n",
-        c(HEADING, "USAGE"),
-        c(CMD, "secrt"),
-        c(CMD, "send"),
-        c(ARG, "[options]")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            ("--ttl", "<ttl>", "TTL for the secret (e.g., 5m, 2h, 1d)"),
-            (
-                "--text",
-                "<value>",
-                "Secret text (visible in shell history)",
-            ),
-            ("-f, --file", "<path>", "Read secret from a file"),
-            (
-                "-m, --multi-line",
-                "",
-                "Multi-line input (read until Ctrl+D)",
-            ),
-            ("--trim", "", "Trim leading/trailing whitespace"),
-            ("-s, --show", "", "Show input as you type"),
-            ("--hidden", "", "Hide input (default, overrides --show)"),
-            ("-p, --passphrase-prompt", "", "Prompt for passphrase"),
-            ("-n, --no-passphrase", "", "Skip default passphrase"),
-            ("--passphrase-env", "<name>", "Read passphrase from env var"),
-            ("--passphrase-file", "<path>", "Read passphrase from file"),
-            ("--base-url", "<url>", "Server URL"),
-            ("--api-key", "<key>", "API key"),
-            ("--json", "", "Output as JSON"),
-            ("--silent", "", "Suppress status output"),
-            ("-h, --help", "", "Show help"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "INPUT"));
-    let _ = writeln!(
-        w,
-        "  Interactive: single-line hidden input (like a password)."
-    );
-    let _ = writeln!(
-        w,
-        "  Use {} for multi-line input, {} or {} for alternatives.",
-        c(OPT, "-m"),
-        c(OPT, "--text"),
-        c(OPT, "-f/--file")
-    );
-    let _ = writeln!(
-        w,
-        "  Use {} or {} to generate and share a random password.",
-        c(CMD, "gen"),
-        c(CMD, "generate")
-    );
-    let _ = writeln!(
-        w,
-        "  Set show_input = true in config to show input by default."
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "EXAMPLES"));
-    let _ = writeln!(
-        w,
-        "  echo \"secret\" | {} {}",
-        c(CMD, "secrt"),
-        c(CMD, "send")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} \"my secret\" {} 5m",
-        c(CMD, "secrt"),
-        c(CMD, "send"),
-        c(OPT, "--text"),
-        c(OPT, "--ttl")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} 32 {} 1h",
-        c(CMD, "secrt"),
-        c(CMD, "send gen"),
-        c(OPT, "-L"),
-        c(OPT, "--ttl")
-    );
+    help::print(deps, &help::SEND);
 }
 
 pub fn print_get_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(
-        w,
-        "{} {} — Retrieve and decrypt a secret\n",
-        c(CMD, "secrt"),
-        c(CMD, "get")
-    );
-    let _ = writeln!(
-        w,
-        "{}\n  {} {} {} {}\n",
-        c(HEADING, "USAGE"),
-        c(CMD, "secrt"),
-        c(CMD, "get"),
-        c(ARG, "<share-url>"),
-        c(ARG, "[options]")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            (
-                "-o, --output",
-                "<path>",
-                "Write output to file (use - for stdout)",
-            ),
-            ("-p, --passphrase-prompt", "", "Prompt for passphrase"),
-            (
-                "-n, --no-passphrase",
-                "",
-                "Skip configured decryption passphrases",
-            ),
-            ("--passphrase-env", "<name>", "Read passphrase from env var"),
-            ("--passphrase-file", "<path>", "Read passphrase from file"),
-            ("--base-url", "<url>", "Server URL"),
-            ("--json", "", "Output as JSON"),
-            ("--silent", "", "Suppress status output"),
-            ("-h, --help", "", "Show help"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "EXAMPLES"));
-    let _ = writeln!(
-        w,
-        "  {} {} https://secrt.ca/s/abc#key",
-        c(CMD, "secrt"),
-        c(CMD, "get")
-    );
-    let _ = writeln!(
-        w,
-        "\n  {} The {} subcommand is optional:",
-        c(DIM, "#"),
-        c(CMD, "get")
-    );
-    let _ = writeln!(
-        w,
-        "  {} https://secrt.ca/s/abc#key {} mysecret.txt",
-        c(CMD, "secrt"),
-        c(OPT, "-o")
-    );
+    help::print(deps, &help::GET);
 }
 
 pub fn print_burn_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(
-        w,
-        "{} {} — Destroy a secret (requires API key)\n",
-        c(CMD, "secrt"),
-        c(CMD, "burn")
-    );
-    let _ = writeln!(
-        w,
-        "{}\n  {} {} {} {}\n",
-        c(HEADING, "USAGE"),
-        c(CMD, "secrt"),
-        c(CMD, "burn"),
-        c(ARG, "<id-or-url>"),
-        c(ARG, "[options]")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            ("--api-key", "<key>", "API key (required)"),
-            ("--base-url", "<url>", "Server URL"),
-            ("--json", "", "Output as JSON"),
-            ("--silent", "", "Suppress status output"),
-            ("-h, --help", "", "Show help"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "EXAMPLES"));
-    let _ = writeln!(
-        w,
-        "  {} {} test-id {} sk_prefix.secret",
-        c(CMD, "secrt"),
-        c(CMD, "burn"),
-        c(OPT, "--api-key")
-    );
+    help::print(deps, &help::BURN);
+}
+
+pub fn print_login_help(deps: &mut Deps) {
+    help::print(deps, &help::LOGIN);
+}
+
+pub fn print_logout_help(deps: &mut Deps) {
+    help::print(deps, &help::LOGOUT);
+}
+
+pub fn print_doctor_help(deps: &mut Deps) {
+    help::print(deps, &help::DOCTOR);
+}
+
+pub fn print_info_help(deps: &mut Deps) {
+    help::print(deps, &help::INFO);
+}
+
+pub fn print_list_help(deps: &mut Deps) {
+    help::print(deps, &help::LIST);
+}
+
+pub fn print_watch_help(deps: &mut Deps) {
+    help::print(deps, &help::WATCH);
+}
+
+pub fn print_dev_server_help(deps: &mut Deps) {
+    help::print(deps, &help::DEV_SERVER);
 }
 
 pub fn print_gen_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(
-        w,
-        "{} {} — Generate a random password\n",
-        c(CMD, "secrt"),
-        c(CMD, "gen")
-    );
-    let _ = writeln!(
-        w,
-        "{}\n  {} {} {}\n",
-        c(HEADING, "USAGE"),
-        c(CMD, "secrt"),
-        c(CMD, "gen"),
-        c(ARG, "[options]")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            ("-L, --length", "<n>", "Password length (default: 20)"),
-            ("-S, --no-symbols", "", "Exclude symbols"),
-            ("-N, --no-numbers", "", "Exclude digits"),
-            ("-C, --no-caps", "", "Exclude uppercase letters"),
-            ("-G, --grouped", "", "Group characters by type"),
-            ("--count", "<n>", "Generate multiple passwords"),
-            ("--json", "", "Output as JSON"),
-            ("-h, --help", "", "Show help"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "CHARACTER SETS"));
-    let _ = writeln!(w, "  Lowercase: a-z  (always included)");
-    let _ = writeln!(w, "  Uppercase: A-Z");
-    let _ = writeln!(w, "  Digits:    0-9");
-    let _ = writeln!(w, "  Symbols:   !@*^_+-=?");
-    let _ = writeln!(w, "\n{}", c(HEADING, "EXAMPLES"));
-    let _ = writeln!(
-        w,
-        "  {} {}                 20-char password, all classes",
-        c(CMD, "secrt"),
-        c(CMD, "gen")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} 32          32-char password",
-        c(CMD, "secrt"),
-        c(CMD, "gen"),
-        c(OPT, "-L")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {}              no symbols",
-        c(CMD, "secrt"),
-        c(CMD, "gen"),
-        c(OPT, "-S")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {}              grouped by char type",
-        c(CMD, "secrt"),
-        c(CMD, "gen"),
-        c(OPT, "-G")
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} 5    five passwords",
-        c(CMD, "secrt"),
-        c(CMD, "gen"),
-        c(OPT, "--count")
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "COMBINED MODE"));
-    let _ = writeln!(
-        w,
-        "  Generate a password and immediately share it as a secret."
-    );
-    let _ = writeln!(
-        w,
-        "  {} {} {} 1h",
-        c(CMD, "secrt"),
-        c(CMD, "gen send"),
-        c(OPT, "--ttl")
-    );
-    let _ = writeln!(
-        w,
-        "  All {} and {} options can be combined.",
-        c(CMD, "gen"),
-        c(CMD, "send")
-    );
+    help::print(deps, &help::GEN);
 }
 
 pub fn print_config_help(deps: &mut Deps) {
-    let c = color_func((deps.is_stdout_tty)());
-    let w = &mut deps.stderr;
-    let _ = writeln!(
-        w,
-        "{} {} — Show config / init / path\n",
-        c(CMD, "secrt"),
-        c(CMD, "config")
-    );
-    let _ = writeln!(w, "{}", c(HEADING, "SUBCOMMANDS"));
-    write_cmd_rows(
-        w,
-        &c,
-        &[
-            ("secrt config", "Show effective config and file path"),
-            ("secrt config init", "Create template config file"),
-            ("secrt config path", "Print config file path"),
-            (
-                "secrt config set-passphrase",
-                "Store passphrase in OS keychain",
-            ),
-            (
-                "secrt config delete-passphrase",
-                "Remove passphrase from OS keychain",
-            ),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "OPTIONS"));
-    write_option_rows(
-        w,
-        &c,
-        &[
-            ("--force", "", "Overwrite existing config file (for init)"),
-            ("-h, --help", "", "Show help"),
-        ],
-    );
-    let _ = writeln!(w, "\n{}", c(HEADING, "CONFIG"));
-    let _ = writeln!(w, "  Settings are loaded from ~/.config/secrt/config.toml.");
-    let _ = writeln!(
-        w,
-        "  Supported keys: api_key, base_url, default_ttl, passphrase,"
-    );
-    let _ = writeln!(w, "  decryption_passphrases, show_input, use_keychain.");
-    let _ = writeln!(
-        w,
-        "  Precedence: CLI flag {} env var {} config file {} default.",
-        c(DIM, "\u{203a}"),
-        c(DIM, "\u{203a}"),
-        c(DIM, "\u{203a}"),
-    );
+    help::print(deps, &help::CONFIG);
+}
+
+pub fn print_man_help(deps: &mut Deps) {
+    help::print(deps, &help::MAN);
 }
 
 #[cfg(test)]
@@ -1374,7 +2189,7 @@ mod tests {
 
     #[test]
     fn flags_empty() {
-        let pa = parse_flags(&s(&[])).unwrap();
+        let pa = parse_flags("send", &s(&[])).unwrap();
         assert!(pa.args.is_empty());
         assert!(!pa.json);
         assert!(pa.base_url.is_empty());
@@ -1383,201 +2198,201 @@ mod tests {
 
     #[test]
     fn flags_json() {
-        let pa = parse_flags(&s(&["--json"])).unwrap();
+        let pa = parse_flags("send", &s(&["--json"])).unwrap();
         assert!(pa.json);
     }
 
     #[test]
     fn flags_base_url() {
-        let pa = parse_flags(&s(&["--base-url", "https://example.com"])).unwrap();
+        let pa = parse_flags("send", &s(&["--base-url", "https://example.com"])).unwrap();
         assert_eq!(pa.base_url, "https://example.com");
         assert!(pa.base_url_from_flag);
     }
 
     #[test]
     fn flags_api_key() {
-        let pa = parse_flags(&s(&["--api-key", "sk_test"])).unwrap();
+        let pa = parse_flags("send", &s(&["--api-key", "sk_test"])).unwrap();
         assert_eq!(pa.api_key, "sk_test");
     }
 
     #[test]
     fn flags_ttl() {
-        let pa = parse_flags(&s(&["--ttl", "5m"])).unwrap();
+        let pa = parse_flags("send", &s(&["--ttl", "5m"])).unwrap();
         assert_eq!(pa.ttl, "5m");
     }
 
     #[test]
     fn flags_text() {
-        let pa = parse_flags(&s(&["--text", "hello"])).unwrap();
+        let pa = parse_flags("send", &s(&["--text", "hello"])).unwrap();
         assert_eq!(pa.text, "hello");
     }
 
     #[test]
     fn flags_file() {
-        let pa = parse_flags(&s(&["--file", "/tmp/secret.txt"])).unwrap();
+        let pa = parse_flags("send", &s(&["--file", "/tmp/secret.txt"])).unwrap();
         assert_eq!(pa.file, "/tmp/secret.txt");
     }
 
     #[test]
     fn flags_multi_line() {
-        let pa = parse_flags(&s(&["--multi-line"])).unwrap();
+        let pa = parse_flags("send", &s(&["--multi-line"])).unwrap();
         assert!(pa.multi_line);
     }
 
     #[test]
     fn flags_multi_line_short() {
-        let pa = parse_flags(&s(&["-m"])).unwrap();
+        let pa = parse_flags("send", &s(&["-m"])).unwrap();
         assert!(pa.multi_line);
     }
 
     #[test]
     fn flags_trim() {
-        let pa = parse_flags(&s(&["--trim"])).unwrap();
+        let pa = parse_flags("send", &s(&["--trim"])).unwrap();
         assert!(pa.trim);
     }
 
     #[test]
     fn flags_show() {
-        let pa = parse_flags(&s(&["--show"])).unwrap();
+        let pa = parse_flags("send", &s(&["--show"])).unwrap();
         assert!(pa.show);
     }
 
     #[test]
     fn flags_show_short() {
-        let pa = parse_flags(&s(&["-s"])).unwrap();
+        let pa = parse_flags("send", &s(&["-s"])).unwrap();
         assert!(pa.show);
     }
 
     #[test]
     fn flags_hidden() {
-        let pa = parse_flags(&s(&["--hidden"])).unwrap();
+        let pa = parse_flags("send", &s(&["--hidden"])).unwrap();
         assert!(pa.hidden);
     }
 
     #[test]
     fn flags_silent() {
-        let pa = parse_flags(&s(&["--silent"])).unwrap();
+        let pa = parse_flags("send", &s(&["--silent"])).unwrap();
         assert!(pa.silent);
     }
 
     #[test]
     fn flags_multi_line_and_trim() {
-        let pa = parse_flags(&s(&["--multi-line", "--trim"])).unwrap();
+        let pa = parse_flags("send", &s(&["--multi-line", "--trim"])).unwrap();
         assert!(pa.multi_line);
         assert!(pa.trim);
     }
 
     #[test]
     fn flags_passphrase_prompt() {
-        let pa = parse_flags(&s(&["--passphrase-prompt"])).unwrap();
+        let pa = parse_flags("send", &s(&["--passphrase-prompt"])).unwrap();
         assert!(pa.passphrase_prompt);
     }
 
     #[test]
     fn flags_passphrase_prompt_short() {
-        let pa = parse_flags(&s(&["-p"])).unwrap();
+        let pa = parse_flags("send", &s(&["-p"])).unwrap();
         assert!(pa.passphrase_prompt);
     }
 
     #[test]
     fn flags_passphrase_env() {
-        let pa = parse_flags(&s(&["--passphrase-env", "MY_PASS"])).unwrap();
+        let pa = parse_flags("send", &s(&["--passphrase-env", "MY_PASS"])).unwrap();
         assert_eq!(pa.passphrase_env, "MY_PASS");
     }
 
     #[test]
     fn flags_passphrase_file() {
-        let pa = parse_flags(&s(&["--passphrase-file", "/tmp/pass"])).unwrap();
+        let pa = parse_flags("send", &s(&["--passphrase-file", "/tmp/pass"])).unwrap();
         assert_eq!(pa.passphrase_file, "/tmp/pass");
     }
 
     #[test]
     fn flags_missing_value_base_url() {
-        let err = parse_flags(&s(&["--base-url"]));
+        let err = parse_flags("send", &s(&["--base-url"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_api_key() {
-        let err = parse_flags(&s(&["--api-key"]));
+        let err = parse_flags("send", &s(&["--api-key"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_ttl() {
-        let err = parse_flags(&s(&["--ttl"]));
+        let err = parse_flags("send", &s(&["--ttl"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_text() {
-        let err = parse_flags(&s(&["--text"]));
+        let err = parse_flags("send", &s(&["--text"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_file() {
-        let err = parse_flags(&s(&["--file"]));
+        let err = parse_flags("send", &s(&["--file"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_output() {
-        let pa = parse_flags(&s(&["--output", "/tmp/out.bin"])).unwrap();
+        let pa = parse_flags("get", &s(&["--output", "/tmp/out.bin"])).unwrap();
         assert_eq!(pa.output, "/tmp/out.bin");
     }
 
     #[test]
     fn flags_output_short() {
-        let pa = parse_flags(&s(&["-o", "out.txt"])).unwrap();
+        let pa = parse_flags("get", &s(&["-o", "out.txt"])).unwrap();
         assert_eq!(pa.output, "out.txt");
     }
 
     #[test]
     fn flags_missing_value_output() {
-        let err = parse_flags(&s(&["--output"]));
+        let err = parse_flags("get", &s(&["--output"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_passphrase_env() {
-        let err = parse_flags(&s(&["--passphrase-env"]));
+        let err = parse_flags("send", &s(&["--passphrase-env"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_missing_value_passphrase_file() {
-        let err = parse_flags(&s(&["--passphrase-file"]));
+        let err = parse_flags("send", &s(&["--passphrase-file"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_help() {
-        let err = parse_flags(&s(&["--help"]));
+        let err = parse_flags("send", &s(&["--help"]));
         assert!(matches!(err, Err(CliError::ShowHelp)));
     }
 
     #[test]
     fn flags_help_short() {
-        let err = parse_flags(&s(&["-h"]));
+        let err = parse_flags("send", &s(&["-h"]));
         assert!(matches!(err, Err(CliError::ShowHelp)));
     }
 
     #[test]
     fn flags_unknown() {
-        let err = parse_flags(&s(&["--bogus"]));
+        let err = parse_flags("send", &s(&["--bogus"]));
         assert!(matches!(err, Err(CliError::Error(_))));
     }
 
     #[test]
     fn flags_positional() {
-        let pa = parse_flags(&s(&["foo", "bar"])).unwrap();
+        let pa = parse_flags("send", &s(&["foo", "bar"])).unwrap();
         assert_eq!(pa.args, vec!["foo", "bar"]);
     }
 
     #[test]
     fn flags_mixed() {
-        let pa = parse_flags(&s(&["myurl", "--json", "--ttl", "5m"])).unwrap();
+        let pa = parse_flags("send", &s(&["myurl", "--json", "--ttl", "5m"])).unwrap();
         assert_eq!(pa.args, vec!["myurl"]);
         assert!(pa.json);
         assert_eq!(pa.ttl, "5m");
@@ -1585,13 +2400,13 @@ mod tests {
 
     #[test]
     fn flags_no_passphrase() {
-        let pa = parse_flags(&s(&["--no-passphrase"])).unwrap();
+        let pa = parse_flags("send", &s(&["--no-passphrase"])).unwrap();
         assert!(pa.no_passphrase);
     }
 
     #[test]
     fn flags_no_passphrase_short() {
-        let pa = parse_flags(&s(&["-n"])).unwrap();
+        let pa = parse_flags("send", &s(&["-n"])).unwrap();
         assert!(pa.no_passphrase);
     }
 
@@ -1599,68 +2414,68 @@ mod tests {
 
     #[test]
     fn flags_eq_ttl() {
-        let pa = parse_flags(&s(&["--ttl=5m"])).unwrap();
+        let pa = parse_flags("send", &s(&["--ttl=5m"])).unwrap();
         assert_eq!(pa.ttl, "5m");
     }
 
     #[test]
     fn flags_eq_base_url() {
-        let pa = parse_flags(&s(&["--base-url=https://example.com"])).unwrap();
+        let pa = parse_flags("send", &s(&["--base-url=https://example.com"])).unwrap();
         assert_eq!(pa.base_url, "https://example.com");
         assert!(pa.base_url_from_flag);
     }
 
     #[test]
     fn flags_eq_api_key() {
-        let pa = parse_flags(&s(&["--api-key=sk_test"])).unwrap();
+        let pa = parse_flags("send", &s(&["--api-key=sk_test"])).unwrap();
         assert_eq!(pa.api_key, "sk_test");
     }
 
     #[test]
     fn flags_eq_text() {
-        let pa = parse_flags(&s(&["--text=hello world"])).unwrap();
+        let pa = parse_flags("send", &s(&["--text=hello world"])).unwrap();
         assert_eq!(pa.text, "hello world");
     }
 
     #[test]
     fn flags_eq_file() {
-        let pa = parse_flags(&s(&["--file=/tmp/secret.txt"])).unwrap();
+        let pa = parse_flags("send", &s(&["--file=/tmp/secret.txt"])).unwrap();
         assert_eq!(pa.file, "/tmp/secret.txt");
     }
 
     #[test]
     fn flags_eq_output() {
-        let pa = parse_flags(&s(&["--output=/tmp/out.bin"])).unwrap();
+        let pa = parse_flags("get", &s(&["--output=/tmp/out.bin"])).unwrap();
         assert_eq!(pa.output, "/tmp/out.bin");
     }
 
     #[test]
     fn flags_eq_passphrase_env() {
-        let pa = parse_flags(&s(&["--passphrase-env=MY_PASS"])).unwrap();
+        let pa = parse_flags("send", &s(&["--passphrase-env=MY_PASS"])).unwrap();
         assert_eq!(pa.passphrase_env, "MY_PASS");
     }
 
     #[test]
     fn flags_eq_passphrase_file() {
-        let pa = parse_flags(&s(&["--passphrase-file=/tmp/pass"])).unwrap();
+        let pa = parse_flags("send", &s(&["--passphrase-file=/tmp/pass"])).unwrap();
         assert_eq!(pa.passphrase_file, "/tmp/pass");
     }
 
     #[test]
     fn flags_eq_empty_value() {
-        let pa = parse_flags(&s(&["--text="])).unwrap();
+        let pa = parse_flags("send", &s(&["--text="])).unwrap();
         assert_eq!(pa.text, "");
     }
 
     #[test]
     fn flags_eq_value_with_equals() {
-        let pa = parse_flags(&s(&["--text=a=b=c"])).unwrap();
+        let pa = parse_flags("send", &s(&["--text=a=b=c"])).unwrap();
         assert_eq!(pa.text, "a=b=c");
     }
 
     #[test]
     fn flags_eq_mixed_with_space() {
-        let pa = parse_flags(&s(&["--ttl=5m", "--text", "hello"])).unwrap();
+        let pa = parse_flags("send", &s(&["--ttl=5m", "--text", "hello"])).unwrap();
         assert_eq!(pa.ttl, "5m");
         assert_eq!(pa.text, "hello");
     }
@@ -1669,31 +2484,31 @@ mod tests {
 
     #[test]
     fn flags_short_concat_value() {
-        let pa = parse_flags(&s(&["-L20"])).unwrap();
+        let pa = parse_flags("send", &s(&["-L20"])).unwrap();
         assert_eq!(pa.gen_length, 20);
     }
 
     #[test]
     fn flags_short_eq_value() {
-        let pa = parse_flags(&s(&["-L=20"])).unwrap();
+        let pa = parse_flags("send", &s(&["-L=20"])).unwrap();
         assert_eq!(pa.gen_length, 20);
     }
 
     #[test]
     fn flags_short_concat_file() {
-        let pa = parse_flags(&s(&["-f/tmp/secret.txt"])).unwrap();
+        let pa = parse_flags("send", &s(&["-f/tmp/secret.txt"])).unwrap();
         assert_eq!(pa.file, "/tmp/secret.txt");
     }
 
     #[test]
     fn flags_short_eq_file() {
-        let pa = parse_flags(&s(&["-f=/tmp/secret.txt"])).unwrap();
+        let pa = parse_flags("send", &s(&["-f=/tmp/secret.txt"])).unwrap();
         assert_eq!(pa.file, "/tmp/secret.txt");
     }
 
     #[test]
     fn flags_short_concat_output() {
-        let pa = parse_flags(&s(&["-oout.txt"])).unwrap();
+        let pa = parse_flags("get", &s(&["-oout.txt"])).unwrap();
         assert_eq!(pa.output, "out.txt");
     }
 
@@ -1701,14 +2516,14 @@ mod tests {
 
     #[test]
     fn flags_double_dash_stops_flags() {
-        let pa = parse_flags(&s(&["--json", "--", "--not-a-flag"])).unwrap();
+        let pa = parse_flags("send", &s(&["--json", "--", "--not-a-flag"])).unwrap();
         assert!(pa.json);
         assert_eq!(pa.args, vec!["--not-a-flag"]);
     }
 
     #[test]
     fn flags_double_dash_all_positional() {
-        let pa = parse_flags(&s(&["--", "-m", "--ttl", "5m"])).unwrap();
+        let pa = parse_flags("send", &s(&["--", "-m", "--ttl", "5m"])).unwrap();
         assert!(!pa.multi_line);
         assert!(pa.ttl.is_empty());
         assert_eq!(pa.args, vec!["-m", "--ttl", "5m"]);
@@ -1716,99 +2531,103 @@ mod tests {
 
     #[test]
     fn flags_double_dash_empty() {
-        let pa = parse_flags(&s(&["--"])).unwrap();
+        let pa = parse_flags("send", &s(&["--"])).unwrap();
         assert!(pa.args.is_empty());
     }
 
     #[test]
     fn flags_double_dash_preserves_earlier() {
-        let pa = parse_flags(&s(&["--ttl", "1h", "--", "myurl"])).unwrap();
+        let pa = parse_flags("send", &s(&["--ttl", "1h", "--", "myurl"])).unwrap();
         assert_eq!(pa.ttl, "1h");
         assert_eq!(pa.args, vec!["myurl"]);
     }
 
-    // --- help text drift tests ---
+    // --- flag table tests ---
     //
-    // Every flag accepted by parse_flags() must appear in at least one help
-    // screen. When adding a new flag, add it here too — the parser test
-    // verifies parse_flags accepts it, and the help test verifies it's
-    // documented.
-
-    /// All flags accepted by parse_flags, grouped by which help screen(s)
-    /// must mention them. Each entry is (flag, needs_value, help_fns) where
-    /// help_fns lists which print_*_help functions should contain the flag.
-    ///   "main" = print_help
-    ///   "send" = print_send_help
-    ///   "get"  = print_get_help
-    ///   "burn" = print_burn_help
-    const FLAG_REGISTRY: &[(&str, bool, &[&str])] = &[
-        // Global flags — should appear in main help
-        ("--base-url", true, &["main", "send", "get", "burn"]),
-        ("--api-key", true, &["main", "send", "burn"]),
-        ("--json", false, &["main", "send", "get", "burn"]),
-        ("--silent", false, &["main", "send", "get", "burn"]),
-        ("-h", false, &["main", "send", "get", "burn"]),
-        ("--help", false, &["main", "send", "get", "burn"]),
-        // Send flags
-        ("--ttl", true, &["send"]),
-        ("--text", true, &["send"]),
-        ("--file", true, &["send"]),
-        ("-f", true, &["send"]),
-        ("-m", false, &["send"]),
-        ("--multi-line", false, &["send"]),
-        ("--trim", false, &["send"]),
-        ("-s", false, &["send"]),
-        ("--show", false, &["send"]),
-        ("--hidden", false, &["send"]),
-        // Passphrase flags — send + get
-        ("-p", false, &["send", "get"]),
-        ("--passphrase-prompt", false, &["send", "get"]),
-        ("-n", false, &["send", "get"]),
-        ("--no-passphrase", false, &["send", "get"]),
-        ("--passphrase-env", true, &["send", "get"]),
-        ("--passphrase-file", true, &["send", "get"]),
-        // Get flags
-        ("-o", true, &["get"]),
-        ("--output", true, &["get"]),
-        // Gen flags
-        ("-L", true, &["gen"]),
-        ("--length", true, &["gen"]),
-        ("-S", false, &["gen"]),
-        ("--no-symbols", false, &["gen"]),
-        ("-N", false, &["gen"]),
-        ("--no-numbers", false, &["gen"]),
-        ("-C", false, &["gen"]),
-        ("--no-caps", false, &["gen"]),
-        ("-G", false, &["gen"]),
-        ("--grouped", false, &["gen"]),
-        ("--count", true, &["gen"]),
-    ];
-
-    /// parse_flags must accept every flag in the registry without error.
+    // parse_flags and the OPTIONS sections of each help screen both come
+    // from the tables in `flags`, so a flag added there is accepted and
+    // documented in one step.
+
+    /// parse_flags must accept every flag in a command's table, spelled
+    /// either way.
     #[test]
     fn registry_flags_accepted_by_parser() {
-        for &(flag, needs_value, _) in FLAG_REGISTRY {
-            let test_val = match flag {
-                "--length" | "-L" | "--count" => "10",
-                _ => "test_val",
-            };
-            let args = if needs_value {
-                s(&[flag, test_val])
-            } else {
-                s(&[flag])
-            };
-            let result = parse_flags(&args);
-            // --help / -h returns ShowHelp, which is fine — it's still "accepted"
-            match result {
-                Ok(_) => {}
-                Err(CliError::ShowHelp) => {}
-                Err(CliError::Error(e)) => {
-                    panic!("parse_flags rejected registered flag {}: {}", flag, e);
+        for cmd in flags::COMMANDS {
+            for def in cmd.accepted() {
+                for spelling in std::iter::once(def.long).chain(def.short) {
+                    let args = match def.arg {
+                        Arg::None => s(&[spelling]),
+                        Arg::Text(_) => s(&[spelling, "test_val"]),
+                        Arg::Count(_) => s(&[spelling, "10"]),
+                    };
+                    // --help / -h returns ShowHelp, which is fine — it's still "accepted"
+                    if let Err(CliError::Error(e)) = parse_flags(cmd.name, &args) {
+                        panic!("{} rejected registered flag {}: {}", cmd.name, spelling, e);
+                    }
                 }
             }
         }
     }
 
+    #[test]
+    fn flags_rejected_by_other_commands() {
+        let cases: &[(&str, &[&str], &str)] = &[
+            ("get", &["--ttl", "5m"], "--ttl cannot be used with get"),
+            (
+                "burn",
+                &["--text", "foo"],
+                "--text cannot be used with burn",
+            ),
+            (
+                "gen",
+                &["--passphrase-env", "X"],
+                "did you mean `secrt send --passphrase-env` or `secrt get --passphrase-env`?",
+            ),
+            ("send", &["-o", "x"], "did you mean `secrt get -o`?"),
+            ("send", &["--ttll", "5m"], "did you mean --ttl?"),
+        ];
+        for &(cmd, args, want) in cases {
+            match parse_flags(cmd, &s(args)) {
+                Err(CliError::Error(e)) => assert!(e.contains(want), "{}: {}", cmd, e),
+                _ => panic!("{} accepted {:?}", cmd, args),
+            }
+        }
+    }
+
+    #[test]
+    fn flags_value_types_checked() {
+        let err = |cmd, args: &[&str]| match parse_flags(cmd, &s(args)) {
+            Err(CliError::Error(e)) => e,
+            _ => panic!("accepted {:?}", args),
+        };
+        assert_eq!(err("send", &["--json=yes"]), "--json does not take a value");
+        assert_eq!(
+            err("gen", &["--length", "0"]),
+            "--length requires a positive integer, got \"0\""
+        );
+        assert_eq!(
+            err("gen", &["--count=x"]),
+            "--count requires a positive integer, got \"x\""
+        );
+    }
+
+    #[test]
+    fn flags_exclusive_groups() {
+        let err = |cmd, args: &[&str]| match parse_flags(cmd, &s(args)) {
+            Err(CliError::Error(e)) => e,
+            _ => panic!("accepted {:?}", args),
+        };
+        assert_eq!(
+            err("get", &["-n", "--passphrase-env", "X"]),
+            "--no-passphrase cannot be combined with --passphrase-env"
+        );
+        assert!(err("send", &["-p", "--passphrase-file", "f"]).contains("at most one"));
+        assert_eq!(
+            err("burn", &["--last", "--from-file", "ids"]),
+            "--from-file cannot be combined with --last"
+        );
+    }
+
     fn capture_help(f: fn(&mut Deps)) -> String {
         let buf = std::rc::Rc::new(std::cell::RefCell::new(Vec::<u8>::new()));
         struct Capture(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
@@ -1821,6 +2640,7 @@ mod tests {
             }
         }
         let mut deps = Deps {
+            style: Default::default(),
             stdin: Box::new(std::io::Cursor::new(Vec::new())),
             stdout: Box::new(Vec::new()),
             stderr: Box::new(Capture(std::rc::Rc::clone(&buf))),
@@ -1832,13 +2652,19 @@ mod tests {
                 Err(io::Error::new(io::ErrorKind::Other, "unused"))
             }),
             make_api: Box::new(|base_url: &str, api_key: &str| {
-                Box::new(crate::client::ApiClient {
-                    base_url: base_url.to_string(),
-                    api_key: api_key.to_string(),
-                })
+                Box::new(crate::client::ApiClient::new(base_url, api_key))
             }),
             get_keychain_secret: Box::new(|_: &str| None),
             get_keychain_secret_list: Box::new(|_: &str| Vec::new()),
+            set_keychain_secret: Box::new(|_: &str, _: &str| Ok(())),
+            delete_keychain_secret: Box::new(|_: &str| Ok(())),
+            sleep: Box::new(|_| {}),
+            copy_to_clipboard: Box::new(|_, _| Err("no clipboard".into())),
+            read_clipboard: Box::new(|_| Err("no clipboard".into())),
+            read_key: Box::new(|_, _| Ok(())),
+            clipboard_available: Box::new(|_| Err("no clipboard".into())),
+            schedule_clipboard_clear: Box::new(|_, _, _| Ok(())),
+            run_command: Box::new(|_, _| Err("not supported".into())),
         };
         f(&mut deps);
         drop(deps);
@@ -1846,36 +2672,49 @@ mod tests {
         String::from_utf8_lossy(&bytes).to_string()
     }
 
-    /// Every flag in the registry must appear in its designated help screen(s).
+    /// Every flag in a command's table must appear in its help screen, and
+    /// the global flags in the main help.
     #[test]
     fn registry_flags_appear_in_help() {
-        let screens: std::collections::HashMap<&str, String> = [
-            ("main", capture_help(print_help)),
-            ("send", capture_help(print_send_help)),
-            ("get", capture_help(print_get_help)),
-            ("burn", capture_help(print_burn_help)),
-            ("gen", capture_help(print_gen_help)),
-        ]
-        .into_iter()
-        .collect();
-
-        for &(flag, _, expected_screens) in FLAG_REGISTRY {
-            for &screen in expected_screens {
-                let text = screens.get(screen).unwrap();
+        type PrintHelp = fn(&mut Deps);
+        let screens: &[(&str, PrintHelp)] = &[
+            ("send", print_send_help),
+            ("get", print_get_help),
+            ("burn", print_burn_help),
+            ("info", print_info_help),
+            ("list", print_list_help),
+            ("watch", print_watch_help),
+            ("gen", print_gen_help),
+            ("login", print_login_help),
+            ("logout", print_logout_help),
+            ("doctor", print_doctor_help),
+            ("dev-server", print_dev_server_help),
+            ("man", print_man_help),
+        ];
+        assert_eq!(screens.len(), flags::COMMANDS.len());
+        for &(name, print) in screens {
+            let text = capture_help(print);
+            for def in flags::command(name).flags() {
                 assert!(
-                    text.contains(flag),
+                    text.contains(&def.label()),
                     "flag {} not found in {} help text",
-                    flag,
-                    screen,
+                    def.long,
+                    name
                 );
             }
         }
+
+        let main = capture_help(print_help);
+        for flag in ["--base-url", "--profile", "--api-key", "--json", "--silent"] {
+            assert!(main.contains(flag), "flag {} not found in main help", flag);
+        }
     }
 
     // --- resolve_globals tests ---
 
     fn make_deps_for_globals(env: std::collections::HashMap<String, String>) -> Deps {
         Deps {
+            style: Default::default(),
             stdin: Box::new(std::io::Cursor::new(Vec::new())),
             stdout: Box::new(Vec::new()),
             stderr: Box::new(Vec::new()),
@@ -1887,13 +2726,19 @@ mod tests {
                 Err(io::Error::new(io::ErrorKind::Other, "no pass"))
             }),
             make_api: Box::new(|base_url: &str, api_key: &str| {
-                Box::new(crate::client::ApiClient {
-                    base_url: base_url.to_string(),
-                    api_key: api_key.to_string(),
-                })
+                Box::new(crate::client::ApiClient::new(base_url, api_key))
             }),
             get_keychain_secret: Box::new(|_key: &str| None),
             get_keychain_secret_list: Box::new(|_key: &str| Vec::new()),
+            set_keychain_secret: Box::new(|_key: &str, _value: &str| Ok(())),
+            delete_keychain_secret: Box::new(|_key: &str| Ok(())),
+            sleep: Box::new(|_| {}),
+            copy_to_clipboard: Box::new(|_, _| Err("no clipboard".into())),
+            read_clipboard: Box::new(|_| Err("no clipboard".into())),
+            read_key: Box::new(|_, _| Ok(())),
+            clipboard_available: Box::new(|_| Err("no clipboard".into())),
+            schedule_clipboard_clear: Box::new(|_, _, _| Ok(())),
+            run_command: Box::new(|_, _| Err("not supported".into())),
         }
     }
 
@@ -1902,10 +2747,24 @@ mod tests {
         let deps = make_deps_for_globals(std::collections::HashMap::new());
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.base_url, "https://secrt.ca");
     }
 
+    #[test]
+    fn globals_profile_overrides_top_level() {
+        let deps = make_deps_for_globals(std::collections::HashMap::new());
+        let config: crate::config::Config = toml::from_str(
+            "base_url = \"https://top.example\"\napi_key = \"sk_top\"\n\
+             [profiles.work]\nbase_url = \"https://work.example\"\n",
+        )
+        .unwrap();
+        let mut pa = ParsedArgs::default();
+        resolve_globals_with_config(&mut pa, &deps, &config.with_profile("work")).unwrap();
+        assert_eq!(pa.base_url, "https://work.example");
+        assert_eq!(pa.api_key, "sk_top");
+    }
+
     #[test]
     fn globals_env_base_url() {
         let mut env = std::collections::HashMap::new();
@@ -1913,7 +2772,7 @@ mod tests {
         let deps = make_deps_for_globals(env);
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.base_url, "https://test.example.com");
     }
 
@@ -1925,7 +2784,7 @@ mod tests {
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
         pa.base_url = "https://flag.example.com".into();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.base_url, "https://flag.example.com");
     }
 
@@ -1936,7 +2795,7 @@ mod tests {
         let deps = make_deps_for_globals(env);
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.api_key, "sk_from_env");
     }
 
@@ -1945,7 +2804,7 @@ mod tests {
         let deps = make_deps_for_globals(std::collections::HashMap::new());
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert!(pa.api_key.is_empty());
     }
 
@@ -1957,7 +2816,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.base_url, "https://config.example.com");
     }
 
@@ -1969,7 +2828,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.api_key, "sk_from_config");
     }
 
@@ -1983,7 +2842,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.api_key, "sk_from_env");
     }
 
@@ -1996,7 +2855,7 @@ mod tests {
         };
         let mut pa = ParsedArgs::default();
         pa.base_url = "https://flag.example.com".into();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.base_url, "https://flag.example.com");
     }
 
@@ -2008,7 +2867,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert!(pa.show_default);
     }
 
@@ -2020,7 +2879,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.ttl, "2h");
     }
 
@@ -2033,7 +2892,7 @@ mod tests {
         };
         let mut pa = ParsedArgs::default();
         pa.ttl = "5m".into();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.ttl, "5m", "--ttl flag should override config");
     }
 
@@ -2045,7 +2904,7 @@ mod tests {
             ..Default::default()
         };
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert_eq!(pa.decryption_passphrases, vec!["pass1", "pass2"]);
     }
 
@@ -2054,10 +2913,89 @@ mod tests {
         let deps = make_deps_for_globals(std::collections::HashMap::new());
         let config = crate::config::Config::default();
         let mut pa = ParsedArgs::default();
-        resolve_globals_with_config(&mut pa, &deps, &config);
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
         assert!(pa.ttl.is_empty(), "ttl should remain empty when no config");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn globals_commands_override_config_values() {
+        let deps = make_deps_for_globals(std::collections::HashMap::new());
+        let config = crate::config::Config {
+            api_key: Some("sk_file".into()),
+            api_key_command: Some("echo sk_cmd".into()),
+            passphrase: Some("file-pass".into()),
+            passphrase_command: Some("printf 'cmd pass'".into()),
+            decryption_passphrases: vec!["b".into(), "c".into()],
+            decryption_passphrases_command: Some("printf 'a\\nb\\n'".into()),
+            ..Default::default()
+        };
+        let mut pa = ParsedArgs::default();
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
+        assert!(pa.api_key.is_empty());
+        assert!(pa.passphrase_default.is_empty());
+        assert_eq!(pa.decryption_passphrases, vec!["b", "c"]);
+        resolve_api_key(&mut pa).unwrap();
+        resolve_passphrase_default(&mut pa).unwrap();
+        resolve_decryption_passphrases(&mut pa).unwrap();
+        assert_eq!(pa.api_key, "sk_cmd");
+        assert_eq!(pa.passphrase_default, "cmd pass");
+        assert_eq!(pa.decryption_passphrases, vec!["a", "b", "c"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn globals_explicit_passphrase_skips_commands() {
+        let deps = make_deps_for_globals(std::collections::HashMap::new());
+        let config = crate::config::Config {
+            passphrase_command: Some("exit 1".into()),
+            decryption_passphrases_command: Some("exit 1".into()),
+            ..Default::default()
+        };
+        for flag in ["--passphrase-env", "--passphrase-file", "--no-passphrase"] {
+            let mut pa = ParsedArgs::default();
+            match flag {
+                "--passphrase-env" => pa.passphrase_env = "PASS".into(),
+                "--passphrase-file" => pa.passphrase_file = "pass.txt".into(),
+                _ => pa.no_passphrase = true,
+            }
+            resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
+            resolve_passphrase_default(&mut pa).unwrap();
+            resolve_decryption_passphrases(&mut pa).unwrap();
+            assert!(pa.passphrase_default.is_empty(), "{}", flag);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn globals_env_api_key_skips_command() {
+        let mut env = std::collections::HashMap::new();
+        env.insert("SECRET_API_KEY".into(), "sk_env".into());
+        let deps = make_deps_for_globals(env);
+        let config = crate::config::Config {
+            api_key_command: Some("exit 1".into()),
+            ..Default::default()
+        };
+        let mut pa = ParsedArgs::default();
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
+        assert_eq!(pa.api_key, "sk_env");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn globals_failing_command_is_an_error() {
+        let deps = make_deps_for_globals(std::collections::HashMap::new());
+        let config = crate::config::Config {
+            passphrase: Some("file-pass".into()),
+            passphrase_command: Some("exit 4".into()),
+            ..Default::default()
+        };
+        let mut pa = ParsedArgs::default();
+        resolve_globals_with_config(&mut pa, &deps, &config).unwrap();
+        let err = resolve_passphrase_default(&mut pa).unwrap_err();
+        assert_eq!(err, "passphrase_command: `exit 4` exited with status 4");
+    }
+
     // --- format_ttl_seconds tests ---
 
     #[test]
@@ -2181,6 +3119,9 @@ mod tests {
         assert!(!looks_like_share_url("send"));
         assert!(!looks_like_share_url("get"));
         assert!(!looks_like_share_url("burn"));
+        assert!(!looks_like_share_url("info"));
+        assert!(!looks_like_share_url("list"));
+        assert!(!looks_like_share_url("watch"));
         assert!(!looks_like_share_url("gen"));
         assert!(!looks_like_share_url("help"));
         assert!(!looks_like_share_url("version"));
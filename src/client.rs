@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::cell::OnceCell;
 use std::time::Duration;
 
 use crate::envelope::b64_encode;
@@ -34,27 +35,30 @@ pub struct ClaimResponse {
 }
 
 /// Server info response from GET /api/v1/info.
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InfoResponse {
     pub authenticated: bool,
     pub ttl: InfoTTL,
     pub limits: InfoLimits,
     pub claim_rate: InfoRate,
+    /// Remaining quota for the authenticated key, when the server reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<InfoQuota>,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InfoTTL {
     pub default_seconds: i64,
     pub max_seconds: i64,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InfoLimits {
     pub public: InfoTier,
     pub authed: InfoTier,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InfoTier {
     pub max_envelope_bytes: i64,
     pub max_secrets: i64,
@@ -62,24 +66,54 @@ pub struct InfoTier {
     pub rate: InfoRate,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct InfoRate {
     pub requests_per_second: f64,
     pub burst: i64,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InfoQuota {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets_remaining: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_remaining: Option<i64>,
+}
+
+/// Secret lifecycle state from GET /api/v1/secrets/{id}/status.
+/// Reading it never consumes the secret.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SecretStatus {
+    pub id: String,
+    /// One of "pending", "claimed", "burned" or "expired".
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_at: Option<String>,
+}
+
 /// Trait abstracting the API for testing.
 pub trait SecretApi {
     fn create(&self, req: CreateRequest) -> Result<CreateResponse, String>;
     fn claim(&self, secret_id: &str, claim_token: &[u8]) -> Result<ClaimResponse, String>;
     fn burn(&self, secret_id: &str) -> Result<(), String>;
     fn info(&self) -> Result<InfoResponse, String>;
+    fn status(&self, secret_id: &str) -> Result<SecretStatus, String>;
+
+    /// Unix time from the server's `Date` header, for clock-skew checks.
+    /// `Ok(None)` if the server doesn't send one.
+    fn server_time(&self) -> Result<Option<i64>, String> {
+        Ok(None)
+    }
 }
 
-/// HTTP API client for secrt.
+/// HTTP API client for secrt. The underlying agent is created on first use
+/// and reused, so a batch of requests shares its connection pool.
 pub struct ApiClient {
     pub base_url: String,
     pub api_key: String,
+    agent: OnceCell<ureq::Agent>,
 }
 
 /// API error response.
@@ -89,13 +123,23 @@ struct ApiErrorResponse {
 }
 
 impl ApiClient {
-    fn agent(&self) -> ureq::Agent {
-        ureq::Agent::new_with_config(
-            ureq::config::Config::builder()
-                .timeout_global(Some(Duration::from_secs(30)))
-                .http_status_as_error(false)
-                .build(),
-        )
+    pub fn new(base_url: &str, api_key: &str) -> Self {
+        ApiClient {
+            base_url: base_url.to_string(),
+            api_key: api_key.to_string(),
+            agent: OnceCell::new(),
+        }
+    }
+
+    fn agent(&self) -> &ureq::Agent {
+        self.agent.get_or_init(|| {
+            ureq::Agent::new_with_config(
+                ureq::config::Config::builder()
+                    .timeout_global(Some(Duration::from_secs(30)))
+                    .http_status_as_error(false)
+                    .build(),
+            )
+        })
     }
 
     fn handle_ureq_error(&self, err: ureq::Error) -> String {
@@ -238,6 +282,27 @@ impl SecretApi for ApiClient {
         Ok(())
     }
 
+    fn status(&self, secret_id: &str) -> Result<SecretStatus, String> {
+        let endpoint = format!("{}/api/v1/secrets/{}/status", self.base_url, secret_id);
+
+        let mut request = self.agent().get(&endpoint);
+        if !self.api_key.is_empty() {
+            request = request.header("X-API-Key", &self.api_key);
+        }
+
+        let resp = request.call().map_err(|e| self.handle_ureq_error(e))?;
+
+        if resp.status().as_u16() != 200 {
+            return Err(self.read_api_error_from_response(resp));
+        }
+
+        let body_str = resp
+            .into_body()
+            .read_to_string()
+            .map_err(|e| format!("decode response: {}", e))?;
+        serde_json::from_str(&body_str).map_err(|e| format!("decode response: {}", e))
+    }
+
     fn info(&self) -> Result<InfoResponse, String> {
         let endpoint = format!("{}/api/v1/info", self.base_url);
 
@@ -269,12 +334,42 @@ impl SecretApi for ApiClient {
 
         Ok(result)
     }
+
+    fn server_time(&self) -> Result<Option<i64>, String> {
+        let endpoint = format!("{}/api/v1/info", self.base_url);
+        let resp = self
+            .agent()
+            .get(&endpoint)
+            .call()
+            .map_err(|e| self.handle_ureq_error(e))?;
+        Ok(resp
+            .headers()
+            .get("date")
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_http_date))
+    }
+}
+
+/// Parse an HTTP `Date` header (RFC 7231 IMF-fixdate) into Unix seconds.
+fn parse_http_date(value: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|t| t.timestamp())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn http_date_parses() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784111777)
+        );
+        assert_eq!(parse_http_date("yesterday"), None);
+    }
+
     // --- format_status_error: friendly fallback messages ---
 
     #[test]
@@ -0,0 +1,371 @@
+//! Clipboard access through the platform's command-line tools: `wl-copy` /
+//! `wl-paste` on Wayland, `xclip` or `xsel` on X11, `pbcopy` / `pbpaste` on
+//! macOS. `get --copy` schedules a clear by starting a detached
+//! `secrt __clipboard-clear` process, which only clears the clipboard if it
+//! still holds the value we put there.
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use ring::digest::{digest, SHA256};
+
+use crate::cli::Deps;
+
+/// Values accepted by the `clipboard_tool` setting.
+pub const TOOLS: &[&str] = &["auto", "wl-copy", "xclip", "xsel", "pbcopy"];
+
+/// How long `get --copy` leaves a secret on the clipboard when
+/// `clipboard_clear` isn't set.
+pub const DEFAULT_CLEAR: &str = "45s";
+
+/// How long `get --copy` leaves a secret on the clipboard, from the
+/// `clipboard_clear` setting.
+pub fn clear_delay(setting: &str) -> Result<Duration, String> {
+    let setting = if setting.is_empty() {
+        DEFAULT_CLEAR
+    } else {
+        setting
+    };
+    crate::envelope::parse_ttl(setting)
+        .map(|s| Duration::from_secs(s as u64))
+        .map_err(|e| format!("invalid clipboard_clear {:?}: {}", setting, e))
+}
+
+/// Resolve the `clipboard_tool` setting to a tool. `auto` (or unset) picks
+/// by platform and session: pbcopy on macOS, wl-copy under Wayland, then
+/// xclip or xsel under X11, skipping tools that aren't installed.
+pub fn pick_tool(
+    configured: &str,
+    getenv: &dyn Fn(&str) -> Option<String>,
+    installed: &dyn Fn(&str) -> bool,
+) -> Result<&'static str, String> {
+    if !configured.is_empty() && configured != "auto" {
+        return TOOLS
+            .iter()
+            .copied()
+            .find(|t| *t == configured)
+            .ok_or_else(|| {
+                format!(
+                    "unknown clipboard_tool {:?} (valid: {})",
+                    configured,
+                    TOOLS.join(", ")
+                )
+            });
+    }
+    if cfg!(target_os = "macos") {
+        return Ok("pbcopy");
+    }
+    let set = |k: &str| getenv(k).is_some_and(|v| !v.is_empty());
+    let mut candidates = Vec::new();
+    if set("WAYLAND_DISPLAY") {
+        candidates.push("wl-copy");
+    }
+    if set("DISPLAY") {
+        candidates.extend(["xclip", "xsel"]);
+    }
+    if candidates.is_empty() {
+        return Err("no clipboard available (neither WAYLAND_DISPLAY nor DISPLAY is set)".into());
+    }
+    candidates
+        .iter()
+        .copied()
+        .find(|t| installed(t))
+        .ok_or_else(|| {
+            format!(
+                "no clipboard tool found; install {} or set clipboard_tool",
+                candidates.join(" or ")
+            )
+        })
+}
+
+/// Copy `data` to the clipboard; copying nothing clears it.
+pub fn copy(configured: &str, data: &[u8]) -> Result<(), String> {
+    let tool = system_tool(configured)?;
+    let args: &[&str] = match (tool, data.is_empty()) {
+        ("wl-copy", true) => &["--clear"],
+        ("xclip", _) => &["-selection", "clipboard"],
+        ("xsel", true) => &["--clipboard", "--delete"],
+        ("xsel", false) => &["--clipboard", "--input"],
+        _ => &[],
+    };
+    let mut child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", tool, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data)
+            .map_err(|e| format!("failed to write to {}: {}", tool, e))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|e| format!("failed to wait for {}: {}", tool, e))?;
+    if !status.success() {
+        return Err(format!("{} failed ({})", tool, status));
+    }
+    Ok(())
+}
+
+/// Read the clipboard's current contents.
+pub fn paste(configured: &str) -> Result<Vec<u8>, String> {
+    let tool = system_tool(configured)?;
+    let (prog, args): (&str, &[&str]) = match tool {
+        "wl-copy" => ("wl-paste", &["--no-newline"]),
+        "xclip" => ("xclip", &["-selection", "clipboard", "-o"]),
+        "xsel" => ("xsel", &["--clipboard", "--output"]),
+        _ => ("pbpaste", &[]),
+    };
+    let out = Command::new(prog)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("failed to run {}: {}", prog, e))?;
+    let empty = prog == "wl-paste" && nothing_copied(&out.stderr);
+    if !out.status.success() && !empty {
+        return Err(format!("{} failed ({})", prog, out.status));
+    }
+    Ok(out.stdout)
+}
+
+/// Whether wl-paste's error output says the clipboard is empty, which it
+/// reports with a non-zero exit.
+fn nothing_copied(stderr: &[u8]) -> bool {
+    let msg = String::from_utf8_lossy(stderr);
+    msg.contains("Nothing is copied") || msg.contains("No selection")
+}
+
+/// Check that the clipboard can be used (a session and its tool are
+/// there) without reading or changing what's on it.
+pub fn probe(configured: &str) -> Result<(), String> {
+    let tool = system_tool(configured)?;
+    if !on_path(tool) {
+        return Err(format!("{} not found", tool));
+    }
+    Ok(())
+}
+
+/// Start a detached `secrt __clipboard-clear` that clears the clipboard
+/// after `delay` if it still holds `data`. Only a hash of `data` is handed
+/// over, on the child's stdin.
+pub fn schedule_clear(configured: &str, data: &[u8], delay: Duration) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("failed to locate secrt: {}", e))?;
+    let mut child = detach(&mut Command::new(exe))
+        .args([
+            "__clipboard-clear",
+            configured,
+            &delay.as_secs().to_string(),
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to start clipboard timer: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(fingerprint(data).as_bytes())
+            .map_err(|e| format!("failed to start clipboard timer: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Run `cmd` in a session of its own, so the clear timer outlives the
+/// terminal and isn't hit by a Ctrl-C or hangup aimed at the caller.
+#[cfg(unix)]
+fn detach(cmd: &mut Command) -> &mut Command {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setsid is async-signal-safe and touches no parent state.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(not(unix))]
+fn detach(cmd: &mut Command) -> &mut Command {
+    cmd
+}
+
+/// Hex SHA-256 of a clipboard value.
+pub fn fingerprint(data: &[u8]) -> String {
+    digest(&SHA256, data)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Hidden `secrt __clipboard-clear <tool> <seconds>`: wait, then clear the
+/// clipboard if its contents still match the fingerprint read from stdin.
+pub fn run_clear(args: &[String], deps: &mut Deps) -> i32 {
+    let [tool, secs] = args else {
+        return 2;
+    };
+    let Ok(secs) = secs.parse::<u64>() else {
+        return 2;
+    };
+    let mut expected = String::new();
+    if deps.stdin.read_to_string(&mut expected).is_err() {
+        return 2;
+    }
+    (deps.sleep)(Duration::from_secs(secs));
+    match (deps.read_clipboard)(tool) {
+        Ok(current) if fingerprint(&current) == expected.trim() => {
+            match (deps.copy_to_clipboard)(tool, &[]) {
+                Ok(()) => 0,
+                Err(_) => 1,
+            }
+        }
+        Ok(_) => 0,
+        Err(_) => 1,
+    }
+}
+
+fn system_tool(configured: &str) -> Result<&'static str, String> {
+    pick_tool(configured, &|k| std::env::var(k).ok(), &on_path)
+}
+
+fn on_path(prog: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|paths| {
+        std::env::split_paths(&paths).any(|dir| is_executable(&dir.join(prog)))
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |k| {
+            vars.iter()
+                .find(|(name, _)| *name == k)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn auto_prefers_wayland() {
+        let all = |_: &str| true;
+        let tool = pick_tool(
+            "",
+            &env(&[("WAYLAND_DISPLAY", "wayland-0"), ("DISPLAY", ":0")]),
+            &all,
+        );
+        assert_eq!(tool.unwrap(), "wl-copy");
+        let tool = pick_tool("auto", &env(&[("DISPLAY", ":0")]), &all);
+        assert_eq!(tool.unwrap(), "xclip");
+    }
+
+    #[test]
+    fn auto_skips_missing_tools() {
+        let only_xsel = |t: &str| t == "xsel";
+        let tool = pick_tool(
+            "",
+            &env(&[("WAYLAND_DISPLAY", "w"), ("DISPLAY", ":0")]),
+            &only_xsel,
+        );
+        assert_eq!(tool.unwrap(), "xsel");
+        let none = |_: &str| false;
+        let err = pick_tool("", &env(&[("DISPLAY", ":0")]), &none).unwrap_err();
+        assert!(err.contains("install xclip or xsel"), "{}", err);
+    }
+
+    #[test]
+    fn auto_without_session_fails() {
+        let err = pick_tool("", &env(&[]), &|_| true).unwrap_err();
+        assert!(err.contains("no clipboard available"), "{}", err);
+    }
+
+    /// Only does something when re-run by `detached_child_survives_parent`:
+    /// starts a detached `sleep`, prints its pid and exits.
+    #[test]
+    #[ignore]
+    #[cfg(target_os = "linux")]
+    fn detached_child_helper() {
+        if std::env::var_os("SECRT_DETACH_HELPER").is_none() {
+            return;
+        }
+        let pid = detach(&mut Command::new("sleep"))
+            .arg("30")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap()
+            .id();
+        println!("pid={}", pid);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn detached_child_survives_parent() {
+        use std::os::unix::process::CommandExt;
+        let helper = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "clipboard::tests::detached_child_helper",
+                "--ignored",
+                "--nocapture",
+            ])
+            .env("SECRT_DETACH_HELPER", "1")
+            .process_group(0)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let group = helper.id() as libc::pid_t;
+        let out = helper.wait_with_output().unwrap();
+        let out = String::from_utf8_lossy(&out.stdout);
+        let pid: libc::pid_t = out
+            .lines()
+            .find_map(|l| l.split_once("pid=").map(|(_, pid)| pid))
+            .expect("helper printed no pid")
+            .parse()
+            .unwrap();
+
+        // The helper is gone; hang up its process group as a closing
+        // terminal would.
+        unsafe { libc::kill(-group, libc::SIGHUP) };
+        std::thread::sleep(Duration::from_millis(200));
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+        let alive = stat
+            .rsplit(')')
+            .next()
+            .is_some_and(|rest| !rest.trim_start().starts_with('Z'));
+        assert_ne!(unsafe { libc::getsid(pid) }, unsafe { libc::getsid(0) });
+        unsafe { libc::kill(pid, libc::SIGKILL) };
+        assert!(alive, "detached child died with its parent");
+    }
+
+    #[test]
+    fn only_an_empty_clipboard_is_not_an_error() {
+        assert!(nothing_copied(b"Nothing is copied\n"));
+        assert!(nothing_copied(b"No selection\n"));
+        assert!(!nothing_copied(b""));
+        assert!(!nothing_copied(b"Failed to connect to a Wayland server\n"));
+    }
+
+    #[test]
+    fn configured_tool_is_used_as_is() {
+        assert_eq!(pick_tool("xsel", &env(&[]), &|_| false).unwrap(), "xsel");
+        let err = pick_tool("clip.exe", &env(&[]), &|_| true).unwrap_err();
+        assert!(err.starts_with("unknown clipboard_tool"), "{}", err);
+    }
+}
@@ -1,39 +1,316 @@
+//! Colors and glyphs for terminal output. What gets used is a `Style` on
+//! `Deps`: `cli::run` sets it from `--color`, `--plain` and the `NO_COLOR` /
+//! `CLICOLOR_FORCE` environment variables, and it is refined from the
+//! config file when a command loads it.
+
+use std::collections::BTreeMap;
+
+/// A semantic color: the name themes use for it and its default SGR code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub name: &'static str,
+    pub code: &'static str,
+}
+
+const fn token(name: &'static str, code: &'static str) -> Token {
+    Token { name, code }
+}
+
 // Semantic color tokens
-pub const CMD: &str = "36"; // cyan — command names
-pub const OPT: &str = "33"; // yellow — flags/options
-pub const ARG: &str = "2"; // dim — argument placeholders
-pub const HEADING: &str = "1"; // bold — section headings
-pub const SUCCESS: &str = "32"; // green — success indicators
-pub const ERROR: &str = "31"; // red — error prefix
-pub const URL: &str = "1;36"; // bold cyan — share URLs
-pub const LABEL: &str = "37"; // white — prompt labels (Secret:, Passphrase:)
-pub const DIM: &str = "2"; // dim — status messages, hints, secondary text
-pub const WARN: &str = "33"; // yellow — warnings, in-progress
+pub const CMD: Token = token("cmd", "36"); // cyan — command names
+pub const OPT: Token = token("opt", "33"); // yellow — flags/options
+pub const ARG: Token = token("arg", "2"); // dim — argument placeholders
+pub const HEADING: Token = token("heading", "1"); // bold — section headings
+pub const SUCCESS: Token = token("success", "32"); // green — success indicators
+pub const ERROR: Token = token("error", "31"); // red — error prefix
+pub const URL: Token = token("url", "1;36"); // bold cyan — share URLs
+pub const LABEL: Token = token("label", "37"); // white — prompt labels (Secret:, Passphrase:)
+pub const DIM: Token = token("dim", "2"); // dim — status messages, hints, secondary text
+pub const WARN: Token = token("warn", "33"); // yellow — warnings, in-progress
+
+/// Every token, for validating `[theme]` keys.
+pub const TOKENS: &[Token] = &[
+    CMD, OPT, ARG, HEADING, SUCCESS, ERROR, URL, LABEL, DIM, WARN,
+];
+
+/// Values accepted by `--color` and the `color` setting.
+pub const MODES: &[&str] = &["auto", "always", "never"];
+
+/// A status symbol and its stand-in for `--plain`.
+#[derive(Clone, Copy)]
+pub struct Glyph {
+    unicode: &'static str,
+    ascii: &'static str,
+}
+
+pub const CHECK: Glyph = Glyph {
+    unicode: "\u{2713}",
+    ascii: "[ok]",
+};
+pub const CROSS: Glyph = Glyph {
+    unicode: "\u{2717}",
+    ascii: "[fail]",
+};
+pub const LOCK: Glyph = Glyph {
+    unicode: "\u{26b7}",
+    ascii: "[locked]",
+};
+pub const PENDING: Glyph = Glyph {
+    unicode: "\u{25CB}",
+    ascii: "[..]",
+};
+pub const SPARKLE: Glyph = Glyph {
+    unicode: "\u{2726}",
+    ascii: "*",
+};
+pub const ARROW: Glyph = Glyph {
+    unicode: "\u{2192}",
+    ascii: "->",
+};
+pub const DASH: Glyph = Glyph {
+    unicode: "\u{2014}",
+    ascii: "-",
+};
+pub const BULLET: Glyph = Glyph {
+    unicode: "\u{2022}",
+    ascii: "*",
+};
+pub const CHEVRON: Glyph = Glyph {
+    unicode: "\u{203a}",
+    ascii: ">",
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Color when the stream is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn parse(s: &str) -> Option<ColorMode> {
+        match s {
+            "auto" => Some(ColorMode::Auto),
+            "always" => Some(ColorMode::Always),
+            "never" => Some(ColorMode::Never),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Style {
+    pub mode: ColorMode,
+    /// ASCII glyphs instead of Unicode symbols.
+    pub ascii: bool,
+    /// SGR codes by token name, replacing the defaults.
+    pub theme: BTreeMap<String, String>,
+    /// The mode and ASCII choice from the command line, which the config
+    /// file can't override.
+    flag: Option<ColorMode>,
+    flag_plain: bool,
+}
+
+impl Style {
+    /// The style for this run. `flag` is the mode from the command line and
+    /// `config` the one from the config file (`--plain` and `plain` count as
+    /// `never`); the first one given wins. Left at `auto`, `NO_COLOR` turns
+    /// colors off and `CLICOLOR_FORCE` turns them on even when not on a
+    /// terminal. `plain` switches to ASCII glyphs.
+    pub fn resolve(
+        flag: Option<ColorMode>,
+        config: Option<ColorMode>,
+        plain: bool,
+        getenv: &dyn Fn(&str) -> Option<String>,
+    ) -> Style {
+        let set = |k: &str| getenv(k).is_some_and(|v| !v.is_empty() && v != "0");
+        let mode = match flag.or(config) {
+            Some(mode) if mode != ColorMode::Auto => mode,
+            _ if getenv("NO_COLOR").is_some_and(|v| !v.is_empty()) => ColorMode::Never,
+            _ if set("CLICOLOR_FORCE") => ColorMode::Always,
+            _ => ColorMode::Auto,
+        };
+        Style {
+            mode,
+            ascii: plain,
+            ..Style::default()
+        }
+    }
+
+    /// The style from `--color` and `--plain`, before any config file is
+    /// read.
+    pub fn from_flags(
+        flag: Option<ColorMode>,
+        plain: bool,
+        getenv: &dyn Fn(&str) -> Option<String>,
+    ) -> Style {
+        let flag = flag.or(plain.then_some(ColorMode::Never));
+        Style {
+            flag,
+            flag_plain: plain,
+            ..Style::resolve(flag, None, plain, getenv)
+        }
+    }
+
+    /// This style with the config file's `color`, `plain` and `[theme]`
+    /// settings layered under the command-line flags. `plain` turns colors
+    /// off even if `color` asks for them; only `--color` overrides it.
+    pub fn with_config(
+        &self,
+        color: Option<ColorMode>,
+        plain: bool,
+        theme: BTreeMap<String, String>,
+        getenv: &dyn Fn(&str) -> Option<String>,
+    ) -> Style {
+        let config = if plain { Some(ColorMode::Never) } else { color };
+        Style {
+            theme,
+            flag: self.flag,
+            flag_plain: self.flag_plain,
+            ..Style::resolve(self.flag, config, self.flag_plain || plain, getenv)
+        }
+    }
 
-pub type ColorFn = Box<dyn Fn(&str, &str) -> String>;
+    /// The symbol to show for `g` in this style.
+    pub fn glyph(&self, g: Glyph) -> &'static str {
+        if self.ascii {
+            g.ascii
+        } else {
+            g.unicode
+        }
+    }
+
+    /// Returns a function that wraps text in ANSI escape codes if this style
+    /// calls for color; in `auto` mode, that is when is_tty is true.
+    pub fn color_func(&self, is_tty: bool) -> ColorFn {
+        if self.colors(is_tty) {
+            let theme = self.theme.clone();
+            Box::new(move |t: Token, text: &str| {
+                let code = theme.get(t.name).map_or(t.code, String::as_str);
+                format!("\x1b[{}m{}\x1b[0m", code, text)
+            })
+        } else {
+            Box::new(|_t: Token, text: &str| text.to_string())
+        }
+    }
 
-/// Returns a function that wraps text in ANSI escape codes if is_tty is true.
-pub fn color_func(is_tty: bool) -> ColorFn {
-    if is_tty {
-        Box::new(|code: &str, text: &str| format!("\x1b[{}m{}\x1b[0m", code, text))
-    } else {
-        Box::new(|_code: &str, text: &str| text.to_string())
+    fn colors(&self, is_tty: bool) -> bool {
+        match self.mode {
+            ColorMode::Auto => is_tty,
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+        }
     }
 }
 
+/// Whether `code` is usable as a theme color: SGR parameters only, so a
+/// config file can't smuggle in other escape sequences.
+pub fn valid_code(code: &str) -> bool {
+    !code.is_empty() && code.chars().all(|c| c.is_ascii_digit() || c == ';')
+}
+
+pub type ColorFn = Box<dyn Fn(Token, &str) -> String>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |k| {
+            vars.iter()
+                .find(|(name, _)| *name == k)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
     #[test]
     fn tty_wraps_ansi() {
-        let c = color_func(true);
-        assert_eq!(c("36", "text"), "\x1b[36mtext\x1b[0m");
+        let c = Style::default().color_func(true);
+        assert_eq!(c(CMD, "text"), "\x1b[36mtext\x1b[0m");
     }
 
     #[test]
     fn non_tty_plain() {
-        let c = color_func(false);
-        assert_eq!(c("36", "text"), "text");
+        let c = Style::default().color_func(false);
+        assert_eq!(c(CMD, "text"), "text");
+    }
+
+    #[test]
+    fn environment_picks_mode() {
+        let mode = |vars: &[(&str, &str)]| Style::resolve(None, None, false, &env(vars)).mode;
+        assert_eq!(mode(&[]), ColorMode::Auto);
+        assert_eq!(mode(&[("NO_COLOR", "1")]), ColorMode::Never);
+        assert_eq!(mode(&[("NO_COLOR", "")]), ColorMode::Auto);
+        assert_eq!(mode(&[("CLICOLOR_FORCE", "1")]), ColorMode::Always);
+        assert_eq!(mode(&[("CLICOLOR_FORCE", "0")]), ColorMode::Auto);
+        assert_eq!(
+            mode(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]),
+            ColorMode::Never
+        );
+    }
+
+    #[test]
+    fn flag_beats_config_beats_environment() {
+        let no_color = env(&[("NO_COLOR", "1")]);
+        let resolve = |flag, config| Style::resolve(flag, config, false, &no_color).mode;
+        assert_eq!(
+            resolve(Some(ColorMode::Always), Some(ColorMode::Never)),
+            ColorMode::Always
+        );
+        assert_eq!(resolve(None, Some(ColorMode::Always)), ColorMode::Always);
+        assert_eq!(
+            resolve(Some(ColorMode::Auto), Some(ColorMode::Always)),
+            ColorMode::Never
+        );
+        assert!(Style::resolve(None, None, true, &env(&[])).ascii);
+    }
+
+    #[test]
+    fn style_sets_colors_theme_and_glyphs() {
+        let mut theme = BTreeMap::new();
+        theme.insert("success".to_string(), "1;32".to_string());
+        let style = Style {
+            mode: ColorMode::Always,
+            ascii: true,
+            theme,
+            ..Style::default()
+        };
+        let c = style.color_func(false);
+        assert_eq!(c(SUCCESS, "ok"), "\x1b[1;32mok\x1b[0m");
+        assert_eq!(c(ERROR, "no"), "\x1b[31mno\x1b[0m");
+        assert_eq!(style.glyph(CHECK), "[ok]");
+        assert_eq!(Style::default().glyph(CHECK), "\u{2713}");
+    }
+
+    #[test]
+    fn config_is_layered_under_flags() {
+        let none = env(&[]);
+        let from_config = |style: &Style| {
+            style.with_config(Some(ColorMode::Always), true, BTreeMap::new(), &none)
+        };
+        // plain = true beats color = "always" from the same file
+        let style = from_config(&Style::from_flags(None, false, &none));
+        assert_eq!((style.mode, style.ascii), (ColorMode::Never, true));
+        let style = from_config(&Style::from_flags(Some(ColorMode::Always), false, &none));
+        assert_eq!((style.mode, style.ascii), (ColorMode::Always, true));
+        let style = from_config(&Style::from_flags(None, true, &none));
+        assert_eq!(style.mode, ColorMode::Never);
+
+        let style = Style::from_flags(None, false, &none).with_config(
+            Some(ColorMode::Always),
+            false,
+            BTreeMap::new(),
+            &none,
+        );
+        assert_eq!((style.mode, style.ascii), (ColorMode::Always, false));
+    }
+
+    #[test]
+    fn theme_codes_are_sgr_only() {
+        assert!(valid_code("38;5;208"));
+        assert!(!valid_code(""));
+        assert!(!valid_code("31m\x1b]0;x"));
     }
 }
@@ -1,183 +1,864 @@
-pub const BASH_COMPLETION: &str = r#"_secrt() {
-    local cur prev commands
+//! Shell completion scripts, generated from the command list in `cli` and
+//! the flag tables in `flags` so they always match what the parser accepts.
+//! Values that depend on the user's setup (profile names, config keys and
+//! recently sent secret IDs) are looked up while completing through the
+//! hidden `secrt __complete <kind>` command.
+
+use std::io::{self, Write};
+
+use chrono::Utc;
+
+use crate::cli::{Deps, CONFIG_SUBCOMMANDS, TOP_LEVEL_COMMANDS};
+use crate::config::{load_config_with, SETTINGS};
+use crate::flags::{self, Arg, Flag};
+use crate::history::{history_path_with, load_history};
+
+/// Shells `secrt completion` can generate a script for.
+pub const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell", "elvish", "nushell"];
+
+/// How many live secret IDs `__complete secrets` offers, newest first.
+const RECENT_SECRETS: usize = 20;
+
+const PASSPHRASE_ACTIONS: &[&str] = &["list", "add", "remove", "promote"];
+
+/// What to offer for a flag's value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Value {
+    None,
+    Any,
+    File,
+    Dir,
+    Command,
+    Profile,
+}
+
+fn value_of(f: &Flag) -> Value {
+    match f.arg {
+        Arg::None => Value::None,
+        _ if f.long == "--profile" => Value::Profile,
+        Arg::Text("<path>") => Value::File,
+        Arg::Text("<dir>") => Value::Dir,
+        Arg::Text("<command>") => Value::Command,
+        Arg::Text(_) | Arg::Count(_) => Value::Any,
+    }
+}
+
+/// What to offer for a command's positional arguments.
+enum Positional {
+    None,
+    Words(&'static [&'static str]),
+    Secrets,
+    Commands,
+    Shells,
+    /// `config <subcommand>`, then a passphrase action or a config key.
+    Config,
+}
+
+struct Entry {
+    /// The command name followed by its aliases.
+    names: Vec<&'static str>,
+    about: &'static str,
+    flags: Vec<&'static Flag>,
+    positional: Positional,
+}
+
+fn entries() -> Vec<Entry> {
+    TOP_LEVEL_COMMANDS
+        .iter()
+        .map(|&(name, about)| {
+            let mut names = vec![name];
+            if name == "gen" {
+                names.push("generate");
+            }
+            let flags: Vec<&'static Flag> = if name == "config" {
+                flags::CONFIG.iter().collect()
+            } else {
+                flags::COMMANDS
+                    .iter()
+                    .find(|c| c.name == name)
+                    .map(|c| c.accepted().collect())
+                    .unwrap_or_default()
+            };
+            let positional = match name {
+                "send" => Positional::Words(&["gen", "generate"]),
+                "gen" => Positional::Words(&["send"]),
+                "burn" | "watch" => Positional::Secrets,
+                "help" | "man" => Positional::Commands,
+                "completion" => Positional::Shells,
+                "config" => Positional::Config,
+                _ => Positional::None,
+            };
+            Entry {
+                names,
+                about,
+                flags,
+                positional,
+            }
+        })
+        .collect()
+}
+
+fn command_names() -> Vec<&'static str> {
+    entries().into_iter().flat_map(|e| e.names).collect()
+}
+
+/// The completion script for `shell`, or None if it isn't supported.
+pub fn script(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash()),
+        "zsh" => Some(zsh()),
+        "fish" => Some(fish()),
+        "powershell" => Some(powershell()),
+        "elvish" => Some(elvish()),
+        "nushell" => Some(nushell()),
+        _ => None,
+    }
+}
+
+/// `secrt __complete <kind>`: print candidates one per line. Config and
+/// history problems are ignored; a completion has nowhere to report them.
+pub fn run_complete(args: &[String], deps: &mut Deps) -> i32 {
+    let values: Vec<String> = match args.first().map(String::as_str) {
+        Some("profiles") => load_config_with(&*deps.getenv, &mut io::sink())
+            .profiles
+            .into_keys()
+            .collect(),
+        Some("config-keys") => SETTINGS.iter().map(|(k, _, _, _)| k.to_string()).collect(),
+        Some("secrets") => {
+            let now = Utc::now();
+            history_path_with(&*deps.getenv)
+                .map(|p| load_history(&p))
+                .unwrap_or_default()
+                .into_iter()
+                .rev()
+                .filter(|e| e.is_live(now))
+                .take(RECENT_SECRETS)
+                .map(|e| e.id)
+                .collect()
+        }
+        _ => return 2,
+    };
+    for v in values {
+        let _ = writeln!(deps.stdout, "{}", v);
+    }
+    0
+}
+
+fn spellings(flags: &[&Flag]) -> Vec<&'static str> {
+    flags
+        .iter()
+        .flat_map(|f| f.short.into_iter().chain([f.long]))
+        .collect()
+}
+
+// --- bash ---
+
+fn bash_words(words: &str) -> String {
+    format!("COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))", words)
+}
+
+fn bash_dynamic(kind: &str) -> String {
+    bash_words(&format!("$(secrt __complete {} 2>/dev/null)", kind))
+}
+
+fn bash() -> String {
+    let mut s = String::from(
+        "_secrt() {
+    local cur prev
     COMPREPLY=()
-    cur="${COMP_WORDS[COMP_CWORD]}"
-    prev="${COMP_WORDS[COMP_CWORD-1]}"
-    commands="send get burn gen generate config version help completion"
+    cur=\"${COMP_WORDS[COMP_CWORD]}\"
+    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"
 
     if [[ ${COMP_CWORD} -eq 1 ]]; then
-        COMPREPLY=($(compgen -W "${commands}" -- "${cur}"))
-        return 0
-    fi
+",
+    );
+    s += &format!("        {}\n", bash_words(&command_names().join(" ")));
+    s += "        return 0\n    fi\n\n    case \"${COMP_WORDS[1]}\" in\n";
+    for e in entries() {
+        if e.flags.is_empty() && matches!(e.positional, Positional::None) {
+            continue;
+        }
+        s += &format!("        {})\n", e.names.join("|"));
+        s += "            case \"${prev}\" in\n";
+        let kinds = [
+            (
+                Value::File,
+                "COMPREPLY=($(compgen -f -- \"${cur}\"))".to_string(),
+            ),
+            (
+                Value::Dir,
+                "COMPREPLY=($(compgen -d -- \"${cur}\"))".to_string(),
+            ),
+            (
+                Value::Command,
+                "COMPREPLY=($(compgen -c -- \"${cur}\"))".to_string(),
+            ),
+            (Value::Profile, bash_dynamic("profiles")),
+            (Value::Any, String::new()),
+        ];
+        for (kind, action) in kinds {
+            let matching: Vec<&Flag> = e
+                .flags
+                .iter()
+                .copied()
+                .filter(|f| value_of(f) == kind)
+                .collect();
+            if matching.is_empty() {
+                continue;
+            }
+            s += &format!("                {})\n", spellings(&matching).join("|"));
+            if !action.is_empty() {
+                s += &format!("                    {}\n", action);
+            }
+            s += "                    return 0\n                    ;;\n";
+        }
+        if let Positional::Config = e.positional {
+            s += &format!(
+                "                passphrases)\n                    {}\n                    return 0\n                    ;;\n",
+                bash_words(&PASSPHRASE_ACTIONS.join(" "))
+            );
+            s += &format!(
+                "                set|get|unset)\n                    {}\n                    return 0\n                    ;;\n",
+                bash_dynamic("config-keys")
+            );
+        }
+        s += "            esac\n";
+        s += &format!(
+            "            if [[ \"${{cur}}\" == -* ]]; then\n                {}\n",
+            bash_words(&spellings(&e.flags).join(" "))
+        );
+        let positional = match e.positional {
+            Positional::None => None,
+            Positional::Words(w) => Some(bash_words(&w.join(" "))),
+            Positional::Secrets => Some(bash_dynamic("secrets")),
+            Positional::Commands => Some(bash_words(&command_names().join(" "))),
+            Positional::Shells => Some(bash_words(&SHELLS.join(" "))),
+            Positional::Config => Some(format!(
+                "[[ ${{COMP_CWORD}} -eq 2 ]] && {}",
+                bash_words(&CONFIG_SUBCOMMANDS.join(" "))
+            )),
+        };
+        if let Some(p) = positional {
+            s += &format!("            else\n                {}\n", p);
+        }
+        s += "            fi\n            ;;\n";
+    }
+    s += "    esac\n    return 0\n}\ncomplete -F _secrt secrt\n";
+    s
+}
 
-    case "${prev}" in
-        send)
-            COMPREPLY=($(compgen -W "gen generate --ttl --api-key --base-url --json --text --file --show --hidden --silent --multi-line --trim --passphrase-prompt --passphrase-env --passphrase-file --help" -- "${cur}"))
-            ;;
-        get)
-            COMPREPLY=($(compgen -W "--output --base-url --json --silent --passphrase-prompt --passphrase-env --passphrase-file --help" -- "${cur}"))
-            ;;
-        burn)
-            COMPREPLY=($(compgen -W "--api-key --base-url --json --silent --help" -- "${cur}"))
-            ;;
-        gen|generate)
-            COMPREPLY=($(compgen -W "send --length --no-symbols --no-numbers --no-caps --grouped --count --json --help" -- "${cur}"))
-            ;;
-        config)
-            COMPREPLY=($(compgen -W "init path set-passphrase delete-passphrase --force" -- "${cur}"))
-            ;;
-        completion)
-            COMPREPLY=($(compgen -W "bash zsh fish" -- "${cur}"))
+// --- zsh ---
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh_flag(f: &Flag) -> String {
+    let value = match value_of(f) {
+        Value::None => String::new(),
+        v => {
+            let action = match v {
+                Value::File => "_files",
+                Value::Dir => "_directories",
+                Value::Command => "_command_names",
+                Value::Profile => "_secrt_profiles",
+                _ => "",
+            };
+            format!(":{}:{}", f.placeholder().trim_matches(['<', '>']), action)
+        }
+    };
+    let spec = format!("[{}]{}", zsh_escape(f.help), value);
+    match f.short {
+        Some(s) => format!("{{{},{}}}'{}'", s, f.long, spec),
+        None => format!("'{}{}'", f.long, spec),
+    }
+}
+
+fn zsh() -> String {
+    let mut s = String::from(
+        "#compdef secrt
+
+_secrt_profiles() {
+    compadd -- ${(f)\"$(secrt __complete profiles 2>/dev/null)\"}
+}
+
+_secrt_secrets() {
+    compadd -- ${(f)\"$(secrt __complete secrets 2>/dev/null)\"}
+}
+
+_secrt_config_arg() {
+    case $words[2] in
+        passphrases)
+",
+    );
+    s += &format!("            compadd {}\n", PASSPHRASE_ACTIONS.join(" "));
+    s += "            ;;
+        set|get|unset)
+            compadd -- ${(f)\"$(secrt __complete config-keys 2>/dev/null)\"}
             ;;
     esac
-    return 0
 }
-complete -F _secrt secrt
-"#;
-
-pub const ZSH_COMPLETION: &str = r#"#compdef secrt
 
 _secrt() {
     local -a commands
     commands=(
-        'send:Encrypt and upload a secret'
-        'get:Retrieve and decrypt a secret'
-        'burn:Destroy a secret (requires API key)'
-        'gen:Generate a random password'
-        'generate:Generate a random password'
-        'config:Show config / init / path'
-        'version:Show version'
-        'help:Show help'
-        'completion:Output shell completion script'
-    )
+";
+    for e in entries() {
+        for name in &e.names {
+            s += &format!("        '{}:{}'\n", name, zsh_escape(e.about));
+        }
+    }
+    s += "    )
 
-    _arguments -C \
-        '1:command:->command' \
+    _arguments -C \\
+        '1:command:->command' \\
         '*::arg:->args'
 
-    case "$state" in
+    case \"$state\" in
         command)
             _describe 'command' commands
             ;;
         args)
             case $words[1] in
-                send)
-                    _arguments \
-                        '1:input source:(gen generate)' \
-                        '--ttl[TTL for secret]:ttl:' \
-                        '--api-key[API key]:key:' \
-                        '--base-url[Server URL]:url:' \
-                        '--json[Output as JSON]' \
-                        '--text[Secret text]:text:' \
-                        '--file[Secret file]:file:_files' \
-                        {-s,--show}'[Show input as you type]' \
-                        '--hidden[Hide input]' \
-                        '--silent[Suppress status output]' \
-                        {-m,--multi-line}'[Multi-line input]' \
-                        '--trim[Trim whitespace]' \
-                        {-p,--passphrase-prompt}'[Prompt for passphrase]' \
-                        '--passphrase-env[Passphrase env var]:var:' \
-                        '--passphrase-file[Passphrase file]:file:_files' \
-                        '--help[Show help]'
-                    ;;
-                get)
-                    _arguments \
-                        {-o,--output}'[Write output to file (- for stdout)]:path:_files' \
-                        '--base-url[Server URL]:url:' \
-                        '--json[Output as JSON]' \
-                        '--silent[Suppress status output]' \
-                        {-p,--passphrase-prompt}'[Prompt for passphrase]' \
-                        '--passphrase-env[Passphrase env var]:var:' \
-                        '--passphrase-file[Passphrase file]:file:_files' \
-                        '--help[Show help]'
-                    ;;
-                burn)
-                    _arguments \
-                        '--api-key[API key]:key:' \
-                        '--base-url[Server URL]:url:' \
-                        '--json[Output as JSON]' \
-                        '--silent[Suppress status output]' \
-                        '--help[Show help]'
-                    ;;
-                gen|generate)
-                    _arguments \
-                        '1:subcommand:(send)' \
-                        {-L,--length}'[Password length]:length:' \
-                        {-S,--no-symbols}'[Exclude symbols]' \
-                        {-N,--no-numbers}'[Exclude digits]' \
-                        {-C,--no-caps}'[Exclude uppercase letters]' \
-                        {-G,--grouped}'[Group characters by type]' \
-                        '--count[Generate multiple passwords]:count:' \
-                        '--json[Output as JSON]' \
-                        '--help[Show help]'
-                    ;;
-                config)
-                    _arguments \
-                        '1:subcommand:(init path set-passphrase delete-passphrase)' \
-                        '--force[Overwrite existing config file]'
-                    ;;
-                completion)
-                    _arguments '1:shell:(bash zsh fish)'
-                    ;;
-            esac
+";
+    for e in entries() {
+        let mut specs: Vec<String> = match e.positional {
+            Positional::None => Vec::new(),
+            Positional::Words(w) => vec![format!("'1:subcommand:({})'", w.join(" "))],
+            Positional::Secrets => vec!["'*:secret:_secrt_secrets'".to_string()],
+            Positional::Commands => vec![format!("'1:command:({})'", command_names().join(" "))],
+            Positional::Shells => vec![format!("'1:shell:({})'", SHELLS.join(" "))],
+            Positional::Config => vec![
+                format!("'1:subcommand:({})'", CONFIG_SUBCOMMANDS.join(" ")),
+                "'2:action or key:_secrt_config_arg'".to_string(),
+            ],
+        };
+        specs.extend(e.flags.iter().map(|f| zsh_flag(f)));
+        if specs.is_empty() {
+            continue;
+        }
+        s += &format!("                {})\n", e.names.join("|"));
+        s += "                    _arguments \\\n";
+        s += &specs
+            .iter()
+            .map(|spec| format!("                        {}", spec))
+            .collect::<Vec<_>>()
+            .join(" \\\n");
+        s += "\n                    ;;\n";
+    }
+    s += "            esac
             ;;
     esac
 }
 
 _secrt
-"#;
-
-pub const FISH_COMPLETION: &str = r#"complete -c secrt -f
-complete -c secrt -n '__fish_use_subcommand' -a send -d 'Encrypt and upload a secret'
-complete -c secrt -n '__fish_use_subcommand' -a get -d 'Retrieve and decrypt a secret'
-complete -c secrt -n '__fish_use_subcommand' -a burn -d 'Destroy a secret (requires API key)'
-complete -c secrt -n '__fish_use_subcommand' -a gen -d 'Generate a random password'
-complete -c secrt -n '__fish_use_subcommand' -a generate -d 'Generate a random password'
-complete -c secrt -n '__fish_use_subcommand' -a config -d 'Show config / init / path'
-complete -c secrt -n '__fish_use_subcommand' -a version -d 'Show version'
-complete -c secrt -n '__fish_use_subcommand' -a help -d 'Show help'
-complete -c secrt -n '__fish_use_subcommand' -a completion -d 'Output shell completion script'
-
-complete -c secrt -n '__fish_seen_subcommand_from send' -l ttl -d 'TTL for secret'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l api-key -d 'API key'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l base-url -d 'Server URL'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l json -d 'Output as JSON'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l text -d 'Secret text'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l file -d 'Secret file' -F
-complete -c secrt -n '__fish_seen_subcommand_from send' -s s -l show -d 'Show input as you type'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l hidden -d 'Hide input'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l silent -d 'Suppress status output'
-complete -c secrt -n '__fish_seen_subcommand_from send' -s m -l multi-line -d 'Multi-line input'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l trim -d 'Trim whitespace'
-complete -c secrt -n '__fish_seen_subcommand_from send' -s p -l passphrase-prompt -d 'Prompt for passphrase'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l passphrase-env -d 'Passphrase env var'
-complete -c secrt -n '__fish_seen_subcommand_from send' -l passphrase-file -d 'Passphrase file' -F
-complete -c secrt -n '__fish_seen_subcommand_from send' -a 'gen generate' -d 'Generate and share a password'
-
-complete -c secrt -n '__fish_seen_subcommand_from get' -s o -l output -d 'Write output to file (- for stdout)' -F
-complete -c secrt -n '__fish_seen_subcommand_from get' -l base-url -d 'Server URL'
-complete -c secrt -n '__fish_seen_subcommand_from get' -l json -d 'Output as JSON'
-complete -c secrt -n '__fish_seen_subcommand_from get' -l silent -d 'Suppress status output'
-complete -c secrt -n '__fish_seen_subcommand_from get' -s p -l passphrase-prompt -d 'Prompt for passphrase'
-complete -c secrt -n '__fish_seen_subcommand_from get' -l passphrase-env -d 'Passphrase env var'
-complete -c secrt -n '__fish_seen_subcommand_from get' -l passphrase-file -d 'Passphrase file' -F
-
-complete -c secrt -n '__fish_seen_subcommand_from burn' -l api-key -d 'API key'
-complete -c secrt -n '__fish_seen_subcommand_from burn' -l base-url -d 'Server URL'
-complete -c secrt -n '__fish_seen_subcommand_from burn' -l json -d 'Output as JSON'
-complete -c secrt -n '__fish_seen_subcommand_from burn' -l silent -d 'Suppress status output'
-
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -s L -l length -d 'Password length'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -s S -l no-symbols -d 'Exclude symbols'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -s N -l no-numbers -d 'Exclude digits'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -s C -l no-caps -d 'Exclude uppercase letters'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -s G -l grouped -d 'Group characters by type'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -l count -d 'Generate multiple passwords'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -l json -d 'Output as JSON'
-complete -c secrt -n '__fish_seen_subcommand_from gen generate' -a 'send' -d 'Generate and share a password'
-
-complete -c secrt -n '__fish_seen_subcommand_from config' -a 'init path set-passphrase delete-passphrase' -d 'Config subcommand'
-complete -c secrt -n '__fish_seen_subcommand_from config' -l force -d 'Overwrite existing config file'
-
-complete -c secrt -n '__fish_seen_subcommand_from completion' -a 'bash zsh fish'
-"#;
+";
+    s
+}
+
+// --- fish ---
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish() -> String {
+    let mut s = String::from(
+        "complete -c secrt -f
+
+function __secrt_using
+    set -l words (commandline -opc)
+    test (count $words) -ge 2; and contains -- $words[2] $argv
+end
+
+function __secrt_config_using
+    set -l words (commandline -opc)
+    test (count $words) -eq 3; and test $words[2] = config; and contains -- $words[3] $argv
+end
+
+",
+    );
+    for e in entries() {
+        for name in &e.names {
+            s += &format!(
+                "complete -c secrt -n '__fish_use_subcommand' -a {} -d {}\n",
+                name,
+                fish_quote(e.about)
+            );
+        }
+    }
+    for e in entries() {
+        let cond = format!("-n '__secrt_using {}'", e.names.join(" "));
+        let mut lines = Vec::new();
+        for f in &e.flags {
+            let mut line = format!("complete -c secrt {}", cond);
+            if let Some(short) = f.short {
+                line += &format!(" -s {}", short.trim_start_matches('-'));
+            }
+            line += &format!(" -l {}", f.long.trim_start_matches('-'));
+            line += match value_of(f) {
+                Value::None => "",
+                Value::Any => " -r",
+                Value::File => " -r -F",
+                Value::Dir => " -x -a '(__fish_complete_directories)'",
+                Value::Command => " -x -a '(__fish_complete_command)'",
+                Value::Profile => " -x -a '(secrt __complete profiles 2>/dev/null)'",
+            };
+            line += &format!(" -d {}", fish_quote(f.help));
+            lines.push(line);
+        }
+        match e.positional {
+            Positional::None => {}
+            Positional::Words(w) => {
+                lines.push(format!("complete -c secrt {} -a '{}'", cond, w.join(" ")));
+            }
+            Positional::Secrets => lines.push(format!(
+                "complete -c secrt {} -a '(secrt __complete secrets 2>/dev/null)' -d 'Sent secret'",
+                cond
+            )),
+            Positional::Commands => lines.push(format!(
+                "complete -c secrt {} -a '{}'",
+                cond,
+                command_names().join(" ")
+            )),
+            Positional::Shells => lines.push(format!(
+                "complete -c secrt {} -a '{}'",
+                cond,
+                SHELLS.join(" ")
+            )),
+            Positional::Config => {
+                lines.push(format!(
+                    "complete -c secrt -n '__secrt_using config; and test (count (commandline -opc)) -eq 2' -a '{}' -d 'Config subcommand'",
+                    CONFIG_SUBCOMMANDS.join(" ")
+                ));
+                lines.push(format!(
+                    "complete -c secrt -n '__secrt_config_using passphrases' -a '{}' -d 'Passphrase action'",
+                    PASSPHRASE_ACTIONS.join(" ")
+                ));
+                lines.push(
+                    "complete -c secrt -n '__secrt_config_using set get unset' -a '(secrt __complete config-keys 2>/dev/null)' -d 'Config key'"
+                        .to_string(),
+                );
+            }
+        }
+        if !lines.is_empty() {
+            s += "\n";
+            s += &lines.join("\n");
+            s += "\n";
+        }
+    }
+    s
+}
+
+// --- PowerShell ---
+
+fn ps_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn ps_list(words: &[&str]) -> String {
+    let quoted: Vec<String> = words.iter().map(|w| ps_quote(w)).collect();
+    format!("@({})", quoted.join(", "))
+}
+
+fn powershell() -> String {
+    let mut s = String::from(
+        "Register-ArgumentCompleter -Native -CommandName secrt -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commands = [ordered]@{
+",
+    );
+    for e in entries() {
+        for name in &e.names {
+            s += &format!("        {} = {}\n", ps_quote(name), ps_quote(e.about));
+        }
+    }
+    s +=
+        "    }\n\n    # L = long flag, S = short flag, V = value kind, H = help\n    $flags = @{\n";
+    for e in entries() {
+        if e.flags.is_empty() {
+            continue;
+        }
+        let rows: Vec<String> = e
+            .flags
+            .iter()
+            .map(|f| {
+                let kind = match value_of(f) {
+                    Value::None => "none",
+                    Value::Any => "any",
+                    Value::File | Value::Dir | Value::Command => "path",
+                    Value::Profile => "profile",
+                };
+                format!(
+                    "            @{{ L = {}; S = {}; V = '{}'; H = {} }}",
+                    ps_quote(f.long),
+                    ps_quote(f.short.unwrap_or("")),
+                    kind,
+                    ps_quote(f.help)
+                )
+            })
+            .collect();
+        for name in &e.names {
+            s += &format!(
+                "        {} = @(\n{}\n        )\n",
+                ps_quote(name),
+                rows.join("\n")
+            );
+        }
+    }
+    s += "    }\n\n    $positional = @{\n";
+    for e in entries() {
+        let value = match e.positional {
+            Positional::None => continue,
+            Positional::Words(w) => ps_list(w),
+            Positional::Secrets => "'@secrets'".to_string(),
+            Positional::Commands => ps_list(&command_names()),
+            Positional::Shells => ps_list(SHELLS),
+            Positional::Config => "'@config'".to_string(),
+        };
+        for name in &e.names {
+            s += &format!("        {} = {}\n", ps_quote(name), value);
+        }
+    }
+    s += "    }\n";
+    s += &format!(
+        "    $configSubcommands = {}\n    $passphraseActions = {}\n",
+        ps_list(CONFIG_SUBCOMMANDS),
+        ps_list(PASSPHRASE_ACTIONS)
+    );
+    s += "
+    $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })
+    if ($wordToComplete -ne '') {
+        $words = @($words | Select-Object -SkipLast 1)
+    }
+    $prev = if ($words.Count -gt 0) { $words[-1] } else { '' }
+
+    $candidates = @()
+    if ($words.Count -eq 0) {
+        $candidates = $commands.GetEnumerator() | ForEach-Object { @{ T = $_.Key; H = $_.Value } }
+    } else {
+        $cmd = $words[0]
+        $flag = $flags[$cmd] | Where-Object { $_.L -eq $prev -or ($_.S -ne '' -and $_.S -eq $prev) } | Select-Object -First 1
+        if ($flag -and $flag.V -ne 'none') {
+            if ($flag.V -ne 'profile') {
+                return
+            }
+            $candidates = secrt __complete profiles 2>$null | ForEach-Object { @{ T = $_; H = 'Profile' } }
+        } elseif ($wordToComplete.StartsWith('-')) {
+            $candidates = $flags[$cmd] | ForEach-Object {
+                $f = $_
+                @($f.S, $f.L) | Where-Object { $_ -ne '' } | ForEach-Object { @{ T = $_; H = $f.H } }
+            }
+        } else {
+            $p = $positional[$cmd]
+            $names = @()
+            if ($p -is [string] -and $p -eq '@secrets') {
+                $names = secrt __complete secrets 2>$null
+            } elseif ($p -is [string] -and $p -eq '@config') {
+                if ($words.Count -eq 1) {
+                    $names = $configSubcommands
+                } elseif ($prev -eq 'passphrases') {
+                    $names = $passphraseActions
+                } elseif ($prev -in 'set', 'get', 'unset') {
+                    $names = secrt __complete config-keys 2>$null
+                }
+            } elseif ($p) {
+                $names = $p
+            }
+            $candidates = $names | ForEach-Object { @{ T = $_; H = $_ } }
+        }
+    }
+
+    $candidates | Where-Object { $_.T -like \"$wordToComplete*\" } | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_.T, $_.T, 'ParameterValue', $_.H)
+    }
+}
+";
+    s
+}
+
+// --- Elvish ---
+
+fn elvish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+fn elvish_list(words: &[&str]) -> String {
+    let quoted: Vec<String> = words.iter().map(|w| elvish_quote(w)).collect();
+    format!("[{}]", quoted.join(" "))
+}
+
+fn elvish() -> String {
+    let mut s = String::from("use str\n\n");
+    s += &format!("var secrt-commands = {}\n\n", elvish_list(&command_names()));
+    s += "# l = long flag, s = short flag, v = value kind\nvar secrt-flags = [\n";
+    for e in entries() {
+        if e.flags.is_empty() {
+            continue;
+        }
+        let rows: Vec<String> = e
+            .flags
+            .iter()
+            .map(|f| {
+                let kind = match value_of(f) {
+                    Value::None => "none",
+                    Value::Any => "any",
+                    Value::File | Value::Dir | Value::Command => "path",
+                    Value::Profile => "profile",
+                };
+                format!(
+                    "    [&l={} &s={} &v={}]",
+                    elvish_quote(f.long),
+                    elvish_quote(f.short.unwrap_or("")),
+                    kind
+                )
+            })
+            .collect();
+        for name in &e.names {
+            s += &format!("  &{}=[\n  {}\n  ]\n", name, rows.join("\n  "));
+        }
+    }
+    s += "]\n\nvar secrt-positional = [\n";
+    for e in entries() {
+        let value = match e.positional {
+            Positional::None => continue,
+            Positional::Words(w) => elvish_list(w),
+            Positional::Secrets => "secrets".to_string(),
+            Positional::Commands => "$secrt-commands".to_string(),
+            Positional::Shells => elvish_list(SHELLS),
+            Positional::Config => "config".to_string(),
+        };
+        for name in &e.names {
+            s += &format!("  &{}={}\n", name, value);
+        }
+    }
+    s += "]\n\n";
+    s += &format!(
+        "var secrt-config-subcommands = {}\nvar secrt-passphrase-actions = {}\n",
+        elvish_list(CONFIG_SUBCOMMANDS),
+        elvish_list(PASSPHRASE_ACTIONS)
+    );
+    s += "
+set edit:completion:arg-completer[secrt] = {|@words|
+  var cur = $words[-1]
+  var args = $words[1..-1]
+  if (== (count $args) 0) {
+    all $secrt-commands
+    return
+  }
+  var cmd = $args[0]
+  var prev = $args[-1]
+  var flags = []
+  if (has-key $secrt-flags $cmd) {
+    set flags = $secrt-flags[$cmd]
+  }
+  for f $flags {
+    if (and (not-eq $f[v] none) (or (eq $f[l] $prev) (eq $f[s] $prev))) {
+      if (eq $f[v] profile) {
+        secrt __complete profiles 2>/dev/null
+      } elif (eq $f[v] path) {
+        edit:complete-filename $cur
+      }
+      return
+    }
+  }
+  if (str:has-prefix $cur -) {
+    for f $flags {
+      put $f[l]
+      if (not-eq $f[s] '') {
+        put $f[s]
+      }
+    }
+    return
+  }
+  if (not (has-key $secrt-positional $cmd)) {
+    return
+  }
+  var p = $secrt-positional[$cmd]
+  if (eq $p secrets) {
+    secrt __complete secrets 2>/dev/null
+  } elif (eq $p config) {
+    if (== (count $args) 1) {
+      all $secrt-config-subcommands
+    } elif (eq $prev passphrases) {
+      all $secrt-passphrase-actions
+    } elif (has-value [set get unset] $prev) {
+      secrt __complete config-keys 2>/dev/null
+    }
+  } else {
+    all $p
+  }
+}
+";
+    s
+}
+
+// --- Nushell ---
+
+fn nu_flag(f: &Flag) -> String {
+    let mut spec = f.long.to_string();
+    if let Some(short) = f.short {
+        spec += &format!("({})", short);
+    }
+    let ty = match (value_of(f), f.arg) {
+        (Value::None, _) => String::new(),
+        (_, Arg::Count(_)) => ": int".to_string(),
+        (Value::File, _) => ": path".to_string(),
+        (Value::Dir, _) => ": directory".to_string(),
+        (Value::Profile, _) => ": string@\"nu-complete secrt profiles\"".to_string(),
+        _ => ": string".to_string(),
+    };
+    format!("    {}{}  # {}", spec, ty, f.help)
+}
+
+fn nu_extern(name: &str, about: &str, params: &[String]) -> String {
+    format!(
+        "# {}\nexport extern \"{}\" [\n{}\n]\n\n",
+        about,
+        name,
+        params.join("\n")
+    )
+}
+
+fn nushell() -> String {
+    let nu_list = |words: &[&str]| format!("[{}]", words.join(" "));
+    let mut s = String::from(
+        "def \"nu-complete secrt profiles\" [] { ^secrt __complete profiles | lines }
+def \"nu-complete secrt secrets\" [] { ^secrt __complete secrets | lines }
+def \"nu-complete secrt config-keys\" [] { ^secrt __complete config-keys | lines }
+",
+    );
+    s += &format!(
+        "def \"nu-complete secrt commands\" [] {{ {} }}\n",
+        nu_list(&command_names())
+    );
+    s += &format!(
+        "def \"nu-complete secrt shells\" [] {{ {} }}\n",
+        nu_list(SHELLS)
+    );
+    s += &format!(
+        "def \"nu-complete secrt config-subcommands\" [] {{ {} }}\n",
+        nu_list(CONFIG_SUBCOMMANDS)
+    );
+    s += &format!(
+        "def \"nu-complete secrt config-args\" [context: string] {{
+    let words = ($context | split row ' ')
+    let sub = if ($words | length) > 2 {{ $words | get 2 }} else {{ '' }}
+    if $sub == 'passphrases' {{
+        {}
+    }} else if $sub in [set get unset] {{
+        nu-complete secrt config-keys
+    }} else {{
+        []
+    }}
+}}
+",
+        nu_list(PASSPHRASE_ACTIONS)
+    );
+    for e in entries() {
+        if let Positional::Words(w) = e.positional {
+            s += &format!(
+                "def \"nu-complete secrt {}-args\" [] {{ {} }}\n",
+                e.names[0],
+                nu_list(w)
+            );
+        }
+    }
+    s += "\n";
+    s += &nu_extern(
+        "secrt",
+        "One-time secret sharing",
+        &[
+            "    command?: string@\"nu-complete secrt commands\"".to_string(),
+            "    ...args: string".to_string(),
+            "    --help(-h)  # Show help".to_string(),
+            "    --version(-v)  # Show version".to_string(),
+        ],
+    );
+    for e in entries() {
+        let mut params: Vec<String> = match e.positional {
+            Positional::None if e.names[0] == "get" => vec!["    url?: string".to_string()],
+            Positional::None => Vec::new(),
+            Positional::Words(_) => vec![format!(
+                "    arg?: string@\"nu-complete secrt {}-args\"",
+                e.names[0]
+            )],
+            Positional::Secrets => {
+                vec!["    ...ids: string@\"nu-complete secrt secrets\"".to_string()]
+            }
+            Positional::Commands => {
+                vec!["    command?: string@\"nu-complete secrt commands\"".to_string()]
+            }
+            Positional::Shells => {
+                vec!["    shell: string@\"nu-complete secrt shells\"".to_string()]
+            }
+            Positional::Config => vec![
+                "    subcommand?: string@\"nu-complete secrt config-subcommands\"".to_string(),
+                "    ...args: string@\"nu-complete secrt config-args\"".to_string(),
+            ],
+        };
+        params.extend(e.flags.iter().map(|f| nu_flag(f)));
+        for name in &e.names {
+            s += &nu_extern(&format!("secrt {}", name), e.about, &params);
+        }
+    }
+    // `secrt gen send` takes send's flags (which include the gen ones).
+    let send: Vec<String> = flags::SEND.accepted().map(nu_flag).collect();
+    for name in ["gen", "generate"] {
+        s += &nu_extern(
+            &format!("secrt {} send", name),
+            "Generate a password and share it",
+            &send,
+        );
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_shell_has_a_script() {
+        for shell in SHELLS {
+            assert!(script(shell).is_some(), "{}", shell);
+        }
+        assert!(script("tcsh").is_none());
+    }
+
+    /// Every flag of every command reaches every script, so the scripts
+    /// can't drift from the flag tables.
+    #[test]
+    fn scripts_cover_every_flag() {
+        for shell in SHELLS {
+            let text = script(shell).unwrap();
+            for cmd in flags::COMMANDS {
+                for f in cmd.accepted() {
+                    let long = if *shell == "fish" {
+                        format!("-l {}", f.long.trim_start_matches('-'))
+                    } else {
+                        f.long.to_string()
+                    };
+                    assert!(text.contains(&long), "{}: {} {}", shell, cmd.name, f.long);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bash_send_offers_no_passphrase() {
+        let text = bash();
+        let start = text.find("        send)").unwrap();
+        let end = text.find("        get)").unwrap();
+        assert!(text[start..end].contains("-n --no-passphrase"));
+    }
+
+    #[test]
+    fn zsh_escapes_descriptions() {
+        assert_eq!(zsh_escape("a [b]: c's"), "a \\[b\\]\\: c'\\''s");
+    }
+}
@@ -1,11 +1,14 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 
+use crate::color::{Style, BULLET};
+
 /// Configuration loaded from the TOML config file.
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Clone, Deserialize)]
 pub struct Config {
     pub api_key: Option<String>,
     pub base_url: Option<String>,
@@ -13,8 +16,333 @@ pub struct Config {
     pub default_ttl: Option<String>,
     pub show_input: Option<bool>,
     pub use_keychain: Option<bool>,
+    pub history: Option<bool>,
+    pub clipboard_tool: Option<String>,
+    pub clipboard_clear: Option<String>,
+    pub reveal: Option<bool>,
+    pub reveal_for: Option<String>,
+    /// `auto`, `always` or `never`; only read at the top level, like
+    /// `plain` and `theme`, since they apply before a profile is chosen.
+    pub color: Option<String>,
+    pub plain: Option<bool>,
+    /// `[theme]`: SGR codes by color token name (`success = "1;32"`).
+    #[serde(default)]
+    pub theme: BTreeMap<String, String>,
     #[serde(default)]
     pub decryption_passphrases: Vec<String>,
+    /// Commands whose stdout supplies the secret of the same name.
+    pub api_key_command: Option<String>,
+    pub passphrase_command: Option<String>,
+    pub decryption_passphrases_command: Option<String>,
+    /// `api_key`, `passphrase` and `decryption_passphrases` (including
+    /// profile ones) sealed with a master passphrase by `config lock`.
+    pub encrypted_secrets: Option<String>,
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Name of the profile merged in by `with_profile` (not read from TOML).
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+    /// Layer each value was read from, keyed by field name
+    /// (`profiles.<name>.<field>` for profile tables).
+    #[serde(skip)]
+    pub sources: BTreeMap<String, ConfigLayer>,
+}
+
+/// A `[profiles.<name>]` table. Every field overrides the top-level
+/// setting of the same name while the profile is active.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Profile {
+    pub api_key: Option<String>,
+    pub base_url: Option<String>,
+    pub passphrase: Option<String>,
+    pub default_ttl: Option<String>,
+    pub show_input: Option<bool>,
+    pub use_keychain: Option<bool>,
+    pub history: Option<bool>,
+    pub clipboard_tool: Option<String>,
+    pub clipboard_clear: Option<String>,
+    pub reveal: Option<bool>,
+    pub reveal_for: Option<String>,
+    pub decryption_passphrases: Option<Vec<String>>,
+    pub api_key_command: Option<String>,
+    pub passphrase_command: Option<String>,
+    pub decryption_passphrases_command: Option<String>,
+}
+
+/// A config file layer. Later layers override earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigLayer {
+    /// `/etc/secrt/config.toml`, shipped by administrators.
+    System,
+    /// `~/.config/secrt/config.toml`.
+    User,
+    /// The nearest `.secrt.toml` in the working directory or a parent.
+    Project,
+}
+
+impl ConfigLayer {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ConfigLayer::System => "system config",
+            ConfigLayer::User => "user config",
+            ConfigLayer::Project => "project config",
+        }
+    }
+}
+
+/// How the active profile was chosen, for `config show`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileSource {
+    Flag,
+    Env,
+    ShareUrl,
+    Default,
+}
+
+impl ProfileSource {
+    pub fn describe(self) -> &'static str {
+        match self {
+            ProfileSource::Flag => "--profile",
+            ProfileSource::Env => "env SECRT_PROFILE",
+            ProfileSource::ShareUrl => "matched share URL host",
+            ProfileSource::Default => "default_profile",
+        }
+    }
+}
+
+impl Config {
+    /// Pick the profile to use. Precedence: `--profile` flag, then
+    /// `SECRT_PROFILE`, then a profile whose `base_url` host matches
+    /// `share_url`, then `default_profile`. Naming a profile that does not
+    /// exist is an error.
+    pub fn select_profile(
+        &self,
+        flag: &str,
+        env: Option<&str>,
+        share_url: Option<&str>,
+    ) -> Result<Option<(String, ProfileSource)>, String> {
+        let explicit = if !flag.is_empty() {
+            Some((flag.to_string(), ProfileSource::Flag))
+        } else {
+            env.filter(|s| !s.is_empty())
+                .map(|s| (s.to_string(), ProfileSource::Env))
+        };
+        if let Some((name, src)) = explicit {
+            if !self.profiles.contains_key(&name) {
+                return Err(self.unknown_profile(&name));
+            }
+            return Ok(Some((name, src)));
+        }
+
+        let default = self.default_profile.as_deref().filter(|s| !s.is_empty());
+        if let Some(host) = share_url.and_then(url_host) {
+            let matches: Vec<&String> = self
+                .profiles
+                .iter()
+                .filter(|(_, p)| p.base_url.as_deref().and_then(url_host).as_deref() == Some(&host))
+                .map(|(name, _)| name)
+                .collect();
+            // Prefer the default profile if it matches too; otherwise the
+            // first match by name keeps the choice deterministic.
+            let pick = matches
+                .iter()
+                .find(|n| Some(n.as_str()) == default)
+                .or(matches.first());
+            if let Some(name) = pick {
+                return Ok(Some(((*name).clone(), ProfileSource::ShareUrl)));
+            }
+        }
+
+        match default {
+            Some(name) if self.profiles.contains_key(name) => {
+                Ok(Some((name.to_string(), ProfileSource::Default)))
+            }
+            Some(name) => Err(format!("default_profile: {}", self.unknown_profile(name))),
+            None => Ok(None),
+        }
+    }
+
+    /// Error for a profile name that isn't configured.
+    pub fn unknown_profile(&self, name: &str) -> String {
+        if self.profiles.is_empty() {
+            format!("unknown profile {:?} (no profiles are configured)", name)
+        } else {
+            let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            format!(
+                "unknown profile {:?} (available: {})",
+                name,
+                names.join(", ")
+            )
+        }
+    }
+
+    /// Return a copy of this config with the named profile's settings
+    /// layered over the top-level ones. The profile must exist.
+    pub fn with_profile(&self, name: &str) -> Config {
+        let mut merged = self.clone();
+        let Some(p) = self.profiles.get(name) else {
+            return merged;
+        };
+        let p = p.clone();
+        macro_rules! overlay {
+            ($($field:ident),*) => {
+                $(if p.$field.is_some() { merged.$field = p.$field; })*
+            };
+        }
+        overlay!(
+            api_key,
+            base_url,
+            passphrase,
+            default_ttl,
+            show_input,
+            use_keychain,
+            history,
+            clipboard_tool,
+            clipboard_clear,
+            reveal,
+            reveal_for,
+            api_key_command,
+            passphrase_command,
+            decryption_passphrases_command
+        );
+        if let Some(dp) = p.decryption_passphrases {
+            merged.decryption_passphrases = dp;
+        }
+        let prefix = format!("profiles.{}.", name);
+        for (key, layer) in &self.sources {
+            if let Some(field) = key.strip_prefix(&prefix) {
+                merged.sources.insert(field.to_string(), *layer);
+            }
+        }
+        merged.active_profile = Some(name.to_string());
+        merged
+    }
+
+    /// Layer the values set in `layer` over this config, recording where
+    /// each one came from.
+    fn merge_layer(&mut self, layer: Config, from: ConfigLayer) {
+        macro_rules! take {
+            ($dst:expr, $src:expr, $prefix:expr, $($field:ident),*) => {
+                $(if $src.$field.is_some() {
+                    $dst.$field = $src.$field;
+                    self.sources.insert(format!("{}{}", $prefix, stringify!($field)), from);
+                })*
+            };
+        }
+        take!(
+            self,
+            layer,
+            "",
+            api_key,
+            base_url,
+            passphrase,
+            default_ttl,
+            show_input,
+            use_keychain,
+            history,
+            clipboard_tool,
+            clipboard_clear,
+            reveal,
+            reveal_for,
+            color,
+            plain,
+            default_profile,
+            encrypted_secrets,
+            api_key_command,
+            passphrase_command,
+            decryption_passphrases_command
+        );
+        if !layer.decryption_passphrases.is_empty() {
+            self.decryption_passphrases = layer.decryption_passphrases;
+            self.sources.insert("decryption_passphrases".into(), from);
+        }
+        for (token, code) in layer.theme {
+            self.sources.insert(format!("theme.{}", token), from);
+            self.theme.insert(token, code);
+        }
+        for (name, p) in layer.profiles {
+            let prefix = format!("profiles.{}.", name);
+            let mut dst = self.profiles.remove(&name).unwrap_or_default();
+            take!(
+                dst,
+                p,
+                prefix,
+                api_key,
+                base_url,
+                passphrase,
+                default_ttl,
+                show_input,
+                use_keychain,
+                history,
+                clipboard_tool,
+                clipboard_clear,
+                reveal,
+                reveal_for,
+                decryption_passphrases,
+                api_key_command,
+                passphrase_command,
+                decryption_passphrases_command
+            );
+            self.profiles.insert(name, dst);
+        }
+    }
+
+    /// Keychain account name for `key`. Profiles keep their own entries
+    /// (`profiles.<name>.<key>`) so one server's key never leaks to another.
+    pub fn keychain_key(&self, key: &str) -> String {
+        match self.active_profile {
+            Some(ref name) => format!("profiles.{}.{}", name, key),
+            None => key.to_string(),
+        }
+    }
+}
+
+/// Turn on `use_keychain` so values just stored in the keychain are read.
+/// The setting is written where it is decided: the active profile's table
+/// if that profile sets it, otherwise the top level. Returns the config
+/// path when the file was changed.
+pub fn enable_keychain(
+    config: &Config,
+    getenv: &dyn Fn(&str) -> Option<String>,
+) -> Result<Option<PathBuf>, String> {
+    if config.use_keychain == Some(true) {
+        return Ok(None);
+    }
+    let path = config_path_with(getenv).ok_or("could not determine config directory")?;
+    let table = config
+        .active_profile
+        .as_ref()
+        .filter(|name| {
+            config
+                .profiles
+                .get(*name)
+                .is_some_and(|p| p.use_keychain.is_some())
+        })
+        .map(|name| profile_table(name));
+    set_config_value(&path, table.as_deref(), "use_keychain", "true")?;
+    Ok(Some(path))
+}
+
+/// TOML table path for a profile, quoting names that aren't bare keys.
+pub fn profile_table(name: &str) -> String {
+    let bare = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        format!("profiles.{}", name)
+    } else {
+        format!("profiles.{:?}", name)
+    }
+}
+
+/// Lowercased `host[:port]` of a URL, or None if it has no scheme.
+pub fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let host = &rest[..end];
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
 }
 
 /// Returns the config file path: $XDG_CONFIG_HOME/secrt/config.toml
@@ -33,73 +361,288 @@ pub fn config_path_with(getenv: &dyn Fn(&str) -> Option<String>) -> Option<PathB
     config_dir.map(|d| d.join("secrt").join("config.toml"))
 }
 
-/// Load config from the standard path. Returns default Config if file
-/// doesn't exist. Writes a warning to stderr if permissions are too open.
+/// Default location of the system-wide config file.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/secrt/config.toml";
+
+/// File name searched for in the working directory and its parents.
+pub const PROJECT_CONFIG_NAME: &str = ".secrt.toml";
+
+/// System config path: `$SECRT_SYSTEM_CONFIG` or /etc/secrt/config.toml.
+pub fn system_config_path_with(getenv: &dyn Fn(&str) -> Option<String>) -> PathBuf {
+    getenv("SECRT_SYSTEM_CONFIG")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(SYSTEM_CONFIG_PATH))
+}
+
+/// Nearest `.secrt.toml` in `$PWD` (or the process working directory)
+/// or one of its parents.
+pub fn project_config_path_with(getenv: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let start = getenv("PWD")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())?;
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_NAME))
+        .find(|p| p.is_file())
+}
+
+/// Config files in load order. The system and user paths are listed even
+/// when missing; the project file only when one was found.
+pub fn config_layers_with(getenv: &dyn Fn(&str) -> Option<String>) -> Vec<(ConfigLayer, PathBuf)> {
+    let mut layers = vec![(ConfigLayer::System, system_config_path_with(getenv))];
+    if let Some(user) = config_path_with(getenv) {
+        layers.push((ConfigLayer::User, user));
+    }
+    if let Some(project) = project_config_path_with(getenv) {
+        layers.push((ConfigLayer::Project, project));
+    }
+    layers
+}
+
+/// Load config from the standard paths. Returns default Config if no file
+/// exists. Writes a warning to stderr if permissions are too open.
 pub fn load_config(stderr: &mut dyn Write) -> Config {
     load_config_with(&|key| std::env::var(key).ok(), stderr)
 }
 
-/// Load config using a custom getenv (for testing/injection).
+/// Load and merge the system, user and project config files using a
+/// custom getenv (for testing/injection).
 pub fn load_config_with(getenv: &dyn Fn(&str) -> Option<String>, stderr: &mut dyn Write) -> Config {
-    let path = match config_path_with(getenv) {
-        Some(p) => p,
-        None => return Config::default(),
-    };
+    let mut config = Config::default();
+    for (layer, path) in config_layers_with(getenv) {
+        if path.is_file() {
+            let mut loaded = load_layer(layer, &path, stderr);
+            check_theme(&mut loaded, &path, stderr);
+            config.merge_layer(loaded, layer);
+        }
+    }
+    config
+}
 
-    if !path.exists() {
-        return Config::default();
+/// Drop `[theme]` entries that don't name a color token or aren't plain SGR
+/// codes, so a config file can't write arbitrary escape sequences.
+fn check_theme(config: &mut Config, path: &Path, stderr: &mut dyn Write) {
+    config.theme.retain(|token, code| {
+        let problem = if !crate::color::TOKENS.iter().any(|t| t.name == token) {
+            let names: Vec<&str> = crate::color::TOKENS.iter().map(|t| t.name).collect();
+            format!("unknown color (valid: {})", names.join(", "))
+        } else if !crate::color::valid_code(code) {
+            "not an SGR code like \"1;32\"".to_string()
+        } else {
+            return true;
+        };
+        let _ = writeln!(
+            stderr,
+            "warning: ignoring theme.{} in {}: {}",
+            token,
+            path.display(),
+            problem
+        );
+        false
+    });
+}
+
+/// Load one layer, dropping secrets it isn't allowed to provide.
+fn load_layer(layer: ConfigLayer, path: &Path, stderr: &mut dyn Write) -> Config {
+    if layer == ConfigLayer::Project {
+        // A checked-in file is shared with everyone who clones the repo.
+        let mut config = load_config_from_path(path, stderr);
+        let removed = strip_secrets(&mut config);
+        if !removed.is_empty() {
+            let _ = writeln!(
+                stderr,
+                "warning: ignoring {} in {}; project config files cannot set secrets",
+                removed.join(", "),
+                path.display()
+            );
+        }
+        // Nor may it run commands just because you cd'd into a checkout.
+        let removed = strip_commands(&mut config);
+        if !removed.is_empty() {
+            let _ = writeln!(
+                stderr,
+                "warning: ignoring {} in {}; project config files cannot run commands",
+                removed.join(", "),
+                path.display()
+            );
+        }
+        // Or point your API key at a server of the repo author's choosing.
+        let removed = strip_servers(&mut config);
+        if !removed.is_empty() {
+            let _ = writeln!(
+                stderr,
+                "warning: ignoring {} in {}; project config files cannot choose the server",
+                removed.join(", "),
+                path.display()
+            );
+        }
+        return config;
     }
 
-    // Check file permissions on Unix
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::MetadataExt;
-        if let Ok(meta) = fs::metadata(&path) {
-            let mode = meta.mode() & 0o777;
-            if mode & 0o077 != 0 {
+    if let Some(mode) = insecure_mode(path) {
+        if layer == ConfigLayer::System {
+            // A world-readable /etc file is normal; only complain if it
+            // tries to hand out secrets.
+            let mut config = load_config_from_path(path, stderr);
+            let removed = strip_secrets(&mut config);
+            if !removed.is_empty() {
                 let _ = writeln!(
                     stderr,
-                    "warning: {} has permissions {:04o}; should be 0600\n\
-                     Secrets in this file are accessible to other users. \
-                     Fix with: chmod 600 {}",
+                    "warning: ignoring {} in {}; it has permissions {:04o}",
+                    removed.join(", "),
                     path.display(),
-                    mode,
-                    path.display()
+                    mode
                 );
-                // Still load non-secret fields, but skip secrets
-                return load_config_filtered(&path, stderr);
             }
+            return config;
         }
+        let _ = writeln!(
+            stderr,
+            "warning: {} has permissions {:04o}; should be 0600\n\
+             Secrets in this file are accessible to other users. \
+             Fix with: chmod 600 {}",
+            path.display(),
+            mode,
+            path.display()
+        );
+        // Still load non-secret fields, but skip secrets
+        return load_config_filtered(path, stderr);
     }
 
-    load_config_from_path(&path, stderr)
+    load_config_from_path(path, stderr)
 }
 
 /// Load config, but omit secret fields (api_key, passphrase,
 /// decryption_passphrases) due to insecure file permissions.
-fn load_config_filtered(path: &PathBuf, stderr: &mut dyn Write) -> Config {
+fn load_config_filtered(path: &Path, stderr: &mut dyn Write) -> Config {
     let mut config = load_config_from_path(path, stderr);
-    config.api_key = None;
-    config.passphrase = None;
-    config.decryption_passphrases = Vec::new();
+    strip_secrets(&mut config);
     config
 }
 
-/// Parse the TOML file at the given path.
-fn load_config_from_path(path: &PathBuf, stderr: &mut dyn Write) -> Config {
-    match fs::read_to_string(path) {
-        Ok(contents) => match toml::from_str::<Config>(&contents) {
-            Ok(config) => config,
-            Err(e) => {
-                let _ = writeln!(stderr, "warning: failed to parse {}: {}", path.display(), e);
-                Config::default()
-            }
-        },
-        Err(e) => {
-            let _ = writeln!(stderr, "warning: failed to read {}: {}", path.display(), e);
-            Config::default()
+/// Clear every secret field, returning the keys that were set.
+fn strip_secrets(config: &mut Config) -> Vec<String> {
+    let mut removed = Vec::new();
+    if config.api_key.take().is_some() {
+        removed.push("api_key".to_string());
+    }
+    if config.passphrase.take().is_some() {
+        removed.push("passphrase".to_string());
+    }
+    if !std::mem::take(&mut config.decryption_passphrases).is_empty() {
+        removed.push("decryption_passphrases".to_string());
+    }
+    if config.encrypted_secrets.take().is_some() {
+        removed.push("encrypted_secrets".to_string());
+    }
+    for (name, profile) in config.profiles.iter_mut() {
+        let table = profile_table(name);
+        if profile.api_key.take().is_some() {
+            removed.push(format!("{}.api_key", table));
+        }
+        if profile.passphrase.take().is_some() {
+            removed.push(format!("{}.passphrase", table));
+        }
+        if profile.decryption_passphrases.take().is_some() {
+            removed.push(format!("{}.decryption_passphrases", table));
         }
     }
+    removed
+}
+
+/// Clear every credential helper command, returning the keys that were set.
+fn strip_commands(config: &mut Config) -> Vec<String> {
+    let mut removed = Vec::new();
+    macro_rules! strip {
+        ($cfg:expr, $prefix:expr, $($field:ident),*) => {
+            $(if $cfg.$field.take().is_some() {
+                removed.push(format!("{}{}", $prefix, stringify!($field)));
+            })*
+        };
+    }
+    strip!(
+        config,
+        "",
+        api_key_command,
+        passphrase_command,
+        decryption_passphrases_command
+    );
+    for (name, profile) in config.profiles.iter_mut() {
+        strip!(
+            profile,
+            format!("{}.", profile_table(name)),
+            api_key_command,
+            passphrase_command,
+            decryption_passphrases_command
+        );
+    }
+    removed
+}
+
+/// Clear the settings that pick which server credentials are sent to
+/// (`base_url`, `default_profile` and every profile table), returning the
+/// keys that were set.
+fn strip_servers(config: &mut Config) -> Vec<String> {
+    let mut removed = Vec::new();
+    if config.base_url.take().is_some() {
+        removed.push("base_url".to_string());
+    }
+    if config.default_profile.take().is_some() {
+        removed.push("default_profile".to_string());
+    }
+    for name in std::mem::take(&mut config.profiles).into_keys() {
+        removed.push(profile_table(&name));
+    }
+    removed
+}
+
+/// Server-selecting settings set anywhere in `config`.
+pub fn server_keys(config: &Config) -> Vec<String> {
+    strip_servers(&mut config.clone())
+}
+
+/// Credential helper commands set anywhere in `config`.
+pub fn command_keys(config: &Config) -> Vec<String> {
+    strip_commands(&mut config.clone())
+}
+
+/// Permission bits of `path` if group or others can access it. Always
+/// None on non-Unix platforms.
+pub fn insecure_mode(path: &Path) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let mode = fs::metadata(path).ok()?.mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Some(mode);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    None
+}
+
+/// Read and parse one config file.
+pub fn parse_config_file(path: &Path) -> Result<Config, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    toml::from_str::<Config>(&contents)
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))
+}
+
+/// Secret keys set anywhere in `config` (top level or profiles).
+pub fn secret_keys(config: &Config) -> Vec<String> {
+    strip_secrets(&mut config.clone())
+}
+
+/// Parse the TOML file at the given path.
+fn load_config_from_path(path: &Path, stderr: &mut dyn Write) -> Config {
+    parse_config_file(path).unwrap_or_else(|e| {
+        let _ = writeln!(stderr, "warning: {}", e);
+        Config::default()
+    })
 }
 
 /// Template content for a new config file.
@@ -129,6 +672,50 @@ pub const CONFIG_TEMPLATE: &str = "\
 # (macOS Keychain, Linux keyutils, Windows Credential Manager).
 # Requires building with --features keychain. Default: false.
 # use_keychain = false
+
+# Credential helpers: run a command and use its stdout instead of storing
+# the secret here. Checked after environment variables and before the OS
+# keychain. decryption_passphrases_command prints one passphrase per line.
+# api_key_command = \"pass show secrt/api-key\"
+# passphrase_command = \"op read op://Private/secrt/passphrase\"
+# decryption_passphrases_command = \"pass show secrt/old-passphrases\"
+
+# Keep a local history of sent secrets for `secrt list` (ID, label, size,
+# expiry; never the decryption key). Default: false.
+# history = false
+
+# Clipboard tool for send/get --copy and send --from-clipboard: auto,
+# wl-copy, xclip, xsel or pbcopy. get --copy clears the clipboard after
+# clipboard_clear if it still holds the secret.
+# clipboard_tool = \"auto\"
+# clipboard_clear = \"45s\"
+
+# Show secrets printed by `get` on the terminal's alternate screen, hidden
+# again on any key or after reveal_for, so they stay out of the scrollback.
+# Default: false.
+# reveal = false
+# reveal_for = \"30s\"
+
+# Colors: auto (when writing to a terminal), always or never. NO_COLOR and
+# CLICOLOR_FORCE are honored when this is auto. plain = true turns colors
+# off and replaces symbols like \u{2713} with ASCII for screen readers.
+# color = \"auto\"
+# plain = false
+#
+# Override colors with SGR codes. Tokens: cmd, opt, arg, heading, success,
+# error, url, label, dim, warn.
+# [theme]
+# success = \"1;32\"
+# url = \"4;34\"
+
+# Named profiles override any of the settings above. Pick one with
+# --profile <name> or SECRT_PROFILE; `secrt get` also picks the profile whose
+# base_url host matches the share URL.
+# default_profile = \"work\"
+#
+# [profiles.work]
+# base_url = \"https://secrt.example.com\"
+# api_key = \"sk_...\"
 ";
 
 /// Create a config file from the template. Returns Ok(path) on success.
@@ -163,29 +750,698 @@ pub fn init_config_at(config_path: Option<PathBuf>, force: bool) -> Result<PathB
     Ok(path)
 }
 
+/// Line-based view of the config file, so edits keep comments, ordering
+/// and formatting that a TOML round-trip would lose.
+struct ConfigLines {
+    lines: Vec<String>,
+}
+
+impl ConfigLines {
+    fn read(path: &Path) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+        };
+        Ok(ConfigLines {
+            lines: contents.lines().map(String::from).collect(),
+        })
+    }
+
+    fn is_header(line: &str) -> bool {
+        line.trim_start().starts_with('[')
+    }
+
+    /// Line range holding the body of `table` (None = top level).
+    fn section(&self, table: Option<&str>) -> Option<(usize, usize)> {
+        let next_header = |from: usize| {
+            self.lines[from..]
+                .iter()
+                .position(|l| Self::is_header(l))
+                .map_or(self.lines.len(), |i| from + i)
+        };
+        match table {
+            None => Some((0, next_header(0))),
+            Some(t) => {
+                let header = format!("[{}]", t);
+                let h = self.lines.iter().position(|l| {
+                    l.trim()
+                        .strip_prefix(&header)
+                        .is_some_and(|rest| rest.is_empty() || rest.trim_start().starts_with('#'))
+                })?;
+                Some((h + 1, next_header(h + 1)))
+            }
+        }
+    }
+
+    /// Lines spanned by the assignment to `key` in `table`, including the
+    /// continuation lines of a multi-line array.
+    fn find(&self, table: Option<&str>, key: &str) -> Option<(usize, usize)> {
+        let (start, end) = self.section(table)?;
+        let first = (start..end).find(|&i| {
+            self.lines[i]
+                .trim_start()
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })?;
+        let last = (first..end)
+            .find(|&j| toml::from_str::<toml::Table>(&self.lines[first..=j].join("\n")).is_ok())
+            .unwrap_or(first);
+        Some((first, last))
+    }
+
+    fn set(&mut self, table: Option<&str>, key: &str, value: &str) {
+        let assignment = format!("{} = {}", key, value);
+        if let Some((first, last)) = self.find(table, key) {
+            self.lines.splice(first..=last, [assignment]);
+            return;
+        }
+        let (start, end) = match self.section(table) {
+            Some(range) => range,
+            None => {
+                if self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+                self.lines.push(format!("[{}]", table.unwrap_or_default()));
+                (self.lines.len(), self.lines.len())
+            }
+        };
+        // Insert after the section's last non-blank line, so comments
+        // describing the next table stay attached to it.
+        let mut at = end;
+        while at > start && self.lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+        if table.is_none() && end < self.lines.len() {
+            while at > start && self.lines[at - 1].trim_start().starts_with('#') {
+                at -= 1;
+            }
+            while at > start && self.lines[at - 1].trim().is_empty() {
+                at -= 1;
+            }
+        }
+        self.lines.insert(at, assignment);
+    }
+
+    fn remove(&mut self, table: Option<&str>, key: &str) -> bool {
+        match self.find(table, key) {
+            Some((first, last)) => {
+                self.lines.drain(first..=last);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Write the file back with mode 0600, refusing to save anything that
+    /// would no longer load. See `replace_private`.
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let mut out = self.lines.join("\n");
+        out.push('\n');
+        toml::from_str::<Config>(&out).map_err(|e| {
+            format!(
+                "refusing to write invalid config to {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        replace_private(path, out.as_bytes())
+    }
+}
+
+/// Replace `path` with `data`, mode 0600, creating its directory if needed.
+/// The data goes to a temporary file next to `path` that replaces it in one
+/// rename, so a crash or full disk never leaves a truncated file behind.
+pub(crate) fn replace_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    let result = write_private(&tmp, data).and_then(|_| {
+        fs::rename(&tmp, path).map_err(|e| format!("failed to replace {}: {}", path.display(), e))
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Create `path` (which must not exist) with mode 0600 and write `data`
+/// to disk before returning.
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let mut f = opts
+        .open(path)
+        .map_err(|e| format!("failed to create {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        f.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("failed to set permissions on {}: {}", path.display(), e))?;
+    }
+    f.write_all(data)
+        .and_then(|_| f.sync_all())
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Set `key = value` in the config file at `path`, under `[table]` or at
+/// the top level when `table` is None. `value` is a TOML literal (e.g.
+/// `true` or `"24h"`). An existing assignment is replaced in place;
+/// otherwise the key is added to the end of its section. Everything else in
+/// the file, including comments, is left untouched. The file is created if
+/// needed and always left with mode 0600.
+pub fn set_config_value(
+    path: &Path,
+    table: Option<&str>,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let mut doc = ConfigLines::read(path)?;
+    doc.set(table, key, value);
+    doc.write(path)
+}
+
+/// Remove `key` from `[table]` (or the top level). Returns false, without
+/// touching the file, if the key wasn't set there.
+pub fn unset_config_value(path: &Path, table: Option<&str>, key: &str) -> Result<bool, String> {
+    let mut doc = ConfigLines::read(path)?;
+    if !doc.remove(table, key) {
+        return Ok(false);
+    }
+    doc.write(path)?;
+    Ok(true)
+}
+
+/// Environment variable holding the master passphrase for
+/// `encrypted_secrets`, for non-interactive use.
+pub const MASTER_PASSPHRASE_ENV: &str = "SECRT_MASTER_PASSPHRASE";
+
+/// The secret values set in `config`: (profile name or None, key, value).
+fn secret_entries(config: &Config) -> Vec<(Option<String>, &'static str, toml::Value)> {
+    let list =
+        |v: &[String]| toml::Value::Array(v.iter().cloned().map(toml::Value::String).collect());
+    let mut entries = Vec::new();
+    let mut push = |table: Option<String>,
+                    api_key: &Option<String>,
+                    passphrase: &Option<String>,
+                    dp: Option<&Vec<String>>| {
+        if let Some(v) = api_key {
+            entries.push((table.clone(), "api_key", toml::Value::String(v.clone())));
+        }
+        if let Some(v) = passphrase {
+            entries.push((table.clone(), "passphrase", toml::Value::String(v.clone())));
+        }
+        if let Some(v) = dp {
+            entries.push((table, "decryption_passphrases", list(v)));
+        }
+    };
+    push(
+        None,
+        &config.api_key,
+        &config.passphrase,
+        Some(&config.decryption_passphrases).filter(|v| !v.is_empty()),
+    );
+    for (name, p) in &config.profiles {
+        push(
+            Some(name.clone()),
+            &p.api_key,
+            &p.passphrase,
+            p.decryption_passphrases.as_ref(),
+        );
+    }
+    entries
+}
+
+/// Seal the secret values in `secrets` with `passphrase`, using the same
+/// PBKDF2 + AES-256-GCM envelope as shared secrets. Returns the string
+/// stored as `encrypted_secrets`.
+fn seal_secrets(
+    secrets: &Config,
+    passphrase: &str,
+    rand_bytes: &dyn Fn(&mut [u8]) -> Result<(), crate::envelope::EnvelopeError>,
+) -> Result<String, String> {
+    let mut doc = toml::Table::new();
+    let mut profiles = toml::Table::new();
+    for (profile, key, value) in secret_entries(secrets) {
+        let dst = match profile {
+            None => &mut doc,
+            Some(name) => profiles
+                .entry(name)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .expect("profile is a table"),
+        };
+        dst.insert(key.to_string(), value);
+    }
+    if !profiles.is_empty() {
+        doc.insert("profiles".into(), toml::Value::Table(profiles));
+    }
+    let plaintext = toml::to_string(&doc).map_err(|e| format!("encode secrets: {}", e))?;
+    let sealed = crate::envelope::seal(crate::envelope::SealParams {
+        plaintext: plaintext.into_bytes(),
+        passphrase: passphrase.to_string(),
+        rand_bytes,
+        hint: None,
+        iterations: 0,
+    })
+    .map_err(|e| format!("encrypt secrets: {}", e))?;
+    let blob = serde_json::json!({
+        "key": crate::envelope::b64_encode(&sealed.url_key),
+        "envelope": sealed.envelope,
+    });
+    Ok(crate::envelope::b64_encode(blob.to_string().as_bytes()))
+}
+
+/// Decrypt an `encrypted_secrets` value into a config holding only the
+/// secret fields.
+pub fn open_secrets(sealed: &str, passphrase: &str) -> Result<Config, String> {
+    let invalid = |e: String| format!("encrypted_secrets is corrupt: {}", e);
+    let raw =
+        crate::envelope::crypto::b64_decode(sealed.trim()).map_err(|e| invalid(e.to_string()))?;
+    let blob: serde_json::Value =
+        serde_json::from_slice(&raw).map_err(|e| invalid(e.to_string()))?;
+    let key = blob["key"]
+        .as_str()
+        .ok_or_else(|| invalid("missing key".into()))
+        .and_then(|k| crate::envelope::crypto::b64_decode(k).map_err(|e| invalid(e.to_string())))?;
+    let plaintext = crate::envelope::open(crate::envelope::OpenParams {
+        envelope: blob["envelope"].clone(),
+        url_key: key,
+        passphrase: passphrase.to_string(),
+    })
+    .map_err(|e| match e {
+        crate::envelope::EnvelopeError::DecryptionFailed => "wrong master passphrase".to_string(),
+        e => invalid(e.to_string()),
+    })?;
+    let text = String::from_utf8(plaintext).map_err(|e| invalid(e.to_string()))?;
+    let mut parsed: Config = toml::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+    // Only secrets belong in the sealed block.
+    let mut secrets = Config {
+        api_key: parsed.api_key.take(),
+        passphrase: parsed.passphrase.take(),
+        decryption_passphrases: std::mem::take(&mut parsed.decryption_passphrases),
+        ..Default::default()
+    };
+    for (name, p) in parsed.profiles {
+        let profile = Profile {
+            api_key: p.api_key,
+            passphrase: p.passphrase,
+            decryption_passphrases: p.decryption_passphrases,
+            ..Default::default()
+        };
+        secrets.profiles.insert(name, profile);
+    }
+    Ok(secrets)
+}
+
+impl Config {
+    /// Fill in secrets decrypted from `encrypted_secrets`. Plaintext values
+    /// already in the config win, so `config set` takes effect before the
+    /// next `config lock`.
+    pub fn fill_secrets(&mut self, sealed: Config) {
+        let layer = self
+            .sources
+            .get("encrypted_secrets")
+            .copied()
+            .unwrap_or(ConfigLayer::User);
+        let mut record = |key: String| {
+            self.sources.insert(key, layer);
+        };
+        if self.api_key.is_none() && sealed.api_key.is_some() {
+            self.api_key = sealed.api_key;
+            record("api_key".into());
+        }
+        if self.passphrase.is_none() && sealed.passphrase.is_some() {
+            self.passphrase = sealed.passphrase;
+            record("passphrase".into());
+        }
+        if self.decryption_passphrases.is_empty() && !sealed.decryption_passphrases.is_empty() {
+            self.decryption_passphrases = sealed.decryption_passphrases;
+            record("decryption_passphrases".into());
+        }
+        for (name, p) in sealed.profiles {
+            let dst = self.profiles.entry(name.clone()).or_default();
+            let prefix = format!("profiles.{}.", name);
+            if dst.api_key.is_none() && p.api_key.is_some() {
+                dst.api_key = p.api_key;
+                record(format!("{}api_key", prefix));
+            }
+            if dst.passphrase.is_none() && p.passphrase.is_some() {
+                dst.passphrase = p.passphrase;
+                record(format!("{}passphrase", prefix));
+            }
+            if dst.decryption_passphrases.is_none() && p.decryption_passphrases.is_some() {
+                dst.decryption_passphrases = p.decryption_passphrases;
+                record(format!("{}decryption_passphrases", prefix));
+            }
+        }
+        self.encrypted_secrets = None;
+    }
+}
+
+/// Move every plaintext secret in the config file at `path` into its
+/// `encrypted_secrets` block, sealed with `passphrase`. Secrets already
+/// sealed there are kept (plaintext values replace them) and must open
+/// with the same passphrase. Returns the keys that were encrypted.
+pub fn lock_config_file(
+    path: &Path,
+    passphrase: &str,
+    rand_bytes: &dyn Fn(&mut [u8]) -> Result<(), crate::envelope::EnvelopeError>,
+) -> Result<Vec<String>, String> {
+    let mut config = parse_config_file(path)?;
+    let plain = secret_entries(&config);
+    if plain.is_empty() {
+        return Err(format!(
+            "no plaintext secrets to encrypt in {}",
+            path.display()
+        ));
+    }
+    if let Some(ref sealed) = config.encrypted_secrets {
+        let sealed = open_secrets(sealed, passphrase)?;
+        config.fill_secrets(sealed);
+    }
+
+    let mut doc = ConfigLines::read(path)?;
+    let mut locked = Vec::new();
+    for (profile, key, _) in &plain {
+        let table = profile.as_deref().map(profile_table);
+        doc.remove(table.as_deref(), key);
+        locked.push(match table {
+            Some(t) => format!("{}.{}", t, key),
+            None => key.to_string(),
+        });
+    }
+    let blob = seal_secrets(&config, passphrase, rand_bytes)?;
+    doc.set(
+        None,
+        "encrypted_secrets",
+        &toml::Value::String(blob).to_string(),
+    );
+    doc.write(path)?;
+    Ok(locked)
+}
+
+/// Decrypt the `encrypted_secrets` block of the config file at `path` and
+/// write its values back as plaintext keys. Returns the keys restored.
+pub fn unlock_config_file(path: &Path, passphrase: &str) -> Result<Vec<String>, String> {
+    let config = parse_config_file(path)?;
+    let sealed = config
+        .encrypted_secrets
+        .as_deref()
+        .ok_or_else(|| format!("{} has no encrypted secrets", path.display()))?;
+    let secrets = open_secrets(sealed, passphrase)?;
+
+    let mut doc = ConfigLines::read(path)?;
+    let mut unlocked = Vec::new();
+    for (profile, key, value) in secret_entries(&secrets) {
+        let table = profile.as_deref().map(profile_table);
+        // A plaintext value set since the last lock is newer; keep it.
+        if doc.find(table.as_deref(), key).is_none() {
+            doc.set(table.as_deref(), key, &value.to_string());
+            unlocked.push(match table {
+                Some(t) => format!("{}.{}", t, key),
+                None => key.to_string(),
+            });
+        }
+    }
+    doc.remove(None, "encrypted_secrets");
+    doc.write(path)?;
+    Ok(unlocked)
+}
+
+/// How a setting's value is checked by `config set`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    Url,
+    Ttl,
+    Bool,
+    Text,
+    List,
+    Profile,
+    /// One of a fixed set of words.
+    Choice(&'static [&'static str]),
+}
+
+/// Every key `config set/get/unset` accepts: (name, kind, is_secret,
+/// description). `default_profile`, `color` and `plain` are only valid at
+/// the top level.
+pub const SETTINGS: &[(&str, SettingKind, bool, &str)] = &[
+    (
+        "api_key",
+        SettingKind::Text,
+        true,
+        "API key for authenticated access",
+    ),
+    ("base_url", SettingKind::Url, false, "Server URL"),
+    (
+        "passphrase",
+        SettingKind::Text,
+        true,
+        "Default passphrase for send and get",
+    ),
+    (
+        "decryption_passphrases",
+        SettingKind::List,
+        true,
+        "Extra passphrases get tries, in order",
+    ),
+    (
+        "default_ttl",
+        SettingKind::Ttl,
+        false,
+        "TTL when send is given none",
+    ),
+    (
+        "show_input",
+        SettingKind::Bool,
+        false,
+        "Echo interactive input",
+    ),
+    (
+        "use_keychain",
+        SettingKind::Bool,
+        false,
+        "Read secrets from the OS keychain",
+    ),
+    (
+        "history",
+        SettingKind::Bool,
+        false,
+        "Keep a local history of sent secrets",
+    ),
+    (
+        "clipboard_tool",
+        SettingKind::Choice(crate::clipboard::TOOLS),
+        false,
+        "Clipboard tool for --copy (default: auto)",
+    ),
+    (
+        "clipboard_clear",
+        SettingKind::Ttl,
+        false,
+        "Clear a copied secret after this long (default: 45s)",
+    ),
+    (
+        "reveal",
+        SettingKind::Bool,
+        false,
+        "Show secrets on the alternate screen, as get --reveal",
+    ),
+    (
+        "reveal_for",
+        SettingKind::Ttl,
+        false,
+        "Hide revealed secrets after this long instead of on a key press",
+    ),
+    (
+        "color",
+        SettingKind::Choice(crate::color::MODES),
+        false,
+        "Use colors: auto, always or never",
+    ),
+    (
+        "plain",
+        SettingKind::Bool,
+        false,
+        "No colors and ASCII symbols, as --plain",
+    ),
+    (
+        "default_profile",
+        SettingKind::Profile,
+        false,
+        "Profile used when none is selected",
+    ),
+    (
+        "api_key_command",
+        SettingKind::Text,
+        false,
+        "Command that prints the API key",
+    ),
+    (
+        "passphrase_command",
+        SettingKind::Text,
+        false,
+        "Command that prints the default passphrase",
+    ),
+    (
+        "decryption_passphrases_command",
+        SettingKind::Text,
+        false,
+        "Command that prints decryption passphrases, one per line",
+    ),
+];
+
+/// Look up a setting by name.
+pub fn setting(key: &str) -> Result<(SettingKind, bool), String> {
+    SETTINGS
+        .iter()
+        .find(|(name, _, _, _)| *name == key)
+        .map(|&(_, kind, secret, _)| (kind, secret))
+        .ok_or_else(|| {
+            let names: Vec<&str> = SETTINGS.iter().map(|(n, _, _, _)| *n).collect();
+            format!("unknown config key {:?} (valid: {})", key, names.join(", "))
+        })
+}
+
+/// Validate `values` for `key` and return the TOML literal to write.
+pub fn parse_setting(key: &str, kind: SettingKind, values: &[String]) -> Result<String, String> {
+    if kind == SettingKind::List {
+        if values.iter().any(|v| v.is_empty()) {
+            return Err(format!("{} entries must not be empty", key));
+        }
+        let list = values.iter().cloned().map(toml::Value::String).collect();
+        return Ok(toml::Value::Array(list).to_string());
+    }
+    let value = match values {
+        [v] => v.as_str(),
+        [] => return Err(format!("missing value for {}", key)),
+        _ => return Err(format!("{} takes a single value", key)),
+    };
+    match kind {
+        SettingKind::Bool => match value {
+            "true" | "false" => Ok(value.to_string()),
+            _ => Err(format!("{} must be true or false, got {:?}", key, value)),
+        },
+        SettingKind::Ttl => {
+            crate::envelope::parse_ttl(value)
+                .map_err(|e| format!("invalid {} {:?}: {}", key, value, e))?;
+            Ok(toml::Value::String(value.to_string()).to_string())
+        }
+        SettingKind::Url => {
+            let has_scheme = value.starts_with("https://") || value.starts_with("http://");
+            if !has_scheme || url_host(value).is_none() {
+                return Err(format!(
+                    "invalid {} {:?}: expected an http:// or https:// URL",
+                    key, value
+                ));
+            }
+            Ok(toml::Value::String(value.to_string()).to_string())
+        }
+        SettingKind::Choice(choices) => {
+            if !choices.contains(&value) {
+                return Err(format!(
+                    "{} must be one of {}, got {:?}",
+                    key,
+                    choices.join(", "),
+                    value
+                ));
+            }
+            Ok(toml::Value::String(value.to_string()).to_string())
+        }
+        SettingKind::Text | SettingKind::Profile | SettingKind::List => {
+            if value.is_empty() {
+                return Err(format!("{} must not be empty", key));
+            }
+            Ok(toml::Value::String(value.to_string()).to_string())
+        }
+    }
+}
+
+/// Read `key` from `[table]` (or the top level) exactly as written in the
+/// file, without inheritance. Lists come back one entry per element.
+pub fn get_config_value(
+    path: &Path,
+    table: Option<&str>,
+    key: &str,
+) -> Result<Option<Vec<String>>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    let doc: toml::Table = toml::from_str(&contents)
+        .map_err(|e| format!("failed to parse {}: {}", path.display(), e))?;
+    let mut scope = &doc;
+    if let Some(t) = table {
+        for part in table_path(t) {
+            match scope.get(&part) {
+                Some(toml::Value::Table(inner)) => scope = inner,
+                _ => return Ok(None),
+            }
+        }
+    }
+    let render = |v: &toml::Value| match v {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    Ok(scope.get(key).map(|v| match v {
+        toml::Value::Array(items) => items.iter().map(render).collect(),
+        other => vec![render(other)],
+    }))
+}
+
+/// Split a table path produced by `profile_table` back into its keys.
+fn table_path(table: &str) -> Vec<String> {
+    match table.split_once('.') {
+        Some((head, rest)) => {
+            let tail = if rest.starts_with('"') {
+                toml::from_str::<toml::Table>(&format!("k = {}", rest))
+                    .ok()
+                    .and_then(|t| t.get("k").and_then(|v| v.as_str().map(String::from)))
+                    .unwrap_or_else(|| rest.to_string())
+            } else {
+                rest.to_string()
+            };
+            vec![head.to_string(), tail]
+        }
+        None => vec![table.to_string()],
+    }
+}
+
 /// Mask a secret value for display. Shows a prefix then dots.
 /// For API keys (typically prefixed like "sk_abc123..."), show first 8 chars.
 /// For passphrases, show only dots.
-pub fn mask_secret(value: &str, is_api_key: bool) -> String {
+pub fn mask_secret(style: &Style, value: &str, is_api_key: bool) -> String {
     if value.is_empty() {
         return String::new();
     }
     if is_api_key {
         let visible = value.len().min(8);
-        let dots = "\u{2022}".repeat(8);
+        let dots = style.glyph(BULLET).repeat(8);
         format!("{}{}", &value[..visible], dots)
     } else {
-        "\u{2022}".repeat(8)
+        style.glyph(BULLET).repeat(8)
     }
 }
 
 /// Mask a list of secret values for display.
 /// Shows `[••••••••, ••••••••]` with the count of entries.
-pub fn mask_secret_list(values: &[String]) -> String {
+pub fn mask_secret_list(style: &Style, values: &[String]) -> String {
     if values.is_empty() {
         return String::new();
     }
-    let dots = "\u{2022}".repeat(8);
+    let dots = style.glyph(BULLET).repeat(8);
     let masked: Vec<&str> = values.iter().map(|_| dots.as_str()).collect();
     format!("[{}]", masked.join(", "))
 }
@@ -304,7 +1560,7 @@ mod tests {
 
     #[test]
     fn mask_api_key_shows_prefix() {
-        let masked = mask_secret("sk_live_abc123xyz789", true);
+        let masked = mask_secret(&Style::default(), "sk_live_abc123xyz789", true);
         assert!(masked.starts_with("sk_live_"));
         assert!(masked.contains('\u{2022}'));
         assert!(!masked.contains("xyz789"));
@@ -312,22 +1568,22 @@ mod tests {
 
     #[test]
     fn mask_api_key_short() {
-        let masked = mask_secret("sk_ab", true);
+        let masked = mask_secret(&Style::default(), "sk_ab", true);
         assert!(masked.starts_with("sk_ab"));
         assert!(masked.contains('\u{2022}'));
     }
 
     #[test]
     fn mask_passphrase_all_dots() {
-        let masked = mask_secret("hunter2", false);
+        let masked = mask_secret(&Style::default(), "hunter2", false);
         assert!(!masked.contains("hunter"));
         assert!(masked.contains('\u{2022}'));
     }
 
     #[test]
     fn mask_empty() {
-        assert_eq!(mask_secret("", true), "");
-        assert_eq!(mask_secret("", false), "");
+        assert_eq!(mask_secret(&Style::default(), "", true), "");
+        assert_eq!(mask_secret(&Style::default(), "", false), "");
     }
 
     #[test]
@@ -397,13 +1653,13 @@ mod tests {
 
     #[test]
     fn mask_secret_list_empty() {
-        assert_eq!(mask_secret_list(&[]), "");
+        assert_eq!(mask_secret_list(&Style::default(), &[]), "");
     }
 
     #[test]
     fn mask_secret_list_multiple() {
         let list = vec!["a".into(), "bb".into(), "ccc".into()];
-        let masked = mask_secret_list(&list);
+        let masked = mask_secret_list(&Style::default(), &list);
         assert!(masked.starts_with('['));
         assert!(masked.ends_with(']'));
         assert!(masked.contains('\u{2022}'));
@@ -562,4 +1818,436 @@ mod tests {
         assert_eq!(config.default_ttl.as_deref(), Some("2h"));
         let _ = fs::remove_dir_all(&dir);
     }
+
+    fn profiles_config() -> Config {
+        toml::from_str(
+            "api_key = \"sk_top\"\n\
+             default_ttl = \"1h\"\n\
+             default_profile = \"home\"\n\
+             [profiles.home]\n\
+             base_url = \"https://secrt.ca\"\n\
+             [profiles.work]\n\
+             base_url = \"https://Secrets.Example.com:8443\"\n\
+             api_key = \"sk_work\"\n\
+             decryption_passphrases = [\"team\"]\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn with_profile_overlays_settings() {
+        let config = profiles_config().with_profile("work");
+        assert_eq!(config.api_key.as_deref(), Some("sk_work"));
+        assert_eq!(
+            config.base_url.as_deref(),
+            Some("https://Secrets.Example.com:8443")
+        );
+        assert_eq!(config.default_ttl.as_deref(), Some("1h"));
+        assert_eq!(config.decryption_passphrases, vec!["team"]);
+        assert_eq!(config.keychain_key("api_key"), "profiles.work.api_key");
+        assert_eq!(profiles_config().keychain_key("api_key"), "api_key");
+    }
+
+    #[test]
+    fn select_profile_precedence() {
+        let config = profiles_config();
+        let pick = |flag: &str, env: Option<&str>, url: Option<&str>| {
+            config.select_profile(flag, env, url).unwrap()
+        };
+        assert_eq!(
+            pick("work", Some("home"), None),
+            Some(("work".into(), ProfileSource::Flag))
+        );
+        assert_eq!(
+            pick("", Some("work"), None),
+            Some(("work".into(), ProfileSource::Env))
+        );
+        assert_eq!(
+            pick("", None, Some("https://secrets.example.com:8443/s/abc#key")),
+            Some(("work".into(), ProfileSource::ShareUrl))
+        );
+        assert_eq!(
+            pick("", None, Some("https://other.example/s/abc#key")),
+            Some(("home".into(), ProfileSource::Default))
+        );
+        assert_eq!(Config::default().select_profile("", None, None), Ok(None));
+    }
+
+    #[test]
+    fn select_unknown_profile_lists_available() {
+        let err = profiles_config()
+            .select_profile("nope", None, None)
+            .unwrap_err();
+        assert!(err.contains("unknown profile \"nope\""), "{}", err);
+        assert!(err.contains("home, work"), "{}", err);
+    }
+
+    #[test]
+    fn filtered_strips_profile_secrets() {
+        let dir = std::env::temp_dir().join("secrt_config_filtered_profiles");
+        let _ = fs::create_dir_all(&dir);
+        let path = dir.join("config.toml");
+        fs::write(
+            &path,
+            "[profiles.work]\nbase_url = \"https://ok.com\"\napi_key = \"sk_work\"\n",
+        )
+        .unwrap();
+        let config = load_config_filtered(&path, &mut Vec::new());
+        let work = &config.profiles["work"];
+        assert!(work.api_key.is_none(), "profile api_key should be stripped");
+        assert_eq!(work.base_url.as_deref(), Some("https://ok.com"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn url_host_parsing() {
+        assert_eq!(
+            url_host("https://Secrt.CA/s/x#k").as_deref(),
+            Some("secrt.ca")
+        );
+        assert_eq!(
+            url_host("http://localhost:8787").as_deref(),
+            Some("localhost:8787")
+        );
+        assert_eq!(url_host("abc123"), None);
+    }
+
+    fn set_value_in(contents: &str, table: Option<&str>, key: &str, value: &str) -> String {
+        let dir = std::path::PathBuf::from(format!(
+            "{}/secrt_config_set_{}",
+            std::env::temp_dir().display(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let path = dir.join("config.toml");
+        let _ = fs::create_dir_all(&dir);
+        fs::write(&path, contents).unwrap();
+        set_config_value(&path, table, key, value).unwrap();
+        let out = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        out
+    }
+
+    #[test]
+    fn set_value_replaces_in_place() {
+        let out = set_value_in(
+            "# keep me\nuse_keychain = false # old\nhistory = true\n",
+            None,
+            "use_keychain",
+            "true",
+        );
+        assert_eq!(out, "# keep me\nuse_keychain = true\nhistory = true\n");
+    }
+
+    #[test]
+    fn set_value_top_level_goes_before_tables() {
+        let out = set_value_in(
+            "history = true\n\n# Work server\n[profiles.work]\nuse_keychain = false\n",
+            None,
+            "use_keychain",
+            "true",
+        );
+        assert_eq!(
+            out,
+            "history = true\nuse_keychain = true\n\n# Work server\n[profiles.work]\nuse_keychain = false\n"
+        );
+        let parsed: Config = toml::from_str(&out).unwrap();
+        assert_eq!(parsed.use_keychain, Some(true));
+        assert_eq!(parsed.profiles["work"].use_keychain, Some(false));
+    }
+
+    #[test]
+    fn set_value_in_table() {
+        let out = set_value_in(
+            "[profiles.work]\nuse_keychain = false\n\n[profiles.lab]\n",
+            Some("profiles.lab"),
+            "use_keychain",
+            "true",
+        );
+        let parsed: Config = toml::from_str(&out).unwrap();
+        assert_eq!(parsed.profiles["work"].use_keychain, Some(false));
+        assert_eq!(parsed.profiles["lab"].use_keychain, Some(true));
+
+        let out = set_value_in("history = true\n", Some("profiles.new"), "history", "false");
+        assert_eq!(out, "history = true\n\n[profiles.new]\nhistory = false\n");
+    }
+
+    #[test]
+    fn set_value_template_stays_commented() {
+        let out = set_value_in(CONFIG_TEMPLATE, None, "use_keychain", "true");
+        assert!(out.starts_with("# secrt configuration\n"));
+        let parsed: Config = toml::from_str(&out).unwrap();
+        assert_eq!(parsed.use_keychain, Some(true));
+        assert!(out.contains("# use_keychain = false"));
+    }
+
+    #[test]
+    fn set_value_replaces_multiline_array() {
+        let out = set_value_in(
+            "decryption_passphrases = [\n  \"a\",\n  \"b\",\n]\n# after\nhistory = true\n",
+            None,
+            "decryption_passphrases",
+            "[\"c\"]",
+        );
+        assert_eq!(
+            out,
+            "decryption_passphrases = [\"c\"]\n# after\nhistory = true\n"
+        );
+    }
+
+    #[test]
+    fn set_value_refuses_invalid_result() {
+        let dir = std::env::temp_dir().join("secrt_config_set_invalid");
+        let path = dir.join("config.toml");
+        let _ = fs::create_dir_all(&dir);
+        fs::write(
+            &path,
+            "history = true
+",
+        )
+        .unwrap();
+        let err = set_config_value(&path, None, "history", "\"yes\"").unwrap_err();
+        assert!(err.contains("refusing to write invalid config"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "history = true\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn set_value_replaces_file_with_private_copy() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join("secrt_config_set_atomic");
+        let path = dir.join("config.toml");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&path, "history = true\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        set_config_value(&path, None, "api_key", "\"sk_live_x\"").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "history = true\napi_key = \"sk_live_x\"\n"
+        );
+        let names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("config.toml")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unset_value_keeps_rest_of_file() {
+        let dir = std::env::temp_dir().join("secrt_config_unset");
+        let path = dir.join("config.toml");
+        let _ = fs::create_dir_all(&dir);
+        fs::write(
+            &path,
+            "# top\nhistory = true\n\n[profiles.work]\n# work ttl\ndefault_ttl = \"1h\"\nshow_input = true\n",
+        )
+        .unwrap();
+        assert!(unset_config_value(&path, Some("profiles.work"), "default_ttl").unwrap());
+        assert!(!unset_config_value(&path, None, "default_ttl").unwrap());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# top\nhistory = true\n\n[profiles.work]\n# work ttl\nshow_input = true\n"
+        );
+        assert_eq!(
+            get_config_value(&path, Some("profiles.work"), "show_input").unwrap(),
+            Some(vec!["true".to_string()])
+        );
+        assert_eq!(get_config_value(&path, None, "show_input").unwrap(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn lock_and_unlock_roundtrip() {
+        let dir = std::env::temp_dir().join("secrt_config_lock");
+        let path = dir.join("config.toml");
+        let _ = fs::create_dir_all(&dir);
+        let original = "# mine\napi_key = \"sk_live_x\"\ndecryption_passphrases = [\"a\", \"b\"]\nhistory = true\n\n[profiles.work]\nbase_url = \"https://work.example\"\npassphrase = \"wp\"\n";
+        fs::write(&path, original).unwrap();
+        let rand = |buf: &mut [u8]| {
+            buf.fill(7);
+            Ok(())
+        };
+
+        let locked = lock_config_file(&path, "master", &rand).unwrap();
+        assert_eq!(
+            locked,
+            vec![
+                "api_key",
+                "decryption_passphrases",
+                "profiles.work.passphrase"
+            ]
+        );
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("sk_live_x"), "{}", on_disk);
+        assert!(!on_disk.contains("\"wp\""), "{}", on_disk);
+        assert!(on_disk.starts_with("# mine\nhistory = true\nencrypted_secrets = "));
+
+        let mut config = parse_config_file(&path).unwrap();
+        assert_eq!(
+            open_secrets(config.encrypted_secrets.as_deref().unwrap(), "nope").unwrap_err(),
+            "wrong master passphrase"
+        );
+        let sealed = open_secrets(config.encrypted_secrets.as_deref().unwrap(), "master").unwrap();
+        config.fill_secrets(sealed);
+        assert_eq!(config.api_key.as_deref(), Some("sk_live_x"));
+        assert_eq!(config.decryption_passphrases, vec!["a", "b"]);
+        let work = config.with_profile("work");
+        assert_eq!(work.passphrase.as_deref(), Some("wp"));
+        assert_eq!(work.base_url.as_deref(), Some("https://work.example"));
+
+        let restored = unlock_config_file(&path, "master").unwrap();
+        assert_eq!(restored.len(), 3);
+        let config = parse_config_file(&path).unwrap();
+        assert!(config.encrypted_secrets.is_none());
+        assert_eq!(config.api_key.as_deref(), Some("sk_live_x"));
+        assert_eq!(config.profiles["work"].passphrase.as_deref(), Some("wp"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn plaintext_secrets_beat_sealed_ones() {
+        let rand = |buf: &mut [u8]| {
+            buf.fill(3);
+            Ok(())
+        };
+        let sealed = Config {
+            api_key: Some("sk_sealed".into()),
+            passphrase: Some("sealed-pass".into()),
+            ..Default::default()
+        };
+        let blob = seal_secrets(&sealed, "m", &rand).unwrap();
+        let mut config = Config {
+            api_key: Some("sk_plain".into()),
+            encrypted_secrets: Some(blob.clone()),
+            ..Default::default()
+        };
+        config.fill_secrets(open_secrets(&blob, "m").unwrap());
+        assert_eq!(config.api_key.as_deref(), Some("sk_plain"));
+        assert_eq!(config.passphrase.as_deref(), Some("sealed-pass"));
+        assert!(config.encrypted_secrets.is_none());
+    }
+
+    #[test]
+    fn parse_setting_validates() {
+        let one = |key: &str, v: &str| {
+            let (kind, _) = setting(key).unwrap();
+            parse_setting(key, kind, &[v.to_string()])
+        };
+        assert_eq!(one("default_ttl", "24h").unwrap(), "\"24h\"");
+        assert!(one("default_ttl", "forever").is_err());
+        assert_eq!(one("show_input", "true").unwrap(), "true");
+        assert!(one("show_input", "yes").is_err());
+        assert!(one("base_url", "https://secrt.example").is_ok());
+        assert!(one("base_url", "secrt.example").is_err());
+        assert!(one("base_url", "ftp://secrt.example").is_err());
+        assert!(one("api_key", "").is_err());
+        assert_eq!(one("clipboard_tool", "xsel").unwrap(), "\"xsel\"");
+        let err = one("clipboard_tool", "clip.exe").unwrap_err();
+        assert!(
+            err.starts_with("clipboard_tool must be one of auto, wl-copy"),
+            "{}",
+            err
+        );
+        assert_eq!(one("clipboard_clear", "30s").unwrap(), "\"30s\"");
+        assert_eq!(one("reveal", "true").unwrap(), "true");
+        assert!(one("reveal_for", "soon").is_err());
+        assert_eq!(one("color", "never").unwrap(), "\"never\"");
+        assert!(one("color", "sometimes").is_err());
+        let list = parse_setting(
+            "decryption_passphrases",
+            SettingKind::List,
+            &["a".into(), "b \"c\"".into()],
+        )
+        .unwrap();
+        let parsed: Config = toml::from_str(&format!("decryption_passphrases = {}", list)).unwrap();
+        assert_eq!(parsed.decryption_passphrases, vec!["a", "b \"c\""]);
+        assert!(setting("colour").unwrap_err().contains("valid: api_key"));
+    }
+
+    #[test]
+    fn layers_merge_system_user_project() {
+        let dir = std::env::temp_dir().join("secrt_config_layers");
+        let _ = fs::remove_dir_all(&dir);
+        let user_dir = dir.join("xdg").join("secrt");
+        let repo = dir.join("repo");
+        let nested = repo.join("src").join("deep");
+        fs::create_dir_all(&user_dir).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            dir.join("system.toml"),
+            "base_url = \"https://corp.example\"\ndefault_ttl = \"7d\"\n[profiles.work]\nshow_input = true\n",
+        )
+        .unwrap();
+        fs::write(
+            user_dir.join("config.toml"),
+            "default_ttl = \"1d\"\nhistory = true\n[profiles.work]\nbase_url = \"https://work.example\"\n[theme]\nsuccess = \"1;32\"\nurl = \"4\"\n",
+        )
+        .unwrap();
+        fs::write(
+            repo.join(PROJECT_CONFIG_NAME),
+            "default_ttl = \"1h\"\napi_key = \"sk_checked_in\"\n[profiles.work]\npassphrase = \"oops\"\n[theme]\nurl = \"35\"\nbogus = \"1\"\nerror = \"31m\\u001b]0;pwned\\u0007\"\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(
+                user_dir.join("config.toml"),
+                fs::Permissions::from_mode(0o600),
+            );
+        }
+
+        let env: std::collections::HashMap<&str, String> = [
+            ("SECRT_SYSTEM_CONFIG", dir.join("system.toml")),
+            ("XDG_CONFIG_HOME", dir.join("xdg")),
+            ("PWD", nested.clone()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k, v.to_string_lossy().into_owned()))
+        .collect();
+        let getenv = |key: &str| env.get(key).cloned();
+
+        assert_eq!(
+            project_config_path_with(&getenv),
+            Some(repo.join(PROJECT_CONFIG_NAME))
+        );
+        let mut stderr = Vec::new();
+        let config = load_config_with(&getenv, &mut stderr);
+        let warning = String::from_utf8(stderr).unwrap();
+        assert!(
+            warning.contains("ignoring api_key, profiles.work.passphrase"),
+            "{}",
+            warning
+        );
+
+        assert_eq!(config.base_url.as_deref(), Some("https://corp.example"));
+        assert_eq!(config.default_ttl.as_deref(), Some("1h"));
+        assert_eq!(config.history, Some(true));
+        assert_eq!(config.api_key, None);
+        assert_eq!(config.sources["base_url"], ConfigLayer::System);
+        assert_eq!(config.sources["default_ttl"], ConfigLayer::Project);
+        assert_eq!(config.sources["history"], ConfigLayer::User);
+        assert!(warning.contains("ignoring theme.bogus"), "{}", warning);
+        assert!(warning.contains("ignoring theme.error"), "{}", warning);
+        assert_eq!(config.theme.len(), 2);
+        assert_eq!(config.theme["success"], "1;32");
+        assert_eq!(config.theme["url"], "35");
+        assert_eq!(config.sources["theme.url"], ConfigLayer::Project);
+
+        let work = config.with_profile("work");
+        assert_eq!(work.base_url.as_deref(), Some("https://work.example"));
+        assert_eq!(work.show_input, Some(true));
+        assert_eq!(work.passphrase, None);
+        assert_eq!(work.sources["base_url"], ConfigLayer::User);
+        assert_eq!(work.sources["show_input"], ConfigLayer::System);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,134 @@
+//! Credential helpers: read an API key or passphrase from the stdout of
+//! an external command (`pass show secrt`, `op read ...`, and so on).
+
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a helper may run before it is killed.
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run `cmd` through the platform shell and return its stdout with the
+/// trailing newline removed. The helper's stderr goes straight to ours so
+/// it can prompt or report errors; stdin is closed.
+pub fn run_credential_command(cmd: &str, timeout: Duration) -> Result<String, String> {
+    let out = run(cmd, timeout)?;
+    let value = out.trim_end_matches(['\r', '\n']);
+    if value.is_empty() {
+        return Err(format!("`{}` printed nothing", cmd));
+    }
+    Ok(value.to_string())
+}
+
+/// Like `run_credential_command`, but each non-empty line of stdout is a
+/// separate value.
+pub fn run_credential_list_command(cmd: &str, timeout: Duration) -> Result<Vec<String>, String> {
+    let out = run(cmd, timeout)?;
+    Ok(out
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn shell(cmd: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut c = Command::new("cmd");
+        c.args(["/C", cmd]);
+        c
+    }
+    #[cfg(not(windows))]
+    {
+        let mut c = Command::new("sh");
+        c.args(["-c", cmd]);
+        c
+    }
+}
+
+fn run(cmd: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = shell(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("failed to run `{}`: {}", cmd, e))?;
+
+    // Drain stdout on a thread so a chatty helper can't block on a full pipe.
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let start = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("`{}` timed out after {}s", cmd, timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(format!("failed to wait for `{}`: {}", cmd, e)),
+        }
+    };
+
+    let buf = reader
+        .join()
+        .map_err(|_| format!("failed to read output of `{}`", cmd))?
+        .map_err(|e| format!("failed to read output of `{}`: {}", cmd, e))?;
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!("`{}` exited with status {}", cmd, code),
+            None => format!("`{}` was terminated by a signal", cmd),
+        });
+    }
+    String::from_utf8(buf).map_err(|_| format!("`{}` printed invalid UTF-8", cmd))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trims_trailing_newline() {
+        let v = run_credential_command("printf 'hunter2\\n'", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(v, "hunter2");
+    }
+
+    #[test]
+    fn keeps_inner_whitespace() {
+        let v = run_credential_command("printf ' a b \\r\\n'", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(v, " a b ");
+    }
+
+    #[test]
+    fn empty_output_is_an_error() {
+        let e = run_credential_command("true", COMMAND_TIMEOUT).unwrap_err();
+        assert_eq!(e, "`true` printed nothing");
+    }
+
+    #[test]
+    fn failure_reports_status() {
+        let e = run_credential_command("echo nope; exit 3", COMMAND_TIMEOUT).unwrap_err();
+        assert_eq!(e, "`echo nope; exit 3` exited with status 3");
+    }
+
+    #[test]
+    fn timeout_kills_command() {
+        let start = Instant::now();
+        let e = run_credential_command("sleep 5", Duration::from_millis(100)).unwrap_err();
+        assert!(e.contains("timed out"), "{}", e);
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    fn list_splits_lines() {
+        let v = run_credential_list_command("printf 'a\\n\\nb\\r\\n'", COMMAND_TIMEOUT).unwrap();
+        assert_eq!(v, vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,940 @@
+//! Local in-memory secrt server for development and offline testing
+//! (behind the `dev-server` feature flag).
+//!
+//! Implements the subset of the API the CLI uses: public and authenticated
+//! create, claim, burn, status and info. Secrets live in memory only and
+//! expire by TTL. Limits and rate limits mirror what `/api/v1/info`
+//! advertises, so preflight checks and quota errors can be exercised too.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::cli::{
+    parse_flags, print_dev_server_help, resolve_api_key, resolve_globals, CliError, Deps,
+};
+use crate::client::{
+    InfoLimits, InfoQuota, InfoRate, InfoResponse, InfoTTL, InfoTier, SecretStatus,
+};
+use crate::color::{CHECK, DIM, SUCCESS, URL};
+use crate::envelope::{self, b64_encode, crypto::compute_claim_hash};
+use crate::passphrase::write_error;
+
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:8787";
+
+/// Claimed, burned and expired secrets are remembered this long past their
+/// expiry so `status` can still report what happened to them.
+const TOMBSTONE_SECS: i64 = 86400;
+
+/// Largest request body accepted regardless of configured limits.
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// Server settings. The TTL, tier and claim-rate sections have the same
+/// shape as the `/api/v1/info` response.
+#[derive(Clone)]
+pub struct DevServerConfig {
+    pub listen: String,
+    pub api_keys: Vec<String>,
+    pub ttl: InfoTTL,
+    pub limits: InfoLimits,
+    pub claim_rate: InfoRate,
+    /// When false, rate limits are advertised but not enforced.
+    pub rate_limits: bool,
+}
+
+impl Default for DevServerConfig {
+    fn default() -> Self {
+        DevServerConfig {
+            listen: DEFAULT_LISTEN.into(),
+            api_keys: Vec::new(),
+            ttl: InfoTTL {
+                default_seconds: 86400,
+                max_seconds: 365 * 86400,
+            },
+            limits: InfoLimits {
+                public: InfoTier {
+                    max_envelope_bytes: 256 * 1024,
+                    max_secrets: 10,
+                    max_total_bytes: 2 * 1024 * 1024,
+                    rate: InfoRate {
+                        requests_per_second: 0.5,
+                        burst: 6,
+                    },
+                },
+                authed: InfoTier {
+                    max_envelope_bytes: 1024 * 1024,
+                    max_secrets: 1000,
+                    max_total_bytes: 100 * 1024 * 1024,
+                    rate: InfoRate {
+                        requests_per_second: 5.0,
+                        burst: 20,
+                    },
+                },
+            },
+            claim_rate: InfoRate {
+                requests_per_second: 1.0,
+                burst: 10,
+            },
+            rate_limits: true,
+        }
+    }
+}
+
+/// Limits file for `--limits`: any subset of the `/api/v1/info` response,
+/// so the output of `secrt info --json` can be used directly.
+#[derive(Deserialize)]
+struct LimitsFile {
+    ttl: Option<InfoTTL>,
+    limits: Option<InfoLimits>,
+    claim_rate: Option<InfoRate>,
+}
+
+impl DevServerConfig {
+    /// Apply a limits file on top of the current settings.
+    pub fn apply_limits_json(&mut self, data: &str) -> Result<(), String> {
+        let f: LimitsFile = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        if let Some(ttl) = f.ttl {
+            self.ttl = ttl;
+        }
+        if let Some(limits) = f.limits {
+            self.limits = limits;
+        }
+        if let Some(rate) = f.claim_rate {
+            self.claim_rate = rate;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Pending,
+    Claimed(i64),
+    Burned,
+}
+
+struct StoredSecret {
+    /// Dropped as soon as the secret is claimed, burned or expires.
+    envelope: Option<serde_json::Value>,
+    claim_hash: String,
+    size: usize,
+    expires_at: i64,
+    /// "key:<api key>" or "ip:<address>" for quota accounting.
+    owner: String,
+    state: State,
+}
+
+impl StoredSecret {
+    fn is_live(&self, now: i64) -> bool {
+        self.state == State::Pending && now < self.expires_at
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last: f64,
+}
+
+#[derive(Default)]
+struct Store {
+    secrets: HashMap<String, StoredSecret>,
+    buckets: HashMap<String, Bucket>,
+}
+
+/// A parsed HTTP request.
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    pub api_key: Option<String>,
+    pub host: String,
+    pub peer: String,
+    pub body: Vec<u8>,
+}
+
+pub(crate) struct Response {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+fn respond(status: u16, body: serde_json::Value) -> Response {
+    Response { status, body }
+}
+
+fn error(status: u16, msg: &str) -> Response {
+    respond(status, serde_json::json!({ "error": msg }))
+}
+
+fn rfc3339(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn new_id() -> String {
+    use ring::rand::{SecureRandom, SystemRandom};
+    let mut buf = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut buf)
+        .expect("system random source failed");
+    b64_encode(&buf)
+}
+
+/// Server state shared by all connections.
+pub(crate) struct Shared {
+    config: DevServerConfig,
+    store: Mutex<Store>,
+}
+
+impl Shared {
+    pub(crate) fn new(config: DevServerConfig) -> Self {
+        Shared {
+            config,
+            store: Mutex::new(Store::default()),
+        }
+    }
+
+    /// Handle one request at time `now` (seconds since the epoch).
+    pub(crate) fn handle(&self, req: &Request, now: f64) -> Response {
+        let path = req.path.split('?').next().unwrap_or("");
+        let segs: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let mut store = self.store.lock().unwrap();
+        sweep(&mut store, now as i64);
+
+        match (req.method.as_str(), segs.as_slice()) {
+            ("GET", ["api", "v1", "info"]) => self.info(&store, req, now as i64),
+            ("POST", ["api", "v1", "public", "secrets"]) => self.create(&mut store, req, None, now),
+            ("POST", ["api", "v1", "secrets"]) => match self.auth(req) {
+                Ok(Some(key)) => self.create(&mut store, req, Some(key), now),
+                Ok(None) => error(401, "API key required"),
+                Err(r) => r,
+            },
+            ("POST", ["api", "v1", "secrets", id, "claim"]) => self.claim(&mut store, id, req, now),
+            ("POST", ["api", "v1", "secrets", id, "burn"]) => match self.auth(req) {
+                Ok(Some(key)) => burn(&mut store, id, &key, now as i64),
+                Ok(None) => error(401, "API key required"),
+                Err(r) => r,
+            },
+            ("GET", ["api", "v1", "secrets", id, "status"]) => status(&store, id, now as i64),
+            (
+                _,
+                ["api", "v1", "info"]
+                | ["api", "v1", "public", "secrets"]
+                | ["api", "v1", "secrets"]
+                | ["api", "v1", "secrets", _, "claim" | "burn" | "status"],
+            ) => error(405, "method not allowed"),
+            _ => error(404, "not found"),
+        }
+    }
+
+    /// The caller's API key: `Ok(None)` when none was sent, an error
+    /// response when it isn't one of the configured keys.
+    fn auth(&self, req: &Request) -> Result<Option<String>, Response> {
+        match req.api_key {
+            None => Ok(None),
+            Some(ref k) if self.config.api_keys.contains(k) => Ok(Some(k.clone())),
+            Some(_) => Err(error(401, "invalid API key")),
+        }
+    }
+
+    fn tier(&self, authed: bool) -> &InfoTier {
+        if authed {
+            &self.config.limits.authed
+        } else {
+            &self.config.limits.public
+        }
+    }
+
+    fn info(&self, store: &Store, req: &Request, now: i64) -> Response {
+        let key = match self.auth(req) {
+            Ok(k) => k,
+            Err(r) => return r,
+        };
+        let quota = key.as_ref().map(|k| {
+            let (count, bytes) = usage(store, &format!("key:{}", k), now);
+            let tier = &self.config.limits.authed;
+            InfoQuota {
+                secrets_remaining: (tier.max_secrets > 0)
+                    .then(|| (tier.max_secrets - count).max(0)),
+                bytes_remaining: (tier.max_total_bytes > 0)
+                    .then(|| (tier.max_total_bytes - bytes).max(0)),
+            }
+        });
+        let info = InfoResponse {
+            authenticated: key.is_some(),
+            ttl: self.config.ttl.clone(),
+            limits: self.config.limits.clone(),
+            claim_rate: self.config.claim_rate.clone(),
+            quota,
+        };
+        respond(200, serde_json::to_value(info).unwrap())
+    }
+
+    fn create(&self, store: &mut Store, req: &Request, key: Option<String>, now: f64) -> Response {
+        #[derive(Deserialize)]
+        struct Body {
+            envelope: serde_json::Value,
+            claim_hash: String,
+            ttl_seconds: Option<i64>,
+        }
+
+        let tier = self.tier(key.is_some());
+        let owner = match key {
+            Some(ref k) => format!("key:{}", k),
+            None => format!("ip:{}", req.peer),
+        };
+        if !self.allow(store, &format!("create:{}", owner), &tier.rate, now) {
+            return error(429, "rate limit exceeded");
+        }
+
+        let body: Body = match serde_json::from_slice(&req.body) {
+            Ok(b) => b,
+            Err(_) => return error(400, "invalid request body"),
+        };
+        if !body.envelope.is_object() || body.claim_hash.is_empty() {
+            return error(400, "envelope and claim_hash are required");
+        }
+
+        let ttl = body.ttl_seconds.unwrap_or(self.config.ttl.default_seconds);
+        if ttl <= 0 {
+            return error(400, "ttl_seconds must be positive");
+        }
+        if self.config.ttl.max_seconds > 0 && ttl > self.config.ttl.max_seconds {
+            return error(
+                400,
+                &format!(
+                    "ttl_seconds exceeds maximum of {}",
+                    self.config.ttl.max_seconds
+                ),
+            );
+        }
+
+        let size = serde_json::to_vec(&body.envelope)
+            .map(|v| v.len())
+            .unwrap_or(0);
+        if tier.max_envelope_bytes > 0 && size as i64 > tier.max_envelope_bytes {
+            return error(
+                413,
+                &format!(
+                    "envelope exceeds maximum size of {} bytes",
+                    tier.max_envelope_bytes
+                ),
+            );
+        }
+
+        let now_secs = now as i64;
+        let (count, bytes) = usage(store, &owner, now_secs);
+        if tier.max_secrets > 0 && count >= tier.max_secrets {
+            return error(
+                403,
+                &format!("secret limit reached ({} active)", tier.max_secrets),
+            );
+        }
+        if tier.max_total_bytes > 0 && bytes + size as i64 > tier.max_total_bytes {
+            return error(403, "storage quota exceeded");
+        }
+
+        let id = new_id();
+        let expires_at = now_secs + ttl;
+        store.secrets.insert(
+            id.clone(),
+            StoredSecret {
+                envelope: Some(body.envelope),
+                claim_hash: body.claim_hash,
+                size,
+                expires_at,
+                owner,
+                state: State::Pending,
+            },
+        );
+        respond(
+            201,
+            serde_json::json!({
+                "id": id,
+                "share_url": format!("http://{}/s/{}", req.host, id),
+                "expires_at": rfc3339(expires_at),
+            }),
+        )
+    }
+
+    fn claim(&self, store: &mut Store, id: &str, req: &Request, now: f64) -> Response {
+        #[derive(Deserialize)]
+        struct Body {
+            claim: String,
+        }
+
+        if !self.allow(
+            store,
+            &format!("claim:{}", req.peer),
+            &self.config.claim_rate,
+            now,
+        ) {
+            return error(429, "rate limit exceeded");
+        }
+        let token = match serde_json::from_slice::<Body>(&req.body)
+            .ok()
+            .and_then(|b| envelope::crypto::b64_decode(&b.claim).ok())
+        {
+            Some(t) => t,
+            None => return error(400, "invalid request body"),
+        };
+
+        let now_secs = now as i64;
+        match store.secrets.get_mut(id) {
+            Some(s) if s.is_live(now_secs) && s.claim_hash == compute_claim_hash(&token) => {
+                s.state = State::Claimed(now_secs);
+                let envelope = s.envelope.take().unwrap_or_default();
+                respond(
+                    200,
+                    serde_json::json!({
+                        "envelope": envelope,
+                        "expires_at": rfc3339(s.expires_at),
+                    }),
+                )
+            }
+            // Wrong claims look the same as missing secrets.
+            _ => error(404, "not found"),
+        }
+    }
+
+    /// Token-bucket rate limiting per caller.
+    fn allow(&self, store: &mut Store, key: &str, rate: &InfoRate, now: f64) -> bool {
+        if !self.config.rate_limits || rate.requests_per_second <= 0.0 {
+            return true;
+        }
+        let burst = rate.burst.max(1) as f64;
+        let b = store.buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: burst,
+            last: now,
+        });
+        b.tokens = (b.tokens + (now - b.last) * rate.requests_per_second).min(burst);
+        b.last = now;
+        if b.tokens >= 1.0 {
+            b.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn burn(store: &mut Store, id: &str, key: &str, now: i64) -> Response {
+    let owner = format!("key:{}", key);
+    match store.secrets.get_mut(id) {
+        Some(s) if s.is_live(now) && s.owner == owner => {
+            s.state = State::Burned;
+            s.envelope = None;
+            respond(200, serde_json::json!({ "ok": true }))
+        }
+        _ => error(404, "not found"),
+    }
+}
+
+fn status(store: &Store, id: &str, now: i64) -> Response {
+    let Some(s) = store.secrets.get(id) else {
+        return error(404, "not found");
+    };
+    let (state, claimed_at) = match s.state {
+        State::Claimed(at) => ("claimed", Some(rfc3339(at))),
+        State::Burned => ("burned", None),
+        State::Pending if now >= s.expires_at => ("expired", None),
+        State::Pending => ("pending", None),
+    };
+    let st = SecretStatus {
+        id: id.to_string(),
+        status: state.into(),
+        expires_at: Some(rfc3339(s.expires_at)),
+        claimed_at,
+    };
+    respond(200, serde_json::to_value(st).unwrap())
+}
+
+/// Live secret count and stored bytes for an owner.
+fn usage(store: &Store, owner: &str, now: i64) -> (i64, i64) {
+    store
+        .secrets
+        .values()
+        .filter(|s| s.owner == owner && s.is_live(now))
+        .fold((0, 0), |(n, b), s| (n + 1, b + s.size as i64))
+}
+
+/// Drop expired envelopes, and forget secrets once their tombstone lapses.
+fn sweep(store: &mut Store, now: i64) {
+    store
+        .secrets
+        .retain(|_, s| s.expires_at + TOMBSTONE_SECS > now);
+    for s in store.secrets.values_mut() {
+        if now >= s.expires_at {
+            s.envelope = None;
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        429 => "Too Many Requests",
+        _ => "Error",
+    }
+}
+
+fn read_request(stream: &TcpStream, local: SocketAddr) -> Result<Request, Response> {
+    let bad = || error(400, "malformed request");
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|_| bad())?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(m), Some(p)) => (m.to_string(), p.to_string()),
+        _ => return Err(bad()),
+    };
+
+    let mut api_key = None;
+    let mut host = local.to_string();
+    let mut content_length = 0usize;
+    loop {
+        line.clear();
+        reader.read_line(&mut line).map_err(|_| bad())?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad());
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "x-api-key" => api_key = Some(value.to_string()),
+            "host" => host = value.to_string(),
+            "content-length" => content_length = value.parse().map_err(|_| bad())?,
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(error(413, "request body too large"));
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).map_err(|_| bad())?;
+
+    Ok(Request {
+        method,
+        path,
+        api_key,
+        host,
+        peer: stream
+            .peer_addr()
+            .map(|a| a.ip().to_string())
+            .unwrap_or_default(),
+        body,
+    })
+}
+
+fn serve_connection(shared: &Shared, mut stream: TcpStream, local: SocketAddr) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+    let resp = match read_request(&stream, local) {
+        Ok(req) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0);
+            shared.handle(&req, now)
+        }
+        Err(r) => r,
+    };
+    let body = serde_json::to_vec(&resp.body).unwrap_or_default();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        resp.status,
+        reason(resp.status),
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&body);
+}
+
+/// A running dev server. The listener thread lives until the process exits.
+pub struct DevServer {
+    addr: SocketAddr,
+}
+
+impl DevServer {
+    /// Bind `config.listen` (port 0 picks a free port) and start serving in
+    /// the background.
+    pub fn start(config: DevServerConfig) -> Result<DevServer, String> {
+        let listener = TcpListener::bind(&config.listen)
+            .map_err(|e| format!("listen on {}: {}", config.listen, e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("listen on {}: {}", config.listen, e))?;
+        let shared = Arc::new(Shared::new(config));
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || serve_connection(&shared, stream, addr));
+            }
+        });
+        Ok(DevServer { addr })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+pub fn run_dev_server(args: &[String], deps: &mut Deps) -> i32 {
+    let mut pa = match parse_flags("dev-server", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_dev_server_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Err(e) = resolve_globals(&mut pa, deps).and_then(|_| resolve_api_key(&mut pa)) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+    let is_tty = (deps.is_tty)();
+
+    let mut config = DevServerConfig::default();
+    if !pa.listen.is_empty() {
+        config.listen = pa.listen.clone();
+    }
+    config.rate_limits = !pa.no_rate_limit;
+
+    let configured = if pa.limits.is_empty() {
+        Ok(())
+    } else {
+        std::fs::read_to_string(&pa.limits)
+            .map_err(|e| format!("read {}: {}", pa.limits, e))
+            .and_then(|d| {
+                config
+                    .apply_limits_json(&d)
+                    .map_err(|e| format!("invalid limits file {}: {}", pa.limits, e))
+            })
+    };
+    let ttls = [
+        (&pa.default_ttl, "--default-ttl"),
+        (&pa.max_ttl, "--max-ttl"),
+    ]
+    .into_iter()
+    .filter(|(v, _)| !v.is_empty())
+    .try_for_each(|(v, flag)| {
+        let secs = envelope::parse_ttl(v).map_err(|_| format!("invalid {} {:?}", flag, v))?;
+        if flag == "--default-ttl" {
+            config.ttl.default_seconds = secs;
+        } else {
+            config.ttl.max_seconds = secs;
+        }
+        Ok::<(), String>(())
+    });
+    if let Err(e) = configured.and(ttls) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+        return 2;
+    }
+    if config.ttl.max_seconds > 0 && config.ttl.default_seconds > config.ttl.max_seconds {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            is_tty,
+            "default TTL exceeds the maximum TTL",
+        );
+        return 2;
+    }
+
+    // Without a key, make one up so authenticated commands can be tried.
+    let api_key = if pa.api_key.is_empty() {
+        format!("sk_dev_{}", new_id())
+    } else {
+        pa.api_key.clone()
+    };
+    config.api_keys = vec![api_key.clone()];
+
+    let server = match DevServer::start(config) {
+        Ok(s) => s,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
+            return 1;
+        }
+    };
+
+    if pa.json {
+        let out = serde_json::json!({
+            "base_url": server.base_url(),
+            "api_key": api_key,
+        });
+        let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+        let _ = deps.stdout.flush();
+    } else {
+        let c = deps.style.color_func(is_tty);
+        let _ = writeln!(
+            deps.stderr,
+            "{} secrt dev-server listening on {}",
+            c(SUCCESS, deps.style.glyph(CHECK)),
+            c(URL, &server.base_url())
+        );
+        let _ = writeln!(deps.stderr, "  API key: {}", api_key);
+        let _ = writeln!(
+            deps.stderr,
+            "  {}",
+            c(
+                DIM,
+                "Secrets are kept in memory and lost when the server stops. Ctrl+C to stop."
+            )
+        );
+    }
+
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shared() -> Shared {
+        Shared::new(DevServerConfig {
+            api_keys: vec!["sk_test".into()],
+            ..Default::default()
+        })
+    }
+
+    fn req(method: &str, path: &str, key: Option<&str>, body: serde_json::Value) -> Request {
+        Request {
+            method: method.into(),
+            path: path.into(),
+            api_key: key.map(String::from),
+            host: "127.0.0.1:8787".into(),
+            peer: "127.0.0.1".into(),
+            body: serde_json::to_vec(&body).unwrap(),
+        }
+    }
+
+    fn create(s: &Shared, key: Option<&str>, token: &[u8], ttl: Option<i64>, now: f64) -> Response {
+        let path = if key.is_some() {
+            "/api/v1/secrets"
+        } else {
+            "/api/v1/public/secrets"
+        };
+        let mut body = serde_json::json!({
+            "envelope": {"v": 1},
+            "claim_hash": compute_claim_hash(token),
+        });
+        if let Some(t) = ttl {
+            body["ttl_seconds"] = t.into();
+        }
+        s.handle(&req("POST", path, key, body), now)
+    }
+
+    fn claim(s: &Shared, id: &str, token: &[u8], now: f64) -> Response {
+        let body = serde_json::json!({ "claim": b64_encode(token) });
+        s.handle(
+            &req("POST", &format!("/api/v1/secrets/{}/claim", id), None, body),
+            now,
+        )
+    }
+
+    fn get_status(s: &Shared, id: &str, now: f64) -> String {
+        let r = s.handle(
+            &req(
+                "GET",
+                &format!("/api/v1/secrets/{}/status", id),
+                None,
+                serde_json::Value::Null,
+            ),
+            now,
+        );
+        r.body["status"].as_str().unwrap_or_default().to_string()
+    }
+
+    const NOW: f64 = 1_800_000_000.0;
+
+    #[test]
+    fn create_claim_once() {
+        let s = shared();
+        let r = create(&s, None, b"token", None, NOW);
+        assert_eq!(r.status, 201);
+        let id = r.body["id"].as_str().unwrap().to_string();
+        assert!(r.body["share_url"]
+            .as_str()
+            .unwrap()
+            .starts_with("http://127.0.0.1:8787/s/"));
+
+        assert_eq!(claim(&s, &id, b"wrong", NOW).status, 404);
+        let r = claim(&s, &id, b"token", NOW);
+        assert_eq!(r.status, 200);
+        assert_eq!(r.body["envelope"]["v"], 1);
+        assert_eq!(claim(&s, &id, b"token", NOW).status, 404);
+        assert_eq!(get_status(&s, &id, NOW), "claimed");
+    }
+
+    #[test]
+    fn ttl_expiry_and_limits() {
+        let s = shared();
+        let r = create(&s, None, b"t", Some(60), NOW);
+        let id = r.body["id"].as_str().unwrap().to_string();
+        assert_eq!(get_status(&s, &id, NOW + 59.0), "pending");
+        assert_eq!(get_status(&s, &id, NOW + 60.0), "expired");
+        assert_eq!(claim(&s, &id, b"t", NOW + 61.0).status, 404);
+        // Forgotten entirely once the tombstone lapses.
+        let late = NOW + 60.0 + TOMBSTONE_SECS as f64;
+        assert_eq!(get_status(&s, &id, late), "");
+
+        let r = create(&s, None, b"t", Some(366 * 86400), NOW);
+        assert_eq!(r.status, 400);
+        assert!(r.body["error"].as_str().unwrap().contains("maximum"));
+    }
+
+    #[test]
+    fn api_key_checks() {
+        let s = shared();
+        assert_eq!(create(&s, Some("sk_bad"), b"t", None, NOW).status, 401);
+        let body = serde_json::json!({"envelope": {}, "claim_hash": "x"});
+        let r = s.handle(&req("POST", "/api/v1/secrets", None, body), NOW);
+        assert_eq!(r.status, 401);
+
+        let public = create(&s, None, b"p", None, NOW);
+        let public_id = public.body["id"].as_str().unwrap().to_string();
+        let authed = create(&s, Some("sk_test"), b"a", None, NOW);
+        let authed_id = authed.body["id"].as_str().unwrap().to_string();
+
+        let burn = |id: &str, key: Option<&str>| {
+            s.handle(
+                &req(
+                    "POST",
+                    &format!("/api/v1/secrets/{}/burn", id),
+                    key,
+                    serde_json::Value::Null,
+                ),
+                NOW,
+            )
+            .status
+        };
+        assert_eq!(burn(&authed_id, None), 401);
+        assert_eq!(burn(&public_id, Some("sk_test")), 404);
+        assert_eq!(burn(&authed_id, Some("sk_test")), 200);
+        assert_eq!(get_status(&s, &authed_id, NOW), "burned");
+        assert_eq!(burn(&authed_id, Some("sk_test")), 404);
+    }
+
+    #[test]
+    fn info_reports_auth_and_quota() {
+        let s = shared();
+        create(&s, Some("sk_test"), b"a", None, NOW);
+        let info = |key: Option<&str>| {
+            s.handle(
+                &req("GET", "/api/v1/info", key, serde_json::Value::Null),
+                NOW,
+            )
+        };
+        let r = info(None);
+        assert_eq!(r.body["authenticated"], false);
+        assert!(r.body.get("quota").is_none());
+        let r = info(Some("sk_test"));
+        assert_eq!(r.body["authenticated"], true);
+        assert_eq!(r.body["quota"]["secrets_remaining"], 999);
+        assert_eq!(info(Some("sk_bad")).status, 401);
+    }
+
+    #[test]
+    fn rate_limit_and_secret_limit() {
+        let s = shared();
+        // Public tier: burst of 6 creates, then 0.5/s.
+        for _ in 0..6 {
+            assert_eq!(create(&s, None, b"t", None, NOW).status, 201);
+        }
+        assert_eq!(create(&s, None, b"t", None, NOW).status, 429);
+        assert_eq!(create(&s, None, b"t", None, NOW + 2.0).status, 201);
+
+        // Public tier allows 10 live secrets per caller.
+        let mut t = NOW + 2.0;
+        let mut created = 7;
+        while created < 10 {
+            t += 2.0;
+            assert_eq!(create(&s, None, b"t", None, t).status, 201);
+            created += 1;
+        }
+        let r = create(&s, None, b"t", None, t + 2.0);
+        assert_eq!(r.status, 403);
+
+        let unlimited = Shared::new(DevServerConfig {
+            rate_limits: false,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            assert_eq!(create(&unlimited, None, b"t", None, NOW).status, 201);
+        }
+    }
+
+    #[test]
+    fn envelope_size_limit() {
+        let mut config = DevServerConfig::default();
+        config.limits.public.max_envelope_bytes = 16;
+        let s = Shared::new(config);
+        let body = serde_json::json!({
+            "envelope": {"ct": "x".repeat(32)},
+            "claim_hash": "h",
+        });
+        let r = s.handle(&req("POST", "/api/v1/public/secrets", None, body), NOW);
+        assert_eq!(r.status, 413);
+    }
+
+    #[test]
+    fn routing_errors() {
+        let s = shared();
+        let r = s.handle(
+            &req(
+                "GET",
+                "/api/v1/public/secrets",
+                None,
+                serde_json::Value::Null,
+            ),
+            NOW,
+        );
+        assert_eq!(r.status, 405);
+        let r = s.handle(&req("GET", "/nope", None, serde_json::Value::Null), NOW);
+        assert_eq!(r.status, 404);
+        let r = s.handle(
+            &req(
+                "POST",
+                "/api/v1/public/secrets",
+                None,
+                serde_json::json!("junk"),
+            ),
+            NOW,
+        );
+        assert_eq!(r.status, 400);
+    }
+
+    #[test]
+    fn limits_file_overrides_sections() {
+        let mut config = DevServerConfig::default();
+        config
+            .apply_limits_json(
+                r#"{"base_url":"https://secrt.ca","ttl":{"default_seconds":60,"max_seconds":600}}"#,
+            )
+            .unwrap();
+        assert_eq!(config.ttl.max_seconds, 600);
+        assert_eq!(config.limits.public.max_secrets, 10);
+        assert!(config.apply_limits_json("{\"ttl\": 5}").is_err());
+    }
+}
@@ -0,0 +1,452 @@
+//! `doctor`: the checks we otherwise walk through by hand when secrt
+//! "doesn't work" — config files, keychain, server reachability, the API
+//! key and the local clock.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::cli::{
+    apply_profile, parse_flags, print_doctor_help, resolve_api_key, resolve_globals_with_config,
+    unseal_config, CliError, Deps, ParsedArgs,
+};
+use crate::color::{CHECK, CROSS, DIM, ERROR, OPT, SUCCESS, WARN};
+use crate::config::{self, Config, ConfigLayer};
+use crate::passphrase::write_error;
+
+/// Clock differences up to this many seconds are not worth reporting.
+const MAX_CLOCK_SKEW_SECS: i64 = 60;
+
+/// Keychain account written and removed again to see if the store works.
+const KEYCHAIN_PROBE_KEY: &str = "doctor.probe";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Serialize)]
+struct Check {
+    name: String,
+    status: Status,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    fixed: bool,
+    /// Config file that `--fix` can chmod to 0600.
+    #[serde(skip)]
+    chmod: Option<PathBuf>,
+}
+
+impl Check {
+    fn new(name: &str, status: Status, message: impl Into<String>) -> Self {
+        Check {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+            fix: None,
+            fixed: false,
+            chmod: None,
+        }
+    }
+
+    fn fix(mut self, hint: impl Into<String>) -> Self {
+        self.fix = Some(hint.into());
+        self
+    }
+}
+
+pub fn run_doctor(args: &[String], deps: &mut Deps) -> i32 {
+    let mut pa = match parse_flags("doctor", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_doctor_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Some(extra) = pa.args.first() {
+        let msg = format!("unexpected argument {:?}", extra);
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            &msg,
+        );
+        return 2;
+    }
+
+    let mut checks = vec![check_build()];
+    checks.extend(check_config_files(deps));
+
+    // The config checks above report what loading would warn about, so
+    // keep those warnings off stderr here.
+    let mut raw = config::load_config_with(&*deps.getenv, &mut std::io::sink());
+    crate::cli::apply_config_style(&raw, deps);
+    if raw.encrypted_secrets.is_some() {
+        let can_unseal = (deps.is_tty)() || (deps.getenv)(config::MASTER_PASSPHRASE_ENV).is_some();
+        checks.push(if !can_unseal {
+            raw.encrypted_secrets = None;
+            Check::new(
+                "encrypted_secrets",
+                Status::Skip,
+                "not decrypted without a terminal",
+            )
+            .fix(format!("set {}", config::MASTER_PASSPHRASE_ENV))
+        } else {
+            match unseal_config(&mut raw, deps) {
+                Ok(()) => Check::new("encrypted_secrets", Status::Ok, "decrypted"),
+                Err(e) => Check::new("encrypted_secrets", Status::Fail, e)
+                    .fix("check the master passphrase, or run `secrt config unlock`"),
+            }
+        });
+    }
+    let config = match apply_profile(raw.clone(), &pa.profile, None, deps) {
+        Ok((config, active)) => {
+            if let Some((name, src)) = active {
+                checks.push(Check::new(
+                    "profile",
+                    Status::Ok,
+                    format!("{} ({})", name, src.describe()),
+                ));
+            }
+            config
+        }
+        Err(e) => {
+            checks.push(
+                Check::new("profile", Status::Fail, e)
+                    .fix("pass an existing --profile or fix default_profile"),
+            );
+            raw
+        }
+    };
+    let resolved =
+        resolve_globals_with_config(&mut pa, deps, &config).and_then(|_| resolve_api_key(&mut pa));
+    if let Err(e) = resolved {
+        checks.push(
+            Check::new("credentials", Status::Fail, e)
+                .fix("fix or remove the failing *_command setting"),
+        );
+    }
+
+    checks.push(check_keychain(&config, deps));
+    checks.extend(check_server(&pa, deps));
+
+    if pa.fix {
+        apply_fixes(&mut checks);
+    }
+
+    let failed = checks.iter().any(|c| c.status == Status::Fail);
+    if pa.json {
+        let out = serde_json::json!({ "ok": !failed, "checks": checks });
+        let _ = writeln!(deps.stdout, "{}", out);
+    } else {
+        write_report(&checks, pa.fix, deps);
+    }
+    i32::from(failed)
+}
+
+fn check_build() -> Check {
+    let version = env!("CARGO_PKG_VERSION");
+    if cfg!(feature = "keychain") {
+        Check::new(
+            "build",
+            Status::Ok,
+            format!("secrt {} with keychain support", version),
+        )
+    } else {
+        Check::new(
+            "build",
+            Status::Warn,
+            format!("secrt {} built without the keychain feature", version),
+        )
+        .fix("reinstall with `cargo install secrt --features keychain`")
+    }
+}
+
+/// One check per config layer, mirroring what `load_config_with` warns
+/// about: unreadable or invalid files, loose permissions and secrets in
+/// files that may not hold them.
+fn check_config_files(deps: &Deps) -> Vec<Check> {
+    let mut checks = Vec::new();
+    for (layer, path) in config::config_layers_with(&*deps.getenv) {
+        let name = match layer {
+            ConfigLayer::System => "config.system",
+            ConfigLayer::User => "config.user",
+            ConfigLayer::Project => "config.project",
+        };
+        let shown = path.display().to_string();
+        if !path.exists() {
+            let check = Check::new(name, Status::Skip, format!("{} not found", shown));
+            checks.push(if layer == ConfigLayer::User {
+                check.fix("create one with `secrt config init` (optional)")
+            } else {
+                check
+            });
+            continue;
+        }
+        let parsed = match config::parse_config_file(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                checks.push(
+                    Check::new(name, Status::Fail, e)
+                        .fix(format!("fix the TOML syntax in {}", shown)),
+                );
+                continue;
+            }
+        };
+        let mut secrets = config::secret_keys(&parsed);
+        if layer == ConfigLayer::Project {
+            secrets.extend(config::command_keys(&parsed));
+            secrets.extend(config::server_keys(&parsed));
+        }
+        let mode = config::insecure_mode(&path);
+        let check = match (layer, mode) {
+            (ConfigLayer::Project, _) if !secrets.is_empty() => Check::new(
+                name,
+                Status::Warn,
+                format!(
+                    "{} sets {}, which project files may not; they are ignored",
+                    shown,
+                    secrets.join(", ")
+                ),
+            )
+            .fix(format!(
+                "move them to the user config or keychain and remove them from {}",
+                shown
+            )),
+            (ConfigLayer::System, Some(mode)) if !secrets.is_empty() => Check::new(
+                name,
+                Status::Warn,
+                format!(
+                    "{} has permissions {:04o}, so its {} are ignored",
+                    shown,
+                    mode,
+                    secrets.join(", ")
+                ),
+            )
+            .fix(format!("remove secrets from {}", shown)),
+            (ConfigLayer::User, Some(mode)) => {
+                let mut check = Check::new(
+                    name,
+                    Status::Warn,
+                    format!(
+                        "{} has permissions {:04o}; secrets in it are ignored",
+                        shown, mode
+                    ),
+                )
+                .fix(format!("chmod 600 {}", shown));
+                check.chmod = Some(path.clone());
+                check
+            }
+            _ => Check::new(name, Status::Ok, shown),
+        };
+        checks.push(check);
+    }
+    checks
+}
+
+fn check_keychain(config: &Config, deps: &mut Deps) -> Check {
+    if !cfg!(feature = "keychain") {
+        return Check::new("keychain", Status::Skip, "not available in this build");
+    }
+    if config.use_keychain != Some(true) {
+        return Check::new(
+            "keychain",
+            Status::Skip,
+            "use_keychain is off; keychain entries are not read",
+        )
+        .fix("secrt config set use_keychain true");
+    }
+    let probe = (deps.set_keychain_secret)(KEYCHAIN_PROBE_KEY, "ok")
+        .and_then(|()| (deps.delete_keychain_secret)(KEYCHAIN_PROBE_KEY));
+    if let Err(e) = probe {
+        return Check::new("keychain", Status::Fail, format!("unavailable: {}", e))
+            .fix("unlock the OS keychain, or set use_keychain = false");
+    }
+
+    let mut stored = Vec::new();
+    for key in ["api_key", "passphrase"] {
+        if (deps.get_keychain_secret)(&config.keychain_key(key)).is_some() {
+            stored.push(key.to_string());
+        }
+    }
+    let list = (deps.get_keychain_secret_list)(&config.keychain_key("decryption_passphrases"));
+    if !list.is_empty() {
+        stored.push(format!("{} decryption passphrase(s)", list.len()));
+    }
+    let message = if stored.is_empty() {
+        "available; no entries stored".to_string()
+    } else {
+        format!("available; stored: {}", stored.join(", "))
+    };
+    Check::new("keychain", Status::Ok, message)
+}
+
+/// Connectivity (DNS, TLS, HTTP), API key status and clock skew, all
+/// against `base_url`.
+fn check_server(pa: &ParsedArgs, deps: &mut Deps) -> Vec<Check> {
+    let api = (deps.make_api)(&pa.base_url, &pa.api_key);
+    let info = match api.info() {
+        Ok(info) => info,
+        Err(e) => {
+            let hint = if e.contains("TLS") {
+                "check the server certificate, or a proxy intercepting TLS"
+            } else if e.contains("resolve") {
+                "check base_url for typos and that DNS resolves the host"
+            } else {
+                "check that base_url is right and the server is up"
+            };
+            let skipped = "skipped; server unreachable";
+            return vec![
+                Check::new("connectivity", Status::Fail, e).fix(hint),
+                Check::new("api_key", Status::Skip, skipped),
+                Check::new("clock", Status::Skip, skipped),
+            ];
+        }
+    };
+    let mut checks = vec![Check::new(
+        "connectivity",
+        Status::Ok,
+        format!("reached {}", pa.base_url),
+    )];
+
+    checks.push(if pa.api_key.is_empty() {
+        Check::new(
+            "api_key",
+            Status::Ok,
+            "none configured; using public limits",
+        )
+    } else if info.authenticated {
+        Check::new("api_key", Status::Ok, "accepted by the server")
+    } else {
+        Check::new("api_key", Status::Fail, "rejected by the server")
+            .fix("store a valid key with `secrt login`")
+    });
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    checks.push(match api.server_time() {
+        Ok(Some(server)) => {
+            let skew = now - server;
+            if skew.abs() <= MAX_CLOCK_SKEW_SECS {
+                Check::new(
+                    "clock",
+                    Status::Ok,
+                    format!("within {}s of server time", skew.abs()),
+                )
+            } else {
+                let direction = if skew > 0 { "ahead of" } else { "behind" };
+                Check::new(
+                    "clock",
+                    Status::Warn,
+                    format!("local clock is {}s {} the server", skew.abs(), direction),
+                )
+                .fix("sync the system clock (e.g. enable NTP)")
+            }
+        }
+        Ok(None) => Check::new("clock", Status::Skip, "server sent no Date header"),
+        Err(e) => Check::new(
+            "clock",
+            Status::Warn,
+            format!("could not read server time: {}", e),
+        ),
+    });
+    checks
+}
+
+fn apply_fixes(checks: &mut [Check]) {
+    for check in checks.iter_mut() {
+        let Some(ref path) = check.chmod else {
+            continue;
+        };
+        match chmod_600(path) {
+            Ok(()) => {
+                check.status = Status::Ok;
+                check.message = format!("{} (permissions set to 0600)", path.display());
+                check.fixed = true;
+            }
+            Err(e) => {
+                check.message = format!("{}; fix failed: {}", check.message, e);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn chmod_600(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn chmod_600(_path: &std::path::Path) -> Result<(), String> {
+    Err("not supported on this platform".into())
+}
+
+fn write_report(checks: &[Check], fixing: bool, deps: &mut Deps) {
+    let c = deps.style.color_func((deps.is_stdout_tty)());
+    let w = &mut deps.stdout;
+    let width = checks.iter().map(|ch| ch.name.len()).max().unwrap_or(0);
+    for check in checks {
+        let glyph = match check.status {
+            Status::Ok => c(SUCCESS, deps.style.glyph(CHECK)),
+            Status::Warn => c(WARN, "!"),
+            Status::Fail => c(ERROR, deps.style.glyph(CROSS)),
+            Status::Skip => c(DIM, "-"),
+        };
+        let _ = writeln!(
+            w,
+            "{} {} {}",
+            glyph,
+            c(OPT, &format!("{:<width$}", check.name, width = width)),
+            check.message
+        );
+        if let (Some(ref hint), false) = (&check.fix, check.fixed) {
+            let _ = writeln!(
+                w,
+                "  {:<width$} {}",
+                "",
+                c(DIM, &format!("fix: {}", hint)),
+                width = width
+            );
+        }
+    }
+
+    let count = |s: Status| checks.iter().filter(|ch| ch.status == s).count();
+    let (fails, warns) = (count(Status::Fail), count(Status::Warn));
+    let _ = writeln!(w);
+    if fails == 0 && warns == 0 {
+        let _ = writeln!(w, "{}", c(SUCCESS, "No problems found."));
+    } else {
+        let plural =
+            |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+        let _ = writeln!(
+            w,
+            "{}, {}",
+            plural(fails, "failure"),
+            plural(warns, "warning")
+        );
+    }
+    let fixable = checks.iter().any(|ch| ch.chmod.is_some() && !ch.fixed);
+    if fixable && !fixing {
+        let _ = writeln!(
+            w,
+            "{}",
+            c(DIM, "Run `secrt doctor --fix` to correct file permissions.")
+        );
+    }
+}
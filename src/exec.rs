@@ -0,0 +1,271 @@
+//! `get --exec`: run a command with the secret in its environment, so it
+//! never touches the disk or stdout.
+
+use std::process::Command;
+
+use crate::format::is_var_name;
+
+/// Variable that holds the secret when neither `--env` nor `--dotenv` is
+/// given.
+pub const DEFAULT_VAR: &str = "SECRT_SECRET";
+
+/// Variables a `--dotenv` secret may not set: they change which programs
+/// or libraries run, or make a shell or interpreter run extra code, so
+/// whoever sent the secret could take over the command.
+const DENIED_VARS: &[&str] = &[
+    "PATH",
+    "IFS",
+    "ENV",
+    "BASH_ENV",
+    "SHELLOPTS",
+    "BASHOPTS",
+    "PS4",
+    "PROMPT_COMMAND",
+    "ZDOTDIR",
+    "HOME",
+    "NODE_OPTIONS",
+    "NODE_PATH",
+    "PYTHONSTARTUP",
+    "PYTHONPATH",
+    "PYTHONHOME",
+    "PYTHONINSPECT",
+    "PERL5OPT",
+    "PERL5LIB",
+    "PERLLIB",
+    "RUBYOPT",
+    "RUBYLIB",
+    "JAVA_TOOL_OPTIONS",
+    "JDK_JAVA_OPTIONS",
+    "_JAVA_OPTIONS",
+    "GIT_SSH",
+    "GIT_SSH_COMMAND",
+    "GIT_ASKPASS",
+    "GIT_EXEC_PATH",
+    "GIT_EXTERNAL_DIFF",
+    "GIT_PAGER",
+    "SSH_ASKPASS",
+    "EDITOR",
+    "VISUAL",
+    "PAGER",
+];
+
+/// Name prefixes a `--dotenv` secret may not set: dynamic loader settings
+/// (`LD_PRELOAD`, `DYLD_INSERT_LIBRARIES`, ...), exported bash functions
+/// and git config injected through the environment.
+const DENIED_PREFIXES: &[&str] = &["LD_", "DYLD_", "BASH_FUNC_", "GIT_CONFIG"];
+
+/// Run `cmd` with `vars` added to the environment and return its exit code.
+/// A child killed by a signal reports 128 plus the signal number, as shells do.
+pub fn run_command(cmd: &[String], vars: &[(String, String)]) -> Result<i32, String> {
+    let (prog, args) = cmd.split_first().ok_or("no command given")?;
+    let status = Command::new(prog)
+        .args(args)
+        .envs(vars.iter().map(|(k, v)| (k, v)))
+        .status()
+        .map_err(|e| format!("failed to run {}: {}", prog, e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(sig) = status.signal() {
+            return Ok(128 + sig);
+        }
+    }
+    Ok(status.code().unwrap_or(1))
+}
+
+/// The variables to pass for `secret`: with `dotenv`, one per key of its
+/// `KEY=value` lines; otherwise the whole secret in `name` (or
+/// `DEFAULT_VAR`). Keys the sender could use to run code are refused.
+pub fn secret_vars(
+    secret: &str,
+    name: &str,
+    dotenv: bool,
+) -> Result<Vec<(String, String)>, String> {
+    if !dotenv {
+        let name = if name.is_empty() { DEFAULT_VAR } else { name };
+        // Like `$(...)`, drop trailing newlines
+        return Ok(vec![(
+            name.to_string(),
+            secret.trim_end_matches(['\r', '\n']).to_string(),
+        )]);
+    }
+    let vars = parse_dotenv(secret).ok_or("the secret is not in KEY=value form")?;
+    let denied: Vec<&str> = vars
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .filter(|k| is_denied(k))
+        .collect();
+    if !denied.is_empty() {
+        return Err(format!(
+            "refusing to set {} from the secret; it could make the command run other code",
+            denied.join(", ")
+        ));
+    }
+    Ok(vars)
+}
+
+/// Whether a dotenv secret is refused for setting `key`.
+fn is_denied(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    DENIED_VARS.contains(&upper.as_str()) || DENIED_PREFIXES.iter().any(|p| upper.starts_with(p))
+}
+
+/// Parse `KEY=value` lines, allowing blank lines, `#` comments, an `export`
+/// prefix and single- or double-quoted values. None if any line is
+/// something else, or there are no keys at all.
+pub fn parse_dotenv(text: &str) -> Option<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=')?;
+        let key = key.trim();
+        if !is_var_name(key) {
+            return None;
+        }
+        vars.push((key.to_string(), dotenv_value(value.trim())?));
+    }
+    (!vars.is_empty()).then_some(vars)
+}
+
+fn dotenv_value(raw: &str) -> Option<String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let (value, tail) = rest.split_once('\'')?;
+        return trailing_ok(tail).then(|| value.to_string());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return trailing_ok(chars.as_str()).then_some(value),
+                '\\' => match chars.next()? {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    other => value.push(other),
+                },
+                _ => value.push(c),
+            }
+        }
+        return None;
+    }
+    // Unquoted: a ` #` starts a comment
+    let value = match raw.find(" #") {
+        Some(i) => &raw[..i],
+        None => raw,
+    };
+    Some(value.trim_end().to_string())
+}
+
+/// Whether what follows a closing quote is only whitespace or a comment.
+fn trailing_ok(tail: &str) -> bool {
+    let tail = tail.trim_start();
+    tail.is_empty() || tail.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_dotenv() {
+        let text = "# db\nDB_USER=app\nexport DB_PASS='p@ss word'\n\nTOKEN=\"a\\\"b\\n\" # note\nEMPTY=\nURL=x #y\n";
+        assert_eq!(
+            parse_dotenv(text),
+            Some(pairs(&[
+                ("DB_USER", "app"),
+                ("DB_PASS", "p@ss word"),
+                ("TOKEN", "a\"b\n"),
+                ("EMPTY", ""),
+                ("URL", "x"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn rejects_other_text() {
+        assert_eq!(parse_dotenv("hunter2\n"), None);
+        assert_eq!(parse_dotenv("# only a comment\n"), None);
+        assert_eq!(parse_dotenv("A=1\nnot a line\n"), None);
+        assert_eq!(parse_dotenv("1A=x"), None);
+        assert_eq!(parse_dotenv("A='unclosed"), None);
+        assert_eq!(parse_dotenv("A='x' trailing"), None);
+    }
+
+    #[test]
+    fn named_variable_takes_everything() {
+        assert_eq!(
+            secret_vars("A=1\nB=2\n", "CONF", false),
+            Ok(pairs(&[("CONF", "A=1\nB=2")]))
+        );
+        assert_eq!(
+            secret_vars("A=1\n", "", false),
+            Ok(pairs(&[(DEFAULT_VAR, "A=1")]))
+        );
+    }
+
+    #[test]
+    fn dotenv_sets_one_variable_per_key() {
+        assert_eq!(
+            secret_vars("A=1\nB=2\n", "", true),
+            Ok(pairs(&[("A", "1"), ("B", "2")]))
+        );
+        assert_eq!(
+            secret_vars("hunter2", "", true),
+            Err("the secret is not in KEY=value form".to_string())
+        );
+    }
+
+    #[test]
+    fn dotenv_refuses_code_running_names() {
+        for name in [
+            "PATH",
+            "path",
+            "LD_PRELOAD",
+            "LD_LIBRARY_PATH",
+            "DYLD_INSERT_LIBRARIES",
+            "BASH_ENV",
+            "ENV",
+            "BASH_FUNC_ls",
+            "NODE_OPTIONS",
+            "PYTHONSTARTUP",
+            "PERL5OPT",
+            "JAVA_TOOL_OPTIONS",
+            "GIT_SSH_COMMAND",
+            "GIT_CONFIG_COUNT",
+        ] {
+            let secret = format!("DB_USER=app\n{}=/tmp/x\n", name);
+            let err = secret_vars(&secret, "", true).unwrap_err();
+            assert!(err.contains(name), "{}: {}", name, err);
+        }
+        let err = secret_vars("PATH=/x\nLD_PRELOAD=/y.so\n", "", true).unwrap_err();
+        assert_eq!(
+            err,
+            "refusing to set PATH, LD_PRELOAD from the secret; it could make the command run other code"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_command_passes_vars_and_exit_code() {
+        let cmd: Vec<String> = ["sh", "-c", "test \"$SECRT_T\" = 'a b' && exit 3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(run_command(&cmd, &pairs(&[("SECRT_T", "a b")])), Ok(3));
+        assert_eq!(run_command(&cmd, &[]), Ok(1));
+        let missing = vec!["secrt-no-such-command".to_string()];
+        assert!(run_command(&missing, &[])
+            .unwrap_err()
+            .starts_with("failed to run"));
+    }
+}
@@ -97,8 +97,8 @@ pub fn resolve_output_path(filename: &str) -> Result<PathBuf, String> {
 
     for i in 1..=999 {
         let candidate = match ext {
-            Some(e) => PathBuf::from(format!("{} ({}).{}", stem, i, e)),
-            None => PathBuf::from(format!("{} ({})", stem, i)),
+            Some(e) => base.with_file_name(format!("{} ({}).{}", stem, i, e)),
+            None => base.with_file_name(format!("{} ({})", stem, i)),
         };
         if !candidate.exists() {
             return Ok(candidate);
@@ -138,6 +138,18 @@ pub fn extract_file_hint(envelope: &serde_json::Value) -> Option<FileHint> {
 mod tests {
     use super::*;
 
+    // --- resolve_output_path ---
+
+    #[test]
+    fn resolve_output_path_stays_in_directory() {
+        let dir = std::env::temp_dir().join(format!("secrt_resolve_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.txt"), b"x").unwrap();
+        let path = resolve_output_path(&dir.join("a.txt").to_string_lossy()).unwrap();
+        assert_eq!(path, dir.join("a (1).txt"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     // --- build_file_hint ---
 
     #[test]
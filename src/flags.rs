@@ -0,0 +1,816 @@
+//! Per-command flag tables. `cli::parse_flags` accepts only the flags listed
+//! for the command being run plus the `GLOBAL` ones, and each help screen's
+//! option rows are built from the same entries, so the two cannot drift
+//! apart.
+
+/// What a flag expects after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arg {
+    /// A switch; `--flag=value` is rejected.
+    None,
+    /// Free-form text, shown in help with the given placeholder.
+    Text(&'static str),
+    /// A positive integer, shown in help with the given placeholder.
+    Count(&'static str),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Flag {
+    pub long: &'static str,
+    pub short: Option<&'static str>,
+    pub arg: Arg,
+    pub help: &'static str,
+}
+
+impl Flag {
+    /// The flag column of a help row, e.g. `-f, --file`.
+    pub fn label(&self) -> String {
+        match self.short {
+            Some(s) => format!("{}, {}", s, self.long),
+            None => self.long.to_string(),
+        }
+    }
+
+    /// The argument column of a help row (empty for switches).
+    pub fn placeholder(&self) -> &'static str {
+        match self.arg {
+            Arg::None => "",
+            Arg::Text(p) | Arg::Count(p) => p,
+        }
+    }
+
+    fn matches(&self, flag: &str) -> bool {
+        self.long == flag || self.short == Some(flag)
+    }
+}
+
+pub struct Command {
+    pub name: &'static str,
+    /// Help sections in display order: heading and the flags listed under it.
+    pub sections: &'static [(&'static str, &'static [Flag])],
+    /// Groups of flags of which at most one may be given.
+    pub exclusive: &'static [&'static [&'static str]],
+}
+
+impl Command {
+    /// The flags listed in this command's own help sections.
+    pub fn flags(&self) -> impl Iterator<Item = &'static Flag> {
+        self.sections.iter().flat_map(|(_, flags)| flags.iter())
+    }
+
+    /// Every flag the command accepts: its own, then the `GLOBAL` ones it
+    /// doesn't list itself.
+    pub fn accepted(&self) -> impl Iterator<Item = &'static Flag> + '_ {
+        self.flags().chain(
+            GLOBAL
+                .iter()
+                .filter(move |g| self.flags().all(|f| f.long != g.long)),
+        )
+    }
+
+    /// Look up a flag by its long or short spelling.
+    pub fn find(&self, flag: &str) -> Option<&'static Flag> {
+        self.accepted().find(|f| f.matches(flag))
+    }
+}
+
+const fn switch(long: &'static str, help: &'static str) -> Flag {
+    Flag {
+        long,
+        short: None,
+        arg: Arg::None,
+        help,
+    }
+}
+
+const fn value(long: &'static str, placeholder: &'static str, help: &'static str) -> Flag {
+    Flag {
+        long,
+        short: None,
+        arg: Arg::Text(placeholder),
+        help,
+    }
+}
+
+const fn short(short: &'static str, flag: Flag) -> Flag {
+    Flag {
+        short: Some(short),
+        ..flag
+    }
+}
+
+const BASE_URL: Flag = value("--base-url", "<url>", "Server URL");
+const PROFILE: Flag = value("--profile", "<name>", "Use a named config profile");
+const API_KEY: Flag = value("--api-key", "<key>", "API key");
+const JSON: Flag = switch("--json", "Output as JSON");
+const SILENT: Flag = switch("--silent", "Suppress status output");
+const HELP: Flag = short("-h", switch("--help", "Show help"));
+const FORMAT: Flag = value("--format", "<format>", "Output as json, yaml, env or shell");
+const TEMPLATE: Flag = value(
+    "--template",
+    "<text>",
+    "Output text with {{field}} placeholders",
+);
+
+const PASSPHRASE_PROMPT: Flag = short("-p", switch("--passphrase-prompt", "Prompt for passphrase"));
+const NO_PASSPHRASE: Flag = short("-n", switch("--no-passphrase", "Skip default passphrase"));
+const PASSPHRASE_ENV: Flag = value("--passphrase-env", "<name>", "Read passphrase from env var");
+const PASSPHRASE_FILE: Flag = value("--passphrase-file", "<path>", "Read passphrase from file");
+const PASSPHRASE_CMD: Flag = value(
+    "--passphrase-cmd",
+    "<command>",
+    "Read passphrase from a command's output",
+);
+
+/// Exclusive groups for commands that take the passphrase flags, followed
+/// by any groups of their own.
+macro_rules! passphrase_exclusive {
+    ($($group:expr),* $(,)?) => {
+        &[
+            &["--no-passphrase", "--passphrase-prompt"],
+            &["--no-passphrase", "--passphrase-env"],
+            &["--no-passphrase", "--passphrase-file"],
+            &["--no-passphrase", "--passphrase-cmd"],
+            &[
+                "--passphrase-prompt",
+                "--passphrase-env",
+                "--passphrase-file",
+                "--passphrase-cmd",
+            ],
+            $($group),*
+        ]
+    };
+}
+
+const LENGTH: Flag = Flag {
+    long: "--length",
+    short: Some("-L"),
+    arg: Arg::Count("<n>"),
+    help: "Password length (default: 20)",
+};
+const NO_SYMBOLS: Flag = short("-S", switch("--no-symbols", "Exclude symbols"));
+const NO_NUMBERS: Flag = short("-N", switch("--no-numbers", "Exclude digits"));
+const NO_CAPS: Flag = short("-C", switch("--no-caps", "Exclude uppercase letters"));
+const GROUPED: Flag = short("-G", switch("--grouped", "Group characters by type"));
+
+pub const SEND: Command = Command {
+    name: "send",
+    sections: &[
+        (
+            "OPTIONS",
+            &[
+                value("--ttl", "<ttl>", "TTL for the secret (e.g., 5m, 2h, 1d)"),
+                value(
+                    "--text",
+                    "<value>",
+                    "Secret text (visible in shell history)",
+                ),
+                short("-f", value("--file", "<path>", "Read secret from a file")),
+                switch("--from-clipboard", "Read secret from the clipboard"),
+                short(
+                    "-m",
+                    switch("--multi-line", "Multi-line input (read until Ctrl+D)"),
+                ),
+                switch("--trim", "Trim leading/trailing whitespace"),
+                switch("--copy", "Copy the share link to the clipboard"),
+                value("--label", "<text>", "Label recorded in local history"),
+                value(
+                    "--batch",
+                    "<path>",
+                    "Send one secret per row of a CSV/NDJSON file",
+                ),
+                value(
+                    "--results",
+                    "<path>",
+                    "Write batch links to a file (default: stdout)",
+                ),
+                switch("--wait", "Wait until the secret is claimed (see watch)"),
+                value(
+                    "--interval",
+                    "<dur>",
+                    "Polling interval for --wait (default: 5s)",
+                ),
+                value("--timeout", "<dur>", "Give up waiting after a duration"),
+                short("-s", switch("--show", "Show input as you type")),
+                switch("--hidden", "Hide input (default, overrides --show)"),
+                PASSPHRASE_PROMPT,
+                NO_PASSPHRASE,
+                PASSPHRASE_ENV,
+                PASSPHRASE_FILE,
+                PASSPHRASE_CMD,
+                BASE_URL,
+                PROFILE,
+                API_KEY,
+                JSON,
+                FORMAT,
+                TEMPLATE,
+                value(
+                    "--name",
+                    "<var>",
+                    "Variable for --format env/shell (default: SECRT_LINK)",
+                ),
+                SILENT,
+                HELP,
+            ],
+        ),
+        (
+            "GEN OPTIONS",
+            &[LENGTH, NO_SYMBOLS, NO_NUMBERS, NO_CAPS, GROUPED],
+        ),
+    ],
+    exclusive: passphrase_exclusive![
+        &["--copy", "--batch"],
+        &["--json", "--format", "--template"],
+        &["--format", "--batch"],
+        &["--template", "--batch"],
+    ],
+};
+
+pub const GET: Command = Command {
+    name: "get",
+    sections: &[(
+        "OPTIONS",
+        &[
+            short(
+                "-o",
+                value(
+                    "--output",
+                    "<path>",
+                    "Write output to file (use - for stdout)",
+                ),
+            ),
+            value(
+                "--batch",
+                "<path>",
+                "Claim every share link in a file (- for stdin)",
+            ),
+            value(
+                "--output-dir",
+                "<dir>",
+                "Save batch secrets as files instead of NDJSON",
+            ),
+            switch(
+                "--copy",
+                "Copy the secret to the clipboard instead of printing it",
+            ),
+            switch(
+                "--exec",
+                "Run the command after -- with the secret in its environment",
+            ),
+            value(
+                "--env",
+                "<name>",
+                "Variable for --exec (default: SECRT_SECRET)",
+            ),
+            switch(
+                "--dotenv",
+                "With --exec, set one variable per KEY=value line",
+            ),
+            switch(
+                "--reveal",
+                "Show the secret on the alternate screen until a key is pressed",
+            ),
+            value(
+                "--reveal-for",
+                "<dur>",
+                "Show the secret on the alternate screen for a duration",
+            ),
+            switch(
+                "--raw",
+                "Print control characters in the secret as-is on a terminal",
+            ),
+            PASSPHRASE_PROMPT,
+            Flag {
+                help: "Skip configured decryption passphrases",
+                ..NO_PASSPHRASE
+            },
+            PASSPHRASE_ENV,
+            PASSPHRASE_FILE,
+            PASSPHRASE_CMD,
+            BASE_URL,
+            PROFILE,
+            API_KEY,
+            JSON,
+            FORMAT,
+            TEMPLATE,
+            value(
+                "--name",
+                "<var>",
+                "Variable for --format env/shell (default: SECRT_SECRET)",
+            ),
+            SILENT,
+            HELP,
+        ],
+    )],
+    exclusive: passphrase_exclusive![
+        &["--copy", "--output"],
+        &["--copy", "--batch"],
+        &["--json", "--format", "--template"],
+        &["--format", "--batch"],
+        &["--template", "--batch"],
+        &["--format", "--copy"],
+        &["--template", "--copy"],
+        &["--format", "--output"],
+        &["--template", "--output"],
+        &["--env", "--dotenv"],
+        &["--exec", "--copy"],
+        &["--exec", "--output"],
+        &["--exec", "--batch"],
+        &["--exec", "--json"],
+        &["--exec", "--format"],
+        &["--exec", "--template"],
+        &["--reveal", "--copy"],
+        &["--reveal", "--exec"],
+        &["--reveal", "--output"],
+        &["--reveal", "--batch"],
+        &["--reveal", "--json"],
+        &["--reveal", "--format"],
+        &["--reveal", "--template"],
+        &["--reveal-for", "--copy"],
+        &["--reveal-for", "--exec"],
+        &["--reveal-for", "--output"],
+        &["--reveal-for", "--batch"],
+        &["--reveal-for", "--json"],
+        &["--reveal-for", "--format"],
+        &["--reveal-for", "--template"],
+    ],
+};
+
+pub const BURN: Command = Command {
+    name: "burn",
+    sections: &[
+        (
+            "OPTIONS",
+            &[
+                Flag {
+                    help: "API key (required)",
+                    ..API_KEY
+                },
+                BASE_URL,
+                PROFILE,
+                short("-y", switch("--yes", "Skip the confirmation prompt")),
+                JSON,
+                SILENT,
+                HELP,
+            ],
+        ),
+        (
+            "SELECTORS",
+            &[
+                switch("--last", "The most recently sent live secret"),
+                value("--label", "<glob>", "Live secrets whose label matches"),
+                value(
+                    "--sent-since",
+                    "<dur>",
+                    "Live secrets sent within a duration",
+                ),
+                switch("--all-unexpired", "Every live secret in history"),
+                value(
+                    "--from-file",
+                    "<path>",
+                    "IDs or share URLs, one per line (- for stdin)",
+                ),
+            ],
+        ),
+    ],
+    exclusive: &[
+        &["--from-file", "--last"],
+        &["--from-file", "--label"],
+        &["--from-file", "--sent-since"],
+        &["--from-file", "--all-unexpired"],
+    ],
+};
+
+pub const INFO: Command = Command {
+    name: "info",
+    sections: &[(
+        "OPTIONS",
+        &[
+            Flag {
+                help: "API key (shows authed status and quota)",
+                ..API_KEY
+            },
+            BASE_URL,
+            PROFILE,
+            JSON,
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const LOGIN: Command = Command {
+    name: "login",
+    sections: &[(
+        "OPTIONS",
+        &[
+            Flag {
+                help: "Server to log in to",
+                ..BASE_URL
+            },
+            Flag {
+                help: "Store the key for a config profile",
+                ..PROFILE
+            },
+            Flag {
+                help: "Key to store (default: prompt, or stdin)",
+                ..API_KEY
+            },
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const LOGOUT: Command = Command {
+    name: "logout",
+    sections: &[(
+        "OPTIONS",
+        &[
+            Flag {
+                help: "Remove the key stored for a profile",
+                ..PROFILE
+            },
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const DOCTOR: Command = Command {
+    name: "doctor",
+    sections: &[(
+        "OPTIONS",
+        &[
+            switch("--fix", "Apply safe fixes (chmod 600 the user config)"),
+            Flag {
+                help: "Server to check",
+                ..BASE_URL
+            },
+            Flag {
+                help: "API key to check",
+                ..API_KEY
+            },
+            PROFILE,
+            JSON,
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const LIST: Command = Command {
+    name: "list",
+    sections: &[(
+        "OPTIONS",
+        &[
+            value(
+                "--label",
+                "<glob>",
+                "Only labels matching the pattern (* and ?)",
+            ),
+            value(
+                "--sent-since",
+                "<dur>",
+                "Only secrets sent within a duration (e.g., 7d)",
+            ),
+            switch("--unexpired", "Hide expired and burned secrets"),
+            Flag {
+                help: "Only secrets sent to this server",
+                ..BASE_URL
+            },
+            PROFILE,
+            switch("--prune", "Remove expired entries from history"),
+            JSON,
+            SILENT,
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const WATCH: Command = Command {
+    name: "watch",
+    sections: &[(
+        "OPTIONS",
+        &[
+            value("--interval", "<dur>", "Polling interval (default: 5s)"),
+            value("--timeout", "<dur>", "Give up after a duration"),
+            API_KEY,
+            BASE_URL,
+            PROFILE,
+            Flag {
+                help: "Output the final status as JSON",
+                ..JSON
+            },
+            SILENT,
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const DEV_SERVER: Command = Command {
+    name: "dev-server",
+    sections: &[(
+        "OPTIONS",
+        &[
+            value(
+                "--listen",
+                "<addr>",
+                "Address to listen on (default: 127.0.0.1:8787)",
+            ),
+            Flag {
+                help: "Accepted API key (default: configured or random)",
+                ..API_KEY
+            },
+            value(
+                "--limits",
+                "<path>",
+                "Limits as JSON, e.g. from secrt info --json",
+            ),
+            value("--default-ttl", "<ttl>", "TTL when none is requested"),
+            value("--max-ttl", "<ttl>", "Longest TTL accepted"),
+            switch("--no-rate-limit", "Advertise but don't enforce rate limits"),
+            Flag {
+                help: "Print the base URL and API key as JSON",
+                ..JSON
+            },
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const GEN: Command = Command {
+    name: "gen",
+    sections: &[(
+        "OPTIONS",
+        &[
+            LENGTH,
+            NO_SYMBOLS,
+            NO_NUMBERS,
+            NO_CAPS,
+            GROUPED,
+            Flag {
+                long: "--count",
+                short: None,
+                arg: Arg::Count("<n>"),
+                help: "Generate multiple passwords",
+            },
+            JSON,
+            HELP,
+        ],
+    )],
+    exclusive: &[],
+};
+
+pub const MAN: Command = Command {
+    name: "man",
+    sections: &[("OPTIONS", &[HELP])],
+    exclusive: &[],
+};
+
+/// Options every command accepts, in addition to its own table. The
+/// server, API key and output options are listed by the commands that use
+/// them, so elsewhere they are rejected rather than ignored.
+pub const GLOBAL: &[Flag] = &[PROFILE, COLOR, PLAIN, HELP];
+
+/// The main help's options: the common ones, plus `--version`, which only
+/// works before a command.
+pub const MAIN: &[Flag] = &[
+    Flag {
+        help: "Server URL (default: https://secrt.ca)",
+        ..BASE_URL
+    },
+    PROFILE,
+    Flag {
+        help: "API key for authenticated access",
+        ..API_KEY
+    },
+    JSON,
+    SILENT,
+    COLOR,
+    PLAIN,
+    HELP,
+    short("-v", switch("--version", "Show version")),
+];
+
+/// Taken out of the arguments by `cli::run` before any command parses them,
+/// so they work anywhere on the command line.
+pub const COLOR: Flag = value("--color", "<when>", "Use colors: auto, always or never");
+pub const PLAIN: Flag = switch("--plain", "No colors and ASCII-only symbols");
+
+/// `config` parses its arguments by hand, so it has no `Command`; these are
+/// the flags its help and completions list.
+pub const CONFIG: &[Flag] = &[
+    switch("--force", "Overwrite existing config file (for init)"),
+    Flag {
+        help: "Show or store settings for a profile",
+        ..PROFILE
+    },
+    HELP,
+];
+
+/// Every command that takes flags, in the order used for suggestions.
+pub const COMMANDS: &[&Command] = &[
+    &SEND,
+    &GET,
+    &BURN,
+    &INFO,
+    &LIST,
+    &WATCH,
+    &GEN,
+    &LOGIN,
+    &LOGOUT,
+    &DOCTOR,
+    &DEV_SERVER,
+    &MAN,
+];
+
+/// The flag table for `name`. Panics on an unregistered command, which is a
+/// programming error rather than user input.
+pub fn command(name: &str) -> &'static Command {
+    COMMANDS
+        .iter()
+        .copied()
+        .find(|c| c.name == name)
+        .unwrap_or_else(|| panic!("no flag table for command {:?}", name))
+}
+
+/// Whether `flag` takes a value when given to `command`, so a scan of the
+/// arguments can step over it.
+pub fn takes_value(command: &str, flag: &str) -> bool {
+    let found = if command == "config" {
+        CONFIG.iter().find(|f| f.matches(flag))
+    } else {
+        COMMANDS
+            .iter()
+            .find(|c| c.name == command)
+            .and_then(|c| c.find(flag))
+    };
+    found.is_some_and(|f| !matches!(f.arg, Arg::None))
+}
+
+/// The error for a flag `command` does not accept. Points at the commands
+/// that do take it, or at a similarly spelled flag of this command.
+pub fn unknown_flag_error(command: &str, flag: &str) -> String {
+    let owners: Vec<String> = COMMANDS
+        .iter()
+        .filter(|c| c.find(flag).is_some())
+        .map(|c| format!("`secrt {} {}`", c.name, flag))
+        .collect();
+    if !owners.is_empty() {
+        return format!(
+            "{} cannot be used with {}; did you mean {}?",
+            flag,
+            command,
+            owners.join(" or ")
+        );
+    }
+    match closest_flag(self::command(command), flag) {
+        Some(near) => format!("unknown flag: {} (did you mean {}?)", flag, near),
+        None => format!("unknown flag: {}", flag),
+    }
+}
+
+/// The long flag of `cmd` closest in spelling to `flag`, if any is close
+/// enough to be a plausible typo.
+fn closest_flag(cmd: &Command, flag: &str) -> Option<&'static str> {
+    let typed = flag.trim_start_matches('-');
+    let limit = (typed.chars().count() / 3).max(1);
+    cmd.accepted()
+        .map(|f| (edit_distance(typed, f.long.trim_start_matches('-')), f.long))
+        .filter(|&(d, _)| d <= limit)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, long)| long)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_are_unique_per_command() {
+        for cmd in COMMANDS {
+            let mut seen = Vec::new();
+            for f in cmd.flags() {
+                assert!(
+                    !seen.contains(&f.long),
+                    "{} lists {} twice",
+                    cmd.name,
+                    f.long
+                );
+                seen.push(f.long);
+                if let Some(s) = f.short {
+                    assert!(!seen.contains(&s), "{} lists {} twice", cmd.name, s);
+                    seen.push(s);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exclusive_groups_name_known_flags() {
+        for cmd in COMMANDS {
+            for group in cmd.exclusive {
+                for flag in *group {
+                    assert!(cmd.find(flag).is_some(), "{}: {}", cmd.name, flag);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn global_flags_parse_for_every_command() {
+        for cmd in COMMANDS {
+            for f in GLOBAL {
+                for spelling in std::iter::once(f.long).chain(f.short) {
+                    let mut args = vec![spelling.to_string()];
+                    if f.arg != Arg::None {
+                        args.push("auto".into());
+                    }
+                    let parsed = crate::cli::parse_flags(cmd.name, &args);
+                    match parsed {
+                        Ok(_) => {}
+                        Err(crate::cli::CliError::ShowHelp) if f.long == "--help" => {}
+                        Err(e) => panic!("{} {}: {:?}", cmd.name, spelling, e),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unused_options_are_rejected() {
+        for (cmd, flag) in [
+            ("gen", "--api-key"),
+            ("gen", "--silent"),
+            ("gen", "--base-url"),
+            ("man", "--json"),
+            ("logout", "--api-key"),
+            ("info", "--silent"),
+        ] {
+            let args = vec![flag.to_string(), "x".to_string()];
+            assert!(
+                matches!(
+                    crate::cli::parse_flags(cmd, &args),
+                    Err(crate::cli::CliError::Error(_))
+                ),
+                "{} {}",
+                cmd,
+                flag
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_command_points_at_owner() {
+        assert_eq!(
+            unknown_flag_error("get", "--ttl"),
+            "--ttl cannot be used with get; did you mean `secrt send --ttl`?"
+        );
+        assert_eq!(
+            unknown_flag_error("info", "--label"),
+            "--label cannot be used with info; did you mean `secrt send --label` or `secrt burn --label` or `secrt list --label`?"
+        );
+    }
+
+    #[test]
+    fn typo_suggests_close_flag() {
+        assert_eq!(
+            unknown_flag_error("send", "--txt"),
+            "unknown flag: --txt (did you mean --text?)"
+        );
+        assert_eq!(
+            unknown_flag_error("gen", "--lenght"),
+            "unknown flag: --lenght (did you mean --length?)"
+        );
+        assert_eq!(
+            unknown_flag_error("send", "--bogus"),
+            "unknown flag: --bogus"
+        );
+    }
+
+    #[test]
+    fn edit_distance_counts_edits() {
+        assert_eq!(edit_distance("text", "text"), 0);
+        assert_eq!(edit_distance("txt", "text"), 1);
+        assert_eq!(edit_distance("lenght", "length"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
@@ -0,0 +1,261 @@
+//! `--format` and `--template` for the results of `send` and `get`: JSON,
+//! YAML, a dotenv line, a shell `export`, or free text with `{{field}}`
+//! placeholders.
+
+use serde_json::{Map, Value};
+
+use crate::cli::ParsedArgs;
+
+/// Values accepted by `--format`.
+pub const FORMATS: &[&str] = &["json", "yaml", "env", "shell"];
+
+/// Template fields for `send`.
+pub const SEND_FIELDS: &[&str] = &[
+    "link",
+    "id",
+    "share_url",
+    "expires_at",
+    "expires_local",
+    "password",
+];
+
+/// Template fields for `get`.
+pub const GET_FIELDS: &[&str] = &["value", "filename", "mime", "expires_at", "expires_local"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    /// `NAME=value`, quoted for both dotenv files and `eval`.
+    Env(String),
+    /// `export NAME='value'`.
+    Shell(String),
+    Template(String),
+}
+
+/// The format chosen with `--json`, `--format` or `--template`, or None for
+/// the default output. `fields` are the template fields the command provides
+/// and `default_name` the variable `env` and `shell` use without `--name`.
+/// Checked before anything is sent or claimed.
+pub fn from_args(
+    pa: &ParsedArgs,
+    fields: &[&str],
+    default_name: &str,
+) -> Result<Option<Format>, String> {
+    let name = if pa.name.is_empty() {
+        default_name.to_string()
+    } else if is_var_name(&pa.name) {
+        pa.name.clone()
+    } else {
+        return Err(format!("invalid variable name {:?}", pa.name));
+    };
+    let format = if !pa.template.is_empty() {
+        check_template(&pa.template, fields)?;
+        Some(Format::Template(pa.template.clone()))
+    } else {
+        match pa.format.as_str() {
+            "" if pa.json => Some(Format::Json),
+            "" => None,
+            "json" => Some(Format::Json),
+            "yaml" => Some(Format::Yaml),
+            "env" => Some(Format::Env(name)),
+            "shell" => Some(Format::Shell(name)),
+            other => {
+                return Err(format!(
+                    "unknown format {:?} (valid: {})",
+                    other,
+                    FORMATS.join(", ")
+                ))
+            }
+        }
+    };
+    if !pa.name.is_empty() && !matches!(format, Some(Format::Env(_) | Format::Shell(_))) {
+        return Err("--name requires --format env or shell".into());
+    }
+    Ok(format)
+}
+
+/// Render a result. `record` holds the JSON and YAML fields,
+/// `value` is what `env` and `shell` assign, and `field` resolves template
+/// placeholders (unset fields render empty).
+pub fn render(
+    format: &Format,
+    record: &Map<String, Value>,
+    value: &str,
+    field: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    match format {
+        Format::Json => format!("{}\n", Value::Object(record.clone())),
+        Format::Yaml => record
+            .iter()
+            .map(|(k, v)| format!("{}: {}\n", k, v))
+            .collect(),
+        Format::Env(name) => format!("{}={}\n", name, dotenv_quote(value)),
+        Format::Shell(name) => format!("export {}={}\n", name, shell_quote(value)),
+        Format::Template(tpl) => {
+            let mut out = String::new();
+            let mut rest = tpl.as_str();
+            while let Some(start) = rest.find("{{") {
+                out.push_str(&rest[..start]);
+                // check_template guarantees the closing braces
+                let end = rest[start..].find("}}").unwrap_or(rest.len() - start) + start;
+                let name = rest[start + 2..end].trim();
+                out.push_str(&field(name).unwrap_or_default());
+                rest = rest.get(end + 2..).unwrap_or("");
+            }
+            out.push_str(rest);
+            if !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out
+        }
+    }
+}
+
+/// Format an ISO 8601 UTC timestamp as `YYYY-MM-DD HH:MM TZ` in local time.
+pub fn local_time(iso: &str) -> String {
+    use chrono::{DateTime, Local, Utc};
+
+    if let Ok(utc) = iso.parse::<DateTime<Utc>>() {
+        let local = utc.with_timezone(&Local);
+        local.format("%Y-%m-%d %H:%M %Z").to_string()
+    } else if iso.len() >= 16 {
+        format!("{} {} UTC", &iso[0..10], &iso[11..16])
+    } else {
+        iso.to_string()
+    }
+}
+
+fn check_template(tpl: &str, fields: &[&str]) -> Result<(), String> {
+    let mut rest = tpl;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            return Err("unclosed {{ in --template".into());
+        };
+        let name = rest[start + 2..start + len].trim();
+        if !fields.contains(&name) {
+            return Err(format!(
+                "unknown template field {:?} (available: {})",
+                name,
+                fields.join(", ")
+            ));
+        }
+        rest = &rest[start + len + 2..];
+    }
+    Ok(())
+}
+
+pub(crate) fn is_var_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+/// Characters that never need quoting in either a dotenv file or a shell.
+fn is_plain(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:@%+,=".contains(c))
+}
+
+/// Quote for a dotenv file in a way `eval` reads the same: single quotes
+/// when possible, since both treat their contents literally.
+fn dotenv_quote(s: &str) -> String {
+    if is_plain(s) {
+        s.to_string()
+    } else if !s.contains('\'') {
+        format!("'{}'", s)
+    } else {
+        let mut out = String::from("\"");
+        for c in s.chars() {
+            if matches!(c, '\\' | '"' | '$' | '`') {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out.push('"');
+        out
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    if is_plain(s) {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(format: &str, template: &str, name: &str) -> ParsedArgs {
+        ParsedArgs {
+            format: format.into(),
+            template: template.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn from_args_validates() {
+        let pick = |f, t, n| from_args(&parsed(f, t, n), SEND_FIELDS, "SECRT_LINK");
+        assert_eq!(pick("", "", ""), Ok(None));
+        assert_eq!(
+            pick("env", "", ""),
+            Ok(Some(Format::Env("SECRT_LINK".into())))
+        );
+        assert_eq!(
+            pick("shell", "", "DB"),
+            Ok(Some(Format::Shell("DB".into())))
+        );
+        assert!(pick("toml", "", "")
+            .unwrap_err()
+            .starts_with("unknown format"));
+        assert!(pick("env", "", "1X")
+            .unwrap_err()
+            .starts_with("invalid variable"));
+        assert!(pick("yaml", "", "X")
+            .unwrap_err()
+            .starts_with("--name requires"));
+        assert!(pick("", "{{nope}}", "")
+            .unwrap_err()
+            .contains("unknown template field"));
+        assert!(pick("", "{{link", "").unwrap_err().starts_with("unclosed"));
+    }
+
+    #[test]
+    fn renders_each_format() {
+        let mut record = Map::new();
+        record.insert("id".into(), Value::from("abc"));
+        record.insert("note".into(), Value::from("a: b\n"));
+        let field = |name: &str| (name == "link").then(|| "https://x/s/abc".to_string());
+        let render = |f: Format| render(&f, &record, "it's $HOME", &field);
+        assert_eq!(
+            render(Format::Json),
+            "{\"id\":\"abc\",\"note\":\"a: b\\n\"}\n"
+        );
+        assert_eq!(render(Format::Yaml), "id: \"abc\"\nnote: \"a: b\\n\"\n");
+        assert_eq!(render(Format::Env("P".into())), "P=\"it's \\$HOME\"\n");
+        assert_eq!(
+            render(Format::Shell("P".into())),
+            "export P='it'\\''s $HOME'\n"
+        );
+        assert_eq!(
+            render(Format::Template("{{ link }} ({{password}})".into())),
+            "https://x/s/abc ()\n"
+        );
+    }
+
+    #[test]
+    fn dotenv_quoting() {
+        assert_eq!(dotenv_quote("s3cret-Token_1"), "s3cret-Token_1");
+        assert_eq!(dotenv_quote("two words # x"), "'two words # x'");
+        assert_eq!(dotenv_quote(""), "''");
+        assert_eq!(dotenv_quote("line1\nline2"), "'line1\nline2'");
+    }
+}
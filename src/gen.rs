@@ -10,20 +10,15 @@ const DIGITS: &[u8] = b"0123456789";
 const SYMBOLS: &[u8] = b"!@*^_+-=?";
 
 pub fn run_gen(args: &[String], deps: &mut Deps) -> i32 {
-    let pa = match parse_flags(args) {
-        Ok(pa) => pa,
-        Err(CliError::ShowHelp) => {
-            print_gen_help(deps);
-            return 0;
-        }
-        Err(CliError::Error(e)) => {
-            write_error(&mut deps.stderr, false, (deps.is_tty)(), &e);
-            return 2;
-        }
+    // Combined mode: `secrt gen send ...` → delegate to run_send. Look for
+    // the `send` positional with either table so send-only flags don't trip
+    // gen's validation first.
+    let combined = |command| {
+        parse_flags(command, args)
+            .map(|pa| pa.args.iter().any(|a| a == "send"))
+            .unwrap_or(false)
     };
-
-    // Combined mode: `secrt gen send ...` → delegate to run_send
-    if pa.args.iter().any(|a| a == "send") {
+    if combined("send") || combined("gen") {
         let new_args: Vec<String> = args
             .iter()
             .map(|a| {
@@ -37,6 +32,18 @@ pub fn run_gen(args: &[String], deps: &mut Deps) -> i32 {
         return crate::send::run_send(&new_args, deps);
     }
 
+    let pa = match parse_flags("gen", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_gen_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+
     let count = if pa.gen_count == 0 { 1 } else { pa.gen_count } as usize;
 
     // Generate passwords
@@ -45,7 +52,7 @@ pub fn run_gen(args: &[String], deps: &mut Deps) -> i32 {
         match generate_password_from_args(&pa, &*deps.rand_bytes) {
             Ok(pw) => passwords.push(pw),
             Err(e) => {
-                write_error(&mut deps.stderr, pa.json, (deps.is_tty)(), &e);
+                write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
                 return 2;
             }
         }
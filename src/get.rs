@@ -1,29 +1,60 @@
 use std::fs;
 use std::io::Write;
-
-use crate::cli::{parse_flags, print_get_help, resolve_globals, CliError, Deps};
-use crate::color::{color_func, DIM, LABEL, SUCCESS, WARN};
+use std::time::Duration;
+
+use crate::altscreen::{SignalGuard, ALT_SCREEN_OFF, ALT_SCREEN_ON, CLEAR_SCREEN};
+use crate::batch::run_get_batch;
+use crate::cli::{
+    format_ttl_seconds, parse_flags, print_get_help, resolve_decryption_passphrases,
+    resolve_globals, resolve_passphrase_default, CliError, Deps,
+};
+use crate::clipboard::clear_delay;
+use crate::color::{CHECK, DASH, DIM, LABEL, LOCK, SUCCESS, WARN};
 use crate::envelope::{self, EnvelopeError, OpenParams};
+use crate::exec::{parse_dotenv, secret_vars, DEFAULT_VAR};
 use crate::fileutil::{extract_file_hint, resolve_output_path};
-use crate::passphrase::{resolve_passphrase, write_error};
+use crate::format::{self, is_var_name, local_time, Format, GET_FIELDS};
+use crate::passphrase::{has_explicit_passphrase, resolve_passphrase, write_error};
+use crate::sanitize::{for_terminal, has_controls};
 
 pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
-    let mut pa = match parse_flags(args) {
+    let mut pa = match parse_flags("get", args) {
         Ok(pa) => pa,
         Err(CliError::ShowHelp) => {
             print_get_help(deps);
             return 0;
         }
         Err(CliError::Error(e)) => {
-            write_error(&mut deps.stderr, false, (deps.is_tty)(), &e);
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
             return 2;
         }
     };
-    resolve_globals(&mut pa, deps);
+    if let Err(e) = resolve_globals(&mut pa, deps)
+        .and_then(|_| resolve_passphrase_default(&mut pa))
+        .and_then(|_| resolve_decryption_passphrases(&mut pa))
+    {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    if !pa.batch.is_empty() {
+        return run_get_batch(&pa, deps);
+    }
+    if !pa.output_dir.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "--output-dir requires --batch",
+        );
+        return 2;
+    }
 
     if pa.args.is_empty() {
         write_error(
             &mut deps.stderr,
+            &deps.style,
             pa.json,
             (deps.is_tty)(),
             "share URL is required",
@@ -33,12 +64,56 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
 
     let share_url = &pa.args[0];
 
+    let format = match format::from_args(&pa, GET_FIELDS, "SECRT_SECRET") {
+        Ok(f) => f,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+
+    // Claiming burns the secret, so check --exec and --copy first
+    let exec_check = if pa.exec {
+        if pa.args.len() < 2 {
+            Err("--exec requires a command after --".to_string())
+        } else if !pa.env.is_empty() && !is_var_name(&pa.env) {
+            Err(format!("invalid variable name {:?}", pa.env))
+        } else {
+            Ok(())
+        }
+    } else if !pa.env.is_empty() {
+        Err("--env requires --exec".to_string())
+    } else if pa.dotenv {
+        Err("--dotenv requires --exec".to_string())
+    } else {
+        Ok(())
+    };
+    if let Err(e) = exec_check {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+    if let Err(e) = reveal_delay(&pa.reveal_for) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+    if pa.copy {
+        let check = clear_delay(&pa.clipboard_clear).and_then(|_| {
+            (deps.clipboard_available)(&pa.clipboard_tool)
+                .map_err(|e| format!("clipboard unavailable: {}", e))
+        });
+        if let Err(e) = check {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    }
+
     // Parse URL to extract ID and url_key
     let (id, url_key) = match envelope::parse_share_url(share_url) {
         Ok(r) => r,
         Err(e) => {
             write_error(
                 &mut deps.stderr,
+                &deps.style,
                 pa.json,
                 (deps.is_tty)(),
                 &format!("invalid share URL: {}", e),
@@ -74,6 +149,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
         Err(e) => {
             write_error(
                 &mut deps.stderr,
+                &deps.style,
                 pa.json,
                 (deps.is_tty)(),
                 &format!("key derivation failed: {}", e),
@@ -90,6 +166,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
         Err(e) => {
             write_error(
                 &mut deps.stderr,
+                &deps.style,
                 pa.json,
                 (deps.is_tty)(),
                 &format!("get failed: {}", e),
@@ -102,15 +179,14 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
     let needs_pass = envelope::requires_passphrase(&resp.envelope);
 
     // Determine if an explicit passphrase flag was set
-    let explicit_flag =
-        pa.passphrase_prompt || !pa.passphrase_env.is_empty() || !pa.passphrase_file.is_empty();
+    let explicit_flag = has_explicit_passphrase(&pa);
 
     // --- Phase A: Explicit flag set → use only that passphrase ---
     if explicit_flag {
         let mut passphrase = match resolve_passphrase(&pa, deps) {
             Ok(p) => p,
             Err(e) => {
-                write_error(&mut deps.stderr, pa.json, is_tty, &e);
+                write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
                 return 1;
             }
         };
@@ -124,15 +200,16 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
             }) {
                 Ok(p) => break p,
                 Err(EnvelopeError::DecryptionFailed) if can_retry => {
-                    let c = color_func(is_tty);
+                    let c = deps.style.color_func(is_tty);
                     let _ = writeln!(deps.stderr, "{}", c(WARN, "Wrong passphrase, try again."));
-                    let prompt_c = color_func(true);
+                    let prompt_c = deps.style.color_func(true);
                     let prompt = format!("{} ", prompt_c(LABEL, "Passphrase:"));
                     match (deps.read_pass)(&prompt, &mut deps.stderr) {
                         Ok(p) if !p.is_empty() => passphrase = p,
                         Ok(_) => {
                             write_error(
                                 &mut deps.stderr,
+                                &deps.style,
                                 pa.json,
                                 is_tty,
                                 "passphrase must not be empty",
@@ -142,6 +219,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                         Err(e) => {
                             write_error(
                                 &mut deps.stderr,
+                                &deps.style,
                                 pa.json,
                                 is_tty,
                                 &format!("read passphrase: {}", e),
@@ -151,13 +229,26 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                     }
                 }
                 Err(e) => {
-                    write_error(&mut deps.stderr, pa.json, is_tty, &e.to_string());
+                    write_error(
+                        &mut deps.stderr,
+                        &deps.style,
+                        pa.json,
+                        is_tty,
+                        &e.to_string(),
+                    );
                     return 1;
                 }
             }
         };
 
-        return output_plaintext(&plaintext, &pa, deps, &resp.expires_at, &resp.envelope);
+        return output_plaintext(
+            &plaintext,
+            &pa,
+            format.as_ref(),
+            deps,
+            &resp.expires_at,
+            &resp.envelope,
+        );
     }
 
     // --- Phase B: Try configured passphrases (default + decryption list) ---
@@ -184,6 +275,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                     return output_plaintext(
                         &plaintext,
                         &pa,
+                        format.as_ref(),
                         deps,
                         &resp.expires_at,
                         &resp.envelope,
@@ -193,7 +285,13 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                     // Fall through to candidates or prompt
                 }
                 Err(e) => {
-                    write_error(&mut deps.stderr, pa.json, is_tty, &e.to_string());
+                    write_error(
+                        &mut deps.stderr,
+                        &deps.style,
+                        pa.json,
+                        is_tty,
+                        &e.to_string(),
+                    );
                     return 1;
                 }
             }
@@ -210,6 +308,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                     return output_plaintext(
                         &plaintext,
                         &pa,
+                        format.as_ref(),
                         deps,
                         &resp.expires_at,
                         &resp.envelope,
@@ -217,7 +316,13 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                 }
                 Err(EnvelopeError::DecryptionFailed) => continue,
                 Err(e) => {
-                    write_error(&mut deps.stderr, pa.json, is_tty, &e.to_string());
+                    write_error(
+                        &mut deps.stderr,
+                        &deps.style,
+                        pa.json,
+                        is_tty,
+                        &e.to_string(),
+                    );
                     return 1;
                 }
             }
@@ -230,7 +335,13 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
         if !needs_pass && tried == 0 {
             // No passphrase needed and decryption failed with empty passphrase — this is
             // a genuine decryption error (wrong URL key), not a passphrase issue
-            write_error(&mut deps.stderr, pa.json, is_tty, "decryption failed");
+            write_error(
+                &mut deps.stderr,
+                &deps.style,
+                pa.json,
+                is_tty,
+                "decryption failed",
+            );
             return 1;
         }
 
@@ -238,20 +349,22 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
             if tried > 0 {
                 write_error(
                     &mut deps.stderr,
+                    &deps.style,
                     pa.json,
                     false,
                     &format!(
                         "this secret is passphrase-protected; tried {} configured passphrase(s) \
-                         but none matched. Use -p, --passphrase-env, or --passphrase-file",
+                         but none matched. Use -p, --passphrase-env, --passphrase-file, or --passphrase-cmd",
                         tried,
                     ),
                 );
             } else {
                 write_error(
                     &mut deps.stderr,
+                    &deps.style,
                     pa.json,
                     false,
-                    "this secret is passphrase-protected; use -p, --passphrase-env, or --passphrase-file",
+                    "this secret is passphrase-protected; use -p, --passphrase-env, --passphrase-file, or --passphrase-cmd",
                 );
             }
             return 1;
@@ -259,25 +372,26 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
 
         // TTY: show notice and prompt interactively
         if !pa.silent {
-            let c = color_func(true);
+            let c = deps.style.color_func(true);
             if tried > 0 {
                 let _ = writeln!(
                     deps.stderr,
                     "{} {}",
-                    c(WARN, "\u{26b7}"),
+                    c(WARN, deps.style.glyph(LOCK)),
                     c(
                         DIM,
                         &format!(
-                        "Passphrase-protected \u{2014} {} configured passphrase(s) didn't match",
-                        tried,
-                    )
+                            "Passphrase-protected {} {} configured passphrase(s) didn't match",
+                            deps.style.glyph(DASH),
+                            tried,
+                        )
                     )
                 );
             } else {
                 let _ = writeln!(
                     deps.stderr,
                     "{} {}",
-                    c(WARN, "\u{26b7}"),
+                    c(WARN, deps.style.glyph(LOCK)),
                     c(DIM, "This secret is passphrase-protected")
                 );
             }
@@ -285,13 +399,14 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
 
         // Interactive retry loop
         loop {
-            let c = color_func(true);
+            let c = deps.style.color_func(true);
             let prompt = format!("{} ", c(LABEL, "Passphrase:"));
             let passphrase = match (deps.read_pass)(&prompt, &mut deps.stderr) {
                 Ok(p) if !p.is_empty() => p,
                 Ok(_) => {
                     write_error(
                         &mut deps.stderr,
+                        &deps.style,
                         pa.json,
                         is_tty,
                         "passphrase must not be empty",
@@ -301,6 +416,7 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                 Err(e) => {
                     write_error(
                         &mut deps.stderr,
+                        &deps.style,
                         pa.json,
                         is_tty,
                         &format!("read passphrase: {}", e),
@@ -318,18 +434,25 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
                     return output_plaintext(
                         &plaintext,
                         &pa,
+                        format.as_ref(),
                         deps,
                         &resp.expires_at,
                         &resp.envelope,
                     )
                 }
                 Err(EnvelopeError::DecryptionFailed) => {
-                    let c = color_func(is_tty);
+                    let c = deps.style.color_func(is_tty);
                     let _ = writeln!(deps.stderr, "{}", c(WARN, "Wrong passphrase, try again."));
                     continue;
                 }
                 Err(e) => {
-                    write_error(&mut deps.stderr, pa.json, is_tty, &e.to_string());
+                    write_error(
+                        &mut deps.stderr,
+                        &deps.style,
+                        pa.json,
+                        is_tty,
+                        &e.to_string(),
+                    );
                     return 1;
                 }
             }
@@ -340,58 +463,114 @@ pub fn run_get(args: &[String], deps: &mut Deps) -> i32 {
 /// Output decrypted plaintext to stdout in the appropriate format.
 ///
 /// Decision matrix:
-/// 1. `--json`             → JSON output (with file hint fields and base64 for binary)
-/// 2. `--output -`         → raw bytes to stdout (no label)
+/// 0. `--exec`             → run the command with the secret in its environment
+///    `--copy`             → clipboard, with a summary (or JSON) instead of the secret;
+///                           if that fails, revealed on a TTY and an error otherwise
+/// 1. `--json`/`--format`  → JSON, YAML, env or shell (base64 for binary), or
+///    `--template`            the template filled in
+/// 2. `--output -`         → raw bytes to stdout (no label; escaped on a TTY)
 /// 3. `--output <path>`    → write file, show success on stderr
 /// 4. file hint + TTY      → auto-save to `./hint.filename`, show success on stderr
 /// 5. piped stdout         → raw bytes to stdout
-/// 6. no hint + TTY        → "Secret:" label + text
+/// 6. no hint + TTY        → "Secret:" label + text, on the alternate screen
+///    with `--reveal`
 fn output_plaintext(
     plaintext: &[u8],
     pa: &crate::cli::ParsedArgs,
+    format: Option<&Format>,
     deps: &mut Deps,
     expires_at: &str,
     envelope: &serde_json::Value,
 ) -> i32 {
     let file_hint = extract_file_hint(envelope);
 
-    // 1. JSON mode
-    if pa.json {
-        let mut out = serde_json::Map::new();
+    // 0. --exec → the command's environment; --copy → clipboard instead of stdout
+    if pa.exec {
+        return exec_plaintext(plaintext, pa, deps);
+    }
+    if pa.copy {
+        match copy_plaintext(plaintext, pa, deps, expires_at) {
+            Ok(code) => return code,
+            Err(e) if !(deps.is_stdout_tty)() => {
+                // Never send the secret down a pipe the user didn't ask for
+                write_error(
+                    &mut deps.stderr,
+                    &deps.style,
+                    pa.json,
+                    (deps.is_tty)(),
+                    &format!(
+                        "could not copy to clipboard ({}); the secret has been claimed and is gone",
+                        e
+                    ),
+                );
+                return 1;
+            }
+            Err(e) => {
+                // The secret is already burned; showing it beats losing it
+                let _ = writeln!(
+                    deps.stderr,
+                    "warning: could not copy to clipboard ({}); showing it instead",
+                    e
+                );
+                if let Ok(text) = std::str::from_utf8(plaintext) {
+                    return reveal_plaintext(text, pa, deps);
+                }
+            }
+        }
+    }
+
+    // 1. --json, --format or --template
+    if let Some(format) = format {
+        let mut record = serde_json::Map::new();
 
         // Use base64 for binary data, plain string for valid UTF-8
         if let Some(ref fh) = file_hint {
-            out.insert("type".into(), serde_json::json!(fh.mime.clone()));
-            out.insert("filename".into(), serde_json::json!(fh.filename.clone()));
-            out.insert("mime".into(), serde_json::json!(fh.mime.clone()));
+            record.insert("type".into(), serde_json::json!(fh.mime.clone()));
+            record.insert("filename".into(), serde_json::json!(fh.filename.clone()));
+            record.insert("mime".into(), serde_json::json!(fh.mime.clone()));
         }
 
-        match std::str::from_utf8(plaintext) {
+        let value = match std::str::from_utf8(plaintext) {
             Ok(text) => {
-                out.insert("plaintext".into(), serde_json::json!(text));
+                record.insert("plaintext".into(), serde_json::json!(text));
+                text.to_string()
             }
             Err(_) => {
                 use base64::engine::general_purpose::STANDARD;
                 use base64::Engine;
-                out.insert(
-                    "plaintext_base64".into(),
-                    serde_json::json!(STANDARD.encode(plaintext)),
-                );
+                let encoded = STANDARD.encode(plaintext);
+                record.insert("plaintext_base64".into(), serde_json::json!(encoded));
+                if !matches!(format, Format::Json | Format::Yaml) {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "warning: the secret is binary; writing it base64-encoded"
+                    );
+                }
+                encoded
             }
-        }
+        };
 
-        out.insert("expires_at".into(), serde_json::json!(expires_at));
-        let _ = writeln!(
-            deps.stdout,
-            "{}",
-            serde_json::to_string(&serde_json::Value::Object(out)).unwrap()
-        );
+        record.insert("expires_at".into(), serde_json::json!(expires_at));
+        let field = |name: &str| match name {
+            "value" => Some(value.clone()),
+            "expires_local" => Some(local_time(expires_at)),
+            _ => record.get(name).and_then(|v| v.as_str()).map(String::from),
+        };
+        let out = format::render(format, &record, &value, &field);
+        let out = terminal_safe(&out, pa, deps);
+        let _ = write!(deps.stdout, "{}", out);
         return 0;
     }
 
-    // 2. --output - → raw bytes to stdout
+    // 2. --output - → raw bytes to stdout (escaped on a terminal)
     if pa.output == "-" {
-        let _ = deps.stdout.write_all(plaintext);
+        if pa.raw || !(deps.is_stdout_tty)() {
+            let _ = deps.stdout.write_all(plaintext);
+        } else {
+            let text = String::from_utf8_lossy(plaintext);
+            let text = terminal_safe(&text, pa, deps);
+            let _ = deps.stdout.write_all(text.as_bytes());
+        }
         return 0;
     }
 
@@ -406,7 +585,7 @@ fn output_plaintext(
             let path = match resolve_output_path(&fh.filename) {
                 Ok(p) => p,
                 Err(e) => {
-                    let _ = writeln!(deps.stderr, "error: {}", e);
+                    write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
                     return 1;
                 }
             };
@@ -422,9 +601,11 @@ fn output_plaintext(
 
     // 6. No hint, TTY → text output (with binary detection)
     match std::str::from_utf8(plaintext) {
+        Ok(text) if pa.reveal => return reveal_plaintext(text, pa, deps),
         Ok(text) => {
+            let text = terminal_safe(text, pa, deps);
             if !pa.silent {
-                let c = color_func(true);
+                let c = deps.style.color_func(true);
                 let _ = writeln!(deps.stderr, "{}", c(LABEL, "Secret:"));
             }
             let _ = deps.stdout.write_all(text.as_bytes());
@@ -438,7 +619,7 @@ fn output_plaintext(
             let path = match resolve_output_path(filename) {
                 Ok(p) => p,
                 Err(e) => {
-                    let _ = writeln!(deps.stderr, "error: {}", e);
+                    write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
                     return 1;
                 }
             };
@@ -448,6 +629,160 @@ fn output_plaintext(
     0
 }
 
+/// Copy plaintext to the clipboard and schedule it to be cleared.
+fn copy_plaintext(
+    plaintext: &[u8],
+    pa: &crate::cli::ParsedArgs,
+    deps: &mut Deps,
+    expires_at: &str,
+) -> Result<i32, String> {
+    (deps.copy_to_clipboard)(&pa.clipboard_tool, plaintext)?;
+    let delay = clear_delay(&pa.clipboard_clear)?;
+    if let Err(e) = (deps.schedule_clipboard_clear)(&pa.clipboard_tool, plaintext, delay) {
+        let _ = writeln!(
+            deps.stderr,
+            "warning: the clipboard will not be cleared automatically: {}",
+            e
+        );
+    }
+
+    if pa.json {
+        let out = serde_json::json!({
+            "copied": true,
+            "clear_after_seconds": delay.as_secs(),
+            "expires_at": expires_at,
+        });
+        let _ = writeln!(deps.stdout, "{}", out);
+    } else if !pa.silent {
+        let c = deps.style.color_func((deps.is_tty)());
+        let _ = writeln!(
+            deps.stderr,
+            "{} Copied to clipboard {}",
+            c(SUCCESS, deps.style.glyph(CHECK)),
+            c(
+                DIM,
+                &format!("(clears in {})", format_ttl_seconds(delay.as_secs() as i64))
+            ),
+        );
+    }
+    Ok(0)
+}
+
+/// Run the `--exec` command with the secret in its environment and return
+/// its exit code.
+fn exec_plaintext(plaintext: &[u8], pa: &crate::cli::ParsedArgs, deps: &mut Deps) -> i32 {
+    let is_tty = (deps.is_tty)();
+    let Ok(secret) = std::str::from_utf8(plaintext) else {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            false,
+            is_tty,
+            "the secret is binary and cannot be passed in an environment variable",
+        );
+        return 1;
+    };
+    let vars = match secret_vars(secret, &pa.env, pa.dotenv) {
+        Ok(vars) => vars,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, false, is_tty, &e);
+            return 1;
+        }
+    };
+    if !pa.dotenv && pa.env.is_empty() && !pa.silent && parse_dotenv(secret).is_some() {
+        let _ = writeln!(
+            deps.stderr,
+            "hint: the secret is made of KEY=value lines; it is passed whole as {} \
+             (use --dotenv to set one variable per key)",
+            DEFAULT_VAR
+        );
+    }
+    let _ = deps.stdout.flush();
+    match (deps.run_command)(&pa.args[1..], &vars) {
+        Ok(code) => code,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, false, is_tty, &e);
+            1
+        }
+    }
+}
+
+/// Show a text secret on the terminal's alternate screen until a key is
+/// pressed or `--reveal-for` runs out, then switch back so the secret is
+/// gone from the screen and never reaches the scrollback. An interrupt
+/// while the secret is shown also clears it and switches back.
+fn reveal_plaintext(text: &str, pa: &crate::cli::ParsedArgs, deps: &mut Deps) -> i32 {
+    let text = terminal_safe(text, pa, deps);
+    let c = deps.style.color_func(true);
+    let _guard = SignalGuard::install();
+    let _ = write!(deps.stdout, "{}{}", ALT_SCREEN_ON, CLEAR_SCREEN);
+    if !pa.silent {
+        let _ = writeln!(deps.stdout, "{}", c(LABEL, "Secret:"));
+    }
+    let _ = deps.stdout.write_all(text.as_bytes());
+    if !text.ends_with('\n') {
+        let _ = writeln!(deps.stdout);
+    }
+    let _ = writeln!(deps.stdout);
+    let _ = deps.stdout.flush();
+
+    match reveal_delay(&pa.reveal_for).ok().flatten() {
+        Some(delay) => {
+            for left in (1..=delay.as_secs()).rev() {
+                let msg = format!("Hiding in {}", format_ttl_seconds(left as i64));
+                let _ = write!(deps.stderr, "\r\x1b[K{}", c(DIM, &msg));
+                let _ = deps.stderr.flush();
+                (deps.sleep)(Duration::from_secs(1));
+            }
+        }
+        None => {
+            let prompt = c(DIM, "Press any key to hide");
+            let _ = (deps.read_key)(&prompt, &mut deps.stderr);
+        }
+    }
+
+    let _ = write!(deps.stdout, "{}{}", CLEAR_SCREEN, ALT_SCREEN_OFF);
+    let _ = deps.stdout.flush();
+    if !pa.silent {
+        let _ = writeln!(
+            deps.stderr,
+            "{} Secret hidden",
+            c(SUCCESS, deps.style.glyph(CHECK))
+        );
+    }
+    0
+}
+
+/// Secret text about to be shown on a terminal, with control characters
+/// escaped unless `--raw` was given.
+fn terminal_safe<'a>(
+    text: &'a str,
+    pa: &crate::cli::ParsedArgs,
+    deps: &mut Deps,
+) -> std::borrow::Cow<'a, str> {
+    if pa.raw || !(deps.is_stdout_tty)() || !has_controls(text) {
+        return text.into();
+    }
+    if !pa.silent {
+        let _ = writeln!(
+            deps.stderr,
+            "warning: the secret contains control characters, shown escaped; use --raw to print them as-is"
+        );
+    }
+    for_terminal(text)
+}
+
+/// How long `--reveal-for` (or `reveal_for`) shows a secret; None waits for
+/// a key.
+fn reveal_delay(setting: &str) -> Result<Option<Duration>, String> {
+    if setting.is_empty() {
+        return Ok(None);
+    }
+    envelope::parse_ttl(setting)
+        .map(|s| Some(Duration::from_secs(s as u64)))
+        .map_err(|e| format!("invalid --reveal-for {:?}: {}", setting, e))
+}
+
 /// Write plaintext to a file and show a success message on stderr.
 fn write_file_output(
     path: &str,
@@ -462,17 +797,17 @@ fn write_file_output(
     }
 
     if !pa.silent {
-        let c = color_func((deps.is_tty)());
+        let c = deps.style.color_func((deps.is_tty)());
         let size = plaintext.len();
         let detail = match mime {
-            Some(m) => format!("{}, {} bytes", m, size),
+            Some(m) => format!("{}, {} bytes", for_terminal(m), size),
             None => format!("{} bytes", size),
         };
         let _ = writeln!(
             deps.stderr,
             "{} Saved to {} ({})",
-            c(SUCCESS, "\u{2713}"),
-            path,
+            c(SUCCESS, deps.style.glyph(CHECK)),
+            for_terminal(path),
             c(DIM, &detail),
         );
     }
@@ -0,0 +1,796 @@
+//! Help pages as data. `secrt help <command>` renders them for the terminal
+//! and `secrt man` renders them as roff, and a command's option sections
+//! come from its flag table in `flags`, so the three can't disagree.
+//!
+//! Text in pages uses a small inline markup: `{c:...}` for commands,
+//! `{o:...}` for options, `{a:...}` for arguments and `{d:...}` for dimmed
+//! text such as example comments.
+
+use std::io::Write;
+
+use crate::cli::{Deps, TOP_LEVEL_COMMANDS};
+use crate::color::{ColorFn, Style, ARG, CHEVRON, CMD, DASH, DIM, HEADING, OPT};
+use crate::config::SETTINGS;
+use crate::flags::{self, Flag};
+
+pub enum Body {
+    /// Prose; man pages refill it.
+    Text(&'static [&'static str]),
+    /// Lines shown as written, such as examples.
+    Lines(&'static [&'static str]),
+    /// Aligned name and description rows.
+    Rows(&'static [(&'static str, &'static str)]),
+    /// Option rows for a command without a flag table.
+    Options(&'static [Flag]),
+    /// The config keys from `config::SETTINGS`.
+    Settings,
+}
+
+pub struct Section {
+    /// Empty to continue the previous section after a blank line.
+    pub heading: &'static str,
+    pub body: Body,
+}
+
+pub struct Page {
+    /// None for the top-level page.
+    pub command: Option<&'static str>,
+    pub about: &'static str,
+    pub usage: &'static [&'static str],
+    /// Shown after the flag table's sections, if the command has one.
+    pub sections: &'static [Section],
+}
+
+impl Page {
+    /// The option sections from the command's flag table.
+    pub fn flag_sections(&self) -> &'static [(&'static str, &'static [Flag])] {
+        match self.command {
+            Some(name) => flags::COMMANDS
+                .iter()
+                .find(|c| c.name == name)
+                .map_or(&[], |c| c.sections),
+            None => &[],
+        }
+    }
+
+    pub fn section(&self, heading: &str) -> Option<&'static Section> {
+        self.sections.iter().find(|s| s.heading == heading)
+    }
+}
+
+const fn text(heading: &'static str, lines: &'static [&'static str]) -> Section {
+    Section {
+        heading,
+        body: Body::Text(lines),
+    }
+}
+
+const fn lines(heading: &'static str, lines: &'static [&'static str]) -> Section {
+    Section {
+        heading,
+        body: Body::Lines(lines),
+    }
+}
+
+const fn rows(heading: &'static str, rows: &'static [(&'static str, &'static str)]) -> Section {
+    Section {
+        heading,
+        body: Body::Rows(rows),
+    }
+}
+
+pub const MAIN: Page = Page {
+    command: None,
+    about: "one-time secret sharing",
+    usage: &["{c:secrt} {c:<command>} {a:[options]}"],
+    sections: &[
+        rows("COMMANDS", TOP_LEVEL_COMMANDS),
+        Section {
+            heading: "GLOBAL OPTIONS",
+            body: Body::Options(flags::MAIN),
+        },
+        lines(
+            "EXAMPLES",
+            &[
+                "{d:#} Share a secret from stdin:",
+                "echo \"pw123\" | {c:secrt} {c:send}",
+                "",
+                "{d:#} Generate and share a 32-char password (1h TTL):",
+                "{c:secrt} {c:send gen} {o:-L} 32 {o:--ttl} 1h",
+                "",
+                "{d:#} Retrieve a secret:",
+                "{c:secrt get} https://secrt.ca/s/abc#key",
+            ],
+        ),
+        rows(
+            "ENVIRONMENT",
+            &[
+                ("SECRET_BASE_URL", "Server URL (overrides base_url)"),
+                ("SECRET_API_KEY", "API key (overrides api_key)"),
+                ("SECRT_PROFILE", "Config profile to use"),
+                (
+                    "SECRT_MASTER_PASSPHRASE",
+                    "Master passphrase for a locked config",
+                ),
+                (
+                    "SECRT_SYSTEM_CONFIG",
+                    "System config path (default: /etc/secrt/config.toml)",
+                ),
+                ("XDG_CONFIG_HOME", "Parent directory of secrt/config.toml"),
+                (
+                    "NO_COLOR",
+                    "Turn colors off (unless --color or color is set)",
+                ),
+                (
+                    "CLICOLOR_FORCE",
+                    "Use colors even when not writing to a terminal",
+                ),
+            ],
+        ),
+        rows(
+            "EXIT STATUS",
+            &[
+                ("0", "success"),
+                (
+                    "1",
+                    "the operation failed (network, server or decryption error)",
+                ),
+                (
+                    "2",
+                    "usage error (unknown flag, bad value or missing argument)",
+                ),
+            ],
+        ),
+    ],
+};
+
+pub const SEND: Page = Page {
+    command: Some("send"),
+    about: "Encrypt and upload a secret",
+    usage: &["{c:secrt} {c:send} {a:[options]}"],
+    sections: &[
+        text(
+            "INPUT",
+            &[
+                "Interactive: single-line hidden input (like a password).",
+                "Use {o:-m} for multi-line input, {o:--text}, {o:-f/--file} or {o:--from-clipboard}",
+                "for alternatives.",
+                "Use {c:gen} or {c:generate} to generate and share a random password.",
+                "Set show_input = true in config to show input by default.",
+            ],
+        ),
+        text(
+            "BATCH",
+            &[
+                "{o:--batch} reads rows with recipient, value, and optional ttl and label",
+                "columns (CSV with a header, or NDJSON). A value of \"gen\" generates a",
+                "password. Each recipient gets a distinct link; failed rows are reported",
+                "and the rest are still sent.",
+            ],
+        ),
+        text(
+            "OUTPUT",
+            &[
+                "{o:--format} json or yaml prints id, share_url, share_link and expires_at;",
+                "env prints SECRT_LINK=<link> and shell an export line ({o:--name} renames",
+                "the variable). {o:--template} fields: link, id, share_url, expires_at,",
+                "expires_local and password.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "echo \"secret\" | {c:secrt} {c:send}",
+                "{c:secrt} {c:send} {o:--text} \"my secret\" {o:--ttl} 5m",
+                "{c:secrt} {c:send gen} {o:-L} 32 {o:--ttl} 1h",
+                "{c:secrt} {c:send} {o:--batch} team.csv {o:--results} links.csv",
+                "{c:secrt} {c:send} {o:--template} '{{link}} (expires {{expires_local}})'",
+            ],
+        ),
+    ],
+};
+
+pub const GET: Page = Page {
+    command: Some("get"),
+    about: "Retrieve and decrypt a secret",
+    usage: &[
+        "{c:secrt} {c:get} {a:<share-url>} {a:[options]}",
+        "{c:secrt} {c:get} {a:<share-url>} {o:--exec} {a:[--env NAME | --dotenv]} {o:--} {a:<command> [args...]}",
+    ],
+    sections: &[
+        text(
+            "CLIPBOARD",
+            &[
+                "{o:--copy} puts the secret on the clipboard instead of printing it, and",
+                "clears it after clipboard_clear (default: 45s) unless something else",
+                "was copied since. The tool is wl-copy, xclip or xsel depending on the",
+                "session, or whichever clipboard_tool names.",
+            ],
+        ),
+        text(
+            "REVEAL",
+            &[
+                "{o:--reveal} shows a text secret on the terminal's alternate screen and",
+                "hides it again when a key is pressed, or after {o:--reveal-for}, so it",
+                "isn't left in the scrollback. Set reveal = true (and reveal_for) in",
+                "config to make it the default when printing to a terminal.",
+            ],
+        ),
+        text(
+            "EXEC",
+            &[
+                "{o:--exec} runs the command with the secret in its environment and exits",
+                "with the command's status; nothing is written to disk or stdout. The",
+                "secret goes in the {o:--env} variable (default: SECRT_SECRET). With",
+                "{o:--dotenv}, KEY=value lines set one variable per key; names such as",
+                "PATH, LD_PRELOAD or BASH_ENV that could run other code are refused.",
+            ],
+        ),
+        text(
+            "OUTPUT",
+            &[
+                "{o:--format} env prints a line for a dotenv file or {c:eval}, shell an",
+                "export line; the variable is SECRT_SECRET unless {o:--name} says otherwise.",
+                "{o:--format} json or yaml adds the file name, type and expiry.",
+                "{o:--template} fields: value, filename, mime, expires_at and expires_local.",
+                "On a terminal, control characters in the secret are shown escaped (^[ for",
+                "ESC, <U+202E> for a bidi override) so it can't rewrite the screen or",
+                "disguise itself; {o:--raw} prints them as-is. Piped output is never changed.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:get} https://secrt.ca/s/abc#key",
+                "",
+                "{d:#} The {c:get} subcommand is optional:",
+                "{c:secrt} https://secrt.ca/s/abc#key {o:-o} mysecret.txt",
+                "",
+                "{d:#} One link per line; each result is reported separately:",
+                "{c:secrt} {c:get} {o:--batch} links.txt {o:--output-dir} ./creds",
+                "",
+                "{d:#} Paste it into a password prompt; cleared after 45s:",
+                "{c:secrt} {c:get} https://secrt.ca/s/abc#key {o:--copy}",
+                "",
+                "{d:#} Show it for 30 seconds, then clear it from the screen:",
+                "{c:secrt} {c:get} https://secrt.ca/s/abc#key {o:--reveal-for} 30s",
+                "",
+                "{d:#} Append to a dotenv file:",
+                "{c:secrt} {c:get} https://secrt.ca/s/abc#key {o:--format} env {o:--name} DB_PASS >> .env",
+                "",
+                "{d:#} Hand the secret to a command without saving it:",
+                "{c:secrt} {c:get} https://secrt.ca/s/abc#key {o:--exec} {o:--env} PGPASSWORD {o:--} psql -h db",
+            ],
+        ),
+    ],
+};
+
+pub const BURN: Page = Page {
+    command: Some("burn"),
+    about: "Destroy a secret (requires API key)",
+    usage: &[
+        "{c:secrt} {c:burn} {a:<id-or-url>} {a:[options]}",
+        "{c:secrt} {c:burn} {a:<selector>...} {a:[options]}",
+    ],
+    sections: &[
+        text(
+            "",
+            &[
+                "History selectors read the local send history (history = true) and can",
+                "be combined. Bulk burns list the targets and ask for confirmation; pass",
+                "{o:--yes} when not on a terminal. Exits 1 if any burn fails.",
+                "",
+                "Bulk burns only touch secrets on the active server, since the API key",
+                "belongs to it; pick another with {o:--base-url} or {o:--profile}.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:burn} test-id {o:--api-key} sk_prefix.secret",
+                "{c:secrt} {c:burn} {o:--label} 'alice-*' {o:--sent-since} 30d",
+                "{c:cat} ids.txt | {c:secrt} {c:burn} {o:--from-file} - {o:--yes}",
+            ],
+        ),
+    ],
+};
+
+pub const LOGIN: Page = Page {
+    command: Some("login"),
+    about: "Verify an API key and store it in the OS keychain",
+    usage: &["{c:secrt} {c:login} {a:[options]}"],
+    sections: &[
+        text(
+            "DETAILS",
+            &[
+                "The key is checked against the server before it is saved, and",
+                "use_keychain is turned on in the config file if it isn't already.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:login}",
+                "{c:pass show secrt/work} | {c:secrt} {c:login} {o:--profile} work",
+            ],
+        ),
+    ],
+};
+
+pub const LOGOUT: Page = Page {
+    command: Some("logout"),
+    about: "Remove the stored API key from the OS keychain",
+    usage: &["{c:secrt} {c:logout} {a:[options]}"],
+    sections: &[],
+};
+
+pub const DOCTOR: Page = Page {
+    command: Some("doctor"),
+    about: "Check config, keychain and server connectivity",
+    usage: &["{c:secrt} {c:doctor} {a:[options]}"],
+    sections: &[
+        text(
+            "CHECKS",
+            &[
+                "Build features, each config file (existence, permissions, syntax),",
+                "credential helper commands, the OS keychain, DNS/TLS/connectivity to",
+                "base_url, whether the server accepts the API key, and clock skew",
+                "against the server's Date header.",
+            ],
+        ),
+        text(
+            "EXIT STATUS",
+            &["0 if no check failed (warnings are allowed); 1 otherwise."],
+        ),
+    ],
+};
+
+pub const INFO: Page = Page {
+    command: Some("info"),
+    about: "Show server limits and account quota",
+    usage: &["{c:secrt} {c:info} {a:[options]}"],
+    sections: &[
+        text(
+            "EXIT STATUS",
+            &["0 on success; 1 if the server is unreachable or rejects the API key."],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:info} {o:--base-url} https://secrt.internal",
+                "{c:secrt} {c:info} {o:--json} | jq .limits.authed",
+            ],
+        ),
+    ],
+};
+
+pub const LIST: Page = Page {
+    command: Some("list"),
+    about: "List secrets you have sent (local history)",
+    usage: &["{c:secrt} {c:list} {a:[options]}"],
+    sections: &[
+        text(
+            "HISTORY",
+            &[
+                "History is off by default. Set history = true in the config file to",
+                "record each {c:send} (ID, server, label, size, expiry). Decryption keys are",
+                "never stored. Entries are dropped 30 days after they expire.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:list} {o:--sent-since} 7d {o:--unexpired}",
+                "{c:secrt} {c:list} {o:--label} 'db-*' {o:--json}",
+            ],
+        ),
+    ],
+};
+
+pub const WATCH: Page = Page {
+    command: Some("watch"),
+    about: "Wait until a secret is claimed",
+    usage: &["{c:secrt} {c:watch} {a:<id-or-url>} {a:[options]}"],
+    sections: &[
+        rows(
+            "EXIT STATUS",
+            &[
+                ("0", "claimed"),
+                ("3", "burned before it was claimed"),
+                ("4", "expired without being claimed"),
+                ("5", "--timeout elapsed while still pending"),
+                ("1", "status could not be checked"),
+            ],
+        ),
+        text(
+            "",
+            &["Checking the status never consumes the secret. {c:send} {o:--wait} waits the same way."],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:watch} abc123 {o:--timeout} 1h && rotate-temp-credential",
+                "{c:echo $TOKEN} | {c:secrt} {c:send} {o:--wait}",
+            ],
+        ),
+    ],
+};
+
+pub const DEV_SERVER: Page = Page {
+    command: Some("dev-server"),
+    about: "Run a local in-memory server for testing",
+    usage: &["{c:secrt} {c:dev-server} {a:[options]}"],
+    sections: &[
+        text(
+            "NOTES",
+            &[
+                "Serves the secrt API over plain HTTP with in-memory storage. Secrets",
+                "are lost when it stops. Only included in builds with the {o:dev-server} feature.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:dev-server} {o:--listen} 127.0.0.1:0 {o:--json}",
+                "{c:secrt} {c:send} {o:--base-url} http://127.0.0.1:8787",
+            ],
+        ),
+    ],
+};
+
+pub const GEN: Page = Page {
+    command: Some("gen"),
+    about: "Generate a random password",
+    usage: &["{c:secrt} {c:gen} {a:[options]}"],
+    sections: &[
+        lines(
+            "CHARACTER SETS",
+            &[
+                "Lowercase: a-z  (always included)",
+                "Uppercase: A-Z",
+                "Digits:    0-9",
+                "Symbols:   !@*^_+-=?",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:gen}                 20-char password, all classes",
+                "{c:secrt} {c:gen} {o:-L} 32          32-char password",
+                "{c:secrt} {c:gen} {o:-S}              no symbols",
+                "{c:secrt} {c:gen} {o:-G}              grouped by char type",
+                "{c:secrt} {c:gen} {o:--count} 5    five passwords",
+            ],
+        ),
+        lines(
+            "COMBINED MODE",
+            &[
+                "Generate a password and immediately share it as a secret.",
+                "{c:secrt} {c:gen send} {o:--ttl} 1h",
+                "All {c:gen} and {c:send} options can be combined.",
+            ],
+        ),
+    ],
+};
+
+pub const CONFIG: Page = Page {
+    command: Some("config"),
+    about: "Show, edit and initialize the config",
+    usage: &["{c:secrt} {c:config} {a:[subcommand]} {a:[options]}"],
+    sections: &[
+        rows(
+            "SUBCOMMANDS",
+            &[
+                ("secrt config", "Show effective config and file path"),
+                ("secrt config init", "Create template config file"),
+                ("secrt config path", "Print config file path"),
+                (
+                    "secrt config set <key> <value>",
+                    "Validate and write a setting, keeping comments",
+                ),
+                (
+                    "secrt config get <key>",
+                    "Print a setting as written in the file",
+                ),
+                ("secrt config unset <key>", "Remove a setting from the file"),
+                (
+                    "secrt config lock",
+                    "Encrypt the file's secrets with a master passphrase",
+                ),
+                (
+                    "secrt config unlock",
+                    "Decrypt encrypted secrets back into plaintext",
+                ),
+                (
+                    "secrt config set-passphrase",
+                    "Store passphrase in OS keychain",
+                ),
+                (
+                    "secrt config delete-passphrase",
+                    "Remove passphrase from OS keychain",
+                ),
+                (
+                    "secrt config passphrases [list]",
+                    "Show keychain decryption passphrases (masked)",
+                ),
+                (
+                    "secrt config passphrases add",
+                    "Prompt for and append a decryption passphrase",
+                ),
+                (
+                    "secrt config passphrases remove <n>",
+                    "Remove decryption passphrase #n",
+                ),
+                (
+                    "secrt config passphrases promote <n>",
+                    "Make #n the default passphrase",
+                ),
+            ],
+        ),
+        Section {
+            heading: "OPTIONS",
+            body: Body::Options(flags::CONFIG),
+        },
+        text(
+            "CONFIG",
+            &[
+                "Settings are layered: /etc/secrt/config.toml, then ~/.config/secrt/config.toml,",
+                "then the nearest .secrt.toml above the working directory (no secrets,",
+                "commands, base_url or profiles).",
+                "api_key_command, passphrase_command and decryption_passphrases_command",
+                "read a secret from a command's stdout (checked before the keychain).",
+                "[profiles.<name>] tables override any key; select one with --profile,",
+                "SECRT_PROFILE or default_profile. get matches share URL hosts too.",
+                "config lock keeps secrets encrypted in the file; secrt prompts for the",
+                "master passphrase once per run, or reads SECRT_MASTER_PASSPHRASE.",
+                "color, plain and a [theme] table of SGR codes by color name (success,",
+                "error, url, ...) only apply at the top level, not in profiles.",
+                "Precedence: CLI flag {d:›} env var {d:›} config file {d:›} default.",
+            ],
+        ),
+        Section {
+            heading: "KEYS",
+            body: Body::Settings,
+        },
+    ],
+};
+
+pub const MAN: Page = Page {
+    command: Some("man"),
+    about: "Output a man page",
+    usage: &["{c:secrt} {c:man} {a:[command]}"],
+    sections: &[
+        text(
+            "DETAILS",
+            &[
+                "Writes secrt(1), or secrt-<command>(1) for a command, as roff to stdout.",
+                "The pages are built from the same tables as this help.",
+            ],
+        ),
+        lines(
+            "EXAMPLES",
+            &[
+                "{c:secrt} {c:man} {c:send} | man -l -",
+                "{c:secrt} {c:man} > ~/.local/share/man/man1/secrt.1",
+            ],
+        ),
+    ],
+};
+
+/// Every command page, in the order of the main help.
+pub const PAGES: &[&Page] = &[
+    &SEND,
+    &GET,
+    &BURN,
+    &INFO,
+    &LOGIN,
+    &LOGOUT,
+    &LIST,
+    &WATCH,
+    &DEV_SERVER,
+    &GEN,
+    &CONFIG,
+    &DOCTOR,
+    &MAN,
+];
+
+/// The page for `command`, accepting the `generate` alias.
+pub fn page(command: &str) -> Option<&'static Page> {
+    let command = if command == "generate" {
+        "gen"
+    } else {
+        command
+    };
+    PAGES.iter().copied().find(|p| p.command == Some(command))
+}
+
+/// Split marked-up text into (markup letter, content) pieces.
+pub fn spans(line: &str) -> Vec<(Option<char>, &str)> {
+    let mut out = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        let tag = rest[start + 1..].chars().next();
+        let body = &rest[start + 1..];
+        let end = body.find('}');
+        match (tag, end) {
+            (Some(t @ ('c' | 'o' | 'a' | 'd')), Some(end)) if body[1..].starts_with(':') => {
+                if start > 0 {
+                    out.push((None, &rest[..start]));
+                }
+                out.push((Some(t), &body[2..end]));
+                rest = &body[end + 1..];
+            }
+            _ => {
+                out.push((None, &rest[..start + 1]));
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    if !rest.is_empty() {
+        out.push((None, rest));
+    }
+    out
+}
+
+/// Write `page` to stderr, colored if stdout is a terminal.
+pub fn print(deps: &mut Deps, page: &Page) {
+    crate::cli::apply_help_style(deps);
+    let c = deps.style.color_func((deps.is_stdout_tty)());
+    write_page(&mut deps.stderr, &deps.style, &c, page);
+}
+
+fn write_page(w: &mut dyn Write, style: &Style, c: &ColorFn, page: &Page) {
+    let _ = match page.command {
+        Some(name) => writeln!(
+            w,
+            "{} {} {} {}\n",
+            c(CMD, "secrt"),
+            c(CMD, name),
+            style.glyph(DASH),
+            page.about
+        ),
+        None => writeln!(
+            w,
+            "{} {} {}\n",
+            c(CMD, "secrt"),
+            style.glyph(DASH),
+            page.about
+        ),
+    };
+    if !page.usage.is_empty() {
+        let _ = writeln!(w, "{}", c(HEADING, "USAGE"));
+        for line in page.usage {
+            let _ = writeln!(w, "  {}", markup(style, c, line));
+        }
+        let _ = writeln!(w);
+    }
+    let mut first = true;
+    for (heading, flags) in page.flag_sections() {
+        if !first {
+            let _ = writeln!(w);
+        }
+        first = false;
+        let _ = writeln!(w, "{}", c(HEADING, heading));
+        write_flag_rows(w, c, flags);
+    }
+    for section in page.sections {
+        if !first {
+            let _ = writeln!(w);
+        }
+        first = false;
+        if !section.heading.is_empty() {
+            let _ = writeln!(w, "{}", c(HEADING, section.heading));
+        }
+        match section.body {
+            Body::Text(lines) | Body::Lines(lines) => {
+                for line in lines {
+                    if line.is_empty() {
+                        let _ = writeln!(w);
+                    } else {
+                        let _ = writeln!(w, "  {}", markup(style, c, line));
+                    }
+                }
+            }
+            Body::Rows(rows) => write_cmd_rows(w, c, rows),
+            Body::Options(flags) => write_flag_rows(w, c, flags),
+            Body::Settings => write_cmd_rows(w, c, &settings()),
+        }
+    }
+}
+
+/// `config::SETTINGS` as name and description rows.
+pub fn settings() -> Vec<(&'static str, &'static str)> {
+    SETTINGS
+        .iter()
+        .map(|&(name, _, _, about)| (name, about))
+        .collect()
+}
+
+fn markup(style: &Style, c: &ColorFn, line: &str) -> String {
+    spans(line)
+        .into_iter()
+        .map(|(tag, s)| {
+            // The precedence arrows in page text have an ASCII form too
+            let s = s.replace('\u{203a}', style.glyph(CHEVRON));
+            match tag {
+                Some('c') => c(CMD, &s),
+                Some('o') => c(OPT, &s),
+                Some('a') => c(ARG, &s),
+                Some('d') => c(DIM, &s),
+                _ => s,
+            }
+        })
+        .collect()
+}
+
+/// Write auto-aligned option rows:  flag [arg]   description
+fn write_flag_rows(w: &mut dyn Write, c: &ColorFn, flags: &[Flag]) {
+    let labels: Vec<String> = flags.iter().map(|f| f.label()).collect();
+    let widths: Vec<usize> = flags
+        .iter()
+        .zip(&labels)
+        .map(|(f, label)| match f.placeholder() {
+            "" => label.len(),
+            arg => label.len() + 1 + arg.len(),
+        })
+        .collect();
+    let max = widths.iter().copied().max().unwrap_or(0);
+    for ((f, label), width) in flags.iter().zip(&labels).zip(&widths) {
+        let pad = max - width + 2;
+        match f.placeholder() {
+            "" => {
+                let _ = writeln!(w, "  {}{:pad$}{}", c(OPT, label), "", f.help);
+            }
+            arg => {
+                let _ = writeln!(
+                    w,
+                    "  {} {}{:pad$}{}",
+                    c(OPT, label),
+                    c(ARG, arg),
+                    "",
+                    f.help
+                );
+            }
+        }
+    }
+}
+
+/// Write auto-aligned command rows:  command   description
+fn write_cmd_rows(w: &mut dyn Write, c: &ColorFn, rows: &[(&str, &str)]) {
+    let max = rows.iter().map(|(cmd, _)| cmd.len()).max().unwrap_or(0);
+    for (cmd, desc) in rows {
+        let pad = max - cmd.len() + 2;
+        let _ = writeln!(w, "  {}{:pad$}{}", c(CMD, cmd), "", desc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_split_markup() {
+        assert_eq!(
+            spans("{c:secrt} x {o:--ttl} 1h"),
+            vec![
+                (Some('c'), "secrt"),
+                (None, " x "),
+                (Some('o'), "--ttl"),
+                (None, " 1h"),
+            ]
+        );
+        assert_eq!(spans("a {b} c"), vec![(None, "a {"), (None, "b} c")]);
+    }
+
+    #[test]
+    fn every_command_has_a_page() {
+        for &(name, _) in TOP_LEVEL_COMMANDS {
+            if !matches!(name, "version" | "help" | "completion") {
+                assert!(page(name).is_some(), "no help page for {}", name);
+            }
+        }
+        for cmd in flags::COMMANDS {
+            assert!(page(cmd.name).is_some(), "no help page for {}", cmd.name);
+        }
+    }
+}
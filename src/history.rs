@@ -0,0 +1,541 @@
+//! Local send history: an opt-in JSON Lines log in the config directory
+//! recording what `send` created, and the `list` command that reads it.
+//! Entries never contain the url_key, so the history alone cannot decrypt
+//! anything.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{format_bytes, parse_flags, print_list_help, resolve_globals, CliError, Deps};
+use crate::color::{Style, CHECK, DIM, HEADING, LOCK, SUCCESS};
+use crate::config::replace_private;
+use crate::envelope;
+use crate::passphrase::write_error;
+
+/// Expired entries older than this are dropped whenever the history is written.
+pub const HISTORY_RETENTION_SECS: i64 = 30 * 86400;
+
+/// One secret created by `send`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub base_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+    /// Plaintext size in bytes.
+    pub size: u64,
+    pub created_at: String,
+    pub expires_at: String,
+    #[serde(default)]
+    pub passphrase: bool,
+    /// Set when this CLI burned the secret.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub burned_at: String,
+}
+
+impl HistoryEntry {
+    /// Whether the server-side expiry has passed. Unparseable timestamps
+    /// count as not expired so they stay visible.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        parse_time(&self.expires_at).is_some_and(|t| t <= now)
+    }
+
+    /// Neither expired nor burned, so possibly still claimable.
+    pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.burned_at.is_empty() && !self.is_expired(now)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> &'static str {
+        if !self.burned_at.is_empty() {
+            "burned"
+        } else if self.is_expired(now) {
+            "expired"
+        } else {
+            "active"
+        }
+    }
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    s.parse::<DateTime<Utc>>().ok()
+}
+
+/// Current time as an RFC 3339 UTC timestamp, the format used for `created_at`.
+pub fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Returns the history file path, next to config.toml.
+pub fn history_path_with(getenv: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    crate::config::config_path_with(getenv)
+        .and_then(|p| p.parent().map(|d| d.join("history.jsonl")))
+}
+
+/// Read all entries. A missing file is an empty history; malformed lines
+/// are skipped.
+pub fn load_history(path: &Path) -> Vec<HistoryEntry> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// Rewrite the history file with the given entries (mode 0600 on Unix),
+/// replacing it atomically.
+pub fn save_history(path: &Path, entries: &[HistoryEntry]) -> Result<(), String> {
+    let mut data = String::new();
+    for e in entries {
+        let line = serde_json::to_string(e).map_err(|e| e.to_string())?;
+        data.push_str(&line);
+        data.push('\n');
+    }
+    replace_private(path, data.as_bytes())
+}
+
+/// Drop entries that expired more than `retention_secs` before `now`.
+pub fn prune_stale(
+    entries: Vec<HistoryEntry>,
+    now: DateTime<Utc>,
+    retention_secs: i64,
+) -> Vec<HistoryEntry> {
+    let cutoff = now - chrono::Duration::seconds(retention_secs);
+    entries
+        .into_iter()
+        .filter(|e| !e.is_expired(cutoff))
+        .collect()
+}
+
+/// Record a newly created secret, pruning stale entries on the way.
+pub fn append_history(path: &Path, entry: HistoryEntry) -> Result<(), String> {
+    let mut entries = prune_stale(load_history(path), Utc::now(), HISTORY_RETENTION_SECS);
+    entries.push(entry);
+    save_history(path, &entries)
+}
+
+/// Mark the given IDs as burned. Does nothing if none of them are recorded.
+pub fn mark_burned(path: &Path, ids: &[String]) -> Result<(), String> {
+    let mut entries = load_history(path);
+    let now = now_rfc3339();
+    let mut changed = false;
+    for e in entries.iter_mut() {
+        if e.burned_at.is_empty() && ids.contains(&e.id) {
+            e.burned_at = now.clone();
+            changed = true;
+        }
+    }
+    if changed {
+        save_history(path, &entries)?;
+    }
+    Ok(())
+}
+
+/// Parse a `--sent-since` duration (same grammar as TTLs) into seconds.
+pub fn parse_sent_since(s: &str) -> Result<Option<i64>, String> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    envelope::parse_ttl(s)
+        .map(Some)
+        .map_err(|_| format!("invalid --sent-since duration {:?}", s))
+}
+
+/// Shell-style glob match supporting `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Filters shared by `list` and `burn` when selecting from history.
+#[derive(Default)]
+pub struct HistoryFilter {
+    pub label: String,
+    pub sent_since_secs: Option<i64>,
+    /// Only entries that are neither expired nor burned.
+    pub unexpired: bool,
+    pub base_url: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, e: &HistoryEntry, now: DateTime<Utc>) -> bool {
+        if !self.label.is_empty() && !glob_match(&self.label, &e.label) {
+            return false;
+        }
+        if let Some(secs) = self.sent_since_secs {
+            let cutoff = now - chrono::Duration::seconds(secs);
+            match parse_time(&e.created_at) {
+                Some(t) if t >= cutoff => {}
+                _ => return false,
+            }
+        }
+        if self.unexpired && !e.is_live(now) {
+            return false;
+        }
+        if let Some(ref url) = self.base_url {
+            if e.base_url.trim_end_matches('/') != url.trim_end_matches('/') {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Format an RFC 3339 timestamp as local "YYYY-MM-DD HH:MM".
+fn format_local(iso: &str) -> String {
+    match parse_time(iso) {
+        Some(t) => t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        None => iso.to_string(),
+    }
+}
+
+pub fn run_list(args: &[String], deps: &mut Deps) -> i32 {
+    let mut pa = match parse_flags("list", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_list_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Err(e) = resolve_globals(&mut pa, deps) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    let sent_since_secs = match parse_sent_since(&pa.sent_since) {
+        Ok(s) => s,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+
+    let Some(path) = history_path_with(&*deps.getenv) else {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "could not determine config directory",
+        );
+        return 1;
+    };
+    let entries = load_history(&path);
+    let now = Utc::now();
+
+    if pa.prune {
+        let before = entries.len();
+        let kept = prune_stale(entries, now, 0);
+        let pruned = before - kept.len();
+        if pruned > 0 {
+            if let Err(e) = save_history(&path, &kept) {
+                write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+                return 1;
+            }
+        }
+        if pa.json {
+            let out = serde_json::json!({ "pruned": pruned, "remaining": kept.len() });
+            let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+        } else if !pa.silent {
+            let c = deps.style.color_func((deps.is_tty)());
+            let _ = writeln!(
+                deps.stderr,
+                "{} Pruned {} expired {}; {} remaining.",
+                c(SUCCESS, deps.style.glyph(CHECK)),
+                pruned,
+                if pruned == 1 { "entry" } else { "entries" },
+                kept.len()
+            );
+        }
+        return 0;
+    }
+
+    let filter = HistoryFilter {
+        label: pa.label.clone(),
+        sent_since_secs,
+        unexpired: pa.unexpired,
+        base_url: pa.base_url_from_flag.then(|| pa.base_url.clone()),
+    };
+    let mut matched: Vec<&HistoryEntry> =
+        entries.iter().filter(|e| filter.matches(e, now)).collect();
+    // Newest first
+    matched.reverse();
+
+    if pa.json {
+        let out: Vec<serde_json::Value> = matched
+            .iter()
+            .map(|e| {
+                let mut v = serde_json::to_value(e).unwrap_or_default();
+                v["status"] = serde_json::Value::String(e.status(now).into());
+                v
+            })
+            .collect();
+        let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+        return 0;
+    }
+
+    if matched.is_empty() {
+        if !pa.silent {
+            let msg = if entries.is_empty() && !pa.history {
+                "No send history. Set history = true in the config file to record sent secrets."
+            } else if entries.is_empty() {
+                "No send history yet."
+            } else {
+                "No secrets in history match the filters."
+            };
+            let _ = writeln!(deps.stderr, "{}", msg);
+        }
+        return 0;
+    }
+
+    let c = deps.style.color_func((deps.is_stdout_tty)());
+    write_table(&mut deps.stdout, &deps.style, &c, &matched, now);
+    0
+}
+
+/// Render entries as an aligned table. Cells are padded before colouring so
+/// escape sequences don't throw off the widths.
+fn write_table(
+    w: &mut dyn Write,
+    style: &Style,
+    c: &crate::color::ColorFn,
+    entries: &[&HistoryEntry],
+    now: DateTime<Utc>,
+) {
+    let header = ["ID", "LABEL", "SIZE", "SENT", "EXPIRES", "STATUS"];
+    let rows: Vec<[String; 6]> = entries
+        .iter()
+        .map(|e| {
+            let label = if e.passphrase {
+                format!("{} {}", e.label, style.glyph(LOCK))
+                    .trim_start()
+                    .to_string()
+            } else {
+                e.label.clone()
+            };
+            [
+                e.id.clone(),
+                label,
+                format_bytes(e.size as i64),
+                format_local(&e.created_at),
+                format_local(&e.expires_at),
+                e.status(now).to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+    let pad = |s: &str, i: usize| {
+        let n = widths[i].saturating_sub(s.chars().count());
+        format!("{}{}", s, " ".repeat(n))
+    };
+
+    let head: Vec<String> = header.iter().enumerate().map(|(i, h)| pad(h, i)).collect();
+    let _ = writeln!(w, "{}", c(HEADING, head.join("  ").trim_end()));
+    for row in &rows {
+        let status = if row[5] == "active" {
+            c(SUCCESS, &row[5])
+        } else {
+            c(DIM, &row[5])
+        };
+        let _ = writeln!(
+            w,
+            "{}  {}  {}  {}  {}  {}",
+            pad(&row[0], 0),
+            pad(&row[1], 1),
+            pad(&row[2], 2),
+            pad(&row[3], 3),
+            pad(&row[4], 4),
+            status
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, label: &str, created: &str, expires: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: id.into(),
+            base_url: "https://secrt.ca".into(),
+            label: label.into(),
+            size: 10,
+            created_at: created.into(),
+            expires_at: expires.into(),
+            passphrase: false,
+            burned_at: String::new(),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn glob_basics() {
+        assert!(glob_match("db-*", "db-prod"));
+        assert!(glob_match("*prod*", "db-prod-1"));
+        assert!(glob_match("d?-prod", "db-prod"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("db-*", "web-prod"));
+        assert!(!glob_match("d?", "d"));
+        assert!(!glob_match("", "x"));
+    }
+
+    #[test]
+    fn expired_status() {
+        let e = entry("a", "", "2026-01-01T00:00:00Z", "2026-01-02T00:00:00Z");
+        assert!(!e.is_expired(at("2026-01-01T12:00:00Z")));
+        assert!(e.is_expired(at("2026-01-02T00:00:00Z")));
+        assert_eq!(e.status(at("2026-01-03T00:00:00Z")), "expired");
+    }
+
+    #[test]
+    fn burned_status_wins() {
+        let mut e = entry("a", "", "2026-01-01T00:00:00Z", "2026-01-02T00:00:00Z");
+        let now = at("2026-01-01T12:00:00Z");
+        assert!(e.is_live(now));
+        e.burned_at = "2026-01-01T06:00:00Z".into();
+        assert!(!e.is_live(now));
+        assert_eq!(e.status(now), "burned");
+    }
+
+    #[test]
+    fn unparseable_expiry_is_not_expired() {
+        let e = entry("a", "", "", "garbage");
+        assert!(!e.is_expired(at("2030-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn prune_respects_retention() {
+        let old = entry("old", "", "2026-01-01T00:00:00Z", "2026-01-02T00:00:00Z");
+        let recent = entry("recent", "", "2026-02-01T00:00:00Z", "2026-02-02T00:00:00Z");
+        let now = at("2026-02-10T00:00:00Z");
+        let kept = prune_stale(vec![old.clone(), recent.clone()], now, 30 * 86400);
+        assert_eq!(kept, vec![recent.clone()]);
+        assert!(prune_stale(vec![old, recent], now, 0).is_empty());
+    }
+
+    #[test]
+    fn filter_by_label_age_and_expiry() {
+        let now = at("2026-02-10T00:00:00Z");
+        let a = entry(
+            "a",
+            "db-prod",
+            "2026-02-09T00:00:00Z",
+            "2026-02-11T00:00:00Z",
+        );
+        let b = entry("b", "web", "2026-01-01T00:00:00Z", "2026-01-02T00:00:00Z");
+
+        let f = HistoryFilter {
+            label: "db-*".into(),
+            ..Default::default()
+        };
+        assert!(f.matches(&a, now));
+        assert!(!f.matches(&b, now));
+
+        let f = HistoryFilter {
+            sent_since_secs: Some(7 * 86400),
+            ..Default::default()
+        };
+        assert!(f.matches(&a, now));
+        assert!(!f.matches(&b, now));
+
+        let f = HistoryFilter {
+            unexpired: true,
+            ..Default::default()
+        };
+        assert!(f.matches(&a, now));
+        assert!(!f.matches(&b, now));
+
+        let f = HistoryFilter {
+            base_url: Some("https://other.example/".into()),
+            ..Default::default()
+        };
+        assert!(!f.matches(&a, now));
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let dir = std::env::temp_dir().join("secrt_history_roundtrip");
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("history.jsonl");
+        let e = entry("x", "lbl", "2026-01-01T00:00:00Z", "2099-01-01T00:00:00Z");
+        save_history(&path, std::slice::from_ref(&e)).unwrap();
+        fs::write(
+            &path,
+            format!("{}not json\n", fs::read_to_string(&path).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(load_history(&path), vec![e]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            fs::remove_file(&path).unwrap();
+            save_history(&path, &[]).unwrap();
+            assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_makes_existing_file_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join("secrt_history_private");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.jsonl");
+        fs::write(&path, "").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        let e = entry("x", "lbl", "2026-01-01T00:00:00Z", "2099-01-01T00:00:00Z");
+        save_history(&path, std::slice::from_ref(&e)).unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(load_history(&path), vec![e]);
+        let names: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|d| d.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["history.jsonl"], "temporary file left behind");
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
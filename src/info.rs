@@ -0,0 +1,460 @@
+//! Server info: the `info` command, a short-lived on-disk cache of
+//! `GET /api/v1/info`, and the preflight checks `send` runs against the
+//! advertised limits.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{
+    format_bytes, format_limit, format_ttl_seconds, parse_flags, print_info_help, resolve_api_key,
+    resolve_globals, CliError, Deps,
+};
+use crate::client::{InfoResponse, InfoTier, SecretApi};
+use crate::color::{ColorFn, DIM, HEADING, OPT, SUCCESS, WARN};
+use crate::passphrase::write_error;
+
+pub fn run_info(args: &[String], deps: &mut Deps) -> i32 {
+    let mut pa = match parse_flags("info", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_info_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Err(e) = resolve_globals(&mut pa, deps).and_then(|_| resolve_api_key(&mut pa)) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    let client = (deps.make_api)(&pa.base_url, &pa.api_key);
+    let info = match client.info() {
+        Ok(i) => i,
+        Err(e) => {
+            write_error(
+                &mut deps.stderr,
+                &deps.style,
+                pa.json,
+                (deps.is_tty)(),
+                &format!("info failed: {}", e),
+            );
+            return 1;
+        }
+    };
+
+    let has_key = !pa.api_key.is_empty();
+    let key_rejected = has_key && !info.authenticated;
+
+    if pa.json {
+        let mut out = serde_json::to_value(&info).unwrap_or_default();
+        out["base_url"] = serde_json::Value::String(pa.base_url.clone());
+        let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+    } else {
+        let c = deps.style.color_func((deps.is_stdout_tty)());
+        write_info(&mut deps.stdout, &c, &pa.base_url, has_key, &info);
+    }
+
+    if key_rejected {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "the server did not accept the API key",
+        );
+        return 1;
+    }
+    0
+}
+
+/// Human-readable rendering of an info response.
+fn write_info(w: &mut dyn Write, c: &ColorFn, base_url: &str, has_key: bool, info: &InfoResponse) {
+    let row = |w: &mut dyn Write, key: &str, val: &str, detail: &str| {
+        if detail.is_empty() {
+            let _ = writeln!(w, "  {}: {}", c(OPT, key), val);
+        } else {
+            let _ = writeln!(w, "  {}: {} {}", c(OPT, key), val, c(DIM, detail));
+        }
+    };
+
+    let _ = writeln!(w, "{}", c(HEADING, "SERVER"));
+    row(w, "base_url", base_url, "");
+    let auth = if info.authenticated {
+        c(SUCCESS, "yes")
+    } else if has_key {
+        c(WARN, "no (API key rejected)")
+    } else {
+        "no (no API key)".to_string()
+    };
+    row(w, "authenticated", &auth, "");
+
+    let _ = writeln!(w, "\n{}", c(HEADING, "TTL"));
+    row(
+        w,
+        "default_ttl",
+        &format_ttl_seconds(info.ttl.default_seconds),
+        &format!("({}s)", info.ttl.default_seconds),
+    );
+    row(
+        w,
+        "max_ttl",
+        &format_ttl_seconds(info.ttl.max_seconds),
+        &format!("({}s)", info.ttl.max_seconds),
+    );
+
+    for (label, tier) in [
+        ("PUBLIC", &info.limits.public),
+        ("AUTHED", &info.limits.authed),
+    ] {
+        let _ = writeln!(w, "\n{}", c(HEADING, &format!("{} LIMITS", label)));
+        row(
+            w,
+            "max_envelope",
+            &format_bytes(tier.max_envelope_bytes),
+            "",
+        );
+        row(w, "max_secrets", &format_limit(tier.max_secrets), "");
+        row(w, "max_total", &format_bytes(tier.max_total_bytes), "");
+        row(
+            w,
+            "create_rate",
+            &format!(
+                "{}/s burst {}",
+                tier.rate.requests_per_second, tier.rate.burst
+            ),
+            "",
+        );
+    }
+
+    let _ = writeln!(w, "\n{}", c(HEADING, "CLAIM LIMITS"));
+    row(
+        w,
+        "claim_rate",
+        &format!(
+            "{}/s burst {}",
+            info.claim_rate.requests_per_second, info.claim_rate.burst
+        ),
+        "",
+    );
+
+    if let Some(ref quota) = info.quota {
+        let _ = writeln!(w, "\n{}", c(HEADING, "QUOTA"));
+        if let Some(n) = quota.secrets_remaining {
+            row(w, "secrets_remaining", &n.to_string(), "");
+        }
+        if let Some(b) = quota.bytes_remaining {
+            row(w, "bytes_remaining", &format_bytes(b), "");
+        }
+    }
+}
+
+/// How long a cached info response is considered fresh.
+pub const INFO_CACHE_TTL_SECS: u64 = 300;
+
+/// On-disk cache entry.
+#[derive(Serialize, Deserialize)]
+struct CachedInfo {
+    fetched_at: u64,
+    info: InfoResponse,
+}
+
+/// Returns the cache directory: $XDG_CACHE_HOME/secrt or ~/.cache/secrt.
+pub fn cache_dir_with(getenv: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let cache_dir = getenv("XDG_CACHE_HOME")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cache")));
+    cache_dir.map(|d| d.join("secrt"))
+}
+
+/// Cache file for a (base_url, api_key) pair. The key is hashed so the
+/// API key never touches disk.
+fn info_cache_path(
+    getenv: &dyn Fn(&str) -> Option<String>,
+    base_url: &str,
+    api_key: &str,
+) -> Option<PathBuf> {
+    let mut input = Vec::with_capacity(base_url.len() + api_key.len() + 1);
+    input.extend_from_slice(base_url.as_bytes());
+    input.push(0);
+    input.extend_from_slice(api_key.as_bytes());
+    let hash = digest(&SHA256, &input);
+    let hex: String = hash.as_ref()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    cache_dir_with(getenv).map(|d| d.join(format!("info-{}.json", hex)))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Fetch server info, serving from the cache when a fresh entry exists.
+/// Best effort: returns `None` if the server is unreachable or does not
+/// support the info endpoint. Cache write failures are ignored.
+pub fn fetch_info_cached(
+    api: &dyn SecretApi,
+    getenv: &dyn Fn(&str) -> Option<String>,
+    base_url: &str,
+    api_key: &str,
+) -> Option<InfoResponse> {
+    let path = info_cache_path(getenv, base_url, api_key);
+    let now = now_secs();
+
+    if let Some(ref p) = path {
+        if let Ok(data) = fs::read_to_string(p) {
+            if let Ok(cached) = serde_json::from_str::<CachedInfo>(&data) {
+                if now.saturating_sub(cached.fetched_at) < INFO_CACHE_TTL_SECS {
+                    return Some(cached.info);
+                }
+            }
+        }
+    }
+
+    let info = api.info().ok()?;
+
+    if let Some(p) = path {
+        if let Some(parent) = p.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let entry = CachedInfo {
+            fetched_at: now,
+            info: info.clone(),
+        };
+        if let Ok(data) = serde_json::to_string(&entry) {
+            let _ = fs::write(&p, data);
+        }
+    }
+
+    Some(info)
+}
+
+/// Select the limits tier that applies to a request (authed when an API key is set).
+pub fn tier_for(info: &InfoResponse, authed: bool) -> (&InfoTier, &'static str) {
+    if authed {
+        (&info.limits.authed, "authed")
+    } else {
+        (&info.limits.public, "public")
+    }
+}
+
+/// Check a requested TTL against the server's maximum.
+pub fn check_ttl(ttl_seconds: i64, info: &InfoResponse) -> Result<(), String> {
+    let max = info.ttl.max_seconds;
+    if max > 0 && ttl_seconds > max {
+        return Err(format!(
+            "TTL {} exceeds the server maximum of {} (over by {})",
+            format_ttl_seconds(ttl_seconds),
+            format_ttl_seconds(max),
+            format_ttl_seconds(ttl_seconds - max),
+        ));
+    }
+    Ok(())
+}
+
+/// Check an envelope size against the tier limit. When `exact` is false the
+/// size is a lower bound computed before sealing, and the message says so.
+pub fn check_envelope_size(
+    size: usize,
+    exact: bool,
+    info: &InfoResponse,
+    authed: bool,
+) -> Result<(), String> {
+    let (tier, label) = tier_for(info, authed);
+    let max = tier.max_envelope_bytes;
+    let size = size as i64;
+    if max > 0 && size > max {
+        let qualifier = if exact { "" } else { "at least " };
+        return Err(format!(
+            "secret is too large: envelope is {}{}, the server's {} limit is {} (over by {}{})",
+            qualifier,
+            format_bytes(size),
+            label,
+            format_bytes(max),
+            qualifier,
+            format_bytes(size - max),
+        ));
+    }
+    Ok(())
+}
+
+/// Lower bound on the sealed envelope size for a plaintext of `len` bytes:
+/// the base64url-encoded ciphertext plus the 16-byte GCM tag. The real
+/// envelope adds the JSON framing, nonce, salts and KDF parameters.
+pub fn min_envelope_size(len: usize) -> usize {
+    let ct = len + 16;
+    (ct * 4).div_ceil(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{InfoLimits, InfoRate, InfoTTL};
+    use std::cell::Cell;
+
+    fn info(max_ttl: i64, public_max: i64, authed_max: i64) -> InfoResponse {
+        let tier = |max| InfoTier {
+            max_envelope_bytes: max,
+            max_secrets: 0,
+            max_total_bytes: 0,
+            rate: InfoRate {
+                requests_per_second: 1.0,
+                burst: 1,
+            },
+        };
+        InfoResponse {
+            authenticated: false,
+            ttl: InfoTTL {
+                default_seconds: 86400,
+                max_seconds: max_ttl,
+            },
+            limits: InfoLimits {
+                public: tier(public_max),
+                authed: tier(authed_max),
+            },
+            claim_rate: InfoRate {
+                requests_per_second: 1.0,
+                burst: 1,
+            },
+            quota: None,
+        }
+    }
+
+    struct CountingApi {
+        calls: Cell<u32>,
+        info: InfoResponse,
+    }
+
+    impl SecretApi for CountingApi {
+        fn create(
+            &self,
+            _req: crate::client::CreateRequest,
+        ) -> Result<crate::client::CreateResponse, String> {
+            Err("unused".into())
+        }
+        fn claim(&self, _: &str, _: &[u8]) -> Result<crate::client::ClaimResponse, String> {
+            Err("unused".into())
+        }
+        fn burn(&self, _: &str) -> Result<(), String> {
+            Err("unused".into())
+        }
+        fn info(&self) -> Result<InfoResponse, String> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(self.info.clone())
+        }
+        fn status(&self, _: &str) -> Result<crate::client::SecretStatus, String> {
+            Err("unused".into())
+        }
+    }
+
+    #[test]
+    fn ttl_within_max() {
+        assert!(check_ttl(3600, &info(86400, 0, 0)).is_ok());
+        assert!(check_ttl(86400, &info(86400, 0, 0)).is_ok());
+    }
+
+    #[test]
+    fn ttl_over_max_reports_excess() {
+        let err = check_ttl(172800, &info(86400, 0, 0)).unwrap_err();
+        assert_eq!(err, "TTL 2d exceeds the server maximum of 1d (over by 1d)");
+    }
+
+    #[test]
+    fn ttl_zero_max_is_unlimited() {
+        assert!(check_ttl(999_999, &info(0, 0, 0)).is_ok());
+    }
+
+    #[test]
+    fn envelope_within_limit() {
+        assert!(check_envelope_size(1024, true, &info(0, 1024, 2048), false).is_ok());
+    }
+
+    #[test]
+    fn envelope_over_public_limit() {
+        let err = check_envelope_size(2048, true, &info(0, 1024, 4096), false).unwrap_err();
+        assert_eq!(
+            err,
+            "secret is too large: envelope is 2 KB, the server's public limit is 1 KB (over by 1 KB)"
+        );
+    }
+
+    #[test]
+    fn envelope_uses_authed_tier() {
+        assert!(check_envelope_size(2048, true, &info(0, 1024, 4096), true).is_ok());
+    }
+
+    #[test]
+    fn envelope_estimate_says_at_least() {
+        let err = check_envelope_size(2048, false, &info(0, 1024, 0), false).unwrap_err();
+        assert!(err.contains("at least 2 KB"), "err: {}", err);
+    }
+
+    #[test]
+    fn min_envelope_size_is_base64_of_ciphertext() {
+        // 14 bytes + 16-byte tag = 30 bytes → 40 base64 chars
+        assert_eq!(min_envelope_size(14), 40);
+        assert_eq!(min_envelope_size(0), 22);
+    }
+
+    #[test]
+    fn cache_serves_fresh_entry() {
+        let dir = std::env::temp_dir().join(format!(
+            "secrt_info_cache_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        let dir_str = dir.to_str().unwrap().to_string();
+        let getenv = move |key: &str| -> Option<String> {
+            if key == "XDG_CACHE_HOME" {
+                Some(dir_str.clone())
+            } else {
+                None
+            }
+        };
+        let api = CountingApi {
+            calls: Cell::new(0),
+            info: info(3600, 100, 200),
+        };
+
+        let first = fetch_info_cached(&api, &getenv, "https://a.example", "").unwrap();
+        let second = fetch_info_cached(&api, &getenv, "https://a.example", "").unwrap();
+        assert_eq!(first.ttl.max_seconds, 3600);
+        assert_eq!(second.limits.authed.max_envelope_bytes, 200);
+        assert_eq!(api.calls.get(), 1, "second call should hit the cache");
+
+        // A different API key is a different cache entry
+        fetch_info_cached(&api, &getenv, "https://a.example", "sk_x").unwrap();
+        assert_eq!(api.calls.get(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cache_path_does_not_contain_api_key() {
+        let getenv = |key: &str| -> Option<String> {
+            if key == "XDG_CACHE_HOME" {
+                Some("/tmp/cache".into())
+            } else {
+                None
+            }
+        };
+        let p = info_cache_path(&getenv, "https://a.example", "sk_secret").unwrap();
+        let s = p.to_string_lossy();
+        assert!(s.starts_with("/tmp/cache/secrt/info-"));
+        assert!(!s.contains("sk_secret"));
+    }
+}
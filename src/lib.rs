@@ -1,14 +1,31 @@
+pub mod altscreen;
+pub mod auth;
+pub mod batch;
 pub mod burn;
 pub mod cli;
 pub mod client;
+pub mod clipboard;
 pub mod color;
 pub mod completion;
 pub mod config;
+pub mod credential;
+#[cfg(feature = "dev-server")]
+pub mod devserver;
+pub mod doctor;
 pub mod envelope;
+pub mod exec;
 pub mod fileutil;
+pub mod flags;
+pub mod format;
 pub mod gen;
 pub mod get;
+pub mod help;
+pub mod history;
+pub mod info;
 pub mod keychain;
+pub mod man;
 pub mod mime;
 pub mod passphrase;
+pub mod sanitize;
 pub mod send;
+pub mod watch;
@@ -6,6 +6,7 @@ use secrt::envelope;
 
 fn main() {
     let mut deps = cli::Deps {
+        style: Default::default(),
         stdin: Box::new(io::stdin()),
         stdout: Box::new(io::stdout()),
         stderr: Box::new(io::stderr()),
@@ -19,18 +20,24 @@ fn main() {
                 .map_err(|_| envelope::EnvelopeError::RngError("SystemRandom failed".into()))
         }),
         make_api: Box::new(|base_url: &str, api_key: &str| {
-            Box::new(ApiClient {
-                base_url: base_url.to_string(),
-                api_key: api_key.to_string(),
-            })
+            Box::new(ApiClient::new(base_url, api_key))
         }),
         read_pass: Box::new(|prompt: &str, w: &mut dyn Write| {
             w.write_all(prompt.as_bytes())?;
             w.flush()?;
             rpassword::read_password()
         }),
+        read_key: Box::new(secrt::altscreen::read_key),
         get_keychain_secret: Box::new(secrt::keychain::get_secret),
         get_keychain_secret_list: Box::new(secrt::keychain::get_secret_list),
+        set_keychain_secret: Box::new(secrt::keychain::set_secret),
+        delete_keychain_secret: Box::new(secrt::keychain::delete_secret),
+        sleep: Box::new(std::thread::sleep),
+        copy_to_clipboard: Box::new(secrt::clipboard::copy),
+        read_clipboard: Box::new(secrt::clipboard::paste),
+        clipboard_available: Box::new(secrt::clipboard::probe),
+        schedule_clipboard_clear: Box::new(secrt::clipboard::schedule_clear),
+        run_command: Box::new(secrt::exec::run_command),
     };
 
     let args: Vec<String> = std::env::args().collect();
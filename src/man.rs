@@ -0,0 +1,240 @@
+//! `secrt man`: roff man pages rendered from the same pages as the terminal
+//! help (see `help`). Command pages borrow the environment and exit status
+//! sections of secrt(1) unless they define their own.
+
+use std::io::Write;
+
+use crate::cli::{parse_flags, print_man_help, CliError, Deps};
+use crate::flags::Flag;
+use crate::help::{self, Body, Page};
+use crate::passphrase::write_error;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub fn run_man(args: &[String], deps: &mut Deps) -> i32 {
+    let pa = match parse_flags("man", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_man_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    let page = match pa.args.as_slice() {
+        [] => &help::MAIN,
+        [name] => match help::page(name) {
+            Some(page) => page,
+            None => {
+                let msg = format!("unknown command {:?}", name);
+                write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &msg);
+                return 2;
+            }
+        },
+        _ => {
+            let msg = "man takes at most one command";
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), msg);
+            return 2;
+        }
+    };
+    let _ = write!(deps.stdout, "{}", render(page));
+    0
+}
+
+/// The man page name for `page`: `secrt` or `secrt-<command>`.
+pub fn page_name(page: &Page) -> String {
+    match page.command {
+        Some(name) => format!("secrt-{}", name),
+        None => "secrt".to_string(),
+    }
+}
+
+/// Render `page` as a section 1 man page.
+pub fn render(page: &Page) -> String {
+    let name = page_name(page);
+    let mut out = String::new();
+    out.push_str(&format!(
+        ".TH \"{}\" \"1\" \"\" \"secrt {}\" \"User Commands\"\n",
+        name.to_uppercase(),
+        VERSION
+    ));
+    out.push_str(&format!(
+        ".SH NAME\n{} \\- {}\n",
+        escape(&name),
+        escape(page.about)
+    ));
+    if !page.usage.is_empty() {
+        out.push_str(".SH SYNOPSIS\n");
+        for (i, line) in page.usage.iter().enumerate() {
+            if i > 0 {
+                out.push_str(".br\n");
+            }
+            out.push_str(&line_start(&markup(line)));
+            out.push('\n');
+        }
+    }
+    for (heading, flags) in page.flag_sections() {
+        out.push_str(&format!(".SH {}\n", escape(heading)));
+        write_flags(&mut out, flags);
+    }
+    for section in page.sections {
+        write_section(&mut out, section.heading, &section.body);
+    }
+    if page.command.is_some() {
+        for heading in ["ENVIRONMENT", "EXIT STATUS"] {
+            if page.section(heading).is_none() {
+                if let Some(shared) = help::MAIN.section(heading) {
+                    write_section(&mut out, heading, &shared.body);
+                }
+            }
+        }
+    }
+    out.push_str(".SH SEE ALSO\n");
+    let see_also: Vec<String> = match page.command {
+        None => help::PAGES
+            .iter()
+            .map(|p| format!("\\fB{}\\fR(1)", escape(&page_name(p))))
+            .collect(),
+        Some(name) => {
+            let mut refs = vec!["\\fBsecrt\\fR(1)".to_string()];
+            if name != "config" {
+                refs.push("\\fBsecrt\\-config\\fR(1)".to_string());
+            }
+            refs
+        }
+    };
+    out.push_str(&see_also.join(",\n"));
+    out.push('\n');
+    out
+}
+
+fn write_section(out: &mut String, heading: &str, body: &Body) {
+    if heading.is_empty() {
+        out.push_str(".PP\n");
+    } else {
+        out.push_str(&format!(".SH {}\n", escape(heading)));
+    }
+    match body {
+        Body::Text(lines) => {
+            for line in *lines {
+                out.push_str(&line_start(&markup(line)));
+                out.push('\n');
+            }
+        }
+        Body::Lines(lines) => {
+            out.push_str(".RS 4\n.nf\n");
+            for line in *lines {
+                if line.is_empty() {
+                    out.push_str(".sp\n");
+                } else {
+                    out.push_str(&line_start(&markup(line)));
+                    out.push('\n');
+                }
+            }
+            out.push_str(".fi\n.RE\n");
+        }
+        Body::Rows(rows) => write_rows(out, rows),
+        Body::Options(flags) => write_flags(out, flags),
+        Body::Settings => write_rows(out, &help::settings()),
+    }
+}
+
+fn write_rows(out: &mut String, rows: &[(&str, &str)]) {
+    for (name, desc) in rows {
+        out.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n",
+            escape(name),
+            line_start(&escape(desc))
+        ));
+    }
+}
+
+fn write_flags(out: &mut String, flags: &[Flag]) {
+    for f in flags {
+        let mut label = match f.short {
+            Some(s) => format!("\\fB{}\\fR, \\fB{}\\fR", escape(s), escape(f.long)),
+            None => format!("\\fB{}\\fR", escape(f.long)),
+        };
+        let arg = f
+            .placeholder()
+            .trim_start_matches('<')
+            .trim_end_matches('>');
+        if !arg.is_empty() {
+            label.push_str(&format!(" \\fI{}\\fR", escape(arg)));
+        }
+        out.push_str(&format!(
+            ".TP\n{}\n{}\n",
+            label,
+            line_start(&escape(f.help))
+        ));
+    }
+}
+
+/// Convert help markup to roff font changes: commands and options in bold,
+/// arguments in italics.
+fn markup(line: &str) -> String {
+    help::spans(line)
+        .into_iter()
+        .map(|(tag, s)| match tag {
+            Some('c') | Some('o') => format!("\\fB{}\\fR", escape(s)),
+            Some('a') => format!("\\fI{}\\fR", escape(s)),
+            _ => escape(s),
+        })
+        .collect()
+}
+
+/// Escape text so roff prints it literally.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Guard a text line that would otherwise be read as a roff request.
+fn line_start(s: &str) -> String {
+    if s.starts_with('.') || s.starts_with('\'') {
+        format!("\\&{}", s)
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_roff_specials() {
+        assert_eq!(escape("a-b\\c"), "a\\-b\\ec");
+        assert_eq!(line_start(".secrt.toml"), "\\&.secrt.toml");
+        assert_eq!(markup("{c:secrt} {a:<url>}"), "\\fBsecrt\\fR \\fI<url>\\fR");
+    }
+
+    #[test]
+    fn lists_every_flag_of_the_command() {
+        for page in help::PAGES {
+            let roff = render(page);
+            for (_, flags) in page.flag_sections() {
+                for f in *flags {
+                    assert!(
+                        roff.contains(&format!("\\fB{}\\fR", escape(f.long))),
+                        "{} is missing {}",
+                        page_name(page),
+                        f.long
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn command_pages_share_environment_and_exit_status() {
+        let roff = render(&help::SEND);
+        assert!(roff.contains(".SH ENVIRONMENT\n"));
+        assert!(roff.contains("SECRET_API_KEY"));
+        assert!(roff.contains(".SH EXIT STATUS\n"));
+        let roff = render(&help::WATCH);
+        assert_eq!(roff.matches(".SH EXIT STATUS").count(), 1);
+        assert!(roff.contains("burned before it was claimed"));
+    }
+}
@@ -2,33 +2,51 @@ use std::fs;
 use std::io::Write;
 
 use crate::cli::{Deps, ParsedArgs};
-use crate::color::{color_func, ERROR, LABEL};
+use crate::color::{Style, ERROR, LABEL};
+use crate::credential::{run_credential_command, COMMAND_TIMEOUT};
+use crate::sanitize::for_terminal;
+
+/// Number of explicit passphrase flags given.
+fn explicit_passphrase_flags(args: &ParsedArgs) -> usize {
+    [
+        args.passphrase_prompt,
+        !args.passphrase_env.is_empty(),
+        !args.passphrase_file.is_empty(),
+        !args.passphrase_cmd.is_empty(),
+    ]
+    .iter()
+    .filter(|&&set| set)
+    .count()
+}
 
-/// Extract a passphrase from flags using the provided Deps.
-/// Returns (passphrase, error). Empty passphrase means none requested.
-pub fn resolve_passphrase(args: &ParsedArgs, deps: &mut Deps) -> Result<String, String> {
-    let mut count = 0;
-    if args.passphrase_prompt {
-        count += 1;
-    }
-    if !args.passphrase_env.is_empty() {
-        count += 1;
-    }
-    if !args.passphrase_file.is_empty() {
-        count += 1;
-    }
+/// True if -p, --passphrase-env, --passphrase-file or --passphrase-cmd
+/// was given, so only that passphrase should be tried.
+pub fn has_explicit_passphrase(args: &ParsedArgs) -> bool {
+    explicit_passphrase_flags(args) > 0
+}
+
+/// Reject conflicting passphrase flags; returns how many were given.
+fn check_passphrase_flags(args: &ParsedArgs) -> Result<usize, String> {
+    let count = explicit_passphrase_flags(args);
     if args.no_passphrase && count > 0 {
         return Err(
-            "--no-passphrase cannot be combined with --passphrase-prompt, --passphrase-env, or --passphrase-file"
+            "--no-passphrase cannot be combined with --passphrase-prompt, --passphrase-env, --passphrase-file, or --passphrase-cmd"
                 .into(),
         );
     }
     if count > 1 {
         return Err(
-            "specify at most one of --passphrase-prompt, --passphrase-env, --passphrase-file"
+            "specify at most one of --passphrase-prompt, --passphrase-env, --passphrase-file, --passphrase-cmd"
                 .into(),
         );
     }
+    Ok(count)
+}
+
+/// Extract a passphrase from flags using the provided Deps.
+/// Returns (passphrase, error). Empty passphrase means none requested.
+pub fn resolve_passphrase(args: &ParsedArgs, deps: &mut Deps) -> Result<String, String> {
+    let count = check_passphrase_flags(args)?;
     if count == 0 {
         // Fall back to config file passphrase if set (unless --no-passphrase)
         if !args.no_passphrase && !args.passphrase_default.is_empty() {
@@ -60,8 +78,13 @@ pub fn resolve_passphrase(args: &ParsedArgs, deps: &mut Deps) -> Result<String,
         return Ok(p.to_string());
     }
 
+    if !args.passphrase_cmd.is_empty() {
+        return run_credential_command(&args.passphrase_cmd, COMMAND_TIMEOUT)
+            .map_err(|e| format!("--passphrase-cmd: {}", e));
+    }
+
     // Prompt
-    let c = color_func(true);
+    let c = deps.style.color_func(true);
     let prompt = format!("{} ", c(LABEL, "Passphrase:"));
     let p = (deps.read_pass)(&prompt, &mut deps.stderr)
         .map_err(|e| format!("read passphrase: {}", e))?;
@@ -75,34 +98,13 @@ pub fn resolve_passphrase(args: &ParsedArgs, deps: &mut Deps) -> Result<String,
 /// Like resolve_passphrase but prompts for confirmation on send.
 pub fn resolve_passphrase_for_send(args: &ParsedArgs, deps: &mut Deps) -> Result<String, String> {
     // Check for conflicting flags first
-    let mut count = 0;
-    if args.passphrase_prompt {
-        count += 1;
-    }
-    if !args.passphrase_env.is_empty() {
-        count += 1;
-    }
-    if !args.passphrase_file.is_empty() {
-        count += 1;
-    }
-    if args.no_passphrase && count > 0 {
-        return Err(
-            "--no-passphrase cannot be combined with --passphrase-prompt, --passphrase-env, or --passphrase-file"
-                .into(),
-        );
-    }
-    if count > 1 {
-        return Err(
-            "specify at most one of --passphrase-prompt, --passphrase-env, --passphrase-file"
-                .into(),
-        );
-    }
+    check_passphrase_flags(args)?;
 
     if !args.passphrase_prompt {
         return resolve_passphrase(args, deps);
     }
 
-    let c = color_func(true);
+    let c = deps.style.color_func(true);
     let prompt = format!("{} ", c(LABEL, "Passphrase:"));
     let p1 = (deps.read_pass)(&prompt, &mut deps.stderr)
         .map_err(|e| format!("read passphrase: {}", e))?;
@@ -121,15 +123,17 @@ pub fn resolve_passphrase_for_send(args: &ParsedArgs, deps: &mut Deps) -> Result
 }
 
 /// Write an error message to the writer, in JSON or plain format.
-/// When `is_tty` is true, the "error:" prefix is colored red.
-pub fn write_error(w: &mut dyn Write, json_mode: bool, is_tty: bool, msg: &str) {
+/// When `is_tty` is true, the "error:" prefix is colored red, as `style`
+/// allows. Plain messages have control characters escaped, since they may
+/// quote the server.
+pub fn write_error(w: &mut dyn Write, style: &Style, json_mode: bool, is_tty: bool, msg: &str) {
     if json_mode {
         let _ = writeln!(w, "{{\"error\":{}}}", serde_json::to_string(msg).unwrap());
     } else if is_tty {
-        let c = color_func(true);
-        let _ = writeln!(w, "{} {}", c(ERROR, "error:"), msg);
+        let c = style.color_func(true);
+        let _ = writeln!(w, "{} {}", c(ERROR, "error:"), for_terminal(msg));
     } else {
-        let _ = writeln!(w, "error: {}", msg);
+        let _ = writeln!(w, "error: {}", for_terminal(msg));
     }
 }
 
@@ -147,6 +151,7 @@ mod tests {
     ) -> Deps {
         let responses = Arc::new(Mutex::new(read_pass_responses));
         Deps {
+            style: Default::default(),
             stdin: Box::new(Cursor::new(Vec::new())),
             stdout: Box::new(Vec::new()),
             stderr: Box::new(Vec::new()),
@@ -166,13 +171,19 @@ mod tests {
                 }
             }),
             make_api: Box::new(|base_url: &str, api_key: &str| {
-                Box::new(crate::client::ApiClient {
-                    base_url: base_url.to_string(),
-                    api_key: api_key.to_string(),
-                })
+                Box::new(crate::client::ApiClient::new(base_url, api_key))
             }),
             get_keychain_secret: Box::new(|_key: &str| None),
             get_keychain_secret_list: Box::new(|_key: &str| Vec::new()),
+            set_keychain_secret: Box::new(|_key: &str, _value: &str| Ok(())),
+            delete_keychain_secret: Box::new(|_key: &str| Ok(())),
+            sleep: Box::new(|_| {}),
+            copy_to_clipboard: Box::new(|_, _| Err("no clipboard".into())),
+            read_clipboard: Box::new(|_| Err("no clipboard".into())),
+            read_key: Box::new(|_, _| Ok(())),
+            clipboard_available: Box::new(|_| Err("no clipboard".into())),
+            schedule_clipboard_clear: Box::new(|_, _, _| Ok(())),
+            run_command: Box::new(|_, _| Err("not supported".into())),
         }
     }
 
@@ -464,14 +475,14 @@ mod tests {
     #[test]
     fn plain_format() {
         let mut buf = Vec::new();
-        write_error(&mut buf, false, false, "something broke");
+        write_error(&mut buf, &Style::default(), false, false, "something broke");
         assert_eq!(String::from_utf8(buf).unwrap(), "error: something broke\n");
     }
 
     #[test]
     fn plain_format_tty() {
         let mut buf = Vec::new();
-        write_error(&mut buf, false, true, "something broke");
+        write_error(&mut buf, &Style::default(), false, true, "something broke");
         let output = String::from_utf8(buf).unwrap();
         assert!(output.contains("\x1b[31merror:\x1b[0m"));
         assert!(output.contains("something broke"));
@@ -480,7 +491,7 @@ mod tests {
     #[test]
     fn json_format() {
         let mut buf = Vec::new();
-        write_error(&mut buf, true, false, "something broke");
+        write_error(&mut buf, &Style::default(), true, false, "something broke");
         let output = String::from_utf8(buf).unwrap();
         assert!(output.contains("\"error\""));
         assert!(output.contains("something broke"));
@@ -0,0 +1,128 @@
+//! Neutralizing terminal control characters in text that came from a secret
+//! or a server, so showing it can't move the cursor, rewrite the screen, set
+//! the window title, write to the clipboard (OSC 52) or, with bidi overrides
+//! and other invisible format characters, look different from what it is.
+
+use std::borrow::Cow;
+
+/// Make `text` safe to show on a terminal. Control characters other than
+/// newline, tab and the CR of a CRLF are shown the way `cat -v` shows them:
+/// ESC as `^[`, DEL as `^?`, and C1 controls like CSI as `M-^[`. Bidi
+/// controls and other format characters are shown by code point, e.g.
+/// `<U+202E>`.
+pub fn for_terminal(text: &str) -> Cow<'_, str> {
+    if !has_controls(text) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\r' && chars.peek() == Some(&'\n') {
+            out.push(c);
+        } else if is_unsafe(c) {
+            push_visible(&mut out, c);
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Whether `text` has characters `for_terminal` would change.
+pub fn has_controls(text: &str) -> bool {
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_unsafe(c) && !(c == '\r' && chars.peek() == Some(&'\n')) {
+            return true;
+        }
+    }
+    false
+}
+
+fn is_unsafe(c: char) -> bool {
+    (c.is_control() && c != '\n' && c != '\t') || is_format(c)
+}
+
+/// Invisible characters that change how the text around them is shown:
+/// bidi embeddings, overrides, isolates and marks, zero-width and other
+/// format characters (Unicode category Cf), and the line and paragraph
+/// separators. The zero-width joiners are left alone, since emoji and some
+/// scripts need them.
+fn is_format(c: char) -> bool {
+    matches!(
+        c,
+        '\u{ad}'
+            | '\u{600}'..='\u{605}'
+            | '\u{61c}'
+            | '\u{6dd}'
+            | '\u{70f}'
+            | '\u{8e2}'
+            | '\u{180e}'
+            | '\u{200b}'
+            | '\u{200e}'..='\u{200f}'
+            | '\u{2028}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{206f}'
+            | '\u{feff}'
+            | '\u{fff9}'..='\u{fffb}'
+            | '\u{110bd}'
+            | '\u{110cd}'
+            | '\u{13430}'..='\u{1343f}'
+            | '\u{1bca0}'..='\u{1bca3}'
+            | '\u{1d173}'..='\u{1d17a}'
+            | '\u{e0001}'
+            | '\u{e0020}'..='\u{e007f}'
+    )
+}
+
+fn push_visible(out: &mut String, c: char) {
+    let code = c as u32;
+    if is_format(c) {
+        out.push_str(&format!("<U+{:04X}>", code));
+        return;
+    }
+    if code >= 0x80 {
+        out.push_str("M-");
+    }
+    match code & 0x7f {
+        0x7f => out.push_str("^?"),
+        low => {
+            out.push('^');
+            out.push(char::from(low as u8 + 0x40));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_borrowed() {
+        let text = "line one\n\tline two\r\nüñí©ødé";
+        assert!(matches!(for_terminal(text), Cow::Borrowed(_)));
+        assert!(!has_controls(text));
+    }
+
+    #[test]
+    fn escapes_control_sequences() {
+        assert_eq!(for_terminal("\x1b[2Jgone"), "^[[2Jgone");
+        assert_eq!(for_terminal("\x1b]52;c;aGk=\x07"), "^[]52;c;aGk=^G");
+        assert_eq!(for_terminal("a\rb\x7f\0"), "a^Mb^?^@");
+        assert_eq!(for_terminal("\u{9b}31m"), "M-^[31m");
+        assert!(has_controls("x\r"));
+    }
+
+    #[test]
+    fn escapes_bidi_and_format_characters() {
+        assert_eq!(for_terminal("pay\u{202e}txt.exe"), "pay<U+202E>txt.exe");
+        assert_eq!(
+            for_terminal("\u{2066}a\u{2069}\u{200b}\u{feff}"),
+            "<U+2066>a<U+2069><U+200B><U+FEFF>"
+        );
+        assert_eq!(for_terminal("a\u{2028}b"), "a<U+2028>b");
+        assert!(has_controls("\u{200f}"));
+        // emoji sequences keep their joiner
+        assert!(!has_controls("👩\u{200d}💻"));
+    }
+}
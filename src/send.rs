@@ -1,12 +1,21 @@
 use std::fs;
 use std::io::{Read, Write};
 
-use crate::cli::{parse_flags, print_send_help, resolve_globals, CliError, Deps, ParsedArgs};
+use crate::batch::run_send_batch;
+use crate::cli::{
+    parse_flags, print_send_help, resolve_api_key, resolve_globals, resolve_passphrase_default,
+    CliError, Deps, ParsedArgs,
+};
 use crate::client::CreateRequest;
-use crate::color::{color_func, DIM, LABEL, SUCCESS, URL, WARN};
+use crate::color::{CHECK, DIM, LABEL, PENDING, SPARKLE, SUCCESS, URL, WARN};
 use crate::envelope::{self, format_share_link, SealParams};
+use crate::format::{self, local_time, SEND_FIELDS};
 use crate::gen::generate_password_from_args;
+use crate::history::{append_history, history_path_with, now_rfc3339, HistoryEntry};
+use crate::info::{check_envelope_size, check_ttl, fetch_info_cached, min_envelope_size};
 use crate::passphrase::{resolve_passphrase_for_send, write_error};
+use crate::sanitize::for_terminal;
+use crate::watch::{wait_and_report, watch_options};
 
 fn is_gen_mode(pa: &ParsedArgs) -> bool {
     pa.args
@@ -16,24 +25,52 @@ fn is_gen_mode(pa: &ParsedArgs) -> bool {
 }
 
 pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
-    let mut pa = match parse_flags(args) {
+    let mut pa = match parse_flags("send", args) {
         Ok(pa) => pa,
         Err(CliError::ShowHelp) => {
             print_send_help(deps);
             return 0;
         }
         Err(CliError::Error(e)) => {
-            write_error(&mut deps.stderr, false, (deps.is_tty)(), &e);
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Err(e) = resolve_globals(&mut pa, deps)
+        .and_then(|_| resolve_api_key(&mut pa))
+        .and_then(|_| resolve_passphrase_default(&mut pa))
+    {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    if !pa.batch.is_empty() {
+        return run_send_batch(&pa, deps);
+    }
+    if !pa.results.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "--results requires --batch",
+        );
+        return 2;
+    }
+
+    let format = match format::from_args(&pa, SEND_FIELDS, "SECRT_LINK") {
+        Ok(f) => f,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
             return 2;
         }
     };
-    resolve_globals(&mut pa, deps);
 
     // Read plaintext from exactly one source
     let mut plaintext = match read_plaintext(&pa, deps) {
         Ok(p) => p,
         Err(e) => {
-            write_error(&mut deps.stderr, pa.json, (deps.is_tty)(), &e);
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
             return 2;
         }
     };
@@ -52,6 +89,7 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
         if trimmed.is_empty() {
             write_error(
                 &mut deps.stderr,
+                &deps.style,
                 pa.json,
                 (deps.is_tty)(),
                 "input is empty after trimming",
@@ -66,7 +104,13 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
         match envelope::parse_ttl(&pa.ttl) {
             Ok(ttl) => Some(ttl),
             Err(e) => {
-                write_error(&mut deps.stderr, pa.json, (deps.is_tty)(), &e.to_string());
+                write_error(
+                    &mut deps.stderr,
+                    &deps.style,
+                    pa.json,
+                    (deps.is_tty)(),
+                    &e.to_string(),
+                );
                 return 2;
             }
         }
@@ -74,11 +118,39 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
         None
     };
 
+    let wait_opts = if pa.wait {
+        match watch_options(&pa) {
+            Ok(o) => Some(o),
+            Err(e) => {
+                write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+                return 2;
+            }
+        }
+    } else {
+        None
+    };
+
+    // Preflight against the server's advertised limits (best effort)
+    let client = (deps.make_api)(&pa.base_url, &pa.api_key);
+    let authed = !pa.api_key.is_empty();
+    let server_info = fetch_info_cached(&*client, &*deps.getenv, &pa.base_url, &pa.api_key);
+    if let Some(ref info) = server_info {
+        let check = ttl_seconds
+            .map_or(Ok(()), |ttl| check_ttl(ttl, info))
+            .and_then(|_| {
+                check_envelope_size(min_envelope_size(plaintext.len()), false, info, authed)
+            });
+        if let Err(e) = check {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    }
+
     // Resolve passphrase
     let passphrase = match resolve_passphrase_for_send(&pa, deps) {
         Ok(p) => p,
         Err(e) => {
-            write_error(&mut deps.stderr, pa.json, (deps.is_tty)(), &e);
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
             return 2;
         }
     };
@@ -92,6 +164,7 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
     };
 
     // Seal envelope
+    let plaintext_len = plaintext.len();
     let result = envelope::seal(SealParams {
         plaintext,
         passphrase,
@@ -105,6 +178,7 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
         Err(e) => {
             write_error(
                 &mut deps.stderr,
+                &deps.style,
                 pa.json,
                 (deps.is_tty)(),
                 &format!("encryption failed: {}", e),
@@ -113,6 +187,16 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
         }
     };
 
+    if let Some(ref info) = server_info {
+        let size = serde_json::to_vec(&result.envelope)
+            .map(|v| v.len())
+            .unwrap_or(0);
+        if let Err(e) = check_envelope_size(size, true, info, authed) {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    }
+
     // Upload to server
     let is_tty = (deps.is_tty)();
 
@@ -120,8 +204,13 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
     if let Some(ref pw) = generated_password {
         if !pa.json && !pa.silent {
             if is_tty {
-                let c = color_func(true);
-                let _ = writeln!(deps.stderr, "{} Generated:\n{}", c(SUCCESS, "\u{2726}"), pw);
+                let c = deps.style.color_func(true);
+                let _ = writeln!(
+                    deps.stderr,
+                    "{} Generated:\n{}",
+                    c(SUCCESS, deps.style.glyph(SPARKLE)),
+                    pw
+                );
             } else {
                 let _ = writeln!(deps.stderr, "{}", pw);
             }
@@ -129,15 +218,14 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
     }
 
     if is_tty && !pa.silent {
-        let c = color_func(true);
+        let c = deps.style.color_func(true);
         let _ = write!(
             deps.stderr,
             "{} Encrypting and uploading...",
-            c(WARN, "\u{25CB}")
+            c(WARN, deps.style.glyph(PENDING))
         );
         let _ = deps.stderr.flush();
     }
-    let client = (deps.make_api)(&pa.base_url, &pa.api_key);
 
     let resp = match client.create(CreateRequest {
         envelope: result.envelope,
@@ -146,7 +234,7 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
     }) {
         Ok(r) => {
             if is_tty && !pa.silent {
-                let c = color_func(true);
+                let c = deps.style.color_func(true);
                 let expires_fmt = format_expires(&r.expires_at);
                 let msg = if has_passphrase {
                     "Encrypted and uploaded with passphrase."
@@ -156,7 +244,7 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
                 let _ = write!(
                     deps.stderr,
                     "\r{} {}  {}\n",
-                    c(SUCCESS, "\u{2713}"),
+                    c(SUCCESS, deps.style.glyph(CHECK)),
                     msg,
                     c(DIM, &expires_fmt)
                 );
@@ -167,47 +255,100 @@ pub fn run_send(args: &[String], deps: &mut Deps) -> i32 {
             if is_tty && !pa.silent {
                 let _ = writeln!(deps.stderr);
             }
-            write_error(&mut deps.stderr, pa.json, is_tty, &e);
+            write_error(&mut deps.stderr, &deps.style, pa.json, is_tty, &e);
             return 1;
         }
     };
 
+    if pa.history {
+        record_history(
+            deps,
+            HistoryEntry {
+                id: resp.id.clone(),
+                base_url: pa.base_url.clone(),
+                label: pa.label.clone(),
+                size: plaintext_len as u64,
+                created_at: now_rfc3339(),
+                expires_at: resp.expires_at.clone(),
+                passphrase: has_passphrase,
+                burned_at: String::new(),
+            },
+        );
+    } else if !pa.label.is_empty() && !pa.silent {
+        let _ = writeln!(
+            deps.stderr,
+            "warning: --label is only kept in local history; set history = true in the config file to enable it"
+        );
+    }
+
     // Output
     let share_link = format_share_link(&resp.share_url, &result.url_key);
 
-    if pa.json {
-        let mut out = serde_json::json!({
-            "id": resp.id,
-            "share_url": resp.share_url,
-            "share_link": share_link,
-            "expires_at": resp.expires_at,
-        });
+    if let Some(ref format) = format {
+        let mut record = serde_json::Map::new();
+        record.insert("id".into(), resp.id.clone().into());
+        record.insert("share_url".into(), resp.share_url.clone().into());
+        record.insert("share_link".into(), share_link.clone().into());
+        record.insert("expires_at".into(), resp.expires_at.clone().into());
         if let Some(ref pw) = generated_password {
-            out["password"] = serde_json::Value::String(pw.clone());
+            record.insert("password".into(), pw.clone().into());
+        }
+        let field = |name: &str| match name {
+            "link" => Some(share_link.clone()),
+            "expires_local" => Some(local_time(&resp.expires_at)),
+            _ => record.get(name).and_then(|v| v.as_str()).map(String::from),
+        };
+        let out = format::render(format, &record, &share_link, &field);
+        if (deps.is_stdout_tty)() {
+            let _ = write!(deps.stdout, "{}", for_terminal(&out));
+        } else {
+            let _ = write!(deps.stdout, "{}", out);
         }
-        let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
     } else if (deps.is_stdout_tty)() {
-        let c = color_func(true);
-        let _ = writeln!(deps.stdout, "{}", c(URL, &share_link));
+        let c = deps.style.color_func(true);
+        let _ = writeln!(deps.stdout, "{}", c(URL, &for_terminal(&share_link)));
     } else {
         let _ = writeln!(deps.stdout, "{}", share_link);
     }
 
+    if pa.copy {
+        match (deps.copy_to_clipboard)(&pa.clipboard_tool, share_link.as_bytes()) {
+            Ok(()) if !pa.silent && !pa.json => {
+                let c = deps.style.color_func(is_tty);
+                let _ = writeln!(
+                    deps.stderr,
+                    "{} Link copied to clipboard",
+                    c(SUCCESS, deps.style.glyph(CHECK))
+                );
+            }
+            Ok(()) => {}
+            Err(e) => {
+                let _ = writeln!(deps.stderr, "warning: could not copy link: {}", e);
+            }
+        }
+    }
+
+    if let Some(ref opts) = wait_opts {
+        let _ = deps.stdout.flush();
+        return wait_and_report(&*client, &resp.id, opts, &pa, deps);
+    }
+
     0
 }
 
+/// Append a sent secret to the local history, warning on failure.
+pub(crate) fn record_history(deps: &mut Deps, entry: HistoryEntry) {
+    let recorded = history_path_with(&*deps.getenv)
+        .ok_or_else(|| "could not determine config directory".to_string())
+        .and_then(|p| append_history(&p, entry));
+    if let Err(e) = recorded {
+        let _ = writeln!(deps.stderr, "warning: could not record send history: {}", e);
+    }
+}
+
 /// Format ISO 8601 UTC timestamp to "Expires YYYY-MM-DD HH:MM TZ" in local time.
 fn format_expires(iso: &str) -> String {
-    use chrono::{DateTime, Local, Utc};
-
-    if let Ok(utc) = iso.parse::<DateTime<Utc>>() {
-        let local = utc.with_timezone(&Local);
-        format!("Expires {}", local.format("%Y-%m-%d %H:%M %Z"))
-    } else if iso.len() >= 16 {
-        format!("Expires {} {} UTC", &iso[0..10], &iso[11..16])
-    } else {
-        format!("Expires {}", iso)
-    }
+    format!("Expires {}", for_terminal(&local_time(iso)))
 }
 
 fn read_plaintext(pa: &ParsedArgs, deps: &mut Deps) -> Result<Vec<u8>, String> {
@@ -219,18 +360,21 @@ fn read_plaintext(pa: &ParsedArgs, deps: &mut Deps) -> Result<Vec<u8>, String> {
     if !pa.file.is_empty() {
         sources += 1;
     }
+    if pa.from_clipboard {
+        sources += 1;
+    }
     if gen_mode {
         sources += 1;
     }
 
     if sources > 1 {
-        return Err("specify exactly one input source (stdin, --text, --file, or gen)".into());
+        return Err(
+            "specify exactly one input source (stdin, --text, --file, --from-clipboard, or gen)"
+                .into(),
+        );
     }
 
     if gen_mode {
-        if pa.gen_count > 1 {
-            return Err("--count cannot be used with send".into());
-        }
         let password = generate_password_from_args(pa, &*deps.rand_bytes)?;
         return Ok(password.into_bytes());
     }
@@ -247,9 +391,18 @@ fn read_plaintext(pa: &ParsedArgs, deps: &mut Deps) -> Result<Vec<u8>, String> {
         return Ok(data);
     }
 
+    if pa.from_clipboard {
+        let data = (deps.read_clipboard)(&pa.clipboard_tool)
+            .map_err(|e| format!("read clipboard: {}", e))?;
+        if data.is_empty() {
+            return Err("clipboard is empty".into());
+        }
+        return Ok(data);
+    }
+
     // stdin
     if (deps.is_tty)() && !pa.multi_line {
-        let c = color_func((deps.is_tty)());
+        let c = deps.style.color_func((deps.is_tty)());
         // Determine effective show mode
         let show_input = if pa.hidden {
             false
@@ -309,7 +462,7 @@ fn read_plaintext(pa: &ParsedArgs, deps: &mut Deps) -> Result<Vec<u8>, String> {
     }
 
     if (deps.is_tty)() && pa.multi_line {
-        let c = color_func(true);
+        let c = deps.style.color_func(true);
         if !pa.silent {
             let _ = writeln!(
                 deps.stderr,
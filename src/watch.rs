@@ -0,0 +1,341 @@
+//! Waiting for a secret to be claimed: the `watch` command and `send --wait`.
+//! Both poll the status endpoint, which reports the secret's state without
+//! consuming it.
+
+use std::io::Write;
+use std::time::Duration;
+
+use crate::burn::resolve_target;
+use crate::cli::{
+    parse_flags, print_watch_help, resolve_api_key, resolve_globals, CliError, Deps, ParsedArgs,
+};
+use crate::client::{SecretApi, SecretStatus};
+use crate::color::{CHECK, DIM, PENDING, SUCCESS, WARN};
+use crate::envelope;
+use crate::passphrase::write_error;
+use crate::sanitize::for_terminal;
+
+pub const DEFAULT_WATCH_INTERVAL_SECS: u64 = 5;
+
+/// Consecutive failed status checks tolerated before giving up.
+const MAX_CONSECUTIVE_ERRORS: u32 = 3;
+
+// Exit codes. Usage errors are 2 and runtime failures 1, as elsewhere.
+pub const EXIT_CLAIMED: i32 = 0;
+pub const EXIT_BURNED: i32 = 3;
+pub const EXIT_EXPIRED: i32 = 4;
+pub const EXIT_TIMEOUT: i32 = 5;
+
+pub struct WatchOptions {
+    pub interval: Duration,
+    pub timeout: Option<Duration>,
+}
+
+pub enum WatchOutcome {
+    /// The secret left the pending state.
+    Done(SecretStatus),
+    TimedOut,
+    Failed(String),
+}
+
+/// Read `--interval` and `--timeout` (TTL syntax, e.g. 10s, 5m).
+pub fn watch_options(pa: &ParsedArgs) -> Result<WatchOptions, String> {
+    let interval = if pa.interval.is_empty() {
+        Duration::from_secs(DEFAULT_WATCH_INTERVAL_SECS)
+    } else {
+        envelope::parse_ttl(&pa.interval)
+            .map(|s| Duration::from_secs(s as u64))
+            .map_err(|_| format!("invalid --interval {:?}", pa.interval))?
+    };
+    let timeout = if pa.timeout.is_empty() {
+        None
+    } else {
+        Some(
+            envelope::parse_ttl(&pa.timeout)
+                .map(|s| Duration::from_secs(s as u64))
+                .map_err(|_| format!("invalid --timeout {:?}", pa.timeout))?,
+        )
+    };
+    Ok(WatchOptions { interval, timeout })
+}
+
+/// Poll until the secret is no longer pending, the timeout elapses, or the
+/// status check fails repeatedly.
+pub fn poll_status(
+    api: &dyn SecretApi,
+    secret_id: &str,
+    opts: &WatchOptions,
+    sleep: &dyn Fn(Duration),
+) -> WatchOutcome {
+    let mut waited = Duration::ZERO;
+    let mut errors = 0;
+    loop {
+        match api.status(secret_id) {
+            Ok(st) if st.status != "pending" => return WatchOutcome::Done(st),
+            Ok(_) => errors = 0,
+            Err(e) => {
+                errors += 1;
+                if errors >= MAX_CONSECUTIVE_ERRORS {
+                    return WatchOutcome::Failed(e);
+                }
+            }
+        }
+        if opts.timeout.is_some_and(|t| waited >= t) {
+            return WatchOutcome::TimedOut;
+        }
+        sleep(opts.interval);
+        waited += opts.interval;
+    }
+}
+
+fn exit_code_for(status: &str) -> i32 {
+    match status {
+        "claimed" => EXIT_CLAIMED,
+        "burned" => EXIT_BURNED,
+        "expired" => EXIT_EXPIRED,
+        _ => 1,
+    }
+}
+
+/// Wait for `secret_id` and report the result. Returns the exit code.
+pub fn wait_and_report(
+    api: &dyn SecretApi,
+    secret_id: &str,
+    opts: &WatchOptions,
+    pa: &ParsedArgs,
+    deps: &mut Deps,
+) -> i32 {
+    let is_tty = (deps.is_tty)();
+    let c = deps.style.color_func(is_tty);
+    if is_tty && !pa.silent && !pa.json {
+        let _ = write!(
+            deps.stderr,
+            "{} Waiting for the secret to be claimed...",
+            c(WARN, deps.style.glyph(PENDING))
+        );
+        let _ = deps.stderr.flush();
+    }
+
+    let outcome = poll_status(api, secret_id, opts, &*deps.sleep);
+
+    if is_tty && !pa.silent && !pa.json {
+        let _ = write!(deps.stderr, "\r\x1b[2K");
+    }
+
+    match outcome {
+        WatchOutcome::Done(st) => {
+            let code = exit_code_for(&st.status);
+            if pa.json {
+                let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&st).unwrap());
+            } else if !pa.silent || code != EXIT_CLAIMED {
+                let msg = match st.status.as_str() {
+                    "claimed" => match st.claimed_at {
+                        Some(ref at) => format!("Secret claimed {}.", c(DIM, &format_at(at))),
+                        None => "Secret claimed.".to_string(),
+                    },
+                    "burned" => "Secret was burned before it was claimed.".to_string(),
+                    "expired" => "Secret expired without being claimed.".to_string(),
+                    other => format!("Secret status: {}", for_terminal(other)),
+                };
+                if is_tty && code == EXIT_CLAIMED {
+                    let _ = writeln!(
+                        deps.stderr,
+                        "{} {}",
+                        c(SUCCESS, deps.style.glyph(CHECK)),
+                        msg
+                    );
+                } else {
+                    let _ = writeln!(deps.stderr, "{}", msg);
+                }
+            }
+            code
+        }
+        WatchOutcome::TimedOut => {
+            if pa.json {
+                let out = serde_json::json!({
+                    "id": secret_id,
+                    "status": "pending",
+                    "timed_out": true,
+                });
+                let _ = writeln!(deps.stdout, "{}", serde_json::to_string(&out).unwrap());
+            } else {
+                let _ = writeln!(
+                    deps.stderr,
+                    "Timed out; the secret has not been claimed yet."
+                );
+            }
+            EXIT_TIMEOUT
+        }
+        WatchOutcome::Failed(e) => {
+            write_error(
+                &mut deps.stderr,
+                &deps.style,
+                pa.json,
+                is_tty,
+                &format!("status check failed: {}", e),
+            );
+            1
+        }
+    }
+}
+
+/// Format an ISO 8601 UTC timestamp as "at YYYY-MM-DD HH:MM TZ" in local time.
+fn format_at(iso: &str) -> String {
+    use chrono::{DateTime, Local, Utc};
+    match iso.parse::<DateTime<Utc>>() {
+        Ok(utc) => format!(
+            "at {}",
+            utc.with_timezone(&Local).format("%Y-%m-%d %H:%M %Z")
+        ),
+        Err(_) => format!("at {}", for_terminal(iso)),
+    }
+}
+
+pub fn run_watch(args: &[String], deps: &mut Deps) -> i32 {
+    let mut pa = match parse_flags("watch", args) {
+        Ok(pa) => pa,
+        Err(CliError::ShowHelp) => {
+            print_watch_help(deps);
+            return 0;
+        }
+        Err(CliError::Error(e)) => {
+            write_error(&mut deps.stderr, &deps.style, false, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+    if let Err(e) = resolve_globals(&mut pa, deps).and_then(|_| resolve_api_key(&mut pa)) {
+        write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+        return 2;
+    }
+
+    if pa.args.is_empty() {
+        write_error(
+            &mut deps.stderr,
+            &deps.style,
+            pa.json,
+            (deps.is_tty)(),
+            "secret ID or share URL is required",
+        );
+        return 2;
+    }
+
+    let opts = match watch_options(&pa) {
+        Ok(o) => o,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+
+    let (secret_id, base_url) = match resolve_target(&pa.args[0], &pa, deps) {
+        Ok(t) => t,
+        Err(e) => {
+            write_error(&mut deps.stderr, &deps.style, pa.json, (deps.is_tty)(), &e);
+            return 2;
+        }
+    };
+
+    let client = (deps.make_api)(&base_url, &pa.api_key);
+    wait_and_report(&*client, &secret_id, &opts, &pa, deps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{ClaimResponse, CreateRequest, CreateResponse, InfoResponse};
+    use std::cell::RefCell;
+
+    struct SeqApi {
+        seq: RefCell<Vec<Result<&'static str, String>>>,
+    }
+
+    impl SecretApi for SeqApi {
+        fn create(&self, _: CreateRequest) -> Result<CreateResponse, String> {
+            Err("unused".into())
+        }
+        fn claim(&self, _: &str, _: &[u8]) -> Result<ClaimResponse, String> {
+            Err("unused".into())
+        }
+        fn burn(&self, _: &str) -> Result<(), String> {
+            Err("unused".into())
+        }
+        fn info(&self) -> Result<InfoResponse, String> {
+            Err("unused".into())
+        }
+        fn status(&self, id: &str) -> Result<SecretStatus, String> {
+            let mut seq = self.seq.borrow_mut();
+            let next = if seq.len() > 1 {
+                seq.remove(0)
+            } else {
+                seq[0].clone()
+            };
+            next.map(|s| SecretStatus {
+                id: id.into(),
+                status: s.into(),
+                expires_at: None,
+                claimed_at: None,
+            })
+        }
+    }
+
+    fn opts(timeout: Option<u64>) -> WatchOptions {
+        WatchOptions {
+            interval: Duration::from_secs(5),
+            timeout: timeout.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn polls_until_claimed() {
+        let api = SeqApi {
+            seq: RefCell::new(vec![Ok("pending"), Ok("pending"), Ok("claimed")]),
+        };
+        let sleeps = RefCell::new(0);
+        let out = poll_status(&api, "x", &opts(None), &|_| *sleeps.borrow_mut() += 1);
+        assert!(matches!(out, WatchOutcome::Done(ref s) if s.status == "claimed"));
+        assert_eq!(*sleeps.borrow(), 2);
+    }
+
+    #[test]
+    fn times_out() {
+        let api = SeqApi {
+            seq: RefCell::new(vec![Ok("pending")]),
+        };
+        let sleeps = RefCell::new(0);
+        let out = poll_status(&api, "x", &opts(Some(12)), &|_| *sleeps.borrow_mut() += 1);
+        assert!(matches!(out, WatchOutcome::TimedOut));
+        assert_eq!(*sleeps.borrow(), 3);
+    }
+
+    #[test]
+    fn tolerates_transient_errors() {
+        let api = SeqApi {
+            seq: RefCell::new(vec![
+                Err("blip".into()),
+                Err("blip".into()),
+                Ok("pending"),
+                Err("blip".into()),
+                Ok("expired"),
+            ]),
+        };
+        let out = poll_status(&api, "x", &opts(None), &|_| {});
+        assert!(matches!(out, WatchOutcome::Done(ref s) if s.status == "expired"));
+    }
+
+    #[test]
+    fn gives_up_after_repeated_errors() {
+        let api = SeqApi {
+            seq: RefCell::new(vec![Err("down".into())]),
+        };
+        let out = poll_status(&api, "x", &opts(None), &|_| {});
+        assert!(matches!(out, WatchOutcome::Failed(ref e) if e == "down"));
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        assert_eq!(exit_code_for("claimed"), EXIT_CLAIMED);
+        assert_eq!(exit_code_for("burned"), EXIT_BURNED);
+        assert_eq!(exit_code_for("expired"), EXIT_EXPIRED);
+        assert_eq!(exit_code_for("mystery"), 1);
+    }
+}
@@ -0,0 +1,491 @@
+mod helpers;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use helpers::{args, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::{ClaimResponse, CreateResponse};
+use secrt::envelope::{self, SealParams};
+
+fn created(id: &str) -> Result<CreateResponse, String> {
+    Ok(CreateResponse {
+        id: id.into(),
+        share_url: format!("https://secrt.ca/s/{}", id),
+        expires_at: "2099-01-01T00:00:00Z".into(),
+    })
+}
+
+fn write_input(name: &str, content: &str) -> PathBuf {
+    let dir = PathBuf::from(helpers::unique_temp_dir());
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn create_calls(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    log.lock()
+        .unwrap()
+        .iter()
+        .filter(|c| c.starts_with("create:"))
+        .cloned()
+        .collect()
+}
+
+const CSV: &str = "recipient,value,ttl,label\n\
+                   alice@example.com,hunter2,1h,alice-db\n\
+                   bob@example.com,gen,,bob-db\n";
+
+#[test]
+fn batch_csv_writes_one_link_per_recipient() {
+    let input = write_input("in.csv", CSV);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_create_seq(vec![created("id-alice"), created("id-bob")])
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "send",
+            "--batch",
+            input.to_str().unwrap(),
+            "--ttl",
+            "2d",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+
+    let out = stdout.to_string();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "recipient,label,id,share_link,expires_at,password,error"
+    );
+    assert!(
+        lines[1].starts_with("alice@example.com,alice-db,id-alice,https://secrt.ca/s/id-alice#")
+    );
+    assert!(
+        lines[1].ends_with(",2099-01-01T00:00:00Z,,"),
+        "{}",
+        lines[1]
+    );
+    // Generated rows report the password so it can be delivered separately.
+    let bob: Vec<&str> = lines[2].split(',').collect();
+    assert_eq!(bob[0], "bob@example.com");
+    assert_eq!(bob[5].len(), 20, "{}", lines[2]);
+
+    // Row TTL wins; the --ttl flag is the fallback.
+    assert_eq!(
+        create_calls(&log),
+        vec![
+            "create:https://secrt.ca:3600".to_string(),
+            "create:https://secrt.ca:172800".to_string()
+        ]
+    );
+    assert!(stderr.to_string().contains("Sent 2 of 2 secrets."));
+}
+
+#[test]
+fn batch_links_are_distinct() {
+    let input = write_input("in.csv", "recipient,value\na,same\nb,same\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().mock_create(created("x")).build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    let links: Vec<&str> = out
+        .lines()
+        .skip(1)
+        .map(|l| l.split(',').nth(3).unwrap())
+        .collect();
+    assert_eq!(links.len(), 2);
+    assert_ne!(links[0], links[1], "each row must get its own key");
+}
+
+#[test]
+fn batch_continues_past_failures() {
+    let input = write_input(
+        "in.ndjson",
+        "{\"recipient\":\"a\",\"value\":\"one\"}\n\
+         {\"recipient\":\"b\",\"value\":\"two\"}\n\
+         {\"recipient\":\"c\",\"value\":\"three\"}\n",
+    );
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_create_seq(vec![
+            created("id-a"),
+            Err("server error (500)".into()),
+            created("id-c"),
+        ])
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let err = stderr.to_string();
+    assert!(err.contains("b: server error (500)"), "stderr: {}", err);
+    assert!(
+        err.contains("Sent 2 of 3 secrets; 1 failed."),
+        "stderr: {}",
+        err
+    );
+
+    let out = stdout.to_string();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[2].ends_with(",server error (500)"), "{}", lines[2]);
+    assert!(lines[3].starts_with("c,,id-c,"), "{}", lines[3]);
+}
+
+#[test]
+fn batch_results_file_ndjson() {
+    let input = write_input("in.csv", CSV);
+    let results = input.with_file_name("links.jsonl");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().mock_create(created("id")).build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "send",
+            "--batch",
+            input.to_str().unwrap(),
+            "--results",
+            results.to_str().unwrap(),
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().is_empty());
+    assert!(stderr.to_string().contains("Results written to"));
+
+    let data = std::fs::read_to_string(&results).unwrap();
+    let rows: Vec<serde_json::Value> = data
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["recipient"], "alice@example.com");
+    assert_eq!(rows[0]["label"], "alice-db");
+    assert!(rows[0].get("password").is_none());
+    assert!(rows[1]["password"].is_string());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&results).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn batch_from_stdin_json_output() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"{\"recipient\":\"a\",\"value\":\"v\",\"label\":\"x\"}\n")
+        .mock_create(created("id-a"))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--batch", "-", "--json"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().is_empty(), "{}", stderr.to_string());
+    let row: serde_json::Value = serde_json::from_str(stdout.to_string().trim()).unwrap();
+    assert_eq!(row["id"], "id-a");
+    assert!(row["share_link"].as_str().unwrap().contains('#'));
+}
+
+#[test]
+fn batch_retries_rate_limited_rows() {
+    let input = write_input("in.csv", "recipient,value\na,v\n");
+    let slept = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_create_seq(vec![
+            Err("server error (429): rate limit exceeded".into()),
+            created("id-a"),
+        ])
+        .sleep_log(slept.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(slept.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn batch_invalid_input_sends_nothing() {
+    let input = write_input("in.csv", "recipient,value,ttl\na,v,1h\nb,v,forever\n");
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_create(created("id"))
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(
+        stderr.to_string().contains("line 3:"),
+        "{}",
+        stderr.to_string()
+    );
+    assert!(create_calls(&log).is_empty());
+}
+
+#[test]
+fn batch_conflicts_with_single_send_flags() {
+    for extra in [&["--text", "x"][..], &["--wait"], &["--label", "x"]] {
+        let mut a = vec!["secrt", "send", "--batch", "in.csv"];
+        a.extend_from_slice(extra);
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+        let code = cli::run(&args(&a), &mut deps);
+        assert_eq!(code, 2, "{:?}: {}", extra, stderr.to_string());
+    }
+}
+
+#[test]
+fn results_requires_batch() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "x", "--results", "out.csv"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("--results requires --batch"));
+}
+
+// --- get --batch ---
+
+fn real_rand(buf: &mut [u8]) -> Result<(), secrt::envelope::EnvelopeError> {
+    use ring::rand::{SecureRandom, SystemRandom};
+    SystemRandom::new()
+        .fill(buf)
+        .map_err(|_| secrt::envelope::EnvelopeError::RngError("SystemRandom failed".into()))
+}
+
+/// Seal `plaintext` as secret `id` and return (share_link, claim response).
+fn sealed(
+    id: &str,
+    plaintext: &[u8],
+    passphrase: &str,
+    file: Option<&str>,
+) -> (String, ClaimResponse) {
+    let result = envelope::seal(SealParams {
+        plaintext: plaintext.to_vec(),
+        passphrase: passphrase.to_string(),
+        rand_bytes: &real_rand,
+        hint: file.and_then(secrt::fileutil::build_file_hint),
+        iterations: if passphrase.is_empty() { 0 } else { 300_000 },
+    })
+    .unwrap();
+    let link = envelope::format_share_link(&format!("https://secrt.ca/s/{}", id), &result.url_key);
+    let resp = ClaimResponse {
+        envelope: result.envelope,
+        expires_at: "2099-01-01T00:00:00Z".into(),
+    };
+    (link, resp)
+}
+
+#[test]
+fn get_batch_ndjson_reports_each_link() {
+    let (link_a, resp_a) = sealed("id-a", b"alpha", "", None);
+    let (link_b, _) = sealed("id-b", b"beta", "", None);
+    let input = format!(
+        "# vendor credentials\n{}\n\n{}\nnot a link\n",
+        link_a, link_b
+    );
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(input.as_bytes())
+        .mock_claim_id("id-a", Ok(resp_a))
+        .mock_claim_id("id-b", Err("server error (404): not found".into()))
+        .build();
+    let code = cli::run(&args(&["secrt", "get", "--batch", "-"]), &mut deps);
+    assert_eq!(code, 1);
+
+    let rows: Vec<serde_json::Value> = stdout
+        .to_string()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["id"], "id-a");
+    assert_eq!(rows[0]["line"], 2);
+    assert_eq!(rows[0]["plaintext"], "alpha");
+    assert_eq!(rows[1]["id"], "id-b");
+    assert!(rows[1]["error"].as_str().unwrap().contains("404"));
+    assert_eq!(rows[2]["line"], 5);
+    assert!(rows[2]["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid share URL"));
+
+    let err = stderr.to_string();
+    assert!(
+        err.contains("Retrieved 1 of 3 secrets; 2 failed."),
+        "stderr: {}",
+        err
+    );
+}
+
+#[test]
+fn get_batch_tries_configured_passphrases() {
+    let (link_a, resp_a) = sealed("id-a", b"alpha", "team", None);
+    let (link_b, resp_b) = sealed("id-b", b"beta", "nobody-knows", None);
+    let input = write_input("links.txt", &format!("{}\n{}\n", link_a, link_b));
+    let home = helpers::config_home("decryption_passphrases = [\"old\", \"team\"]\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_claim_id("id-a", Ok(resp_a))
+        .mock_claim_id("id-b", Ok(resp_b))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "get", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let rows: Vec<serde_json::Value> = stdout
+        .to_string()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(rows[0]["plaintext"], "alpha");
+    assert!(rows[1]["error"]
+        .as_str()
+        .unwrap()
+        .contains("none of 2 configured passphrase(s) matched"));
+}
+
+#[test]
+fn get_batch_prompt_is_reused_for_later_links() {
+    let (link_a, resp_a) = sealed("id-a", b"alpha", "shared", None);
+    let (link_b, resp_b) = sealed("id-b", b"beta", "shared", None);
+    let input = write_input("links.txt", &format!("{}\n{}\n", link_a, link_b));
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .is_tty(true)
+        .read_pass(&["shared"])
+        .env("XDG_CONFIG_HOME", "/tmp/secrt_test_no_config")
+        .mock_claim_id("id-a", Ok(resp_a))
+        .mock_claim_id("id-b", Ok(resp_b))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "get", "--batch", input.to_str().unwrap()]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    assert!(out.contains("\"plaintext\":\"beta\""), "{}", out);
+}
+
+#[test]
+fn get_batch_output_dir() {
+    let (link_a, resp_a) = sealed("id-a", b"alpha", "", None);
+    let (link_b, resp_b) = sealed("id-b", b"key data", "", Some("prod.pem"));
+    let (link_c, resp_c) = sealed("id-c", b"other key", "", Some("prod.pem"));
+    let input = write_input(
+        "links.txt",
+        &format!("{}\n{}\n{}\n", link_a, link_b, link_c),
+    );
+    let out_dir = input.with_file_name("out");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim_id("id-a", Ok(resp_a))
+        .mock_claim_id("id-b", Ok(resp_b))
+        .mock_claim_id("id-c", Ok(resp_c))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            "--batch",
+            input.to_str().unwrap(),
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().is_empty(), "secrets must not hit stdout");
+    assert_eq!(std::fs::read(out_dir.join("id-a")).unwrap(), b"alpha");
+    assert_eq!(
+        std::fs::read(out_dir.join("prod.pem")).unwrap(),
+        b"key data"
+    );
+    assert_eq!(
+        std::fs::read(out_dir.join("prod (1).pem")).unwrap(),
+        b"other key"
+    );
+    assert!(stderr.to_string().contains("Retrieved 3 of 3 secrets."));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(out_dir.join("id-a"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn get_batch_output_dir_json_reports_paths() {
+    let (link_a, resp_a) = sealed("id-a", b"alpha", "", None);
+    let input = write_input("links.txt", &format!("{}\n", link_a));
+    let out_dir = input.with_file_name("out");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim_id("id-a", Ok(resp_a))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            "--batch",
+            input.to_str().unwrap(),
+            "--output-dir",
+            out_dir.to_str().unwrap(),
+            "--json",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().is_empty());
+    let row: serde_json::Value = serde_json::from_str(stdout.to_string().trim()).unwrap();
+    assert!(row["path"].as_str().unwrap().ends_with("id-a"));
+    assert!(row.get("plaintext").is_none());
+}
+
+#[test]
+fn get_batch_usage_errors() {
+    for (extra, msg) in [
+        (&["https://secrt.ca/s/x#k"][..], "cannot be combined"),
+        (&["-o", "out.txt"], "--output-dir"),
+    ] {
+        let mut a = vec!["secrt", "get", "--batch", "links.txt"];
+        a.extend_from_slice(extra);
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+        assert_eq!(cli::run(&args(&a), &mut deps), 2);
+        assert!(stderr.to_string().contains(msg), "{}", stderr.to_string());
+    }
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().stdin(b"# nothing\n").build();
+    assert_eq!(
+        cli::run(&args(&["secrt", "get", "--batch", "-"]), &mut deps),
+        2
+    );
+    assert!(stderr.to_string().contains("no links"));
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    assert_eq!(
+        cli::run(
+            &args(&["secrt", "get", "x", "--output-dir", "out"]),
+            &mut deps
+        ),
+        2
+    );
+    assert!(stderr.to_string().contains("--output-dir requires --batch"));
+}
@@ -1,8 +1,12 @@
 mod helpers;
 
-use helpers::{args, TestDepsBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use helpers::{args, config_home, TestDepsBuilder};
 use secrt::cli;
 use secrt::envelope::crypto::b64_encode;
+use secrt::history::{load_history, save_history, HistoryEntry};
 
 /// Non-routable address to ensure API calls fail
 const DEAD_URL: &str = "http://127.0.0.1:19191";
@@ -221,3 +225,455 @@ fn burn_success_tty_shows_checkmark() {
         err
     );
 }
+
+// --- Bulk burn from history / file ---
+
+fn history_file(home: &Path) -> PathBuf {
+    home.join("secrt").join("history.jsonl")
+}
+
+/// History with two live secrets (alice-db older, alice-web newer), one
+/// expired, and one already burned.
+fn seeded_home() -> PathBuf {
+    let home = config_home("history = true\n");
+    let now = chrono::Utc::now();
+    let iso = |d: chrono::Duration| (now + d).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let entry = |id: &str, label: &str, sent_h: i64, exp_h: i64, burned: bool| HistoryEntry {
+        id: id.into(),
+        base_url: "https://secrt.ca".into(),
+        label: label.into(),
+        size: 8,
+        created_at: iso(chrono::Duration::hours(-sent_h)),
+        expires_at: iso(chrono::Duration::hours(exp_h)),
+        passphrase: false,
+        burned_at: if burned {
+            iso(chrono::Duration::hours(-1))
+        } else {
+            String::new()
+        },
+    };
+    save_history(
+        &history_file(&home),
+        &[
+            entry("expired-1", "alice-old", 72, -48, false),
+            entry("alice-db", "alice-db", 5, 19, false),
+            entry("burned-1", "alice-vpn", 4, 20, true),
+            entry("alice-web", "alice-web", 2, 22, false),
+        ],
+    )
+    .unwrap();
+    home
+}
+
+fn burn_calls(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    log.lock()
+        .unwrap()
+        .iter()
+        .filter_map(|c| c.rsplit(':').next().map(String::from))
+        .collect()
+}
+
+#[test]
+fn burn_last_burns_most_recent_live_secret() {
+    let home = seeded_home();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "--last", "--yes", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(burn_calls(&log), vec!["alice-web"]);
+    assert!(stderr.to_string().contains("Burned 1 of 1 secret."));
+
+    let entries = load_history(&history_file(&home));
+    let web = entries.iter().find(|e| e.id == "alice-web").unwrap();
+    assert!(!web.burned_at.is_empty(), "history should record the burn");
+    let db = entries.iter().find(|e| e.id == "alice-db").unwrap();
+    assert!(db.burned_at.is_empty());
+}
+
+#[test]
+fn burn_label_prompts_and_confirms() {
+    let home = seeded_home();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .is_tty(true)
+        .stdin(b"y\n")
+        .mock_burn(Ok(()))
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--label",
+            "alice-*",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let err = stderr.to_string();
+    assert!(err.contains("Burn 2 secrets? [y/N]"), "stderr: {}", err);
+    // Expired and already-burned entries are skipped
+    assert_eq!(burn_calls(&log), vec!["alice-db", "alice-web"]);
+}
+
+#[test]
+fn burn_prompt_declined_burns_nothing() {
+    let home = seeded_home();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .is_tty(true)
+        .stdin(b"n\n")
+        .mock_burn(Ok(()))
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "--all-unexpired", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    assert!(stderr.to_string().contains("Aborted."));
+    assert!(burn_calls(&log).is_empty());
+}
+
+#[test]
+fn burn_bulk_without_tty_requires_yes() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "--all-unexpired", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(
+        stderr.to_string().contains("--yes"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[test]
+fn burn_sent_since_reports_partial_failure() {
+    let home = seeded_home();
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .mock_burn_id(
+            "alice-db",
+            Err("server error (404): secret not found or already claimed".into()),
+        )
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--sent-since",
+            "1d",
+            "--yes",
+            "--json",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 1, "stderr: {}", stderr.to_string());
+    let json: serde_json::Value = serde_json::from_str(stdout.to_string().trim()).unwrap();
+    assert_eq!(json["burned"], serde_json::json!(["alice-web"]));
+    assert_eq!(json["failed"][0]["id"], "alice-db");
+    assert!(json["failed"][0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("not found"));
+}
+
+#[test]
+fn burn_partial_failure_summary() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .mock_burn_id("alice-web", Err("server error (500)".into()))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--all-unexpired",
+            "-y",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let err = stderr.to_string();
+    assert!(
+        err.contains("alice-web: server error (500)"),
+        "stderr: {}",
+        err
+    );
+    assert!(
+        err.contains("Burned 1 of 2 secrets; 1 failed."),
+        "stderr: {}",
+        err
+    );
+}
+
+#[test]
+fn burn_bulk_failure_is_escaped() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .mock_burn_id(
+            "alice-web",
+            Err("server error (500): \x1b]0;pwned\x07".into()),
+        )
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--all-unexpired",
+            "-y",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let err = stderr.to_string();
+    assert!(!err.contains('\x1b'), "{:?}", err);
+    assert!(
+        err.contains("alice-web: server error (500): ^[]0;pwned^G"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn burn_from_stdin_ids_and_urls() {
+    let url = make_share_url("https://secrt.ca", "from-url");
+    let input = format!("# revoked\nplain-id\n\n{}\n", url);
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(input.as_bytes())
+        .mock_burn(Ok(()))
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--from-file",
+            "-",
+            "--yes",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let calls = log.lock().unwrap().clone();
+    assert_eq!(
+        calls,
+        vec![
+            "burn:https://secrt.ca:plain-id".to_string(),
+            "burn:https://secrt.ca:from-url".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn burn_from_file_refuses_other_servers() {
+    let url = make_share_url("https://other.example", "from-url");
+    let input = format!("plain-id\n{}\n", url);
+    let clients = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(input.as_bytes())
+        .mock_burn(Ok(()))
+        .mock_client_log(clients.clone())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--from-file",
+            "-",
+            "--yes",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    let err = stderr.to_string();
+    assert!(
+        err.contains("line 2: https://other.example is not on the active server"),
+        "{}",
+        err
+    );
+    assert!(clients.lock().unwrap().is_empty());
+}
+
+#[test]
+fn burn_history_only_selects_the_active_server() {
+    let home = seeded_home();
+    let now = chrono::Utc::now();
+    let iso = |h: i64| {
+        (now + chrono::Duration::hours(h)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    };
+    let mut entries = load_history(&history_file(&home));
+    entries.push(HistoryEntry {
+        id: "elsewhere".into(),
+        base_url: "https://other.example".into(),
+        label: "alice-other".into(),
+        size: 8,
+        created_at: iso(-1),
+        expires_at: iso(23),
+        passphrase: false,
+        burned_at: String::new(),
+    });
+    save_history(&history_file(&home), &entries).unwrap();
+
+    let clients = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .mock_client_log(clients.clone())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--all-unexpired",
+            "-y",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let err = stderr.to_string();
+    assert!(
+        err.contains("skipping 1 matching secret(s) sent to other servers"),
+        "{}",
+        err
+    );
+    assert!(!err.contains("elsewhere"), "{}", err);
+    assert!(clients
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(url, _)| url == "https://secrt.ca"));
+}
+
+#[test]
+fn burn_from_file_reports_bad_line() {
+    let dir = PathBuf::from(helpers::unique_temp_dir());
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("ids.txt");
+    std::fs::write(&path, "ok-id\nbad/url#short\n").unwrap();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_burn(Ok(())).build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "burn",
+            "--from-file",
+            path.to_str().unwrap(),
+            "--yes",
+            "--api-key",
+            "sk_test",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(
+        stderr.to_string().contains("line 2"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[test]
+fn burn_id_with_selector_conflicts() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "some-id", "--last", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("cannot be combined"));
+}
+
+#[test]
+fn burn_selector_with_no_matches() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "--label", "bob-*", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("Nothing to burn"));
+}
+
+#[test]
+fn burn_single_id_marks_history() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "burn", "alice-db", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let entries = load_history(&history_file(&home));
+    let db = entries.iter().find(|e| e.id == "alice-db").unwrap();
+    assert!(!db.burned_at.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn burn_does_not_run_passphrase_commands() {
+    let dir = PathBuf::from(helpers::unique_temp_dir());
+    std::fs::create_dir_all(&dir).unwrap();
+    let marker = dir.join("ran");
+    let touch = format!("\"touch {}\"", marker.display());
+    let home = config_home(&format!(
+        "api_key_command = {0}\npassphrase_command = {0}\n\
+         decryption_passphrases_command = {0}\n",
+        touch
+    ));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_burn(Ok(()))
+        .build();
+    // --api-key makes api_key_command unused too
+    let code = cli::run(
+        &args(&["secrt", "burn", "test-id-123", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(
+        !marker.exists(),
+        "burn must not run unused credential commands"
+    );
+}
@@ -0,0 +1,47 @@
+mod helpers;
+
+use helpers::{args, TestDepsBuilder};
+use secrt::cli;
+
+#[test]
+fn dev_server_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "help", "dev-server"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("--no-rate-limit"));
+}
+
+#[cfg(not(feature = "dev-server"))]
+#[test]
+fn dev_server_not_built() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "dev-server"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("--features dev-server"));
+}
+
+#[cfg(feature = "dev-server")]
+#[test]
+fn dev_server_rejects_bad_options() {
+    let dir = std::path::PathBuf::from(helpers::unique_temp_dir());
+    std::fs::create_dir_all(&dir).unwrap();
+    let bad_limits = dir.join("limits.json");
+    std::fs::write(&bad_limits, "{\"ttl\": 5}").unwrap();
+
+    for (flags, msg) in [
+        (vec!["--max-ttl", "forever"], "invalid --max-ttl"),
+        (vec!["--default-ttl", "2d", "--max-ttl", "1d"], "exceeds"),
+        (
+            vec!["--limits", bad_limits.to_str().unwrap()],
+            "invalid limits file",
+        ),
+        (vec!["--listen", "not-an-address"], "listen on"),
+    ] {
+        let mut a = vec!["secrt", "dev-server"];
+        a.extend_from_slice(&flags);
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+        let code = cli::run(&args(&a), &mut deps);
+        assert_ne!(code, 0, "{:?}", flags);
+        assert!(stderr.to_string().contains(msg), "{}", stderr.to_string());
+    }
+}
@@ -354,11 +354,149 @@ fn completion_no_arg() {
 #[test]
 fn completion_unknown() {
     let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
-    let code = cli::run(&args(&["secrt", "completion", "powershell"]), &mut deps);
+    let code = cli::run(&args(&["secrt", "completion", "tcsh"]), &mut deps);
     assert_eq!(code, 2);
     assert!(stderr.to_string().contains("unsupported shell"));
 }
 
+#[test]
+fn completion_new_shells() {
+    for (shell, marker) in [
+        ("powershell", "Register-ArgumentCompleter"),
+        ("elvish", "edit:completion:arg-completer[secrt]"),
+        ("nushell", "export extern \"secrt send\""),
+    ] {
+        let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+        let code = cli::run(&args(&["secrt", "completion", shell]), &mut deps);
+        assert_eq!(code, 0, "{}", shell);
+        assert!(stdout.to_string().contains(marker), "{}", shell);
+    }
+}
+
+#[test]
+fn complete_profiles_and_config_keys() {
+    let dir = setup_config("[profiles.work]\n[profiles.home]\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", dir.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "__complete", "profiles"]), &mut deps);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.to_string(), "home\nwork\n");
+
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "__complete", "config-keys"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stdout.to_string().lines().any(|l| l == "default_ttl"));
+}
+
+#[test]
+fn complete_recent_live_secrets() {
+    let dir = setup_config("");
+    let entry = |id: &str, expires: &str| {
+        format!(
+            "{{\"id\":\"{}\",\"base_url\":\"https://secrt.ca\",\"size\":1,\"created_at\":\"2026-01-01T00:00:00Z\",\"expires_at\":\"{}\"}}\n",
+            id, expires
+        )
+    };
+    let history = entry("old", "2099-01-01T00:00:00Z")
+        + &entry("expired", "2020-01-01T00:00:00Z")
+        + &entry("new", "2099-01-01T00:00:00Z");
+    fs::write(dir.join("secrt").join("history.jsonl"), history).unwrap();
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", dir.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "__complete", "secrets"]), &mut deps);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.to_string(), "new\nold\n");
+}
+
+#[test]
+fn complete_unknown_kind() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "__complete", "bogus"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stdout.to_string().is_empty());
+}
+
+// --- Man pages ---
+
+#[test]
+fn man_top_level() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "man"]), &mut deps);
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    assert!(out.starts_with(".TH \"SECRT\" \"1\""), "{}", out);
+    assert!(out.contains(".SH ENVIRONMENT\n"));
+    assert!(out.contains("SECRET_BASE_URL"));
+    assert!(out.contains(".SH EXIT STATUS\n"));
+    assert!(out.contains(".SH EXAMPLES\n"));
+    assert!(out.contains("\\fBsecrt\\-send\\fR(1)"));
+}
+
+#[test]
+fn man_every_command() {
+    for cmd in [
+        "send",
+        "get",
+        "burn",
+        "info",
+        "login",
+        "logout",
+        "list",
+        "watch",
+        "dev-server",
+        "gen",
+        "config",
+        "doctor",
+        "man",
+    ] {
+        let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+        let code = cli::run(&args(&["secrt", "man", cmd]), &mut deps);
+        assert_eq!(code, 0, "{}", cmd);
+        let out = stdout.to_string();
+        let th = format!(".TH \"SECRT-{}\" \"1\"", cmd.to_uppercase());
+        assert!(out.starts_with(&th), "{}", cmd);
+        assert!(out.contains(".SH EXIT STATUS\n"), "{}", cmd);
+        assert!(out.contains("SECRET_API_KEY"), "{}", cmd);
+    }
+}
+
+#[test]
+fn man_config_lists_keys() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "man", "config"]), &mut deps);
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    assert!(out.contains(".SH KEYS\n"));
+    assert!(out.contains("\\fBdecryption_passphrases_command\\fR"));
+}
+
+#[test]
+fn man_generate_alias() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "man", "generate"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stdout.to_string().starts_with(".TH \"SECRT-GEN\""));
+}
+
+#[test]
+fn man_unknown_command() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "man", "bogus"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stdout.to_string().is_empty());
+    assert!(stderr.to_string().contains("unknown command"));
+}
+
+#[test]
+fn man_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "man", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("secrt man"));
+}
+
 // --- Config display tests for new fields ---
 
 /// Helper to create a temp config dir with a config.toml containing the given TOML content.
@@ -400,7 +538,7 @@ fn config_shows_default_ttl() {
         err
     );
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "config should show source: {}",
         err
     );
@@ -681,6 +819,7 @@ fn mock_info_response(authenticated: bool) -> InfoResponse {
             requests_per_second: 1.0,
             burst: 10,
         },
+        quota: None,
     }
 }
 
@@ -861,7 +1000,7 @@ fn config_show_use_keychain_from_config() {
         err
     );
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "use_keychain source should be config file: {}",
         err
     );
@@ -879,7 +1018,7 @@ fn config_show_decryption_passphrases_from_keychain_and_config() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("keychain + config file"),
+        err.contains("keychain + user config"),
         "should show combined source: {}",
         err
     );
@@ -935,7 +1074,7 @@ fn config_show_passphrase_from_config_file() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "passphrase source should be config file: {}",
         err
     );
@@ -957,7 +1096,7 @@ fn config_show_api_key_from_config_file() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "api_key source should be config file: {}",
         err
     );
@@ -1009,7 +1148,7 @@ fn config_show_show_input_from_config() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("show_input") && err.contains("true") && err.contains("config file"),
+        err.contains("show_input") && err.contains("true") && err.contains("(user config)"),
         "show_input should show true from config file: {}",
         err
     );
@@ -1128,7 +1267,7 @@ fn config_show_api_key_from_keychain_fallback_to_config() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "api_key source should fall back to config file: {}",
         err
     );
@@ -1146,9 +1285,691 @@ fn config_show_passphrase_keychain_fallback_to_config() {
     assert_eq!(code, 0);
     let err = stderr.to_string();
     assert!(
-        err.contains("config file"),
+        err.contains("(user config)"),
         "passphrase should fall back to config file: {}",
         err
     );
     let _ = fs::remove_dir_all(&cfg_dir);
 }
+
+#[test]
+fn config_shows_active_profile_and_sources() {
+    let cfg_dir = setup_config(
+        "default_ttl = \"2h\"\n\
+         base_url = \"https://top.example\"\n\
+         default_profile = \"work\"\n\
+         [profiles.work]\n\
+         base_url = \"https://work.example\"\n\
+         [profiles.lab]\n\
+         default_ttl = \"5m\"\n",
+    );
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "config"]), &mut deps);
+    assert_eq!(code, 0);
+    let err = stderr.to_string();
+    assert!(err.contains("profile: work (default_profile)"), "{}", err);
+    assert!(
+        err.contains("base_url: https://work.example (user config, profile work)"),
+        "{}",
+        err
+    );
+    assert!(err.contains("default_ttl: 2h (user config)"), "{}", err);
+    assert!(err.contains("PROFILES"), "{}", err);
+    assert!(err.contains("(inherits base_url)"), "{}", err);
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .env("SECRT_PROFILE", "work")
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "--profile", "lab"]), &mut deps);
+    assert_eq!(code, 0);
+    let err = stderr.to_string();
+    assert!(err.contains("profile: lab (--profile)"), "{}", err);
+    assert!(
+        err.contains("default_ttl: 5m (user config, profile lab)"),
+        "{}",
+        err
+    );
+    assert!(
+        err.contains("base_url: https://top.example (user config)"),
+        "{}",
+        err
+    );
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "--profile", "nope"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("unknown profile"));
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_profile_reads_its_own_keychain_entries() {
+    let cfg_dir = setup_config("use_keychain = true\n[profiles.work]\n");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .keychain_secret("api_key", "sk_global_key")
+        .keychain_secret("profiles.work.api_key", "sk_work_key")
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "--profile", "work"]), &mut deps);
+    assert_eq!(code, 0);
+    let err = stderr.to_string();
+    assert!(err.contains("sk_work_"), "{}", err);
+    assert!(!err.contains("sk_globa"), "{}", err);
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+// --- config passphrases ---
+
+fn passphrase_deps(
+    cfg_dir: &std::path::Path,
+    store: &std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+    pass: &[&str],
+) -> (cli::Deps, helpers::SharedBuf, helpers::SharedBuf) {
+    TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .read_pass(pass)
+        .keychain_store(store.clone())
+        .build()
+}
+
+fn stored_list(
+    store: &std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+) -> Option<Vec<String>> {
+    store
+        .lock()
+        .unwrap()
+        .get("decryption_passphrases")
+        .map(|raw| serde_json::from_str(raw).unwrap())
+}
+
+#[test]
+fn config_passphrases_add_list_remove() {
+    let cfg_dir = setup_config("decryption_passphrases = [\"from-file\"]\n");
+    let store = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &["alpha", "alpha"]);
+    let code = cli::run(&args(&["secrt", "config", "passphrases", "add"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert!(err
+        .to_string()
+        .contains("Added decryption passphrase #1 to OS keychain"));
+    assert!(err.to_string().contains("Enabled use_keychain"));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &["beta", "beta"]);
+    assert_eq!(
+        cli::run(&args(&["secrt", "config", "passphrases", "add"]), &mut deps),
+        0
+    );
+    assert!(!err.to_string().contains("Enabled use_keychain"));
+    assert_eq!(stored_list(&store).unwrap(), vec!["alpha", "beta"]);
+
+    let (mut deps, out, _err) = passphrase_deps(&cfg_dir, &store, &[]);
+    assert_eq!(
+        cli::run(
+            &args(&["secrt", "config", "passphrases", "list"]),
+            &mut deps
+        ),
+        0
+    );
+    let listed = out.to_string();
+    assert!(listed.contains(" 1. \u{2022}"), "{}", listed);
+    assert!(listed.contains(" 2. \u{2022}"), "{}", listed);
+    assert!(!listed.contains("alpha"), "{}", listed);
+    assert!(listed.contains("+ 1 more in the config file"), "{}", listed);
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &[]);
+    assert_eq!(
+        cli::run(
+            &args(&["secrt", "config", "passphrases", "remove", "1"]),
+            &mut deps
+        ),
+        0
+    );
+    assert!(err.to_string().contains("Removed decryption passphrase #1"));
+    assert_eq!(stored_list(&store).unwrap(), vec!["beta"]);
+
+    // Removing the last entry deletes the keychain item
+    let (mut deps, _out, _err) = passphrase_deps(&cfg_dir, &store, &[]);
+    assert_eq!(
+        cli::run(
+            &args(&["secrt", "config", "passphrases", "remove", "1"]),
+            &mut deps
+        ),
+        0
+    );
+    assert!(stored_list(&store).is_none());
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_passphrases_promote_keeps_old_default() {
+    let cfg_dir = setup_config("use_keychain = true\n");
+    let store = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+    store
+        .lock()
+        .unwrap()
+        .insert("passphrase".into(), "old-default".into());
+    store.lock().unwrap().insert(
+        "decryption_passphrases".into(),
+        "[\"one\",\"new-default\",\"three\"]".into(),
+    );
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &[]);
+    let code = cli::run(
+        &args(&["secrt", "config", "passphrases", "promote", "2"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert!(err
+        .to_string()
+        .contains("Passphrase #2 is now the default passphrase; previous default kept as #2"));
+    assert_eq!(
+        store.lock().unwrap().get("passphrase").map(String::as_str),
+        Some("new-default")
+    );
+    assert_eq!(
+        stored_list(&store).unwrap(),
+        vec!["one", "old-default", "three"]
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_passphrases_errors() {
+    let cfg_dir = setup_config("use_keychain = true\n");
+    let store = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &[]);
+    let code = cli::run(
+        &args(&["secrt", "config", "passphrases", "remove", "1"]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("no decryption passphrases"));
+
+    store
+        .lock()
+        .unwrap()
+        .insert("decryption_passphrases".into(), "[\"a\"]".into());
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &[]);
+    let code = cli::run(
+        &args(&["secrt", "config", "passphrases", "promote", "5"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(err
+        .to_string()
+        .contains("invalid index \"5\" (expected 1-1)"));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &["a", "a"]);
+    let code = cli::run(&args(&["secrt", "config", "passphrases", "add"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("already in the list"));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &["x", "y"]);
+    let code = cli::run(&args(&["secrt", "config", "passphrases", "add"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("do not match"));
+
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &[]);
+    let code = cli::run(
+        &args(&["secrt", "config", "passphrases", "rotate"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(err.to_string().contains("unknown passphrases action"));
+    assert_eq!(stored_list(&store).unwrap(), vec!["a"]);
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_passphrases_profile_uses_scoped_entry() {
+    let cfg_dir = setup_config("use_keychain = true\n[profiles.work]\n");
+    let store = std::sync::Arc::new(std::sync::Mutex::new(std::collections::HashMap::new()));
+    let (mut deps, _out, err) = passphrase_deps(&cfg_dir, &store, &["team", "team"]);
+    let code = cli::run(
+        &args(&["secrt", "config", "passphrases", "add", "--profile", "work"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert_eq!(
+        store
+            .lock()
+            .unwrap()
+            .get("profiles.work.decryption_passphrases")
+            .map(String::as_str),
+        Some("[\"team\"]")
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+fn config_deps(cfg_dir: &std::path::Path) -> (cli::Deps, helpers::SharedBuf, helpers::SharedBuf) {
+    TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .build()
+}
+
+fn read_cfg(cfg_dir: &std::path::Path) -> String {
+    fs::read_to_string(cfg_dir.join("secrt").join("config.toml")).unwrap()
+}
+
+#[test]
+fn config_set_get_unset_roundtrip() {
+    let cfg_dir =
+        setup_config("# my defaults\ndefault_ttl = \"1h\" # short\n\n# Work\n[profiles.work]\n");
+
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&["secrt", "config", "set", "default_ttl", "2d"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert!(err.to_string().contains("Set default_ttl = \"2d\" in"));
+
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "config",
+            "set",
+            "profiles.work.base_url",
+            "https://work.example",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert_eq!(
+        read_cfg(&cfg_dir),
+        "# my defaults\ndefault_ttl = \"2d\"\n\n# Work\n[profiles.work]\nbase_url = \"https://work.example\"\n"
+    );
+
+    let (mut deps, out, _err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&["secrt", "config", "get", "base_url", "--profile", "work"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out.to_string(), "https://work.example\n");
+
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&["secrt", "config", "unset", "default_ttl"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+
+    let (mut deps, out, err) = config_deps(&cfg_dir);
+    let code = cli::run(&args(&["secrt", "config", "get", "default_ttl"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(out.to_string().is_empty());
+    assert!(err.to_string().contains("default_ttl is not set"));
+    assert!(read_cfg(&cfg_dir).starts_with("# my defaults\n\n# Work\n"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(cfg_dir.join("secrt").join("config.toml"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_set_validates_values() {
+    let cfg_dir = setup_config("history = true\n");
+    for (argv, want) in [
+        (vec!["default_ttl", "soon"], "invalid default_ttl"),
+        (
+            vec!["base_url", "secrt.example"],
+            "expected an http:// or https:// URL",
+        ),
+        (vec!["show_input", "yes"], "must be true or false"),
+        (vec!["default_profile", "nope"], "unknown profile \"nope\""),
+        (vec!["colour", "red"], "unknown config key \"colour\""),
+        (vec!["history"], "missing value for history"),
+        (vec!["profiles.work.default_profile", "work"], "top level"),
+    ] {
+        let (mut deps, _out, err) = config_deps(&cfg_dir);
+        let mut a = vec!["secrt", "config", "set"];
+        a.extend(argv);
+        let code = cli::run(&args(&a), &mut deps);
+        assert_eq!(code, 2, "{:?}", a);
+        assert!(
+            err.to_string().contains(want),
+            "{:?}: {}",
+            a,
+            err.to_string()
+        );
+    }
+    assert_eq!(read_cfg(&cfg_dir), "history = true\n");
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_set_refuses_secrets_with_keychain() {
+    let cfg_dir = setup_config("use_keychain = true\n");
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&["secrt", "config", "set", "api_key", "sk_live_x"]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let err = err.to_string();
+    assert!(err.contains("refusing to write api_key"), "{}", err);
+    assert!(err.contains("secrt login"), "{}", err);
+    assert_eq!(read_cfg(&cfg_dir), "use_keychain = true\n");
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_set_secret_masks_output() {
+    let cfg_dir = setup_config("");
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "config",
+            "set",
+            "decryption_passphrases",
+            "old",
+            "older",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert!(!err.to_string().contains("older"));
+
+    let (mut deps, out, _err) = config_deps(&cfg_dir);
+    let code = cli::run(
+        &args(&["secrt", "config", "get", "decryption_passphrases"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out.to_string().lines().count(), 2);
+    assert!(!out.to_string().contains("old"));
+    assert!(read_cfg(&cfg_dir).contains("decryption_passphrases = [\"old\", \"older\"]"));
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_show_labels_layers() {
+    let cfg_dir = setup_config("default_ttl = \"1d\"\n");
+    let repo = cfg_dir.join("repo");
+    fs::create_dir_all(repo.join("sub")).unwrap();
+    fs::write(
+        repo.join(".secrt.toml"),
+        "default_ttl = \"1h\"\napi_key = \"sk_leaked\"\n",
+    )
+    .unwrap();
+    let system = cfg_dir.join("system.toml");
+    fs::write(&system, "base_url = \"https://corp.example\"\n").unwrap();
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .env("SECRT_SYSTEM_CONFIG", system.to_str().unwrap())
+        .env("PWD", repo.join("sub").to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "config"]), &mut deps);
+    assert_eq!(code, 0);
+    let err = stderr.to_string();
+    assert!(err.contains("CONFIG FILES"), "{}", err);
+    assert!(err.contains("project config"), "{}", err);
+    assert!(err.contains(".secrt.toml"), "{}", err);
+    assert!(
+        err.contains("base_url: https://corp.example (system config)"),
+        "{}",
+        err
+    );
+    assert!(err.contains("default_ttl: 1h (project config)"), "{}", err);
+    assert!(err.contains("ignoring api_key"), "{}", err);
+    assert!(!err.contains("sk_leak"), "{}", err);
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_lock_then_unseal_on_load() {
+    let cfg_dir = setup_config("api_key = \"sk_live_locked\"\nhistory = true\n");
+
+    let (mut deps, _out, err) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .is_tty(true)
+        .read_pass(&["master", "master"])
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "lock"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert!(err.to_string().contains("Encrypted api_key in"));
+    let cfg = read_cfg(&cfg_dir);
+    assert!(!cfg.contains("sk_live_locked"), "{}", cfg);
+    assert!(cfg.contains("encrypted_secrets = "), "{}", cfg);
+
+    // No terminal and no SECRT_MASTER_PASSPHRASE: skipped with a warning
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(&args(&["secrt", "config"]), &mut deps);
+    assert_eq!(code, 0);
+    let err = err.to_string();
+    assert!(
+        err.contains("skipping encrypted_secrets; set SECRT_MASTER_PASSPHRASE"),
+        "{}",
+        err
+    );
+    assert!(!err.contains("sk_live_"), "{}", err);
+
+    // A wrong passphrase at the prompt can be retried
+    let (mut deps, _out, err) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .is_tty(true)
+        .read_pass(&["wrong", "master"])
+        .build();
+    let code = cli::run(&args(&["secrt", "config"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    let err = err.to_string();
+    assert!(err.contains("Wrong passphrase, try again."), "{}", err);
+    assert!(err.contains("api_key: sk_live_"), "{}", err);
+    assert!(err.contains("(user config)"), "{}", err);
+
+    let (mut deps, _out, err) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .env("SECRT_MASTER_PASSPHRASE", "master")
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "unlock"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", err.to_string());
+    assert_eq!(
+        read_cfg(&cfg_dir),
+        "history = true\napi_key = \"sk_live_locked\"\n"
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_lock_errors() {
+    let cfg_dir = setup_config("history = true\n");
+    let (mut deps, _out, err) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .env("SECRT_MASTER_PASSPHRASE", "master")
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "lock"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("no plaintext secrets to encrypt"));
+
+    let (mut deps, _out, err) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .env("SECRT_MASTER_PASSPHRASE", "master")
+        .build();
+    let code = cli::run(&args(&["secrt", "config", "unlock"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("has no encrypted secrets"));
+
+    // Without a terminal the passphrase must come from the environment
+    let (mut deps, _out, err) = config_deps(&cfg_dir);
+    let code = cli::run(&args(&["secrt", "config", "lock"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(err.to_string().contains("set SECRT_MASTER_PASSPHRASE"));
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+// --- Color and glyph control ---
+
+fn help_output(env: &[(&str, &str)], is_tty: bool, argv: &[&str]) -> (i32, String) {
+    let mut builder = TestDepsBuilder::new().is_tty(is_tty).is_stdout_tty(is_tty);
+    for (k, v) in env {
+        builder = builder.env(k, v);
+    }
+    let (mut deps, _stdout, stderr) = builder.build();
+    let code = cli::run(&args(argv), &mut deps);
+    (code, stderr.to_string())
+}
+
+#[test]
+fn no_color_env_turns_colors_off() {
+    let (code, err) = help_output(&[], true, &["secrt", "help"]);
+    assert_eq!(code, 0);
+    assert!(err.contains("\x1b["), "{}", err);
+    let (_, err) = help_output(&[("NO_COLOR", "1")], true, &["secrt", "help"]);
+    assert!(!err.contains("\x1b["), "{}", err);
+    let (_, err) = help_output(&[("CLICOLOR_FORCE", "1")], false, &["secrt", "help"]);
+    assert!(err.contains("\x1b["), "{}", err);
+}
+
+#[test]
+fn color_flag_overrides_environment() {
+    let (code, err) = help_output(
+        &[("NO_COLOR", "1")],
+        false,
+        &["secrt", "--color", "always", "help"],
+    );
+    assert_eq!(code, 0);
+    assert!(err.contains("\x1b[1m"), "{}", err);
+    let (code, err) = help_output(&[], true, &["secrt", "help", "send", "--color=never"]);
+    assert_eq!(code, 0);
+    assert!(!err.contains("\x1b["), "{}", err);
+    let (code, err) = help_output(&[], true, &["secrt", "help", "--color", "sometimes"]);
+    assert_eq!(code, 2);
+    assert!(err.contains("unknown --color \"sometimes\""), "{}", err);
+}
+
+#[test]
+fn style_flags_as_values_are_left_alone() {
+    let (code, err) = help_output(&[], false, &["secrt", "gen", "--count", "--plain"]);
+    assert_eq!(code, 2);
+    assert!(err.contains("\"--plain\""), "{}", err);
+}
+
+#[test]
+fn plain_uses_ascii_symbols() {
+    let dir = std::env::temp_dir().join("secrt_plain_init_test");
+    let _ = fs::remove_dir_all(&dir);
+    let (code, err) = help_output(
+        &[("XDG_CONFIG_HOME", dir.to_str().unwrap())],
+        true,
+        &["secrt", "config", "init", "--plain"],
+    );
+    assert_eq!(code, 0, "{}", err);
+    assert!(err.starts_with("[ok] Created config file"), "{}", err);
+    assert!(
+        !err.contains('\u{2713}') && !err.contains("\x1b["),
+        "{}",
+        err
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    for page in ["help", "config"] {
+        let (_, err) = help_output(&[], true, &["secrt", "--plain", "help", page]);
+        assert!(err.is_ascii(), "{}", err);
+    }
+}
+
+#[test]
+fn command_style_comes_from_the_loaded_config() {
+    let cfg_dir = setup_config("color = \"sometimes\"\nplain = true\n");
+    let burn = |extra: &[&str]| {
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+            .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+            .is_tty(true)
+            .mock_burn(Ok(()))
+            .build();
+        let mut argv = vec!["secrt", "burn", "abc123", "--api-key", "sk_test"];
+        argv.extend_from_slice(extra);
+        let code = cli::run(&args(&argv), &mut deps);
+        assert_eq!(code, 0);
+        stderr.to_string()
+    };
+    let err = burn(&[]);
+    assert!(err.contains("[ok] Secret burned."), "{:?}", err);
+    assert!(!err.contains("\x1b["), "{:?}", err);
+    assert_eq!(
+        err.matches("ignoring color \"sometimes\"").count(),
+        1,
+        "{}",
+        err
+    );
+
+    let err = burn(&["--color", "always"]);
+    assert!(
+        err.contains("\x1b[32m[ok]\x1b[0m Secret burned."),
+        "{:?}",
+        err
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_plain_beats_config_color() {
+    let cfg_dir = setup_config("color = \"always\"\nplain = true\n");
+    let xdg = cfg_dir.to_str().unwrap();
+    let (_, err) = help_output(&[("XDG_CONFIG_HOME", xdg)], true, &["secrt", "help"]);
+    assert!(!err.contains("\x1b["), "{:?}", err);
+    let (_, err) = help_output(
+        &[("XDG_CONFIG_HOME", xdg)],
+        true,
+        &["secrt", "help", "--color", "always"],
+    );
+    assert!(err.contains("\x1b["), "{:?}", err);
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn config_theme_and_plain() {
+    let cfg_dir = setup_config("plain = true\n[theme]\nheading = \"4;35\"\nbogus = \"1\"\n");
+    let xdg = cfg_dir.to_str().unwrap();
+    let (_, err) = help_output(&[("XDG_CONFIG_HOME", xdg)], true, &["secrt", "help"]);
+    assert!(!err.contains("\x1b["), "plain turns colors off: {}", err);
+    let (_, err) = help_output(
+        &[("XDG_CONFIG_HOME", xdg)],
+        true,
+        &["secrt", "help", "--color", "always"],
+    );
+    assert!(err.contains("\x1b[4;35mUSAGE"), "{}", err);
+    assert!(!err.contains("bogus"), "{}", err);
+
+    let (code, err) = help_output(&[("XDG_CONFIG_HOME", xdg)], false, &["secrt", "config"]);
+    assert_eq!(code, 0);
+    assert!(err.contains("ignoring theme.bogus"), "{}", err);
+
+    let (code, err) = help_output(
+        &[("XDG_CONFIG_HOME", xdg)],
+        false,
+        &[
+            "secrt",
+            "config",
+            "set",
+            "--profile",
+            "work",
+            "color",
+            "never",
+        ],
+    );
+    assert_eq!(code, 2, "{}", err);
+    assert!(
+        err.contains("color can only be set at the top level"),
+        "{}",
+        err
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
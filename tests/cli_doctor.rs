@@ -0,0 +1,278 @@
+mod helpers;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use helpers::{args, config_home, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::{InfoLimits, InfoRate, InfoResponse, InfoTTL, InfoTier};
+
+fn mock_info(authenticated: bool) -> InfoResponse {
+    let tier = InfoTier {
+        max_envelope_bytes: 262144,
+        max_secrets: 10,
+        max_total_bytes: 2097152,
+        rate: InfoRate {
+            requests_per_second: 0.5,
+            burst: 6,
+        },
+    };
+    InfoResponse {
+        authenticated,
+        ttl: InfoTTL {
+            default_seconds: 86400,
+            max_seconds: 604800,
+        },
+        limits: InfoLimits {
+            public: tier.clone(),
+            authed: tier,
+        },
+        claim_rate: InfoRate {
+            requests_per_second: 1.0,
+            burst: 10,
+        },
+        quota: None,
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn check<'a>(report: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == name)
+        .unwrap_or_else(|| panic!("no {} check in {}", name, report))
+}
+
+#[test]
+fn doctor_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "doctor", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("secrt doctor"));
+    assert!(stderr.to_string().contains("--fix"));
+}
+
+#[test]
+fn doctor_all_good() {
+    let home = config_home("default_ttl = \"1h\"\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .env("SECRET_API_KEY", "sk_live_ok")
+        .mock_info(Ok(mock_info(true)))
+        .mock_server_time(Ok(Some(now() - 2)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor"]), &mut deps);
+    let out = stdout.to_string();
+    assert_eq!(code, 0, "{}", out);
+    assert!(out.contains("\u{2713} connectivity"), "{}", out);
+    assert!(out.contains("reached https://secrt.ca"), "{}", out);
+    assert!(out.contains("accepted by the server"), "{}", out);
+    assert!(out.contains("of server time"), "{}", out);
+    if cfg!(feature = "keychain") {
+        assert!(out.contains("No problems found."), "{}", out);
+    }
+}
+
+#[test]
+fn doctor_unreachable_server_fails() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", "/tmp/secrt_test_no_config")
+        .mock_info(Err(
+            "cannot resolve host https://secrt.invalid: dns failed".into()
+        ))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "doctor",
+            "--json",
+            "--base-url",
+            "https://secrt.invalid",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    assert_eq!(report["ok"], false);
+    let conn = check(&report, "connectivity");
+    assert_eq!(conn["status"], "fail");
+    assert!(conn["fix"].as_str().unwrap().contains("DNS"));
+    assert_eq!(check(&report, "clock")["status"], "skip");
+    assert_eq!(check(&report, "config.user")["status"], "skip");
+}
+
+#[test]
+fn doctor_reports_rejected_key_and_clock_skew() {
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", "/tmp/secrt_test_no_config")
+        .mock_info(Ok(mock_info(false)))
+        .mock_server_time(Ok(Some(now() - 600)))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "doctor", "--json", "--api-key", "sk_bad"]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let key = check(&report, "api_key");
+    assert_eq!(key["status"], "fail");
+    assert!(key["fix"].as_str().unwrap().contains("secrt login"));
+    let clock = check(&report, "clock");
+    assert_eq!(clock["status"], "warn");
+    assert!(
+        clock["message"].as_str().unwrap().contains("ahead of"),
+        "{}",
+        clock
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_fix_tightens_config_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let home = config_home("api_key = \"sk_in_file\"\n");
+    let path = home.join("secrt").join("config.toml");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor"]), &mut deps);
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    assert!(out.contains("has permissions 0644"), "{}", out);
+    assert!(out.contains("fix: chmod 600"), "{}", out);
+    assert!(out.contains("secrt doctor --fix"), "{}", out);
+    // Reported by doctor itself, not repeated as a load warning
+    assert!(
+        !stderr.to_string().contains("warning:"),
+        "{}",
+        stderr.to_string()
+    );
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o644);
+
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--fix"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stdout.to_string().contains("permissions set to 0600"));
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn doctor_reports_config_parse_error() {
+    let home = config_home("default_ttl = \n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--json"]), &mut deps);
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let user = check(&report, "config.user");
+    assert_eq!(user["status"], "fail");
+    assert!(user["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("failed to parse"));
+}
+
+#[test]
+fn doctor_project_secrets_warn() {
+    let home = config_home("");
+    let repo = home.join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(repo.join(".secrt.toml"), "api_key = \"sk_committed\"\n").unwrap();
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .env("PWD", repo.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--json"]), &mut deps);
+    assert_eq!(code, 0);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let project = check(&report, "config.project");
+    assert_eq!(project["status"], "warn");
+    assert!(project["message"].as_str().unwrap().contains("api_key"));
+}
+
+#[cfg(feature = "keychain")]
+#[test]
+fn doctor_keychain_unavailable() {
+    let home = config_home("use_keychain = true\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .keychain_set_error("keychain set error: Platform secure storage failure")
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--json"]), &mut deps);
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let kc = check(&report, "keychain");
+    assert_eq!(kc["status"], "fail");
+    assert!(kc["message"]
+        .as_str()
+        .unwrap()
+        .contains("Platform secure storage failure"));
+}
+
+#[cfg(feature = "keychain")]
+#[test]
+fn doctor_keychain_lists_entries() {
+    let home = config_home("use_keychain = true\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .keychain_secret("api_key", "sk_live_kc")
+        .keychain_secret_list("decryption_passphrases", &["a", "b"])
+        .mock_info(Ok(mock_info(true)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--json"]), &mut deps);
+    assert_eq!(code, 0, "{}", stdout.to_string());
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let kc = check(&report, "keychain");
+    assert_eq!(kc["status"], "ok");
+    assert_eq!(
+        kc["message"],
+        "available; stored: api_key, 2 decryption passphrase(s)"
+    );
+    assert_eq!(check(&report, "api_key")["status"], "ok");
+}
+
+#[test]
+fn doctor_rejects_positional_args() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "doctor", "now"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("unexpected argument"));
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_reports_failing_credential_command() {
+    let home = config_home("api_key_command = \"exit 7\"\n");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .build();
+    let code = cli::run(&args(&["secrt", "doctor", "--json"]), &mut deps);
+    assert_eq!(code, 1);
+    let report: serde_json::Value = serde_json::from_str(&stdout.to_string()).unwrap();
+    let creds = check(&report, "credentials");
+    assert_eq!(creds["status"], "fail");
+    assert_eq!(
+        creds["message"],
+        "api_key_command: `exit 7` exited with status 7"
+    );
+}
@@ -1,11 +1,12 @@
 mod helpers;
 
 use std::fs;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use std::collections::HashMap;
 
-use helpers::{args, TestDepsBuilder};
+use helpers::{args, config_home, TestDepsBuilder};
 use secrt::cli;
 use secrt::client::ClaimResponse;
 use secrt::envelope::crypto::b64_encode;
@@ -251,6 +252,104 @@ fn get_success_with_passphrase() {
     assert_eq!(stdout.to_string(), "passphrase protected");
 }
 
+#[cfg(unix)]
+#[test]
+fn get_with_passphrase_cmd() {
+    let (share_link, seal_result) = seal_test_secret(b"from a helper", "helper-pass");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().mock_claim(Ok(mock_resp)).build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            &share_link,
+            "--passphrase-cmd",
+            "echo helper-pass",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(stdout.to_string(), "from a helper");
+}
+
+#[cfg(unix)]
+#[test]
+fn get_passphrase_cmd_failure() {
+    let (share_link, seal_result) = seal_test_secret(b"x", "helper-pass");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_claim(Ok(mock_resp)).build();
+    let code = cli::run(
+        &args(&["secrt", "get", &share_link, "--passphrase-cmd", "exit 2"]),
+        &mut deps,
+    );
+    assert_eq!(code, 1);
+    assert!(
+        stderr
+            .to_string()
+            .contains("--passphrase-cmd: `exit 2` exited with status 2"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn get_tries_decryption_passphrases_command() {
+    let (share_link, seal_result) = seal_test_secret(b"rotated", "second");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    let home = config_home("decryption_passphrases_command = \"printf 'first\\nsecond\\n'\"\n");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_claim(Ok(mock_resp))
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(stdout.to_string(), "rotated");
+}
+
+#[cfg(unix)]
+#[test]
+fn get_ignores_commands_in_project_config() {
+    let (share_link, seal_result) = seal_test_secret(b"x", "pw");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    let home = config_home("");
+    let repo = home.join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    let marker = home.join("ran");
+    fs::write(
+        repo.join(".secrt.toml"),
+        format!("passphrase_command = \"touch {}\"\n", marker.display()),
+    )
+    .unwrap();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .env("PWD", repo.to_str().unwrap())
+        .mock_claim(Ok(mock_resp))
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+    assert_eq!(code, 1);
+    let err = stderr.to_string();
+    assert!(
+        err.contains("ignoring passphrase_command in"),
+        "stderr: {}",
+        err
+    );
+    assert!(err.contains("project config files cannot run commands"));
+    assert!(!marker.exists(), "project config command must not run");
+}
+
 #[test]
 fn get_decryption_error() {
     let plaintext = b"will fail";
@@ -598,7 +697,7 @@ fn get_passphrase_conflicting_flags() {
         ]),
         &mut deps,
     );
-    assert_eq!(code, 1);
+    assert_eq!(code, 2);
     assert!(
         stderr.to_string().contains("at most one"),
         "stderr: {}",
@@ -1415,3 +1514,561 @@ fn get_tty_no_candidates_shows_notice() {
         err
     );
 }
+
+// --- Profiles ---
+
+#[test]
+fn get_picks_profile_matching_share_url_host() {
+    let plaintext = b"profile by host";
+    let (share_link, seal_result) = seal_test_secret(plaintext, "correct");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    let cfg_dir = setup_config(
+        "default_profile = \"work\"\n\
+         [profiles.work]\n\
+         base_url = \"https://secrets.example.com\"\n\
+         decryption_passphrases = [\"wrong\"]\n\
+         [profiles.public]\n\
+         base_url = \"https://SECRT.ca\"\n\
+         decryption_passphrases = [\"correct\"]\n",
+    );
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .is_tty(false)
+        .mock_claim(Ok(mock_resp))
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(stdout.to_string(), "profile by host");
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn get_unknown_profile_is_usage_error() {
+    let cfg_dir = setup_config("[profiles.work]\nbase_url = \"https://secrets.example.com\"\n");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            &make_share_url("https://secrt.ca", "abc"),
+            "--profile",
+            "nope",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    let err = stderr.to_string();
+    assert!(
+        err.contains("unknown profile \"nope\" (available: work)"),
+        "stderr: {}",
+        err
+    );
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+// --- Clipboard ---
+
+fn claim_of(plaintext: &[u8]) -> (String, ClaimResponse) {
+    let (share_link, seal_result) = seal_test_secret(plaintext, "");
+    let resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T00:00:00Z".into(),
+    };
+    (share_link, resp)
+}
+
+#[test]
+fn get_copy_skips_stdout_and_schedules_clear() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let clip = Arc::new(Mutex::new(b"old".to_vec()));
+    let clears = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim(Ok(resp))
+        .clipboard(clip.clone())
+        .clipboard_clear_log(clears.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link, "--copy"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().is_empty());
+    assert_eq!(*clip.lock().unwrap(), b"hunter2");
+    assert_eq!(*clears.lock().unwrap(), vec![Duration::from_secs(45)]);
+    assert!(stderr
+        .to_string()
+        .contains("Copied to clipboard (clears in 45s)"));
+}
+
+#[test]
+fn get_copy_uses_configured_clear_delay() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let cfg_dir = config_home("clipboard_clear = \"2m\"\n");
+    let clears = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+        .mock_claim(Ok(resp))
+        .clipboard_clear_log(clears.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "get", &share_link, "--copy", "--json"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(*clears.lock().unwrap(), vec![Duration::from_secs(120)]);
+    let out: serde_json::Value = serde_json::from_str(stdout.to_string().trim()).unwrap();
+    assert_eq!(out["copied"], true);
+    assert_eq!(out["clear_after_seconds"], 120);
+    assert!(out.get("plaintext").is_none());
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn get_copy_checks_clipboard_before_claiming() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_call_log(log.clone())
+        .clipboard_error("no clipboard available")
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            &make_share_url("https://secrt.ca", "abc"),
+            "--copy",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("clipboard unavailable"));
+    assert!(log.lock().unwrap().is_empty(), "secret must not be claimed");
+}
+
+#[test]
+fn get_copy_check_leaves_clipboard_alone() {
+    let clip = Arc::new(Mutex::new(b"user data".to_vec()));
+    let (mut deps, _stdout, _stderr) = TestDepsBuilder::new()
+        .clipboard(clip.clone())
+        .clipboard_error("must not be touched")
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            &make_share_url("https://secrt.ca", "abc"),
+            "--copy",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert_eq!(*clip.lock().unwrap(), b"user data");
+}
+
+#[test]
+fn get_copy_failure_does_not_print_to_pipe() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim(Ok(resp))
+        .clipboard_copy_error("xclip failed")
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link, "--copy"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.to_string().is_empty(),
+        "stdout: {}",
+        stdout.to_string()
+    );
+    assert!(stderr.to_string().contains("could not copy to clipboard"));
+}
+
+#[test]
+fn get_copy_failure_reveals_on_terminal() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim(Ok(resp))
+        .clipboard_copy_error("xclip failed")
+        .is_tty(true)
+        .is_stdout_tty(true)
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link, "--copy"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let out = stdout.to_string();
+    assert!(
+        out.starts_with("\x1b[?1049h"),
+        "not on the alternate screen: {:?}",
+        out
+    );
+    assert!(out.contains("hunter2"));
+    assert!(stderr.to_string().contains("showing it instead"));
+}
+
+#[test]
+fn get_copy_conflicts_with_output() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "get", "https://x/s/a#b", "--copy", "-o", "f"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr
+        .to_string()
+        .contains("--copy cannot be combined with --output"));
+}
+
+#[test]
+fn clipboard_clear_only_clears_our_value() {
+    let fp = secrt::clipboard::fingerprint(b"hunter2");
+    for (current, cleared) in [(&b"hunter2"[..], true), (&b"something else"[..], false)] {
+        let clip = Arc::new(Mutex::new(current.to_vec()));
+        let slept = Arc::new(Mutex::new(Vec::new()));
+        let (mut deps, _stdout, _stderr) = TestDepsBuilder::new()
+            .stdin(fp.as_bytes())
+            .clipboard(clip.clone())
+            .sleep_log(slept.clone())
+            .build();
+        let code = cli::run(
+            &args(&["secrt", "__clipboard-clear", "auto", "45"]),
+            &mut deps,
+        );
+        assert_eq!(code, 0);
+        assert_eq!(*slept.lock().unwrap(), vec![Duration::from_secs(45)]);
+        assert_eq!(clip.lock().unwrap().is_empty(), cleared);
+    }
+}
+
+fn claim_formatted(plaintext: &[u8], extra: &[&str]) -> (i32, String, String) {
+    let (share_link, seal_result) = seal_test_secret(plaintext, "");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T12:00:00Z".into(),
+    };
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().mock_claim(Ok(mock_resp)).build();
+    let mut argv = vec!["secrt", "get", &share_link];
+    argv.extend(extra);
+    let code = cli::run(&args(&argv), &mut deps);
+    (code, stdout.to_string(), stderr.to_string())
+}
+
+#[test]
+fn get_format_env_with_name() {
+    let (code, out, err) = claim_formatted(b"s3cret", &["--format", "env", "--name", "DB_PASS"]);
+    assert_eq!(code, 0, "stderr: {}", err);
+    assert_eq!(out, "DB_PASS=s3cret\n");
+
+    let (_, out, _) = claim_formatted(b"it's a $secret", &["--format", "env"]);
+    assert_eq!(out, "SECRT_SECRET=\"it's a \\$secret\"\n");
+}
+
+#[test]
+fn get_format_shell_quotes() {
+    let (code, out, err) = claim_formatted(b"a b'c\n", &["--format", "shell"]);
+    assert_eq!(code, 0, "stderr: {}", err);
+    assert_eq!(out, "export SECRT_SECRET='a b'\\''c\n'\n");
+}
+
+#[test]
+fn get_format_yaml_and_template() {
+    let (code, out, err) = claim_formatted(b"line1\nline2", &["--format", "yaml"]);
+    assert_eq!(code, 0, "stderr: {}", err);
+    assert_eq!(
+        out,
+        "expires_at: \"2026-02-09T12:00:00Z\"\nplaintext: \"line1\\nline2\"\n"
+    );
+
+    let (code, out, _) = claim_formatted(b"hunter2", &["--template", "pw: {{value}}"]);
+    assert_eq!(code, 0);
+    assert_eq!(out, "pw: hunter2\n");
+}
+
+#[test]
+fn get_format_env_binary_is_base64() {
+    let (code, out, err) = claim_formatted(&[0x80, 0xFF], &["--format", "env"]);
+    assert_eq!(code, 0);
+    assert_eq!(out, "SECRT_SECRET=gP8=\n");
+    assert!(err.contains("base64-encoded"), "{}", err);
+}
+
+#[test]
+fn get_format_checked_before_claiming() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_call_log(log.clone()).build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "get",
+            &make_share_url("https://secrt.ca", "abc"),
+            "--template",
+            "{{plaintext}}",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("unknown template field"));
+    assert!(log.lock().unwrap().is_empty(), "secret must not be claimed");
+}
+
+fn claim_exec(
+    plaintext: &[u8],
+    extra: &[&str],
+    exit: i32,
+) -> (i32, Vec<helpers::RunCommand>, String) {
+    let (share_link, seal_result) = seal_test_secret(plaintext, "");
+    let mock_resp = ClaimResponse {
+        envelope: seal_result.envelope,
+        expires_at: "2026-02-09T12:00:00Z".into(),
+    };
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim(Ok(mock_resp))
+        .command_log(log.clone(), exit)
+        .build();
+    let mut argv = vec!["secrt", "get", &share_link];
+    argv.extend(extra);
+    let code = cli::run(&args(&argv), &mut deps);
+    assert_eq!(stdout.to_string(), "", "--exec must not write to stdout");
+    let commands = log.lock().unwrap().clone();
+    (code, commands, stderr.to_string())
+}
+
+fn vars(list: &[(&str, &str)]) -> Vec<(String, String)> {
+    list.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn get_exec_named_variable() {
+    let (code, commands, err) = claim_exec(
+        b"hunter2\n",
+        &["--exec", "--env", "PGPASSWORD", "--", "psql", "-h", "db"],
+        0,
+    );
+    assert_eq!(code, 0, "stderr: {}", err);
+    assert_eq!(
+        commands,
+        vec![(
+            args(&["psql", "-h", "db"]),
+            vars(&[("PGPASSWORD", "hunter2")])
+        )]
+    );
+}
+
+#[test]
+fn get_exec_dotenv_keys_and_exit_code() {
+    let (code, commands, _) = claim_exec(
+        b"DB_USER=app\nDB_PASS='p w'\n",
+        &["--exec", "--dotenv", "--", "./migrate"],
+        42,
+    );
+    assert_eq!(code, 42, "the command's exit code is passed through");
+    assert_eq!(
+        commands[0].1,
+        vars(&[("DB_USER", "app"), ("DB_PASS", "p w")])
+    );
+}
+
+#[test]
+fn get_exec_plain_secret_uses_default_variable() {
+    let (code, commands, err) = claim_exec(b"hunter2", &["--exec", "--", "env"], 0);
+    assert_eq!(code, 0);
+    assert_eq!(commands[0].1, vars(&[("SECRT_SECRET", "hunter2")]));
+    assert!(err.is_empty(), "{}", err);
+}
+
+#[test]
+fn get_exec_dotenv_secret_needs_opt_in() {
+    let (code, commands, err) = claim_exec(b"PATH=/tmp/evil\n", &["--exec", "--", "env"], 0);
+    assert_eq!(code, 0);
+    assert_eq!(commands[0].1, vars(&[("SECRT_SECRET", "PATH=/tmp/evil")]));
+    assert!(err.contains("use --dotenv"), "{}", err);
+}
+
+#[test]
+fn get_exec_dotenv_refuses_code_running_names() {
+    for name in [
+        "PATH",
+        "LD_PRELOAD",
+        "DYLD_INSERT_LIBRARIES",
+        "BASH_ENV",
+        "NODE_OPTIONS",
+    ] {
+        let secret = format!("DB_USER=app\n{}=/tmp/evil\n", name);
+        let (code, commands, err) =
+            claim_exec(secret.as_bytes(), &["--exec", "--dotenv", "--", "env"], 0);
+        assert_eq!(code, 1, "{}", name);
+        assert!(commands.is_empty(), "{}: the command must not run", name);
+        assert!(
+            err.contains(&format!("refusing to set {} from the secret", name)),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn get_exec_checked_before_claiming() {
+    for (argv, msg) in [
+        (&["--exec"][..], "--exec requires a command after --"),
+        (
+            &["--exec", "--env", "A-B", "--", "env"][..],
+            "invalid variable name",
+        ),
+        (&["--env", "X"][..], "--env requires --exec"),
+        (&["--dotenv"][..], "--dotenv requires --exec"),
+        (
+            &["--exec", "--env", "X", "--dotenv", "--", "env"][..],
+            "--env cannot be combined with --dotenv",
+        ),
+        (
+            &["--exec", "--json", "--", "env"][..],
+            "--exec cannot be combined with --json",
+        ),
+    ] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_call_log(log.clone()).build();
+        let url = make_share_url("https://secrt.ca", "abc");
+        let mut full = vec!["secrt", "get", &url];
+        full.extend(argv);
+        assert_eq!(cli::run(&args(&full), &mut deps), 2);
+        assert!(stderr.to_string().contains(msg), "{}", stderr.to_string());
+        assert!(log.lock().unwrap().is_empty(), "secret must not be claimed");
+    }
+}
+
+#[test]
+fn get_reveal_uses_alternate_screen_until_a_key() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .is_stdout_tty(true)
+        .mock_claim(Ok(resp))
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link, "--reveal"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let out = stdout.to_string();
+    assert!(out.starts_with("\x1b[?1049h"), "{:?}", out);
+    assert!(out.ends_with("\x1b[?1049l"), "{:?}", out);
+    assert!(out.contains("hunter2\n"));
+    let err = stderr.to_string();
+    assert!(err.contains("Press any key to hide"), "{}", err);
+    assert!(err.contains("Secret hidden"), "{}", err);
+}
+
+#[test]
+fn get_reveal_for_counts_down() {
+    let (share_link, resp) = claim_of(b"hunter2");
+    let slept = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .is_stdout_tty(true)
+        .sleep_log(slept.clone())
+        .mock_claim(Ok(resp))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "get", &share_link, "--reveal-for", "3s"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(slept.lock().unwrap().len(), 3);
+    assert!(stderr.to_string().contains("Hiding in 3s"));
+    assert!(stdout.to_string().ends_with("\x1b[?1049l"));
+}
+
+#[test]
+fn get_reveal_from_config_only_on_a_terminal() {
+    let cfg_dir = config_home("reveal = true\nreveal_for = \"2s\"\n");
+    for (tty, revealed) in [(true, true), (false, false)] {
+        let (share_link, resp) = claim_of(b"hunter2");
+        let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+            .env("XDG_CONFIG_HOME", cfg_dir.to_str().unwrap())
+            .is_stdout_tty(tty)
+            .mock_claim(Ok(resp))
+            .build();
+        let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+        assert_eq!(code, 0);
+        let out = stdout.to_string();
+        assert_eq!(out.contains("\x1b[?1049h"), revealed, "{:?}", out);
+        if !revealed {
+            assert_eq!(out, "hunter2");
+        }
+    }
+    let _ = fs::remove_dir_all(&cfg_dir);
+}
+
+#[test]
+fn get_reveal_checked_before_claiming() {
+    for (argv, msg) in [
+        (&["--reveal-for", "soon"][..], "invalid --reveal-for"),
+        (
+            &["--reveal", "--json"][..],
+            "--reveal cannot be combined with --json",
+        ),
+    ] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_call_log(log.clone()).build();
+        let url = make_share_url("https://secrt.ca", "abc");
+        let mut full = vec!["secrt", "get", &url];
+        full.extend(argv);
+        assert_eq!(cli::run(&args(&full), &mut deps), 2);
+        assert!(stderr.to_string().contains(msg), "{}", stderr.to_string());
+        assert!(log.lock().unwrap().is_empty(), "secret must not be claimed");
+    }
+}
+
+#[test]
+fn get_tty_escapes_control_sequences() {
+    let secret = b"pw\x1b]52;c;ZXZpbA==\x07\x1b[2J";
+    let (share_link, resp) = claim_of(secret);
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .is_stdout_tty(true)
+        .mock_claim(Ok(resp))
+        .build();
+    let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+    assert_eq!(code, 0);
+    assert_eq!(stdout.to_string(), "pw^[]52;c;ZXZpbA==^G^[[2J\n");
+    assert!(stderr.to_string().contains("use --raw"));
+}
+
+#[test]
+fn get_raw_and_piped_output_are_byte_exact() {
+    let secret = b"pw\x1b[31mred\r";
+    for (tty, flags) in [(true, &["--raw"][..]), (false, &[][..])] {
+        let (share_link, resp) = claim_of(secret);
+        let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+            .is_stdout_tty(tty)
+            .mock_claim(Ok(resp))
+            .build();
+        let mut argv = vec!["secrt", "get", &share_link];
+        argv.extend(flags);
+        assert_eq!(cli::run(&args(&argv), &mut deps), 0);
+        assert!(stdout.to_string().starts_with("pw\x1b[31mred\r"));
+        assert!(!stderr.to_string().contains("control characters"));
+    }
+}
+
+#[test]
+fn get_tty_escapes_formatted_output() {
+    let (share_link, resp) = claim_of(b"a\x1b[Hb");
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .is_stdout_tty(true)
+        .mock_claim(Ok(resp))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "get", &share_link, "--template", "{{value}}"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(stdout.to_string(), "a^[[Hb\n");
+}
+
+#[test]
+fn get_error_from_server_is_escaped() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_claim(Err("server error (404): \x1b]0;pwned\x07".into()))
+        .build();
+    let url = make_share_url("https://secrt.ca", "abc");
+    assert_eq!(cli::run(&args(&["secrt", "get", &url]), &mut deps), 1);
+    let err = stderr.to_string();
+    assert!(!err.contains('\x1b'), "{:?}", err);
+    assert!(err.contains("^[]0;pwned^G"), "{:?}", err);
+}
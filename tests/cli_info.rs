@@ -0,0 +1,166 @@
+mod helpers;
+
+use helpers::{args, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::{InfoLimits, InfoQuota, InfoRate, InfoResponse, InfoTTL, InfoTier};
+
+fn mock_info(authenticated: bool, quota: Option<InfoQuota>) -> InfoResponse {
+    InfoResponse {
+        authenticated,
+        ttl: InfoTTL {
+            default_seconds: 86400,
+            max_seconds: 604800,
+        },
+        limits: InfoLimits {
+            public: InfoTier {
+                max_envelope_bytes: 262144,
+                max_secrets: 10,
+                max_total_bytes: 2097152,
+                rate: InfoRate {
+                    requests_per_second: 0.5,
+                    burst: 6,
+                },
+            },
+            authed: InfoTier {
+                max_envelope_bytes: 1048576,
+                max_secrets: 0,
+                max_total_bytes: 20971520,
+                rate: InfoRate {
+                    requests_per_second: 2.0,
+                    burst: 20,
+                },
+            },
+        },
+        claim_rate: InfoRate {
+            requests_per_second: 1.0,
+            burst: 10,
+        },
+        quota,
+    }
+}
+
+#[test]
+fn info_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "info", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("secrt info"));
+}
+
+#[test]
+fn help_info() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "help", "info"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("--json"));
+}
+
+#[test]
+fn info_unknown_flag() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "info", "--bogus"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("unknown flag"));
+}
+
+#[test]
+fn info_human_output() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info(false, None)))
+        .build();
+    let code = cli::run(&args(&["secrt", "info"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let out = stdout.to_string();
+    assert!(out.contains("base_url: https://secrt.ca"), "out: {}", out);
+    assert!(
+        out.contains("authenticated: no (no API key)"),
+        "out: {}",
+        out
+    );
+    assert!(out.contains("default_ttl: 1d"), "out: {}", out);
+    assert!(out.contains("max_ttl: 7d"), "out: {}", out);
+    assert!(out.contains("PUBLIC LIMITS"), "out: {}", out);
+    assert!(out.contains("AUTHED LIMITS"), "out: {}", out);
+    assert!(out.contains("max_envelope: 256 KB"), "out: {}", out);
+    assert!(out.contains("max_envelope: 1 MB"), "out: {}", out);
+    assert!(out.contains("max_secrets: unlimited"), "out: {}", out);
+    assert!(out.contains("create_rate: 0.5/s burst 6"), "out: {}", out);
+    assert!(out.contains("claim_rate: 1/s burst 10"), "out: {}", out);
+    assert!(!out.contains("QUOTA"), "no quota reported: {}", out);
+}
+
+#[test]
+fn info_shows_quota_when_reported() {
+    let quota = InfoQuota {
+        secrets_remaining: Some(42),
+        bytes_remaining: Some(1048576),
+    };
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("SECRET_API_KEY", "sk_test")
+        .mock_info(Ok(mock_info(true, Some(quota))))
+        .build();
+    let code = cli::run(&args(&["secrt", "info"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let out = stdout.to_string();
+    assert!(out.contains("authenticated: yes"), "out: {}", out);
+    assert!(out.contains("QUOTA"), "out: {}", out);
+    assert!(out.contains("secrets_remaining: 42"), "out: {}", out);
+    assert!(out.contains("bytes_remaining: 1 MB"), "out: {}", out);
+}
+
+#[test]
+fn info_json_output() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info(false, None)))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "info", "--json", "--base-url", "https://x.example"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let json: serde_json::Value =
+        serde_json::from_str(stdout.to_string().trim()).expect("invalid JSON output");
+    assert_eq!(json["base_url"], "https://x.example");
+    assert_eq!(json["authenticated"], false);
+    assert_eq!(json["ttl"]["max_seconds"], 604800);
+    assert_eq!(json["limits"]["public"]["max_envelope_bytes"], 262144);
+    assert_eq!(json["limits"]["authed"]["rate"]["burst"], 20);
+    assert_eq!(json["claim_rate"]["burst"], 10);
+    assert!(json.get("quota").is_none());
+}
+
+#[test]
+fn info_server_unreachable() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Err("connection refused".into()))
+        .build();
+    let code = cli::run(&args(&["secrt", "info"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(stdout.to_string().is_empty());
+    assert!(
+        stderr
+            .to_string()
+            .contains("info failed: connection refused"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[test]
+fn info_rejected_api_key_exits_1() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info(false, None)))
+        .build();
+    let code = cli::run(&args(&["secrt", "info", "--api-key", "sk_bad"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(
+        stdout.to_string().contains("no (API key rejected)"),
+        "stdout: {}",
+        stdout.to_string()
+    );
+    assert!(
+        stderr.to_string().contains("did not accept the API key"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
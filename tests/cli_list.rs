@@ -0,0 +1,253 @@
+mod helpers;
+
+use std::fs;
+use std::path::Path;
+
+use helpers::{args, config_home, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::CreateResponse;
+use secrt::history::{load_history, save_history, HistoryEntry};
+
+fn history_file(home: &Path) -> std::path::PathBuf {
+    home.join("secrt").join("history.jsonl")
+}
+
+fn entry(id: &str, label: &str, created: &str, expires: &str) -> HistoryEntry {
+    HistoryEntry {
+        id: id.into(),
+        base_url: "https://secrt.ca".into(),
+        label: label.into(),
+        size: 42,
+        created_at: created.into(),
+        expires_at: expires.into(),
+        passphrase: false,
+        burned_at: String::new(),
+    }
+}
+
+fn seeded_home() -> std::path::PathBuf {
+    let home = config_home("history = true\n");
+    let now = chrono::Utc::now();
+    let iso = |d: chrono::Duration| (now + d).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    save_history(
+        &history_file(&home),
+        &[
+            entry(
+                "old-expired",
+                "web-staging",
+                &iso(-chrono::Duration::days(20)),
+                &iso(-chrono::Duration::days(19)),
+            ),
+            entry(
+                "db-active",
+                "db-prod",
+                &iso(-chrono::Duration::hours(2)),
+                &iso(chrono::Duration::hours(22)),
+            ),
+            entry(
+                "db-expired",
+                "db-old",
+                &iso(-chrono::Duration::days(3)),
+                &iso(-chrono::Duration::days(2)),
+            ),
+        ],
+    )
+    .unwrap();
+    home
+}
+
+fn list_json(home: &Path, extra: &[&str]) -> Vec<serde_json::Value> {
+    let mut a = vec!["secrt", "list", "--json"];
+    a.extend_from_slice(extra);
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&a), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    serde_json::from_str(&stdout.to_string()).unwrap()
+}
+
+fn ids(v: &[serde_json::Value]) -> Vec<&str> {
+    v.iter().map(|e| e["id"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn list_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "list", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("secrt list"));
+    assert!(stderr.to_string().contains("--sent-since"));
+}
+
+#[test]
+fn list_empty_history_hints_at_config() {
+    let home = config_home("");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "list"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stdout.to_string().is_empty());
+    assert!(
+        stderr.to_string().contains("history = true"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[test]
+fn list_json_newest_first_with_status() {
+    let home = seeded_home();
+    let out = list_json(&home, &[]);
+    assert_eq!(ids(&out), vec!["db-expired", "db-active", "old-expired"]);
+    assert_eq!(out[1]["status"], "active");
+    assert_eq!(out[0]["status"], "expired");
+    assert_eq!(out[1]["label"], "db-prod");
+    assert_eq!(out[1]["size"], 42);
+}
+
+#[test]
+fn list_filters() {
+    let home = seeded_home();
+    assert_eq!(
+        ids(&list_json(&home, &["--label", "db-*"])),
+        vec!["db-expired", "db-active"]
+    );
+    assert_eq!(ids(&list_json(&home, &["--unexpired"])), vec!["db-active"]);
+    assert_eq!(
+        ids(&list_json(&home, &["--sent-since", "7d"])),
+        vec!["db-expired", "db-active"]
+    );
+    assert!(list_json(&home, &["--base-url", "https://other.example"]).is_empty());
+}
+
+#[test]
+fn list_invalid_sent_since() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "list", "--sent-since", "soon"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("--sent-since"));
+}
+
+#[test]
+fn list_human_table() {
+    let home = seeded_home();
+    let (mut deps, stdout, _stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "list"]), &mut deps);
+    assert_eq!(code, 0);
+    let out = stdout.to_string();
+    assert!(out.starts_with("ID"), "out: {}", out);
+    assert!(out.contains("STATUS"));
+    assert!(out.contains("db-prod"));
+    assert!(out.contains("42 bytes"));
+    assert!(out.contains("active"));
+}
+
+#[test]
+fn list_prune_removes_expired() {
+    let home = seeded_home();
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "list", "--prune"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(
+        stderr.to_string().contains("Pruned 2 expired entries"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+    let remaining = load_history(&history_file(&home));
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].id, "db-active");
+}
+
+#[test]
+fn send_records_history_without_url_key() {
+    let home = config_home("history = true\n");
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_create(Ok(CreateResponse {
+            id: "hist-1".into(),
+            share_url: "https://secrt.ca/s/hist-1".into(),
+            expires_at: "2099-01-01T00:00:00Z".into(),
+        }))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--label", "db-prod"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+
+    let link = stdout.to_string();
+    let url_key = link.trim().split('#').nth(1).unwrap().to_string();
+
+    let raw = fs::read_to_string(history_file(&home)).unwrap();
+    assert!(!raw.contains(&url_key), "history must not contain the key");
+
+    let entries = load_history(&history_file(&home));
+    assert_eq!(entries.len(), 1);
+    let e = &entries[0];
+    assert_eq!(e.id, "hist-1");
+    assert_eq!(e.label, "db-prod");
+    assert_eq!(e.size, 5);
+    assert_eq!(e.base_url, "https://secrt.ca");
+    assert_eq!(e.expires_at, "2099-01-01T00:00:00Z");
+    assert!(!e.passphrase);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let mode = fs::metadata(history_file(&home)).unwrap().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+}
+
+#[test]
+fn send_without_history_records_nothing() {
+    let home = config_home("");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_create(Ok(CreateResponse {
+            id: "nohist".into(),
+            share_url: "https://secrt.ca/s/nohist".into(),
+            expires_at: "2099-01-01T00:00:00Z".into(),
+        }))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--label", "x"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0);
+    assert!(!history_file(&home).exists());
+    assert!(
+        stderr.to_string().contains("--label is only kept"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn list_does_not_run_credential_commands() {
+    let dir = std::path::PathBuf::from(helpers::unique_temp_dir());
+    std::fs::create_dir_all(&dir).unwrap();
+    let marker = dir.join("ran");
+    let touch = format!("\"touch {}\"", marker.display());
+    let home = config_home(&format!(
+        "history = true\napi_key_command = {0}\npassphrase_command = {0}\n\
+         decryption_passphrases_command = {0}\n",
+        touch
+    ));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "list"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(!marker.exists(), "list must not run credential commands");
+}
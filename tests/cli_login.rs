@@ -0,0 +1,250 @@
+mod helpers;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use helpers::{args, config_home, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::{InfoLimits, InfoRate, InfoResponse, InfoTTL, InfoTier};
+
+fn mock_info(authenticated: bool) -> InfoResponse {
+    let tier = InfoTier {
+        max_envelope_bytes: 262144,
+        max_secrets: 10,
+        max_total_bytes: 2097152,
+        rate: InfoRate {
+            requests_per_second: 0.5,
+            burst: 6,
+        },
+    };
+    InfoResponse {
+        authenticated,
+        ttl: InfoTTL {
+            default_seconds: 86400,
+            max_seconds: 604800,
+        },
+        limits: InfoLimits {
+            public: tier.clone(),
+            authed: tier,
+        },
+        claim_rate: InfoRate {
+            requests_per_second: 1.0,
+            burst: 10,
+        },
+        quota: None,
+    }
+}
+
+fn read_config(home: &std::path::Path) -> String {
+    std::fs::read_to_string(home.join("secrt").join("config.toml")).unwrap()
+}
+
+#[test]
+fn login_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "login", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("secrt login"));
+}
+
+#[test]
+fn login_prompts_validates_and_stores() {
+    let home = config_home("# my settings\nhistory = true\n");
+    let store = Arc::new(Mutex::new(HashMap::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .is_tty(true)
+        .read_pass(&["  sk_live_abc123\n"])
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(true)))
+        .keychain_store(store.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let err = stderr.to_string();
+    assert!(err.contains("API key: "), "{}", err);
+    assert!(
+        err.contains("Logged in to https://secrt.ca; API key saved to OS keychain"),
+        "{}",
+        err
+    );
+    assert!(err.contains("Enabled use_keychain"), "{}", err);
+    assert_eq!(
+        store.lock().unwrap().get("api_key").map(String::as_str),
+        Some("sk_live_abc123")
+    );
+    assert_eq!(
+        read_config(&home),
+        "# my settings\nhistory = true\nuse_keychain = true\n"
+    );
+}
+
+#[test]
+fn login_reads_key_from_stdin_without_tty() {
+    let home = config_home("use_keychain = true\n");
+    let store = Arc::new(Mutex::new(HashMap::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_from_pipe\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(true)))
+        .keychain_store(store.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(!stderr.to_string().contains("Enabled use_keychain"));
+    assert_eq!(
+        store.lock().unwrap().get("api_key").map(String::as_str),
+        Some("sk_from_pipe")
+    );
+    assert_eq!(read_config(&home), "use_keychain = true\n");
+}
+
+#[test]
+fn login_rejected_key_is_not_stored() {
+    let home = config_home("");
+    let store = Arc::new(Mutex::new(HashMap::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_bad\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(false)))
+        .keychain_store(store.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(
+        stderr
+            .to_string()
+            .contains("https://secrt.ca did not accept this API key"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+    assert!(store.lock().unwrap().is_empty());
+    assert_eq!(read_config(&home), "");
+}
+
+#[test]
+fn login_unreachable_server() {
+    let home = config_home("");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_key\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Err("connection refused".into()))
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(stderr
+        .to_string()
+        .contains("could not verify API key with https://secrt.ca: connection refused"));
+}
+
+#[test]
+fn login_empty_key() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"\n")
+        .env("XDG_CONFIG_HOME", "/tmp/secrt_test_no_config")
+        .mock_info(Ok(mock_info(true)))
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(stderr.to_string().contains("must not be empty"));
+}
+
+#[test]
+fn login_keychain_failure() {
+    let home = config_home("");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_key\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(true)))
+        .keychain_set_error("keychain feature not enabled; rebuild with --features keychain")
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(stderr.to_string().contains("keychain feature not enabled"));
+    assert_eq!(read_config(&home), "");
+}
+
+#[test]
+fn login_profile_stores_scoped_key() {
+    let home =
+        config_home("[profiles.work]\nbase_url = \"https://work.example\"\nuse_keychain = false\n");
+    let store = Arc::new(Mutex::new(HashMap::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_work\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_info(Ok(mock_info(true)))
+        .keychain_store(store.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "login", "--profile", "work"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stderr
+        .to_string()
+        .contains("Logged in to https://work.example (profile work)"));
+    assert_eq!(
+        store
+            .lock()
+            .unwrap()
+            .get("profiles.work.api_key")
+            .map(String::as_str),
+        Some("sk_work")
+    );
+    // The profile turned keychain reads off, so that's where it's enabled
+    assert_eq!(
+        read_config(&home),
+        "[profiles.work]\nbase_url = \"https://work.example\"\nuse_keychain = true\n"
+    );
+}
+
+#[test]
+fn login_warns_when_env_key_overrides() {
+    let home = config_home("use_keychain = true\n");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"sk_new\n")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .env("SECRET_API_KEY", "sk_env")
+        .mock_info(Ok(mock_info(true)))
+        .build();
+    let code = cli::run(&args(&["secrt", "login"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stderr
+        .to_string()
+        .contains("SECRET_API_KEY is set and takes precedence"));
+}
+
+#[test]
+fn login_rejects_positional_args() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "login", "sk_oops"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("unexpected argument"));
+}
+
+#[test]
+fn logout_removes_key() {
+    let home = config_home("use_keychain = true\n");
+    let store = Arc::new(Mutex::new(HashMap::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .keychain_secret("api_key", "sk_old")
+        .keychain_secret("passphrase", "keep-me")
+        .keychain_store(store.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "logout"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stderr
+        .to_string()
+        .contains("Removed API key for https://secrt.ca from OS keychain"));
+    let store = store.lock().unwrap();
+    assert!(!store.contains_key("api_key"));
+    assert!(store.contains_key("passphrase"));
+}
+
+#[test]
+fn logout_without_stored_key_fails() {
+    let home = config_home("");
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .build();
+    let code = cli::run(&args(&["secrt", "logout"]), &mut deps);
+    assert_eq!(code, 1);
+    assert!(stderr.to_string().contains("No matching entry"));
+}
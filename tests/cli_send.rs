@@ -1,8 +1,10 @@
 mod helpers;
 
+use std::sync::{Arc, Mutex};
+
 use helpers::{args, TestDepsBuilder};
 use secrt::cli;
-use secrt::client::CreateResponse;
+use secrt::client::{CreateResponse, InfoLimits, InfoRate, InfoResponse, InfoTTL, InfoTier};
 
 /// Use a non-routable address to ensure API calls fail
 const DEAD_URL: &str = "http://127.0.0.1:19191";
@@ -228,6 +230,27 @@ fn send_non_tty_no_status_message() {
     );
 }
 
+#[test]
+fn send_tty_output_escapes_server_strings() {
+    let resp = CreateResponse {
+        id: "test-id-123".into(),
+        share_url: "https://secrt.ca/s/\x1b]0;pwned\x07".into(),
+        expires_at: "\x1b]0;later\x07".into(),
+    };
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .is_tty(true)
+        .is_stdout_tty(true)
+        .mock_create(Ok(resp))
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--text", "x"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let (out, err) = (stdout.to_string(), stderr.to_string());
+    assert!(!out.contains("\x1b]0;"), "{:?}", out);
+    assert!(out.contains("^[]0;pwned^G"), "{:?}", out);
+    assert!(!err.contains("\x1b]0;"), "{:?}", err);
+    assert!(err.contains("^[]0;later^G"), "{:?}", err);
+}
+
 // --- Multi-line and trim tests ---
 
 #[test]
@@ -641,6 +664,31 @@ fn send_passphrase_conflicting_flags() {
     );
 }
 
+#[test]
+fn send_passphrase_cmd_conflicts_with_file() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "send",
+            "--passphrase-cmd",
+            "echo pw",
+            "--passphrase-file",
+            "/tmp/pw",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(
+        stderr.to_string().contains("--passphrase-cmd"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+}
+
 #[test]
 fn send_api_error_tty_silent() {
     let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
@@ -785,3 +833,390 @@ fn send_hidden_read_error() {
         stderr.to_string()
     );
 }
+
+// --- Preflight validation against server limits ---
+
+fn mock_info_limits(max_ttl: i64, public_max: i64, authed_max: i64) -> InfoResponse {
+    let tier = |max| InfoTier {
+        max_envelope_bytes: max,
+        max_secrets: 0,
+        max_total_bytes: 0,
+        rate: InfoRate {
+            requests_per_second: 1.0,
+            burst: 10,
+        },
+    };
+    InfoResponse {
+        authenticated: false,
+        ttl: InfoTTL {
+            default_seconds: 3600,
+            max_seconds: max_ttl,
+        },
+        limits: InfoLimits {
+            public: tier(public_max),
+            authed: tier(authed_max),
+        },
+        claim_rate: InfoRate {
+            requests_per_second: 1.0,
+            burst: 10,
+        },
+        quota: None,
+    }
+}
+
+#[test]
+fn send_preflight_ttl_over_max() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info_limits(86400, 0, 0)))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--ttl", "3d"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2, "stderr: {}", stderr.to_string());
+    assert!(
+        stderr
+            .to_string()
+            .contains("TTL 3d exceeds the server maximum of 1d (over by 2d)"),
+        "stderr: {}",
+        stderr.to_string()
+    );
+    assert!(stdout.to_string().is_empty(), "should not upload");
+}
+
+#[test]
+fn send_preflight_ttl_within_max() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info_limits(86400, 0, 0)))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--ttl", "1d"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().contains("#"));
+}
+
+#[test]
+fn send_preflight_too_large_before_sealing() {
+    let big = "x".repeat(4096);
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info_limits(0, 1024, 0)))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--text", &big]), &mut deps);
+    assert_eq!(code, 2, "stderr: {}", stderr.to_string());
+    let err = stderr.to_string();
+    assert!(err.contains("secret is too large"), "stderr: {}", err);
+    assert!(err.contains("public limit is 1 KB"), "stderr: {}", err);
+    assert!(err.contains("at least"), "stderr: {}", err);
+    assert!(stdout.to_string().is_empty(), "should not upload");
+}
+
+#[test]
+fn send_preflight_too_large_after_sealing() {
+    // 1 byte passes the pre-seal estimate but the framed envelope does not fit
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info_limits(0, 100, 0)))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--text", "x"]), &mut deps);
+    assert_eq!(code, 2, "stderr: {}", stderr.to_string());
+    let err = stderr.to_string();
+    assert!(err.contains("secret is too large"), "stderr: {}", err);
+    assert!(!err.contains("at least"), "should be exact: {}", err);
+    assert!(stdout.to_string().is_empty(), "should not upload");
+}
+
+#[test]
+fn send_preflight_uses_authed_tier_with_api_key() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Ok(mock_info_limits(0, 100, 1048576)))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--api-key", "sk_test"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().contains("#"));
+}
+
+#[test]
+fn send_preflight_skipped_when_info_unavailable() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_info(Err("connection refused".into()))
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "hello", "--ttl", "1w"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().contains("#"));
+}
+
+// --- Profiles ---
+
+fn create_log_for(profile_args: &[&str], env: &[(&str, &str)]) -> (i32, Vec<String>) {
+    let home = helpers::config_home(
+        "base_url = \"https://top.example\"\n\
+         default_ttl = \"1h\"\n\
+         [profiles.work]\n\
+         base_url = \"https://work.example\"\n\
+         default_ttl = \"5m\"\n\
+         [profiles.lab]\n\
+         base_url = \"http://localhost:8787\"\n",
+    );
+    let log = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut builder = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .mock_create(Ok(mock_send_response()))
+        .mock_call_log(log.clone());
+    for (k, v) in env {
+        builder = builder.env(k, v);
+    }
+    let (mut deps, _stdout, _stderr) = builder.build();
+    let mut argv = vec!["secrt", "send"];
+    argv.extend_from_slice(profile_args);
+    let code = cli::run(&args(&argv), &mut deps);
+    let calls = log.lock().unwrap().clone();
+    (code, calls)
+}
+
+#[test]
+fn send_profile_flag_overrides_top_level() {
+    let (code, calls) = create_log_for(&["--profile", "work"], &[]);
+    assert_eq!(code, 0);
+    assert_eq!(calls, vec!["create:https://work.example:300"]);
+}
+
+#[test]
+fn send_profile_from_env_and_flag_wins() {
+    let (code, calls) = create_log_for(&[], &[("SECRT_PROFILE", "lab")]);
+    assert_eq!(code, 0);
+    assert_eq!(calls, vec!["create:http://localhost:8787:3600"]);
+
+    let (code, calls) = create_log_for(&["--profile", "work"], &[("SECRT_PROFILE", "lab")]);
+    assert_eq!(code, 0);
+    assert_eq!(calls, vec!["create:https://work.example:300"]);
+}
+
+#[test]
+fn send_without_profile_uses_top_level() {
+    let (code, calls) = create_log_for(&[], &[]);
+    assert_eq!(code, 0);
+    assert_eq!(calls, vec!["create:https://top.example:3600"]);
+}
+
+#[test]
+fn send_project_config_cannot_redirect_api_key() {
+    let home = helpers::config_home("base_url = \"https://top.example\"\napi_key = \"sk_user\"\n");
+    let repo = home.join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    std::fs::write(
+        repo.join(".secrt.toml"),
+        "base_url = \"https://evil.example\"\n\
+         default_profile = \"evil\"\n\
+         [profiles.evil]\n\
+         base_url = \"https://evil.example\"\n",
+    )
+    .unwrap();
+    let clients = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .env("XDG_CONFIG_HOME", home.to_str().unwrap())
+        .env("PWD", repo.to_str().unwrap())
+        .mock_create(Ok(mock_send_response()))
+        .mock_client_log(clients.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "send"]), &mut deps);
+    let err = stderr.to_string();
+    assert_eq!(code, 0, "stderr: {}", err);
+    assert!(
+        err.contains("ignoring base_url, default_profile, profiles.evil in"),
+        "{}",
+        err
+    );
+    assert!(err.contains("project config files cannot choose the server"));
+    let clients = clients.lock().unwrap().clone();
+    assert!(!clients.is_empty());
+    for (base_url, api_key) in &clients {
+        assert_eq!(base_url, "https://top.example");
+        assert_eq!(api_key, "sk_user");
+    }
+}
+
+// --- Clipboard ---
+
+#[test]
+fn send_copy_puts_link_on_clipboard() {
+    let clip = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_create(Ok(mock_send_response()))
+        .clipboard(clip.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--copy"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let link = stdout.to_string().trim().to_string();
+    assert!(link.starts_with("https://secrt.ca/s/test-id-123#"));
+    assert_eq!(
+        String::from_utf8(clip.lock().unwrap().clone()).unwrap(),
+        link
+    );
+    assert!(stderr.to_string().contains("Link copied to clipboard"));
+}
+
+#[test]
+fn send_copy_failure_still_prints_link() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_create(Ok(mock_send_response()))
+        .clipboard_error("no clipboard available")
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--copy"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stdout.to_string().contains("test-id-123"));
+    assert!(stderr
+        .to_string()
+        .contains("warning: could not copy link: no clipboard available"));
+}
+
+#[test]
+fn send_from_clipboard() {
+    let clip = Arc::new(Mutex::new(b"from the clipboard".to_vec()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_create(Ok(mock_send_response()))
+        .clipboard(clip)
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--from-clipboard"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+}
+
+#[test]
+fn send_from_empty_clipboard() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "send", "--from-clipboard"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("clipboard is empty"));
+}
+
+#[test]
+fn send_from_clipboard_is_one_input_source() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--from-clipboard", "--text", "x"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("exactly one input source"));
+}
+
+#[test]
+fn send_format_env_and_shell() {
+    for (format, name, expected) in [
+        ("env", None, "SECRT_LINK='https://secrt.ca/s/test-id-123#"),
+        (
+            "shell",
+            Some("LINK"),
+            "export LINK='https://secrt.ca/s/test-id-123#",
+        ),
+    ] {
+        let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+            .stdin(b"my secret")
+            .mock_create(Ok(mock_send_response()))
+            .build();
+        let mut argv = vec!["secrt", "send", "--format", format];
+        if let Some(name) = name {
+            argv.extend(["--name", name]);
+        }
+        let code = cli::run(&args(&argv), &mut deps);
+        assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+        let out = stdout.to_string();
+        assert!(out.starts_with(expected), "{}: {}", format, out);
+        assert_eq!(out.lines().count(), 1);
+    }
+}
+
+#[test]
+fn send_format_yaml() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--format", "yaml"]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let out = stdout.to_string();
+    assert!(out.contains("id: \"test-id-123\"\n"), "{}", out);
+    assert!(
+        out.contains("expires_at: \"2026-02-09T00:00:00Z\"\n"),
+        "{}",
+        out
+    );
+    assert!(out.contains("share_link: \"https://secrt.ca/s/test-id-123#"));
+}
+
+#[test]
+fn send_template() {
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_create(Ok(mock_send_response()))
+        .build();
+    let code = cli::run(
+        &args(&[
+            "secrt",
+            "send",
+            "--template",
+            "id={{id}} at {{ expires_at }}",
+        ]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(
+        stdout.to_string(),
+        "id=test-id-123 at 2026-02-09T00:00:00Z\n"
+    );
+}
+
+#[test]
+fn send_template_unknown_field_fails_before_upload() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"my secret")
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--template", "{{url}}"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr
+        .to_string()
+        .contains("unknown template field \"url\""));
+    assert!(log.lock().unwrap().is_empty(), "nothing should be uploaded");
+}
+
+#[test]
+fn send_format_conflicts() {
+    for (argv, msg) in [
+        (
+            &["--json", "--format", "yaml"][..],
+            "specify at most one of --json, --format, --template",
+        ),
+        (&["--format", "toml"][..], "unknown format \"toml\""),
+        (
+            &["--name", "X"][..],
+            "--name requires --format env or shell",
+        ),
+    ] {
+        let (mut deps, _stdout, stderr) = TestDepsBuilder::new().stdin(b"x").build();
+        let mut full = vec!["secrt", "send"];
+        full.extend(argv);
+        assert_eq!(cli::run(&args(&full), &mut deps), 2);
+        assert!(stderr.to_string().contains(msg), "{}", stderr.to_string());
+    }
+}
@@ -0,0 +1,258 @@
+mod helpers;
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use helpers::{args, TestDepsBuilder};
+use secrt::cli;
+use secrt::client::{CreateResponse, SecretStatus};
+
+fn status(s: &str) -> Result<SecretStatus, String> {
+    Ok(SecretStatus {
+        id: "abc123".into(),
+        status: s.into(),
+        expires_at: Some("2099-01-01T00:00:00Z".into()),
+        claimed_at: if s == "claimed" {
+            Some("2026-02-09T12:00:00Z".into())
+        } else {
+            None
+        },
+    })
+}
+
+fn watch(extra: &[&str], seq: Vec<Result<SecretStatus, String>>) -> (i32, String, String) {
+    let mut a = vec!["secrt", "watch", "abc123"];
+    a.extend_from_slice(extra);
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().mock_status(seq).build();
+    let code = cli::run(&args(&a), &mut deps);
+    (code, stdout.to_string(), stderr.to_string())
+}
+
+#[test]
+fn watch_help() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "watch", "--help"]), &mut deps);
+    assert_eq!(code, 0);
+    assert!(stderr.to_string().contains("EXIT STATUS"));
+}
+
+#[test]
+fn watch_requires_id() {
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "watch"]), &mut deps);
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("required"));
+}
+
+#[test]
+fn watch_invalid_interval() {
+    let (code, _, err) = watch(&["--interval", "often"], vec![status("pending")]);
+    assert_eq!(code, 2);
+    assert!(err.contains("--interval"), "stderr: {}", err);
+}
+
+#[test]
+fn watch_claimed_exits_0() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_status(vec![
+            status("pending"),
+            status("pending"),
+            status("claimed"),
+        ])
+        .sleep_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "watch", "abc123", "--interval", "10s"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stderr.to_string().contains("Secret claimed"));
+    assert_eq!(*log.lock().unwrap(), vec![Duration::from_secs(10); 2]);
+}
+
+#[test]
+fn watch_burned_exits_3() {
+    let (code, _, err) = watch(&[], vec![status("burned")]);
+    assert_eq!(code, 3);
+    assert!(err.contains("burned"), "stderr: {}", err);
+}
+
+#[test]
+fn watch_expired_exits_4() {
+    let (code, _, err) = watch(&[], vec![status("pending"), status("expired")]);
+    assert_eq!(code, 4);
+    assert!(err.contains("expired"), "stderr: {}", err);
+}
+
+#[test]
+fn watch_timeout_exits_5() {
+    let (code, out, _) = watch(
+        &["--timeout", "30s", "--interval", "10s", "--json"],
+        vec![status("pending")],
+    );
+    assert_eq!(code, 5);
+    let json: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+    assert_eq!(json["status"], "pending");
+    assert_eq!(json["timed_out"], true);
+}
+
+#[test]
+fn watch_status_errors_exit_1() {
+    let (code, _, err) = watch(&[], vec![Err("server error (404)".into())]);
+    assert_eq!(code, 1);
+    assert!(err.contains("status check failed"), "stderr: {}", err);
+}
+
+#[test]
+fn watch_server_strings_are_escaped() {
+    let (code, _, err) = watch(&[], vec![status("\x1b]0;pwned\x07")]);
+    assert_eq!(code, 1);
+    assert!(!err.contains('\x1b'), "{:?}", err);
+    assert!(err.contains("Secret status: ^[]0;pwned^G"), "{:?}", err);
+
+    let (code, _, err) = watch(&[], vec![Err("server error (500): \x1b[2J".into())]);
+    assert_eq!(code, 1);
+    assert!(!err.contains('\x1b'), "{:?}", err);
+    assert!(err.contains("^[[2J"), "{:?}", err);
+}
+
+#[test]
+fn watch_json_reports_final_status() {
+    let (code, out, _) = watch(&["--json"], vec![status("claimed")]);
+    assert_eq!(code, 0);
+    let json: serde_json::Value = serde_json::from_str(out.trim()).unwrap();
+    assert_eq!(json["status"], "claimed");
+    assert_eq!(json["claimed_at"], "2026-02-09T12:00:00Z");
+}
+
+#[test]
+fn watch_share_url_uses_its_host() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let url = format!(
+        "https://other.example/s/abc123#{}",
+        secrt::envelope::crypto::b64_encode(&[7u8; 32])
+    );
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new()
+        .mock_status(vec![status("claimed")])
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(&args(&["secrt", "watch", &url]), &mut deps);
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["status:https://other.example:abc123".to_string()]
+    );
+}
+
+#[test]
+fn send_wait_prints_link_then_waits() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .mock_create(Ok(CreateResponse {
+            id: "abc123".into(),
+            share_url: "https://secrt.ca/s/abc123".into(),
+            expires_at: "2099-01-01T00:00:00Z".into(),
+        }))
+        .mock_status(vec![status("pending"), status("claimed")])
+        .mock_call_log(log.clone())
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "tmp-token", "--wait"]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    assert!(stdout.to_string().starts_with("https://secrt.ca/s/abc123#"));
+    assert!(stderr.to_string().contains("Secret claimed"));
+    let calls = log.lock().unwrap().clone();
+    assert_eq!(calls.iter().filter(|c| c.starts_with("status:")).count(), 2);
+}
+
+#[test]
+fn send_wait_expired_exit_code() {
+    let (mut deps, _stdout, _stderr) = TestDepsBuilder::new()
+        .mock_create(Ok(CreateResponse {
+            id: "abc123".into(),
+            share_url: "https://secrt.ca/s/abc123".into(),
+            expires_at: "2099-01-01T00:00:00Z".into(),
+        }))
+        .mock_status(vec![status("expired")])
+        .build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "x", "--wait"]),
+        &mut deps,
+    );
+    assert_eq!(code, 4);
+}
+
+#[test]
+fn send_wait_bad_timeout_fails_before_upload() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().mock_call_log(log.clone()).build();
+    let code = cli::run(
+        &args(&["secrt", "send", "--text", "x", "--wait", "--timeout", "-1"]),
+        &mut deps,
+    );
+    assert_eq!(code, 2);
+    assert!(stderr.to_string().contains("--timeout"));
+    assert!(log.lock().unwrap().is_empty());
+}
+
+/// Minimal HTTP stand-in: answers each request with the next canned status
+/// and records the request lines it saw.
+fn status_server(statuses: &[&'static str]) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen_thread = seen.clone();
+    let statuses = statuses.to_vec();
+    std::thread::spawn(move || {
+        for (i, stream) in listener.incoming().enumerate() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            // Drain headers
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+            seen_thread
+                .lock()
+                .unwrap()
+                .push(request_line.trim().to_string());
+            let st = statuses[i.min(statuses.len() - 1)];
+            let body = format!("{{\"id\":\"abc123\",\"status\":\"{}\"}}", st);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        }
+    });
+    (format!("http://{}", addr), seen)
+}
+
+#[test]
+fn watch_real_client_only_reads_status() {
+    let (base_url, seen) = status_server(&["pending", "claimed"]);
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "watch", "abc123", "--base-url", &base_url]),
+        &mut deps,
+    );
+    assert_eq!(code, 0, "stderr: {}", stderr.to_string());
+    let seen = seen.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            "GET /api/v1/secrets/abc123/status HTTP/1.1".to_string(),
+            "GET /api/v1/secrets/abc123/status HTTP/1.1".to_string(),
+        ]
+    );
+}
@@ -6,17 +6,57 @@
 //!
 //! For burn/api-key tests:
 //!   SECRET_E2E_BASE_URL=https://secrt.ca SECRET_E2E_API_KEY=sk_... cargo test e2e -- --ignored
+//!
+//! With the `dev-server` feature and no `SECRET_E2E_BASE_URL`, the tests run
+//! offline against an in-process dev server instead:
+//!   cargo test --features dev-server --test e2e
 
 mod helpers;
 
 use helpers::{args, TestDepsBuilder};
 use secrt::cli;
 
-fn base_url() -> String {
+#[cfg(feature = "dev-server")]
+const DEV_API_KEY: &str = "sk_e2e_dev";
+
+/// Base URL of a dev server shared by all tests in this file.
+#[cfg(feature = "dev-server")]
+fn dev_server_url() -> String {
+    use secrt::devserver::{DevServer, DevServerConfig};
+    use std::sync::OnceLock;
+
+    static URL: OnceLock<String> = OnceLock::new();
+    URL.get_or_init(|| {
+        let server = DevServer::start(DevServerConfig {
+            listen: "127.0.0.1:0".into(),
+            api_keys: vec![DEV_API_KEY.into()],
+            rate_limits: false,
+            ..Default::default()
+        })
+        .expect("start dev server");
+        server.base_url()
+    })
+    .clone()
+}
+
+fn remote_base_url() -> String {
     std::env::var("SECRET_E2E_BASE_URL").unwrap_or_default()
 }
 
+fn base_url() -> String {
+    let url = remote_base_url();
+    #[cfg(feature = "dev-server")]
+    if url.is_empty() {
+        return dev_server_url();
+    }
+    url
+}
+
 fn api_key() -> String {
+    #[cfg(feature = "dev-server")]
+    if remote_base_url().is_empty() {
+        return DEV_API_KEY.into();
+    }
     std::env::var("SECRET_E2E_API_KEY").unwrap_or_default()
 }
 
@@ -29,7 +69,7 @@ fn should_skip_api_key() -> bool {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_send_get_roundtrip() {
     if should_skip() {
         return;
@@ -58,7 +98,7 @@ fn e2e_send_get_roundtrip() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_send_with_passphrase() {
     if should_skip() {
         return;
@@ -99,7 +139,7 @@ fn e2e_send_with_passphrase() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_send_with_ttl() {
     if should_skip() {
         return;
@@ -124,7 +164,7 @@ fn e2e_send_with_ttl() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_send_get_json() {
     if should_skip() {
         return;
@@ -154,7 +194,7 @@ fn e2e_send_get_json() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_send_with_api_key() {
     if should_skip_api_key() {
         return;
@@ -186,7 +226,7 @@ fn e2e_send_with_api_key() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_burn() {
     if should_skip_api_key() {
         return;
@@ -225,7 +265,7 @@ fn e2e_burn() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_server_info_unauthenticated() {
     if should_skip() {
         return;
@@ -233,10 +273,7 @@ fn e2e_server_info_unauthenticated() {
     let url = base_url();
 
     // Use the API client directly to test info endpoint
-    let client = secrt::client::ApiClient {
-        base_url: url,
-        api_key: String::new(),
-    };
+    let client = secrt::client::ApiClient::new(&url, "");
 
     use secrt::client::SecretApi;
     let info = client.info().expect("info() should succeed");
@@ -253,7 +290,7 @@ fn e2e_server_info_unauthenticated() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_server_info_authenticated() {
     if should_skip_api_key() {
         return;
@@ -261,10 +298,7 @@ fn e2e_server_info_authenticated() {
     let url = base_url();
     let key = api_key();
 
-    let client = secrt::client::ApiClient {
-        base_url: url,
-        api_key: key,
-    };
+    let client = secrt::client::ApiClient::new(&url, &key);
 
     use secrt::client::SecretApi;
     let info = client.info().expect("info() should succeed");
@@ -272,7 +306,7 @@ fn e2e_server_info_authenticated() {
 }
 
 #[test]
-#[ignore]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
 fn e2e_config_show_with_server_info() {
     if should_skip() {
         return;
@@ -317,3 +351,38 @@ fn e2e_config_show_with_server_info() {
         err
     );
 }
+
+#[test]
+#[cfg_attr(not(feature = "dev-server"), ignore)]
+fn e2e_watch_reports_claim() {
+    if should_skip() {
+        return;
+    }
+    let url = base_url();
+
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new()
+        .stdin(b"e2e-watch-test")
+        .env("SECRET_BASE_URL", &url)
+        .build();
+    let code = cli::run(&args(&["secrt", "send", "--json"]), &mut deps);
+    assert_eq!(code, 0, "send failed: {}", stderr.to_string());
+    let json: serde_json::Value = serde_json::from_str(stdout.to_string().trim()).unwrap();
+    let share_link = json["share_link"].as_str().unwrap().to_string();
+
+    // Still pending: watch times out without consuming the secret.
+    let (mut deps, _stdout, _stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(
+        &args(&["secrt", "watch", &share_link, "--timeout", "1s"]),
+        &mut deps,
+    );
+    assert_eq!(code, 5);
+
+    let (mut deps, stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "get", &share_link]), &mut deps);
+    assert_eq!(code, 0, "get failed: {}", stderr.to_string());
+    assert_eq!(stdout.to_string(), "e2e-watch-test");
+
+    let (mut deps, _stdout, stderr) = TestDepsBuilder::new().build();
+    let code = cli::run(&args(&["secrt", "watch", &share_link]), &mut deps);
+    assert_eq!(code, 0, "watch failed: {}", stderr.to_string());
+}
@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 
 use secrt::cli::Deps;
 use secrt::client::{
-    ApiClient, ClaimResponse, CreateRequest, CreateResponse, InfoResponse, SecretApi,
+    ApiClient, ClaimResponse, CreateRequest, CreateResponse, InfoResponse, SecretApi, SecretStatus,
 };
 use secrt::envelope::EnvelopeError;
 
@@ -40,18 +40,39 @@ impl Write for SharedBuf {
 #[derive(Clone)]
 pub struct MockApiResponses {
     pub create: Option<Result<CreateResponse, String>>,
+    /// Successive create results, used before `create` until exhausted.
+    pub create_seq: Arc<Mutex<Vec<Result<CreateResponse, String>>>>,
     pub claim: Option<Result<ClaimResponse, String>>,
+    /// Per-ID claim results, checked before `claim`.
+    pub claim_by_id: HashMap<String, Result<ClaimResponse, String>>,
     pub burn: Option<Result<(), String>>,
+    /// Per-ID burn results, checked before `burn`.
+    pub burn_by_id: HashMap<String, Result<(), String>>,
     pub info: Option<Result<InfoResponse, String>>,
+    /// Unix time reported as the server's `Date` header.
+    pub server_time: Option<Result<Option<i64>, String>>,
+    /// Successive status results; the last one repeats once the rest are used.
+    pub status: Arc<Mutex<Vec<Result<SecretStatus, String>>>>,
+    /// Records every call as "method:base_url:arg" (shared across clones).
+    pub calls: Arc<Mutex<Vec<String>>>,
+    /// Records the (base_url, api_key) of every client built (shared across clones).
+    pub clients: Arc<Mutex<Vec<(String, String)>>>,
 }
 
 impl Default for MockApiResponses {
     fn default() -> Self {
         MockApiResponses {
             create: None,
+            create_seq: Arc::new(Mutex::new(Vec::new())),
             claim: None,
+            claim_by_id: HashMap::new(),
             burn: None,
+            burn_by_id: HashMap::new(),
             info: None,
+            server_time: None,
+            status: Arc::new(Mutex::new(Vec::new())),
+            calls: Arc::new(Mutex::new(Vec::new())),
+            clients: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
@@ -59,16 +80,36 @@ impl Default for MockApiResponses {
 /// A mock API client for testing.
 pub struct MockApi {
     responses: MockApiResponses,
+    base_url: String,
 }
 
 impl MockApi {
     pub fn new(responses: MockApiResponses) -> Self {
-        MockApi { responses }
+        MockApi {
+            responses,
+            base_url: String::new(),
+        }
+    }
+
+    fn log(&self, method: &str, arg: &str) {
+        self.responses
+            .calls
+            .lock()
+            .unwrap()
+            .push(format!("{}:{}:{}", method, self.base_url, arg));
     }
 }
 
 impl SecretApi for MockApi {
-    fn create(&self, _req: CreateRequest) -> Result<CreateResponse, String> {
+    fn create(&self, req: CreateRequest) -> Result<CreateResponse, String> {
+        let ttl = req.ttl_seconds.map(|t| t.to_string()).unwrap_or_default();
+        self.log("create", &ttl);
+        {
+            let mut seq = self.responses.create_seq.lock().unwrap();
+            if !seq.is_empty() {
+                return seq.remove(0);
+            }
+        }
         match &self.responses.create {
             Some(Ok(r)) => Ok(CreateResponse {
                 id: r.id.clone(),
@@ -80,8 +121,14 @@ impl SecretApi for MockApi {
         }
     }
 
-    fn claim(&self, _secret_id: &str, _claim_token: &[u8]) -> Result<ClaimResponse, String> {
-        match &self.responses.claim {
+    fn claim(&self, secret_id: &str, _claim_token: &[u8]) -> Result<ClaimResponse, String> {
+        self.log("claim", secret_id);
+        let resp = self
+            .responses
+            .claim_by_id
+            .get(secret_id)
+            .or(self.responses.claim.as_ref());
+        match resp {
             Some(Ok(r)) => Ok(ClaimResponse {
                 envelope: r.envelope.clone(),
                 expires_at: r.expires_at.clone(),
@@ -91,7 +138,11 @@ impl SecretApi for MockApi {
         }
     }
 
-    fn burn(&self, _secret_id: &str) -> Result<(), String> {
+    fn burn(&self, secret_id: &str) -> Result<(), String> {
+        self.log("burn", secret_id);
+        if let Some(r) = self.responses.burn_by_id.get(secret_id) {
+            return r.clone();
+        }
         match &self.responses.burn {
             Some(Ok(())) => Ok(()),
             Some(Err(e)) => Err(e.clone()),
@@ -106,6 +157,20 @@ impl SecretApi for MockApi {
             None => Err("mock: info not configured".into()),
         }
     }
+
+    fn server_time(&self) -> Result<Option<i64>, String> {
+        self.responses.server_time.clone().unwrap_or(Ok(None))
+    }
+
+    fn status(&self, secret_id: &str) -> Result<SecretStatus, String> {
+        self.log("status", secret_id);
+        let mut seq = self.responses.status.lock().unwrap();
+        match seq.len() {
+            0 => Err("mock: status not configured".into()),
+            1 => seq[0].clone(),
+            _ => seq.remove(0),
+        }
+    }
 }
 
 /// Build test Deps with configurable options.
@@ -117,10 +182,21 @@ pub struct TestDepsBuilder {
     read_pass_responses: Vec<String>,
     read_pass_error: Option<String>,
     mock_responses: Option<MockApiResponses>,
-    keychain_secrets: HashMap<String, String>,
+    keychain_secrets: Arc<Mutex<HashMap<String, String>>>,
+    keychain_set_error: Option<String>,
     keychain_secret_lists: HashMap<String, Vec<String>>,
+    slept: Arc<Mutex<Vec<std::time::Duration>>>,
+    clipboard: Arc<Mutex<Vec<u8>>>,
+    clipboard_error: Option<String>,
+    clipboard_copy_error: Option<String>,
+    clipboard_clears: Arc<Mutex<Vec<std::time::Duration>>>,
+    commands: Arc<Mutex<Vec<RunCommand>>>,
+    command_exit: i32,
 }
 
+/// A command passed to `run_command`: its arguments and the variables it got.
+pub type RunCommand = (Vec<String>, Vec<(String, String)>);
+
 impl TestDepsBuilder {
     pub fn new() -> Self {
         TestDepsBuilder {
@@ -131,8 +207,16 @@ impl TestDepsBuilder {
             read_pass_responses: Vec::new(),
             read_pass_error: None,
             mock_responses: None,
-            keychain_secrets: HashMap::new(),
+            keychain_secrets: Arc::new(Mutex::new(HashMap::new())),
+            keychain_set_error: None,
             keychain_secret_lists: HashMap::new(),
+            slept: Arc::new(Mutex::new(Vec::new())),
+            clipboard: Arc::new(Mutex::new(Vec::new())),
+            clipboard_error: None,
+            clipboard_copy_error: None,
+            clipboard_clears: Arc::new(Mutex::new(Vec::new())),
+            commands: Arc::new(Mutex::new(Vec::new())),
+            command_exit: 0,
         }
     }
 
@@ -174,6 +258,17 @@ impl TestDepsBuilder {
         self
     }
 
+    /// Create results returned in order; falls back to `mock_create` after.
+    pub fn mock_create_seq(mut self, seq: Vec<Result<CreateResponse, String>>) -> Self {
+        *self
+            .mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .create_seq
+            .lock()
+            .unwrap() = seq;
+        self
+    }
+
     pub fn mock_claim(mut self, resp: Result<ClaimResponse, String>) -> Self {
         self.mock_responses
             .get_or_insert_with(MockApiResponses::default)
@@ -181,6 +276,14 @@ impl TestDepsBuilder {
         self
     }
 
+    pub fn mock_claim_id(mut self, id: &str, resp: Result<ClaimResponse, String>) -> Self {
+        self.mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .claim_by_id
+            .insert(id.to_string(), resp);
+        self
+    }
+
     pub fn mock_burn(mut self, resp: Result<(), String>) -> Self {
         self.mock_responses
             .get_or_insert_with(MockApiResponses::default)
@@ -188,6 +291,79 @@ impl TestDepsBuilder {
         self
     }
 
+    pub fn mock_burn_id(mut self, id: &str, resp: Result<(), String>) -> Self {
+        self.mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .burn_by_id
+            .insert(id.to_string(), resp);
+        self
+    }
+
+    /// Record the durations passed to `sleep` (which never actually sleeps).
+    pub fn sleep_log(mut self, log: Arc<Mutex<Vec<std::time::Duration>>>) -> Self {
+        self.slept = log;
+        self
+    }
+
+    /// Share the in-memory clipboard with the test.
+    pub fn clipboard(mut self, store: Arc<Mutex<Vec<u8>>>) -> Self {
+        self.clipboard = store;
+        self
+    }
+
+    /// Make every clipboard operation fail with `msg`.
+    pub fn clipboard_error(mut self, msg: &str) -> Self {
+        self.clipboard_error = Some(msg.to_string());
+        self
+    }
+
+    /// Make copying to the clipboard fail with `msg`, while it still looks
+    /// available.
+    pub fn clipboard_copy_error(mut self, msg: &str) -> Self {
+        self.clipboard_copy_error = Some(msg.to_string());
+        self
+    }
+
+    /// Record the delays of scheduled clipboard clears.
+    pub fn clipboard_clear_log(mut self, log: Arc<Mutex<Vec<std::time::Duration>>>) -> Self {
+        self.clipboard_clears = log;
+        self
+    }
+
+    /// Record commands run through `run_command`, which exit with `code`.
+    pub fn command_log(mut self, log: Arc<Mutex<Vec<RunCommand>>>, code: i32) -> Self {
+        self.commands = log;
+        self.command_exit = code;
+        self
+    }
+
+    /// Status results returned in order, e.g. pending, pending, claimed.
+    pub fn mock_status(mut self, seq: Vec<Result<SecretStatus, String>>) -> Self {
+        *self
+            .mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .status
+            .lock()
+            .unwrap() = seq;
+        self
+    }
+
+    /// Share a call log with the mock API so tests can inspect requests.
+    pub fn mock_call_log(mut self, log: Arc<Mutex<Vec<String>>>) -> Self {
+        self.mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .calls = log;
+        self
+    }
+
+    /// Share a log of the (base_url, api_key) each API client was built with.
+    pub fn mock_client_log(mut self, log: Arc<Mutex<Vec<(String, String)>>>) -> Self {
+        self.mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .clients = log;
+        self
+    }
+
     pub fn mock_info(mut self, resp: Result<InfoResponse, String>) -> Self {
         self.mock_responses
             .get_or_insert_with(MockApiResponses::default)
@@ -195,12 +371,35 @@ impl TestDepsBuilder {
         self
     }
 
-    pub fn keychain_secret(mut self, key: &str, val: &str) -> Self {
+    pub fn mock_server_time(mut self, resp: Result<Option<i64>, String>) -> Self {
+        self.mock_responses
+            .get_or_insert_with(MockApiResponses::default)
+            .server_time = Some(resp);
+        self
+    }
+
+    pub fn keychain_secret(self, key: &str, val: &str) -> Self {
         self.keychain_secrets
+            .lock()
+            .unwrap()
             .insert(key.to_string(), val.to_string());
         self
     }
 
+    /// Share the in-memory keychain with the test so it can inspect what
+    /// commands stored or deleted. Entries added earlier are carried over.
+    pub fn keychain_store(mut self, store: Arc<Mutex<HashMap<String, String>>>) -> Self {
+        let existing = std::mem::take(&mut *self.keychain_secrets.lock().unwrap());
+        store.lock().unwrap().extend(existing);
+        self.keychain_secrets = store;
+        self
+    }
+
+    pub fn keychain_set_error(mut self, msg: &str) -> Self {
+        self.keychain_set_error = Some(msg.to_string());
+        self
+    }
+
     pub fn keychain_secret_list(mut self, key: &str, vals: &[&str]) -> Self {
         self.keychain_secret_lists.insert(
             key.to_string(),
@@ -217,12 +416,21 @@ impl TestDepsBuilder {
 
         let is_tty = self.is_tty;
         let is_stdout_tty = self.is_stdout_tty;
-        let env = self.env;
+        let mut env = self.env;
+        // Isolate the server-info cache per test unless a test sets its own
+        env.entry("XDG_CACHE_HOME".into())
+            .or_insert_with(unique_temp_dir);
+        // Keep the machine's /etc config and any .secrt.toml above the
+        // test's working directory out of the picture
+        env.entry("SECRT_SYSTEM_CONFIG".into())
+            .or_insert_with(|| format!("{}/system.toml", unique_temp_dir()));
+        env.entry("PWD".into()).or_insert_with(unique_temp_dir);
 
         let read_pass_responses = Arc::new(Mutex::new(self.read_pass_responses));
         let read_pass_error = self.read_pass_error;
 
         let deps = Deps {
+            style: Default::default(),
             stdin: Box::new(Cursor::new(self.stdin_data)),
             stdout: Box::new(stdout_clone),
             stderr: Box::new(stderr_clone),
@@ -239,33 +447,119 @@ impl TestDepsBuilder {
                 let _ = w.write_all(prompt.as_bytes());
                 let _ = w.flush();
                 if let Some(ref msg) = read_pass_error {
-                    return Err(io::Error::new(io::ErrorKind::Other, msg.clone()));
+                    return Err(io::Error::other(msg.clone()));
                 }
                 let mut responses = read_pass_responses.lock().unwrap();
                 if responses.is_empty() {
-                    Err(io::Error::new(io::ErrorKind::Other, "no password input"))
+                    Err(io::Error::other("no password input"))
                 } else {
                     Ok(responses.remove(0))
                 }
             }),
+            read_key: Box::new(|prompt: &str, w: &mut dyn Write| {
+                let _ = w.write_all(prompt.as_bytes());
+                w.flush()
+            }),
             get_keychain_secret: {
-                let kc = self.keychain_secrets;
-                Box::new(move |key: &str| kc.get(key).cloned())
+                let kc = self.keychain_secrets.clone();
+                Box::new(move |key: &str| kc.lock().unwrap().get(key).cloned())
+            },
+            set_keychain_secret: {
+                let kc = self.keychain_secrets.clone();
+                let set_error = self.keychain_set_error;
+                Box::new(move |key: &str, val: &str| {
+                    if let Some(ref msg) = set_error {
+                        return Err(msg.clone());
+                    }
+                    kc.lock().unwrap().insert(key.to_string(), val.to_string());
+                    Ok(())
+                })
+            },
+            delete_keychain_secret: {
+                let kc = self.keychain_secrets.clone();
+                Box::new(move |key: &str| match kc.lock().unwrap().remove(key) {
+                    Some(_) => Ok(()),
+                    None => Err(
+                        "keychain delete error: No matching entry found in secure storage".into(),
+                    ),
+                })
             },
             get_keychain_secret_list: {
                 let kcl = self.keychain_secret_lists;
-                Box::new(move |key: &str| kcl.get(key).cloned().unwrap_or_default())
+                let kc = self.keychain_secrets.clone();
+                // Fall back to parsing the plain entry, like keychain::get_secret_list
+                Box::new(move |key: &str| {
+                    if let Some(list) = kcl.get(key) {
+                        return list.clone();
+                    }
+                    match kc.lock().unwrap().get(key) {
+                        Some(raw) if !raw.is_empty() => {
+                            serde_json::from_str(raw).unwrap_or_else(|_| vec![raw.clone()])
+                        }
+                        _ => Vec::new(),
+                    }
+                })
+            },
+            sleep: {
+                let slept = self.slept;
+                Box::new(move |d: std::time::Duration| slept.lock().unwrap().push(d))
+            },
+            copy_to_clipboard: {
+                let cb = self.clipboard.clone();
+                let err = self.clipboard_error.clone().or(self.clipboard_copy_error);
+                Box::new(move |_tool: &str, data: &[u8]| match err {
+                    Some(ref msg) => Err(msg.clone()),
+                    None => {
+                        *cb.lock().unwrap() = data.to_vec();
+                        Ok(())
+                    }
+                })
+            },
+            read_clipboard: {
+                let cb = self.clipboard.clone();
+                let err = self.clipboard_error.clone();
+                Box::new(move |_tool: &str| match err {
+                    Some(ref msg) => Err(msg.clone()),
+                    None => Ok(cb.lock().unwrap().clone()),
+                })
+            },
+            clipboard_available: {
+                let err = self.clipboard_error.clone();
+                Box::new(move |_tool: &str| match err {
+                    Some(ref msg) => Err(msg.clone()),
+                    None => Ok(()),
+                })
+            },
+            schedule_clipboard_clear: {
+                let log = self.clipboard_clears;
+                Box::new(move |_tool: &str, _data: &[u8], d: std::time::Duration| {
+                    log.lock().unwrap().push(d);
+                    Ok(())
+                })
+            },
+            run_command: {
+                let log = self.commands;
+                let code = self.command_exit;
+                Box::new(move |cmd: &[String], vars: &[(String, String)]| {
+                    log.lock().unwrap().push((cmd.to_vec(), vars.to_vec()));
+                    Ok(code)
+                })
             },
             make_api: if let Some(mock_responses) = self.mock_responses {
-                Box::new(move |_base_url: &str, _api_key: &str| {
-                    Box::new(MockApi::new(mock_responses.clone())) as Box<dyn SecretApi>
+                Box::new(move |base_url: &str, api_key: &str| {
+                    mock_responses
+                        .clients
+                        .lock()
+                        .unwrap()
+                        .push((base_url.to_string(), api_key.to_string()));
+                    Box::new(MockApi {
+                        responses: mock_responses.clone(),
+                        base_url: base_url.to_string(),
+                    }) as Box<dyn SecretApi>
                 })
             } else {
                 Box::new(|base_url: &str, api_key: &str| {
-                    Box::new(ApiClient {
-                        base_url: base_url.to_string(),
-                        api_key: api_key.to_string(),
-                    }) as Box<dyn SecretApi>
+                    Box::new(ApiClient::new(base_url, api_key)) as Box<dyn SecretApi>
                 })
             },
         };
@@ -274,7 +568,34 @@ impl TestDepsBuilder {
     }
 }
 
+/// A fresh, not-yet-created path under the system temp dir.
+pub fn unique_temp_dir() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    std::env::temp_dir()
+        .join(format!("secrt_test_{}_{}", std::process::id(), n))
+        .to_string_lossy()
+        .to_string()
+}
+
 /// Helper to build args vec from a slice of &str.
 pub fn args(strs: &[&str]) -> Vec<String> {
     strs.iter().map(|s| s.to_string()).collect()
 }
+
+/// Create a fresh XDG config home containing `secrt/config.toml` (mode 0600)
+/// with the given contents. Returns the directory to use as XDG_CONFIG_HOME.
+pub fn config_home(toml_content: &str) -> std::path::PathBuf {
+    let dir = std::path::PathBuf::from(unique_temp_dir());
+    let secrt_dir = dir.join("secrt");
+    std::fs::create_dir_all(&secrt_dir).unwrap();
+    let config_path = secrt_dir.join("config.toml");
+    std::fs::write(&config_path, toml_content).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&config_path, std::fs::Permissions::from_mode(0o600));
+    }
+    dir
+}